@@ -0,0 +1,364 @@
+use crate::classfile_constants::{JVM_ACC_ABSTRACT, JVM_ACC_ANNOTATION, JVM_ACC_BRIDGE, JVM_ACC_ENUM, JVM_ACC_FINAL, JVM_ACC_INTERFACE, JVM_ACC_MODULE, JVM_ACC_NATIVE, JVM_ACC_PRIVATE, JVM_ACC_PROTECTED, JVM_ACC_PUBLIC, JVM_ACC_STATIC, JVM_ACC_STRICT, JVM_ACC_SUPER, JVM_ACC_SYNCHRONIZED, JVM_ACC_SYNTHETIC, JVM_ACC_TRANSIENT, JVM_ACC_VARARGS, JVM_ACC_VOLATILE};
+use crate::common::error::{ErrorKind, MessageError, Result};
+use std::fmt::{Display, Formatter};
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Not, Sub, SubAssign};
+
+/// 同一标志位在不同上下文中含义不同，如SUPER与SYNCHRONIZED、VOLATILE与BRIDGE，
+/// 因此按类、字段、方法、内部类分别定义
+macro_rules! access_flags {
+    ($(#[$meta:meta])* $name:ident { $($flag:ident = $value:expr,)* }) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+        pub struct $name(u16);
+
+        impl $name {
+            $(pub const $flag: $name = $name($value as u16);)*
+
+            /// 该上下文中定义的全部标志，按标志位从低到高排列
+            const NAMES: &'static [($name, &'static str)] = &[$(($name::$flag, concat!("ACC_", stringify!($flag))),)*];
+
+            pub const fn empty() -> $name {
+                $name(0)
+            }
+
+            /// 保留所有位，包括该上下文中未定义的位
+            pub const fn from_bits(bits: u16) -> $name {
+                $name(bits)
+            }
+
+            pub const fn bits(&self) -> u16 {
+                self.0
+            }
+
+            pub const fn is_empty(&self) -> bool {
+                self.0 == 0
+            }
+
+            /// 是否包含other中的全部标志
+            pub const fn contains(&self, other: $name) -> bool {
+                self.0 & other.0 == other.0
+            }
+
+            /// 是否包含other中的任一标志
+            pub const fn intersects(&self, other: $name) -> bool {
+                self.0 & other.0 != 0
+            }
+
+            pub fn insert(&mut self, other: $name) {
+                self.0 |= other.0;
+            }
+
+            pub fn remove(&mut self, other: $name) {
+                self.0 &= !other.0;
+            }
+
+            pub fn set(&mut self, other: $name, value: bool) {
+                if value {
+                    self.insert(other);
+                } else {
+                    self.remove(other);
+                }
+            }
+
+            /// javap -v形式的标志名，如ACC_PUBLIC，按标志位从低到高排列
+            pub fn flag_names(&self) -> Vec<&'static str> {
+                Self::NAMES.iter()
+                    .filter(|(flag, _)| self.contains(*flag))
+                    .map(|(_, name)| *name)
+                    .collect()
+            }
+
+            /// 按不含ACC_前缀的标志名查找，不区分大小写，如public、varargs
+            pub fn from_flag_name(name: &str) -> Option<$name> {
+                Self::NAMES.iter()
+                    .find(|(_, flag_name)| flag_name[4..].eq_ignore_ascii_case(name))
+                    .map(|(flag, _)| *flag)
+            }
+
+            #[inline]
+            fn visibility_count(&self) -> u32 {
+                (self.0 & (JVM_ACC_PUBLIC | JVM_ACC_PRIVATE | JVM_ACC_PROTECTED) as u16).count_ones()
+            }
+
+            fn write_keywords(&self, f: &mut Formatter<'_>, keywords: &[($name, &str)]) -> std::fmt::Result {
+                let mut first = true;
+                for (flag, keyword) in keywords {
+                    if self.contains(*flag) {
+                        if !first {
+                            write!(f, " ")?;
+                        }
+                        write!(f, "{}", keyword)?;
+                        first = false;
+                    }
+                }
+                Ok(())
+            }
+        }
+
+        impl From<u16> for $name {
+            fn from(value: u16) -> Self {
+                $name(value)
+            }
+        }
+
+        impl From<$name> for u16 {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl BitOr for $name {
+            type Output = $name;
+
+            fn bitor(self, rhs: Self) -> Self::Output {
+                $name(self.0 | rhs.0)
+            }
+        }
+
+        impl BitOrAssign for $name {
+            fn bitor_assign(&mut self, rhs: Self) {
+                self.0 |= rhs.0;
+            }
+        }
+
+        impl BitAnd for $name {
+            type Output = $name;
+
+            fn bitand(self, rhs: Self) -> Self::Output {
+                $name(self.0 & rhs.0)
+            }
+        }
+
+        impl BitAndAssign for $name {
+            fn bitand_assign(&mut self, rhs: Self) {
+                self.0 &= rhs.0;
+            }
+        }
+
+        impl Sub for $name {
+            type Output = $name;
+
+            fn sub(self, rhs: Self) -> Self::Output {
+                $name(self.0 & !rhs.0)
+            }
+        }
+
+        impl SubAssign for $name {
+            fn sub_assign(&mut self, rhs: Self) {
+                self.0 &= !rhs.0;
+            }
+        }
+
+        impl Not for $name {
+            type Output = $name;
+
+            fn not(self) -> Self::Output {
+                $name(!self.0)
+            }
+        }
+    };
+}
+
+access_flags! {
+    /// 类访问标志，JVMS 4.1
+    ClassAccessFlags {
+        PUBLIC = JVM_ACC_PUBLIC,
+        FINAL = JVM_ACC_FINAL,
+        SUPER = JVM_ACC_SUPER,
+        INTERFACE = JVM_ACC_INTERFACE,
+        ABSTRACT = JVM_ACC_ABSTRACT,
+        SYNTHETIC = JVM_ACC_SYNTHETIC,
+        ANNOTATION = JVM_ACC_ANNOTATION,
+        ENUM = JVM_ACC_ENUM,
+        MODULE = JVM_ACC_MODULE,
+    }
+}
+
+access_flags! {
+    /// 字段访问标志，JVMS 4.5
+    FieldAccessFlags {
+        PUBLIC = JVM_ACC_PUBLIC,
+        PRIVATE = JVM_ACC_PRIVATE,
+        PROTECTED = JVM_ACC_PROTECTED,
+        STATIC = JVM_ACC_STATIC,
+        FINAL = JVM_ACC_FINAL,
+        VOLATILE = JVM_ACC_VOLATILE,
+        TRANSIENT = JVM_ACC_TRANSIENT,
+        SYNTHETIC = JVM_ACC_SYNTHETIC,
+        ENUM = JVM_ACC_ENUM,
+    }
+}
+
+access_flags! {
+    /// 方法访问标志，JVMS 4.6
+    MethodAccessFlags {
+        PUBLIC = JVM_ACC_PUBLIC,
+        PRIVATE = JVM_ACC_PRIVATE,
+        PROTECTED = JVM_ACC_PROTECTED,
+        STATIC = JVM_ACC_STATIC,
+        FINAL = JVM_ACC_FINAL,
+        SYNCHRONIZED = JVM_ACC_SYNCHRONIZED,
+        BRIDGE = JVM_ACC_BRIDGE,
+        VARARGS = JVM_ACC_VARARGS,
+        NATIVE = JVM_ACC_NATIVE,
+        ABSTRACT = JVM_ACC_ABSTRACT,
+        STRICT = JVM_ACC_STRICT,
+        SYNTHETIC = JVM_ACC_SYNTHETIC,
+    }
+}
+
+access_flags! {
+    /// InnerClasses属性中的内部类访问标志，JVMS 4.7.6
+    InnerClassAccessFlags {
+        PUBLIC = JVM_ACC_PUBLIC,
+        PRIVATE = JVM_ACC_PRIVATE,
+        PROTECTED = JVM_ACC_PROTECTED,
+        STATIC = JVM_ACC_STATIC,
+        FINAL = JVM_ACC_FINAL,
+        INTERFACE = JVM_ACC_INTERFACE,
+        ABSTRACT = JVM_ACC_ABSTRACT,
+        SYNTHETIC = JVM_ACC_SYNTHETIC,
+        ANNOTATION = JVM_ACC_ANNOTATION,
+        ENUM = JVM_ACC_ENUM,
+    }
+}
+
+#[inline]
+fn illegal_flags(context: &str, flags: &[&str], reason: &str) -> MessageError {
+    MessageError::of(ErrorKind::InvalidAccessFlags, &format!("非法的{}访问标志组合[{}]: {}", context, flags.join(", "), reason))
+}
+
+impl ClassAccessFlags {
+    /// 检查JVMS 4.1中的标志组合约束
+    pub fn validate(&self) -> Result<()> {
+        let names = self.flag_names();
+        let error = |reason| Err(illegal_flags("类", &names, reason));
+        if self.contains(Self::MODULE) {
+            if *self != Self::MODULE {
+                return error("module不能与其他标志同时使用");
+            }
+        } else if self.contains(Self::INTERFACE) {
+            if !self.contains(Self::ABSTRACT) {
+                return error("interface必须同时为abstract");
+            }
+            if self.intersects(Self::FINAL | Self::SUPER | Self::ENUM) {
+                return error("interface不能为final、super或enum");
+            }
+        } else {
+            if self.contains(Self::ANNOTATION) {
+                return error("annotation必须同时为interface");
+            }
+            if self.contains(Self::FINAL | Self::ABSTRACT) {
+                return error("final与abstract不能同时使用");
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Display for ClassAccessFlags {
+    /// 接口隐含abstract，与javap一致不输出
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let flags = if self.contains(Self::INTERFACE) { *self - Self::ABSTRACT } else { *self };
+        flags.write_keywords(f, &[(Self::PUBLIC, "public"), (Self::ABSTRACT, "abstract"), (Self::FINAL, "final")])
+    }
+}
+
+impl FieldAccessFlags {
+    /// 检查JVMS 4.5中的标志组合约束，in_interface表示字段属于接口
+    pub fn validate(&self, in_interface: bool) -> Result<()> {
+        let names = self.flag_names();
+        let error = |reason| Err(illegal_flags("字段", &names, reason));
+        if self.visibility_count() > 1 {
+            return error("public、private、protected至多只能有一个");
+        }
+        if self.contains(Self::FINAL | Self::VOLATILE) {
+            return error("final与volatile不能同时使用");
+        }
+        if in_interface {
+            let required = Self::PUBLIC | Self::STATIC | Self::FINAL;
+            if !self.contains(required) || !(*self - required - Self::SYNTHETIC).is_empty() {
+                return error("接口字段必须为public static final，且只能附加synthetic");
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Display for FieldAccessFlags {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.write_keywords(f, &[
+            (Self::PUBLIC, "public"), (Self::PROTECTED, "protected"), (Self::PRIVATE, "private"),
+            (Self::STATIC, "static"), (Self::FINAL, "final"), (Self::TRANSIENT, "transient"),
+            (Self::VOLATILE, "volatile"),
+        ])
+    }
+}
+
+impl MethodAccessFlags {
+    /// 检查JVMS 4.6中的标志组合约束（class文件版本52及以上），in_interface表示方法属于接口
+    pub fn validate(&self, in_interface: bool) -> Result<()> {
+        let names = self.flag_names();
+        let error = |reason| Err(illegal_flags("方法", &names, reason));
+        if self.visibility_count() > 1 {
+            return error("public、private、protected至多只能有一个");
+        }
+        if in_interface {
+            if self.visibility_count() != 1 || self.contains(Self::PROTECTED) {
+                return error("接口方法必须为public或private之一");
+            }
+            if self.intersects(Self::FINAL | Self::SYNCHRONIZED | Self::NATIVE) {
+                return error("接口方法不能为final、synchronized或native");
+            }
+        }
+        if self.contains(Self::ABSTRACT)
+            && self.intersects(Self::PRIVATE | Self::STATIC | Self::FINAL | Self::SYNCHRONIZED | Self::NATIVE | Self::STRICT) {
+            return error("abstract方法不能为private、static、final、synchronized、native或strict");
+        }
+        Ok(())
+    }
+}
+
+impl Display for MethodAccessFlags {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.write_keywords(f, &[
+            (Self::PUBLIC, "public"), (Self::PROTECTED, "protected"), (Self::PRIVATE, "private"),
+            (Self::ABSTRACT, "abstract"), (Self::STATIC, "static"), (Self::FINAL, "final"),
+            (Self::SYNCHRONIZED, "synchronized"), (Self::NATIVE, "native"), (Self::STRICT, "strictfp"),
+        ])
+    }
+}
+
+impl InnerClassAccessFlags {
+    pub fn validate(&self) -> Result<()> {
+        let names = self.flag_names();
+        let error = |reason| Err(illegal_flags("内部类", &names, reason));
+        if self.visibility_count() > 1 {
+            return error("public、private、protected至多只能有一个");
+        }
+        if self.contains(Self::INTERFACE) {
+            if !self.contains(Self::ABSTRACT) {
+                return error("interface必须同时为abstract");
+            }
+            if self.intersects(Self::FINAL | Self::ENUM) {
+                return error("interface不能为final或enum");
+            }
+        } else if self.contains(Self::ANNOTATION) {
+            return error("annotation必须同时为interface");
+        }
+        if self.contains(Self::FINAL | Self::ABSTRACT) {
+            return error("final与abstract不能同时使用");
+        }
+        Ok(())
+    }
+}
+
+impl Display for InnerClassAccessFlags {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let flags = if self.contains(Self::INTERFACE) { *self - Self::ABSTRACT } else { *self };
+        flags.write_keywords(f, &[
+            (Self::PUBLIC, "public"), (Self::PROTECTED, "protected"), (Self::PRIVATE, "private"),
+            (Self::ABSTRACT, "abstract"), (Self::STATIC, "static"), (Self::FINAL, "final"),
+        ])
+    }
+}
@@ -0,0 +1,266 @@
+use crate::attribute_info::{self, AttributeInfo, ElementValuePair, OriginAttribute};
+use crate::common::constants::*;
+use crate::common::error::{ErrorKind, MessageError, Result};
+use crate::constant_pool::{ConstantPool, ConstantValue};
+
+/// 常量索引已解析的注解，type_name为注解类型的描述符，如Lsample/Sample$Keep;
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedAnnotation {
+    pub type_name: String,
+    pub elements: Vec<(String, ResolvedElementValue)>,
+}
+
+/// 注解元素值，各变体对应element_value的tag
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResolvedElementValue {
+    Byte(i8),
+    Char(u16),
+    Double(f64),
+    Float(f32),
+    Int(i32),
+    Long(i64),
+    Short(i16),
+    Boolean(bool),
+    String(String),
+    /// 枚举类型的描述符及常量名
+    Enum {
+        type_name: String,
+        const_name: String,
+    },
+    /// 返回类型描述符，如Ljava/lang/Object;、[I、V
+    Class(String),
+    Annotation(ResolvedAnnotation),
+    Array(Vec<ResolvedElementValue>),
+}
+
+impl ResolvedAnnotation {
+    /// 没有元素的注解，name为注解类型的内部类名
+    pub fn new(name: &str) -> ResolvedAnnotation {
+        ResolvedAnnotation {
+            type_name: format!("L{};", name),
+            elements: Vec::new(),
+        }
+    }
+
+    /// 注解类型的内部类名，如sample/Sample$Keep
+    pub fn class_name(&self) -> &str {
+        self.type_name.strip_prefix('L')
+            .and_then(|name| name.strip_suffix(';'))
+            .unwrap_or(&self.type_name)
+    }
+
+    /// 显式指定的元素值，未指定时返回None，不会回退到注解类型中AnnotationDefault的默认值
+    pub fn element(&self, name: &str) -> Option<&ResolvedElementValue> {
+        self.elements.iter().find(|(element, _)| element == name).map(|(_, value)| value)
+    }
+
+    pub fn resolve(annotation: &attribute_info::Annotation, pool: &ConstantPool) -> Result<ResolvedAnnotation> {
+        let mut elements = Vec::with_capacity(annotation.element_value_pairs.len());
+        for pair in &annotation.element_value_pairs {
+            elements.push((pool.utf8(pair.name_index)?.to_string(), ResolvedElementValue::resolve(&pair.value, pool)?));
+        }
+        Ok(ResolvedAnnotation {
+            type_name: pool.utf8(annotation.type_index)?.to_string(),
+            elements,
+        })
+    }
+
+    /// 转为属性中的注解结构，所需常量按需加入常量池
+    pub fn to_raw(&self, pool: &mut ConstantPool) -> attribute_info::Annotation {
+        attribute_info::Annotation {
+            type_index: pool.add_utf8(&self.type_name),
+            element_value_pairs: self.elements.iter()
+                .map(|(name, value)| ElementValuePair {
+                    name_index: pool.add_utf8(name),
+                    value: value.to_raw(pool),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl ResolvedElementValue {
+    pub fn resolve(value: &attribute_info::ElementValue, pool: &ConstantPool) -> Result<ResolvedElementValue> {
+        Ok(match value {
+            attribute_info::ElementValue::Const(b's', index) => ResolvedElementValue::String(pool.utf8(*index)?.to_string()),
+            attribute_info::ElementValue::Const(tag, index) => match (tag, pool.get(*index)?) {
+                (b'B', ConstantValue::ConstantInteger(value)) => ResolvedElementValue::Byte(*value as i8),
+                (b'C', ConstantValue::ConstantInteger(value)) => ResolvedElementValue::Char(*value as u16),
+                (b'S', ConstantValue::ConstantInteger(value)) => ResolvedElementValue::Short(*value as i16),
+                (b'Z', ConstantValue::ConstantInteger(value)) => ResolvedElementValue::Boolean(*value != 0),
+                (b'I', ConstantValue::ConstantInteger(value)) => ResolvedElementValue::Int(*value),
+                (b'J', ConstantValue::ConstantLong(value)) => ResolvedElementValue::Long(*value),
+                (b'F', ConstantValue::ConstantFloat(value)) => ResolvedElementValue::Float(*value),
+                (b'D', ConstantValue::ConstantDouble(value)) => ResolvedElementValue::Double(*value),
+                (_, value) => {
+                    let msg = format!("注解元素类型{}与常量[{}]的类型{}不符", *tag as char, index, value.kind_name());
+                    return Err(MessageError::of(ErrorKind::ConstantMismatch, &msg));
+                }
+            },
+            attribute_info::ElementValue::Enum(type_index, name_index) => ResolvedElementValue::Enum {
+                type_name: pool.utf8(*type_index)?.to_string(),
+                const_name: pool.utf8(*name_index)?.to_string(),
+            },
+            attribute_info::ElementValue::Class(index) => ResolvedElementValue::Class(pool.utf8(*index)?.to_string()),
+            attribute_info::ElementValue::Annotation(annotation) => ResolvedElementValue::Annotation(ResolvedAnnotation::resolve(annotation, pool)?),
+            attribute_info::ElementValue::Array(values) => ResolvedElementValue::Array(
+                values.iter().map(|value| ResolvedElementValue::resolve(value, pool)).collect::<Result<_>>()?
+            ),
+        })
+    }
+
+    pub fn to_raw(&self, pool: &mut ConstantPool) -> attribute_info::ElementValue {
+        let integer = |pool: &mut ConstantPool, tag: u8, value: i32| {
+            attribute_info::ElementValue::Const(tag, pool.add_constant(ConstantValue::ConstantInteger(value)))
+        };
+        match self {
+            ResolvedElementValue::Byte(value) => integer(pool, b'B', *value as i32),
+            ResolvedElementValue::Char(value) => integer(pool, b'C', *value as i32),
+            ResolvedElementValue::Short(value) => integer(pool, b'S', *value as i32),
+            ResolvedElementValue::Boolean(value) => integer(pool, b'Z', *value as i32),
+            ResolvedElementValue::Int(value) => integer(pool, b'I', *value),
+            ResolvedElementValue::Long(value) => attribute_info::ElementValue::Const(b'J', pool.add_constant(ConstantValue::ConstantLong(*value))),
+            ResolvedElementValue::Float(value) => attribute_info::ElementValue::Const(b'F', pool.add_constant(ConstantValue::ConstantFloat(*value))),
+            ResolvedElementValue::Double(value) => attribute_info::ElementValue::Const(b'D', pool.add_constant(ConstantValue::ConstantDouble(*value))),
+            ResolvedElementValue::String(value) => attribute_info::ElementValue::Const(b's', pool.add_utf8(value)),
+            ResolvedElementValue::Enum { type_name, const_name } => {
+                attribute_info::ElementValue::Enum(pool.add_utf8(type_name), pool.add_utf8(const_name))
+            }
+            ResolvedElementValue::Class(descriptor) => attribute_info::ElementValue::Class(pool.add_utf8(descriptor)),
+            ResolvedElementValue::Annotation(annotation) => attribute_info::ElementValue::Annotation(annotation.to_raw(pool)),
+            ResolvedElementValue::Array(values) => attribute_info::ElementValue::Array(values.iter().map(|value| value.to_raw(pool)).collect()),
+        }
+    }
+}
+
+fn resolve_all(annotations: &[attribute_info::Annotation], pool: &ConstantPool) -> Result<Vec<ResolvedAnnotation>> {
+    annotations.iter().map(|annotation| ResolvedAnnotation::resolve(annotation, pool)).collect()
+}
+
+/// 列表为空时移除属性，否则替换同名属性
+fn write_attribute(attributes: &mut Vec<OriginAttribute>, pool: &mut ConstantPool, info: Option<AttributeInfo>, name: &str) -> Result<()> {
+    match info {
+        Some(info) => attribute_info::set_attribute(attributes, pool, name, info.to_bytes()?),
+        None => {
+            attribute_info::remove_attribute(attributes, pool, name);
+        }
+    }
+    Ok(())
+}
+
+/// 类、字段或方法上的注解，visible与invisible分别对应RuntimeVisibleAnnotations与RuntimeInvisibleAnnotations
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Annotations {
+    pub visible: Vec<ResolvedAnnotation>,
+    pub invisible: Vec<ResolvedAnnotation>,
+}
+
+impl Annotations {
+    pub fn read(attributes: &[OriginAttribute], pool: &ConstantPool) -> Result<Annotations> {
+        let mut annotations = Annotations::default();
+        for attribute in attributes {
+            match attribute.info(pool)? {
+                AttributeInfo::RuntimeVisibleAnnotations(list) => annotations.visible.extend(resolve_all(list, pool)?),
+                AttributeInfo::RuntimeInvisibleAnnotations(list) => annotations.invisible.extend(resolve_all(list, pool)?),
+                _ => {}
+            }
+        }
+        Ok(annotations)
+    }
+
+    /// 替换attributes中的注解属性，没有注解的一侧移除对应属性
+    pub fn write(&self, attributes: &mut Vec<OriginAttribute>, pool: &mut ConstantPool) -> Result<()> {
+        let visible = (!self.visible.is_empty())
+            .then(|| AttributeInfo::RuntimeVisibleAnnotations(self.visible.iter().map(|annotation| annotation.to_raw(pool)).collect()));
+        write_attribute(attributes, pool, visible, RUNTIME_VISIBLE_ANNOTATIONS_VISIBLE_TAG)?;
+        let invisible = (!self.invisible.is_empty())
+            .then(|| AttributeInfo::RuntimeInvisibleAnnotations(self.invisible.iter().map(|annotation| annotation.to_raw(pool)).collect()));
+        write_attribute(attributes, pool, invisible, RUNTIME_INVISIBLE_ANNOTATIONS_INVISIBLE_TAG)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.visible.is_empty() && self.invisible.is_empty()
+    }
+
+    /// 先可见后不可见
+    pub fn iter(&self) -> impl Iterator<Item = &ResolvedAnnotation> {
+        self.visible.iter().chain(&self.invisible)
+    }
+
+    /// 按注解类型的内部类名查找，如sample/Sample$Keep
+    pub fn find(&self, class_name: &str) -> Option<&ResolvedAnnotation> {
+        self.iter().find(|annotation| annotation.class_name() == class_name)
+    }
+
+    /// 是否带有指定类型的注解，常用于@Keep等标记注解
+    pub fn contains(&self, class_name: &str) -> bool {
+        self.find(class_name).is_some()
+    }
+}
+
+/// 方法参数上的注解，按参数顺序排列，visible与invisible分别对应
+/// RuntimeVisibleParameterAnnotations与RuntimeInvisibleParameterAnnotations
+///
+/// 参数数量由属性决定，可能少于描述符中的参数数量（如编译器省略的合成参数）
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ParameterAnnotations {
+    pub visible: Vec<Vec<ResolvedAnnotation>>,
+    pub invisible: Vec<Vec<ResolvedAnnotation>>,
+}
+
+impl ParameterAnnotations {
+    pub fn read(attributes: &[OriginAttribute], pool: &ConstantPool) -> Result<ParameterAnnotations> {
+        let mut annotations = ParameterAnnotations::default();
+        for attribute in attributes {
+            match attribute.info(pool)? {
+                AttributeInfo::RuntimeVisibleParameterAnnotations(parameters) => {
+                    annotations.visible = parameters.iter().map(|list| resolve_all(list, pool)).collect::<Result<_>>()?;
+                }
+                AttributeInfo::RuntimeInvisibleParameterAnnotations(parameters) => {
+                    annotations.invisible = parameters.iter().map(|list| resolve_all(list, pool)).collect::<Result<_>>()?;
+                }
+                _ => {}
+            }
+        }
+        Ok(annotations)
+    }
+
+    /// 替换attributes中的参数注解属性，所有参数都没有注解的一侧移除对应属性
+    pub fn write(&self, attributes: &mut Vec<OriginAttribute>, pool: &mut ConstantPool) -> Result<()> {
+        let to_raw = |parameters: &[Vec<ResolvedAnnotation>], pool: &mut ConstantPool| -> Option<Vec<Vec<attribute_info::Annotation>>> {
+            parameters.iter().any(|list| !list.is_empty()).then(|| parameters.iter()
+                .map(|list| list.iter().map(|annotation| annotation.to_raw(pool)).collect())
+                .collect())
+        };
+        let visible = to_raw(&self.visible, pool).map(AttributeInfo::RuntimeVisibleParameterAnnotations);
+        write_attribute(attributes, pool, visible, RUNTIME_VISIBLE_PARAMETER_ANNOTATIONS_VISIBLE_TAG)?;
+        let invisible = to_raw(&self.invisible, pool).map(AttributeInfo::RuntimeInvisibleParameterAnnotations);
+        write_attribute(attributes, pool, invisible, RUNTIME_INVISIBLE_PARAMETER_ANNOTATIONS_INVISIBLE_TAG)
+    }
+
+    /// 第index个参数上的注解，先可见后不可见
+    pub fn parameter(&self, index: usize) -> impl Iterator<Item = &ResolvedAnnotation> {
+        self.visible.get(index).into_iter().flatten()
+            .chain(self.invisible.get(index).into_iter().flatten())
+    }
+
+    pub fn contains(&self, index: usize, class_name: &str) -> bool {
+        self.parameter(index).any(|annotation| annotation.class_name() == class_name)
+    }
+}
+
+/// 注解类型方法上AnnotationDefault属性的默认值
+pub fn read_default(attributes: &[OriginAttribute], pool: &ConstantPool) -> Result<Option<ResolvedElementValue>> {
+    for attribute in attributes {
+        if let AttributeInfo::AnnotationDefault(value) = attribute.info(pool)? {
+            return Ok(Some(ResolvedElementValue::resolve(value, pool)?));
+        }
+    }
+    Ok(None)
+}
+
+/// 设置或移除（value为None时）AnnotationDefault属性
+pub fn write_default(attributes: &mut Vec<OriginAttribute>, pool: &mut ConstantPool, value: Option<&ResolvedElementValue>) -> Result<()> {
+    let info = value.map(|value| AttributeInfo::AnnotationDefault(value.to_raw(pool)));
+    write_attribute(attributes, pool, info, ANNOTATION_DEFAULT_TAG)
+}
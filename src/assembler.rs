@@ -0,0 +1,1166 @@
+use crate::access_flags::{ClassAccessFlags, FieldAccessFlags, MethodAccessFlags};
+use crate::attribute_info::{AttributeInfo, BootstrapMethod, CodeAttribute, OriginAttribute};
+use crate::classfile_constants::*;
+use crate::common::constants::*;
+use crate::common::error::{ErrorKind, MessageError, Result};
+use crate::common::opcode::{array_type, opcode_by_name, opcode_name, opcodes::*};
+use crate::constant_pool::{ConstantPool, ConstantValue, MemberRef};
+use crate::descriptor::MethodDescriptor;
+use crate::field_info::FieldInfo;
+use crate::instruction::{Instruction, Instructions};
+use crate::instruction_list::{Label, MethodBody};
+use crate::jclass_info::{JClassInfo, DEFAULT_MAJOR_VERSION, JCLASS_MAGIC};
+use crate::method_info::MethodInfo;
+use crate::stack_map::{regenerate_stack_map_table, ClassHierarchy, SimpleClassHierarchy};
+use crate::util::mutf8;
+use crate::verifier::STACK_MAP_MIN_VERSION;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt::Write;
+
+/// 将Jasmin风格的汇编文本编译为JClassInfo，常量池按需分配
+///
+/// 文本按行解析，位于行首或空白之后的;开始注释；字符串使用双引号，支持\n、\t、\r、\"、\\、\uXXXX转义，
+/// 以及直接写出MUTF-8字节的\xHH转义，用于表示孤立代理项等无法解码的字符串。
+/// 类名与描述符均为内部形式，字段引用写作owner/name descriptor，方法引用写作owner/name(descriptor)
+///
+/// ```text
+/// .version 52 0
+/// .class public super sample/Hello
+/// .super java/lang/Object
+/// .implements java/lang/Runnable
+/// .source Hello.java
+///
+/// .field private static final GREETING Ljava/lang/String; = "hello"
+///
+/// .method public run()V
+///     .limit stack 2
+///     .throws java/lang/IllegalStateException
+/// Start:
+///     .line 10
+///     getstatic java/lang/System/out Ljava/io/PrintStream;
+///     ldc "hello"
+///     invokevirtual java/io/PrintStream/println(Ljava/lang/String;)V
+/// End:
+///     return
+/// Handler:
+///     athrow
+///     .catch java/lang/RuntimeException from Start to End using Handler
+/// .end method
+/// ```
+///
+/// - 省略.version时为52.0，省略.super时父类为java/lang/Object，.super none表示没有父类
+/// - 常量写作42、10L、1.5f、1.5d、"text"、class X、methodtype (I)V或handle invokestatic owner/name(desc)，
+///   引用接口方法的invokestatic、invokespecial及方法句柄在成员前加interface
+/// - tableswitch low之后逐行列出目标标签，lookupswitch之后逐行写出key : label，均以default : label结束
+/// - invokedynamic写作name(desc)后接引导方法的引用类型、成员及静态参数，引导方法自动加入BootstrapMethods
+/// - 局部变量索引或iinc增量超出范围时自动使用wide，ldc的常量索引超过255时自动使用ldc_w
+/// - .signature在方法中作用于方法，紧随.field之后作用于该字段，否则作用于类
+/// - 省略.limit时通过数据流分析计算
+/// - 版本不低于50时为各方法生成StackMapTable，类层次中只登记了汇编出的类，
+///   分支合并处出现不同引用类型时，应使用assemble_with_hierarchy提供准确的类层次
+///
+/// 错误信息以行号开头，语法错误的类型为ErrorKind::Syntax
+pub fn assemble(text: &str) -> Result<JClassInfo> {
+    let class = parse(text)?;
+    let mut hierarchy = SimpleClassHierarchy::new();
+    hierarchy.add_class(&class)?;
+    with_stack_map_tables(class, &hierarchy)
+}
+
+/// 同assemble，生成StackMapTable时使用指定的类层次
+pub fn assemble_with_hierarchy(text: &str, hierarchy: &dyn ClassHierarchy) -> Result<JClassInfo> {
+    with_stack_map_tables(parse(text)?, hierarchy)
+}
+
+fn parse(text: &str) -> Result<JClassInfo> {
+    let mut assembler = Assembler::new();
+    for (i, line) in text.lines().enumerate() {
+        assembler.line(line).map_err(|e| MessageError { msg: format!("第{}行: {}", i + 1, e.msg), ..e })?;
+    }
+    assembler.finish()
+}
+
+fn with_stack_map_tables(mut class: JClassInfo, hierarchy: &dyn ClassHierarchy) -> Result<JClassInfo> {
+    if class.major_version >= STACK_MAP_MIN_VERSION {
+        for i in 0..class.methods.len() {
+            regenerate_stack_map_table(&mut class, i, hierarchy)?;
+        }
+    }
+    Ok(class)
+}
+
+/// 将类输出为assemble可接受的文本
+///
+/// 仅输出汇编语法能表示的内容：类头、字段及ConstantValue、方法及Code、Exceptions、Signature、SourceFile、
+/// LineNumberTable与BootstrapMethods，其余属性被忽略。跳转目标及异常范围以L加pc命名
+pub fn to_assembly(class: &JClassInfo) -> Result<String> {
+    Emitter { class, pool: &class.constant_pool, out: String::new() }.run()
+}
+
+fn syntax_error(msg: &str) -> MessageError {
+    MessageError::of(ErrorKind::Syntax, msg)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Str(String),
+    /// 无法按MUTF-8解码的字符串字面量
+    Raw(Vec<u8>),
+    /// 单独或位于单词末尾的冒号，用于标签定义与switch分支
+    Colon,
+}
+
+fn tokenize(line: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = line.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == ';' {
+            break;
+        } else if c == ':' {
+            chars.next();
+            tokens.push(Token::Colon);
+        } else if c == '"' {
+            chars.next();
+            let bytes = read_string(&mut chars)?;
+            tokens.push(match mutf8::decode(&bytes) {
+                Some(value) => Token::Str(value),
+                None => Token::Raw(bytes),
+            });
+        } else {
+            let mut word = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() {
+                    break;
+                }
+                word.push(c);
+                chars.next();
+            }
+            if word.len() > 1 && word.ends_with(':') {
+                word.pop();
+                tokens.push(Token::Word(word));
+                tokens.push(Token::Colon);
+            } else {
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// 读取至结束引号，返回MUTF-8编码的字节
+fn read_string(chars: &mut impl Iterator<Item = char>) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    let mut push = |bytes: &mut Vec<u8>, c: char| bytes.extend_from_slice(&mutf8::encode(c.encode_utf8(&mut [0; 4])));
+    loop {
+        match chars.next().ok_or_else(|| syntax_error("字符串缺少结束引号"))? {
+            '"' => return Ok(bytes),
+            '\\' => match chars.next().ok_or_else(|| syntax_error("字符串缺少结束引号"))? {
+                'n' => bytes.push(b'\n'),
+                't' => bytes.push(b'\t'),
+                'r' => bytes.push(b'\r'),
+                'b' => bytes.push(0x08),
+                'f' => bytes.push(0x0c),
+                '"' => bytes.push(b'"'),
+                '\'' => bytes.push(b'\''),
+                '\\' => bytes.push(b'\\'),
+                'x' => {
+                    let hex: String = chars.take(2).collect();
+                    bytes.push(u8::from_str_radix(&hex, 16).map_err(|_| syntax_error(&format!("无效的转义\\x{}", hex)))?);
+                }
+                'u' => {
+                    let hex: String = chars.take(4).collect();
+                    let code = u16::from_str_radix(&hex, 16).map_err(|_| syntax_error(&format!("无效的转义\\u{}", hex)))?;
+                    match char::from_u32(code as u32) {
+                        Some(c) => push(&mut bytes, c),
+                        // 代理项按单个UTF-16码元编码，成对出现时即为补充平面字符的MUTF-8形式
+                        None => bytes.extend_from_slice(&[0xE0 | (code >> 12) as u8, 0x80 | (code >> 6 & 0x3F) as u8, 0x80 | (code & 0x3F) as u8]),
+                    }
+                }
+                c => return Err(syntax_error(&format!("无效的转义\\{}", c))),
+            },
+            c => push(&mut bytes, c),
+        }
+    }
+}
+
+/// 与read_string对应的字符串字面量
+fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => quoted.push_str("\\r"),
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if c.is_control() => {
+                let _ = write!(quoted, "\\u{:04x}", c as u32);
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// 无法解码的字符串，可打印的ASCII字符原样输出，其余字节以\xHH输出
+fn quote_bytes(bytes: &[u8]) -> String {
+    let mut quoted = String::with_capacity(bytes.len() + 2);
+    quoted.push('"');
+    for &byte in bytes {
+        match byte {
+            b'"' | b'\\' => {
+                quoted.push('\\');
+                quoted.push(byte as char);
+            }
+            0x20..=0x7E => quoted.push(byte as char),
+            _ => {
+                let _ = write!(quoted, "\\x{:02x}", byte);
+            }
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// 一行的词法单元，按顺序读取
+struct Tokens {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Tokens {
+    fn next(&mut self, what: &str) -> Result<Token> {
+        let token = self.tokens.get(self.pos).cloned().ok_or_else(|| syntax_error(&format!("缺少{}", what)))?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn word(&mut self, what: &str) -> Result<String> {
+        match self.next(what)? {
+            Token::Word(word) => Ok(word),
+            token => Err(syntax_error(&format!("{}应为单词，实际为{:?}", what, token))),
+        }
+    }
+
+    fn number<T: std::str::FromStr>(&mut self, what: &str) -> Result<T> {
+        let word = self.word(what)?;
+        word.parse().map_err(|_| syntax_error(&format!("{}不是有效的数值: {}", what, word)))
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    /// 下一个单词为keyword时跳过并返回true
+    fn accept(&mut self, keyword: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Word(word)) if word == keyword) {
+            self.pos += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect(&mut self, keyword: &str) -> Result<()> {
+        if self.accept(keyword) {
+            return Ok(());
+        }
+        Err(syntax_error(&format!("缺少{}", keyword)))
+    }
+
+    fn colon(&mut self) -> Result<()> {
+        match self.next(":")? {
+            Token::Colon => Ok(()),
+            token => Err(syntax_error(&format!("应为:，实际为{:?}", token))),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos >= self.tokens.len()
+    }
+
+    fn finish(&self) -> Result<()> {
+        match self.peek() {
+            Some(token) => Err(syntax_error(&format!("多余的内容{:?}", token))),
+            None => Ok(()),
+        }
+    }
+}
+
+enum PendingSwitch {
+    Table { low: i32, targets: Vec<Label> },
+    Lookup { pairs: Vec<(i32, Label)> },
+}
+
+/// 正在汇编的方法
+struct MethodState {
+    index: usize,
+    body: MethodBody,
+    labels: HashMap<String, Label>,
+    placed: HashSet<String>,
+    switch: Option<PendingSwitch>,
+}
+
+impl MethodState {
+    fn label(&mut self, name: &str) -> Label {
+        if let Some(label) = self.labels.get(name) {
+            return *label;
+        }
+        let label = self.body.list.new_label();
+        self.labels.insert(name.to_string(), label);
+        label
+    }
+}
+
+struct Assembler {
+    class: JClassInfo,
+    declared: bool,
+    super_class: Option<String>,
+    source: Option<u16>,
+    signature: Option<u16>,
+    bootstrap_methods: Vec<BootstrapMethod>,
+    method: Option<MethodState>,
+    /// 上一行是否为.field，决定.signature的作用对象
+    after_field: bool,
+}
+
+impl Assembler {
+    fn new() -> Assembler {
+        Assembler {
+            class: JClassInfo {
+                magic: JCLASS_MAGIC,
+                major_version: DEFAULT_MAJOR_VERSION,
+                constant_pool: ConstantPool::new(1),
+                ..Default::default()
+            },
+            declared: false,
+            super_class: Some("java/lang/Object".to_string()),
+            source: None,
+            signature: None,
+            bootstrap_methods: Vec::new(),
+            method: None,
+            after_field: false,
+        }
+    }
+
+    fn pool(&mut self) -> &mut ConstantPool {
+        &mut self.class.constant_pool
+    }
+
+    fn line(&mut self, line: &str) -> Result<()> {
+        let mut tokens = Tokens { tokens: tokenize(line)?, pos: 0 };
+        if tokens.is_empty() {
+            return Ok(());
+        }
+        if self.method.as_ref().is_some_and(|method| method.switch.is_some()) {
+            if matches!(tokens.peek(), Some(Token::Word(word)) if word.starts_with('.')) {
+                return Err(syntax_error("switch缺少default分支"));
+            }
+            return self.switch_case(&mut tokens);
+        }
+        let after_field = std::mem::take(&mut self.after_field);
+        let first = match tokens.peek() {
+            Some(Token::Word(word)) => word.clone(),
+            Some(token) => return Err(syntax_error(&format!("无法识别的内容{:?}", token))),
+            None => return Ok(()),
+        };
+        if !first.starts_with('.') {
+            return self.code_line(&mut tokens);
+        }
+        tokens.pos += 1;
+        match first.as_str() {
+            ".version" => {
+                self.class.major_version = tokens.number("主版本号")?;
+                if !tokens.is_empty() {
+                    self.class.minor_version = tokens.number("次版本号")?;
+                }
+            }
+            ".class" => self.class_header(&mut tokens)?,
+            ".super" => {
+                let name = tokens.word("父类名")?;
+                self.super_class = if name == "none" { None } else { Some(name) };
+            }
+            ".implements" => {
+                let name = tokens.word("接口名")?;
+                let index = self.pool().add_class(&name);
+                self.class.interfaces.push(index);
+            }
+            ".source" => {
+                let name = match tokens.next("源文件名")? {
+                    Token::Word(name) | Token::Str(name) => name,
+                    _ => return Err(syntax_error("源文件名无效")),
+                };
+                self.source = Some(self.pool().add_utf8(&name));
+            }
+            ".signature" => {
+                let signature = match tokens.next("签名")? {
+                    Token::Word(signature) | Token::Str(signature) => signature,
+                    _ => return Err(syntax_error("签名无效")),
+                };
+                let index = self.pool().add_utf8(&signature);
+                if let Some(method) = &mut self.method {
+                    method.body.signature = Some(index);
+                } else if after_field {
+                    let attribute = AttributeInfo::Signature(index).to_origin(self.pool())?;
+                    if let Some(field) = self.class.fields.last_mut() {
+                        field.attributes.push(attribute);
+                    }
+                } else {
+                    self.signature = Some(index);
+                }
+            }
+            ".field" => {
+                self.field(&mut tokens)?;
+                self.after_field = true;
+            }
+            ".method" => self.method(&mut tokens)?,
+            ".limit" | ".throws" | ".catch" | ".line" | ".end" => self.method_directive(&first, &mut tokens)?,
+            _ => return Err(syntax_error(&format!("未知指示{}", first))),
+        }
+        tokens.finish()
+    }
+
+    fn class_header(&mut self, tokens: &mut Tokens) -> Result<()> {
+        if self.declared {
+            return Err(syntax_error("重复的.class"));
+        }
+        let mut words = Vec::new();
+        while !tokens.is_empty() {
+            words.push(tokens.word("类名")?);
+        }
+        let name = words.pop().ok_or_else(|| syntax_error("缺少类名"))?;
+        let mut access = ClassAccessFlags::empty();
+        for word in &words {
+            access |= ClassAccessFlags::from_flag_name(word).ok_or_else(|| syntax_error(&format!("未知的类访问标志{}", word)))?;
+        }
+        self.class.access_flags = access.bits();
+        self.class.class_index = self.pool().add_class(&name);
+        self.declared = true;
+        Ok(())
+    }
+
+    fn field(&mut self, tokens: &mut Tokens) -> Result<()> {
+        let mut words = Vec::new();
+        let mut has_value = false;
+        while !tokens.is_empty() {
+            if tokens.accept("=") {
+                has_value = true;
+                break;
+            }
+            words.push(tokens.word("字段")?);
+        }
+        if words.len() < 2 {
+            return Err(syntax_error("缺少字段名或描述符"));
+        }
+        let descriptor = words.pop().unwrap_or_default();
+        let name = words.pop().unwrap_or_default();
+        let mut access = FieldAccessFlags::empty();
+        for word in &words {
+            access |= FieldAccessFlags::from_flag_name(word).ok_or_else(|| syntax_error(&format!("未知的字段访问标志{}", word)))?;
+        }
+        let mut field = FieldInfo {
+            access_flags: access.bits(),
+            name: self.pool().add_utf8(&name),
+            descriptor: self.pool().add_utf8(&descriptor),
+            attributes: Vec::new(),
+        };
+        if has_value {
+            let index = self.field_value(&descriptor, tokens)?;
+            field.attributes.push(AttributeInfo::ConstantValue(index).to_origin(self.pool())?);
+        }
+        self.class.fields.push(field);
+        Ok(())
+    }
+
+    /// ConstantValue的常量，整数字面量按描述符转换为long、float或double
+    fn field_value(&mut self, descriptor: &str, tokens: &mut Tokens) -> Result<u16> {
+        let value = match tokens.next("字段初始值")? {
+            Token::Str(value) if descriptor == "Ljava/lang/String;" => return Ok(self.pool().add_string(&value)),
+            Token::Raw(bytes) if descriptor == "Ljava/lang/String;" => return Ok(self.raw_string(bytes)),
+            Token::Word(word) => parse_number(&word)?,
+            token => return Err(syntax_error(&format!("字段初始值无效{:?}", token))),
+        };
+        let value = match (descriptor, value) {
+            ("I" | "S" | "C" | "B" | "Z", value @ ConstantValue::ConstantInteger(_)) => value,
+            ("J", ConstantValue::ConstantInteger(value)) => ConstantValue::ConstantLong(value as i64),
+            ("F", ConstantValue::ConstantInteger(value)) => ConstantValue::ConstantFloat(value as f32),
+            ("D", ConstantValue::ConstantInteger(value)) => ConstantValue::ConstantDouble(value as f64),
+            ("J", value @ ConstantValue::ConstantLong(_)) |
+            ("F", value @ ConstantValue::ConstantFloat(_)) |
+            ("D", value @ ConstantValue::ConstantDouble(_)) => value,
+            (descriptor, value) => {
+                return Err(syntax_error(&format!("{}类型的字段不能使用{}常量", descriptor, value.kind_name())));
+            }
+        };
+        Ok(self.pool().add_constant(value))
+    }
+
+    fn method(&mut self, tokens: &mut Tokens) -> Result<()> {
+        if self.method.is_some() {
+            return Err(syntax_error("上一个方法缺少.end method"));
+        }
+        let mut words = Vec::new();
+        while !tokens.is_empty() {
+            words.push(tokens.word("方法")?);
+        }
+        let name_descriptor = words.pop().ok_or_else(|| syntax_error("缺少方法名"))?;
+        let split = name_descriptor.find('(').ok_or_else(|| syntax_error(&format!("缺少方法描述符: {}", name_descriptor)))?;
+        let (name, descriptor) = name_descriptor.split_at(split);
+        let mut access = MethodAccessFlags::empty();
+        for word in &words {
+            access |= MethodAccessFlags::from_flag_name(word).ok_or_else(|| syntax_error(&format!("未知的方法访问标志{}", word)))?;
+        }
+        let method = MethodInfo {
+            access_flags: access.bits(),
+            name: self.pool().add_utf8(name),
+            descriptor: self.pool().add_utf8(descriptor),
+            attributes: Vec::new(),
+        };
+        self.class.methods.push(method);
+        self.method = Some(MethodState {
+            index: self.class.methods.len() - 1,
+            body: MethodBody::new(),
+            labels: HashMap::new(),
+            placed: HashSet::new(),
+            switch: None,
+        });
+        Ok(())
+    }
+
+    fn method_directive(&mut self, directive: &str, tokens: &mut Tokens) -> Result<()> {
+        if directive == ".end" {
+            tokens.expect("method")?;
+            return self.end_method();
+        }
+        let mut method = self.method.take().ok_or_else(|| syntax_error(&format!("{}只能用于方法中", directive)))?;
+        let result = self.method_directive_in(&mut method, directive, tokens);
+        self.method = Some(method);
+        result
+    }
+
+    fn method_directive_in(&mut self, method: &mut MethodState, directive: &str, tokens: &mut Tokens) -> Result<()> {
+        match directive {
+            ".limit" => {
+                let kind = tokens.word("stack或locals")?;
+                let value = tokens.number(".limit的值")?;
+                match kind.as_str() {
+                    "stack" => method.body.max_stack = Some(value),
+                    "locals" => method.body.max_locals = Some(value),
+                    _ => return Err(syntax_error(&format!("未知的.limit类型{}", kind))),
+                }
+            }
+            ".throws" => {
+                let name = tokens.word("异常类名")?;
+                method.body.throws.push(self.pool().add_class(&name));
+            }
+            ".catch" => {
+                let name = tokens.word("异常类名")?;
+                let catch_type = if name == "all" { 0 } else { self.pool().add_class(&name) };
+                tokens.expect("from")?;
+                let start = method.label(&tokens.word("起始标签")?);
+                tokens.expect("to")?;
+                let end = method.label(&tokens.word("结束标签")?);
+                tokens.expect("using")?;
+                let handler = method.label(&tokens.word("处理标签")?);
+                method.body.list.add_exception_handler(start, end, handler, catch_type);
+            }
+            _ => {
+                let line = tokens.number("行号")?;
+                let label = method.body.list.new_label();
+                method.body.list.place_label(label);
+                method.body.lines.push((label, line));
+            }
+        }
+        Ok(())
+    }
+
+    /// 标签定义及指令
+    fn code_line(&mut self, tokens: &mut Tokens) -> Result<()> {
+        let mut method = self.method.take().ok_or_else(|| syntax_error("指令只能用于方法中"))?;
+        let result = self.code_line_in(&mut method, tokens);
+        self.method = Some(method);
+        result
+    }
+
+    fn code_line_in(&mut self, method: &mut MethodState, tokens: &mut Tokens) -> Result<()> {
+        if tokens.tokens.get(1) == Some(&Token::Colon) {
+            let name = tokens.word("标签")?;
+            tokens.colon()?;
+            if !method.placed.insert(name.clone()) {
+                return Err(syntax_error(&format!("标签{}重复定义", name)));
+            }
+            let label = method.label(&name);
+            method.body.list.place_label(label);
+            if tokens.is_empty() {
+                return Ok(());
+            }
+        }
+        let mnemonic = tokens.word("指令")?;
+        let opcode = opcode_by_name(&mnemonic).ok_or_else(|| syntax_error(&format!("未知指令{}", mnemonic)))?;
+        let instruction = match opcode {
+            BIPUSH => {
+                let value: i32 = tokens.number("bipush的值")?;
+                Instruction::Bipush(i8::try_from(value).map_err(|_| syntax_error(&format!("bipush的值{}超出范围", value)))?)
+            }
+            SIPUSH => {
+                let value: i32 = tokens.number("sipush的值")?;
+                Instruction::Sipush(i16::try_from(value).map_err(|_| syntax_error(&format!("sipush的值{}超出范围", value)))?)
+            }
+            LDC | LDC_W | LDC2_W => {
+                let index = self.constant(tokens)?;
+                let wide = matches!(self.pool().get(index)?, ConstantValue::ConstantLong(_) | ConstantValue::ConstantDouble(_));
+                match opcode {
+                    LDC2_W if wide => Instruction::Ldc2W(index),
+                    LDC2_W => return Err(syntax_error("ldc2_w只能加载long或double常量")),
+                    _ if wide => return Err(syntax_error(&format!("{}不能加载long或double常量，应使用ldc2_w", mnemonic))),
+                    LDC if index <= u8::MAX as u16 => Instruction::Ldc(index),
+                    _ => Instruction::LdcW(index),
+                }
+            }
+            ILOAD..=ALOAD | ISTORE..=ASTORE | RET => {
+                let index: u16 = tokens.number("局部变量索引")?;
+                match u8::try_from(index) {
+                    Ok(index) => Instruction::Local(opcode, index),
+                    Err(_) => Instruction::WideLocal(opcode, index),
+                }
+            }
+            IINC => {
+                let index: u16 = tokens.number("局部变量索引")?;
+                let value: i16 = tokens.number("iinc的增量")?;
+                match (u8::try_from(index), i8::try_from(value)) {
+                    (Ok(index), Ok(value)) => Instruction::Iinc(index, value),
+                    _ => Instruction::WideIinc(index, value),
+                }
+            }
+            IFEQ..=JSR | IFNULL | IFNONNULL | GOTO_W | JSR_W => {
+                let label = method.label(&tokens.word("跳转目标")?);
+                method.body.list.jump(opcode, label);
+                return tokens.finish();
+            }
+            TABLESWITCH => {
+                method.switch = Some(PendingSwitch::Table { low: tokens.number("tableswitch的low")?, targets: Vec::new() });
+                return tokens.finish();
+            }
+            LOOKUPSWITCH => {
+                method.switch = Some(PendingSwitch::Lookup { pairs: Vec::new() });
+                return tokens.finish();
+            }
+            GETSTATIC..=PUTFIELD => {
+                let member = tokens.word("字段引用")?;
+                let (owner, name) = split_member(&member)?;
+                let descriptor = tokens.word("字段描述符")?;
+                Instruction::Field(opcode, self.pool().add_fieldref(owner, name, &descriptor))
+            }
+            INVOKEVIRTUAL..=INVOKESTATIC => {
+                let interface = tokens.accept("interface");
+                let member = tokens.word("方法引用")?;
+                let (owner, name, descriptor) = split_method(&member)?;
+                let index = if interface {
+                    self.pool().add_interface_methodref(owner, name, descriptor)
+                } else {
+                    self.pool().add_methodref(owner, name, descriptor)
+                };
+                Instruction::Invoke(opcode, index)
+            }
+            INVOKEINTERFACE => {
+                let member = tokens.word("方法引用")?;
+                let (owner, name, descriptor) = split_method(&member)?;
+                let count = if tokens.is_empty() {
+                    u8::try_from(MethodDescriptor::parse(descriptor)?.argument_slots(false))
+                        .map_err(|_| syntax_error("invokeinterface的参数过多"))?
+                } else {
+                    tokens.number("invokeinterface的count")?
+                };
+                Instruction::InvokeInterface(self.pool().add_interface_methodref(owner, name, descriptor), count)
+            }
+            INVOKEDYNAMIC => {
+                let name_descriptor = tokens.word("调用点名称及描述符")?;
+                let split = name_descriptor.find('(').ok_or_else(|| syntax_error(&format!("缺少方法描述符: {}", name_descriptor)))?;
+                let (name, descriptor) = name_descriptor.split_at(split);
+                let method_ref = self.method_handle(tokens)?;
+                let mut arguments = Vec::new();
+                while !tokens.is_empty() {
+                    arguments.push(self.constant(tokens)?);
+                }
+                let bootstrap = self.bootstrap_method(method_ref, arguments)?;
+                Instruction::InvokeDynamic(self.pool().add_invoke_dynamic(bootstrap, name, descriptor))
+            }
+            NEW | ANEWARRAY | CHECKCAST | INSTANCEOF => {
+                let name = tokens.word("类名")?;
+                Instruction::Type(opcode, self.pool().add_class(&name))
+            }
+            NEWARRAY => {
+                let name = tokens.word("数组元素类型")?;
+                Instruction::NewArray(array_type_by_name(&name).ok_or_else(|| syntax_error(&format!("未知的数组元素类型{}", name)))?)
+            }
+            MULTIANEWARRAY => {
+                let name = tokens.word("数组类型")?;
+                let dimensions = tokens.number("维数")?;
+                Instruction::MultiANewArray(self.pool().add_class(&name), dimensions)
+            }
+            WIDE => return Err(syntax_error("wide由局部变量索引自动生成，不能直接使用")),
+            _ => Instruction::Simple(opcode),
+        };
+        method.body.list.push(instruction);
+        tokens.finish()
+    }
+
+    /// tableswitch与lookupswitch之后的分支行
+    fn switch_case(&mut self, tokens: &mut Tokens) -> Result<()> {
+        let Some(method) = self.method.as_mut() else { return Ok(()) };
+        if tokens.accept("default") {
+            tokens.colon()?;
+            let default = method.label(&tokens.word("default目标")?);
+            match method.switch.take() {
+                Some(PendingSwitch::Table { low, targets }) => method.body.list.table_switch(low, default, targets),
+                Some(PendingSwitch::Lookup { pairs }) => method.body.list.lookup_switch(default, pairs),
+                None => return Ok(()),
+            };
+            return tokens.finish();
+        }
+        match method.switch.as_mut() {
+            Some(PendingSwitch::Table { .. }) => {
+                let label = method.label(&tokens.word("分支目标")?);
+                if let Some(PendingSwitch::Table { targets, .. }) = method.switch.as_mut() {
+                    targets.push(label);
+                }
+            }
+            Some(PendingSwitch::Lookup { .. }) => {
+                let key = tokens.number("分支的key")?;
+                tokens.colon()?;
+                let label = method.label(&tokens.word("分支目标")?);
+                if let Some(PendingSwitch::Lookup { pairs }) = method.switch.as_mut() {
+                    pairs.push((key, label));
+                }
+            }
+            None => {}
+        }
+        tokens.finish()
+    }
+
+    fn end_method(&mut self) -> Result<()> {
+        let method = self.method.take().ok_or_else(|| syntax_error(".end method之前没有.method"))?;
+        if method.switch.is_some() {
+            return Err(syntax_error("switch缺少default分支"));
+        }
+        let mut undefined: Vec<_> = method.labels.keys().filter(|name| !method.placed.contains(*name)).cloned().collect();
+        if !undefined.is_empty() {
+            undefined.sort();
+            return Err(syntax_error(&format!("标签{}未定义", undefined.join(", "))));
+        }
+        let info = &self.class.methods[method.index];
+        let descriptor = MethodDescriptor::parse(self.class.constant_pool.utf8(info.descriptor)?)?;
+        let argument_slots = descriptor.argument_slots(info.access().contains(MethodAccessFlags::STATIC));
+        let attributes = method.body.into_attributes(self.pool(), argument_slots)?;
+        self.class.methods[method.index].attributes = attributes;
+        Ok(())
+    }
+
+    /// 常量字面量，见assemble的说明
+    fn constant(&mut self, tokens: &mut Tokens) -> Result<u16> {
+        match tokens.next("常量")? {
+            Token::Str(value) => Ok(self.pool().add_string(&value)),
+            Token::Raw(bytes) => Ok(self.raw_string(bytes)),
+            Token::Word(word) => match word.as_str() {
+                "class" => {
+                    let name = tokens.word("类名")?;
+                    Ok(self.pool().add_class(&name))
+                }
+                "methodtype" => {
+                    let descriptor = tokens.word("方法描述符")?;
+                    Ok(self.pool().add_method_type(&descriptor))
+                }
+                "handle" => self.method_handle(tokens),
+                _ => {
+                    let value = parse_number(&word)?;
+                    Ok(self.pool().add_constant(value))
+                }
+            },
+            Token::Colon => Err(syntax_error("缺少常量")),
+        }
+    }
+
+    fn raw_string(&mut self, bytes: Vec<u8>) -> u16 {
+        let utf8 = self.pool().add_constant(ConstantValue::ConstantUtf8Raw(bytes));
+        self.pool().add_constant(ConstantValue::ConstantString(utf8))
+    }
+
+    /// 引用类型后接成员，字段写作owner/name descriptor，方法写作owner/name(descriptor)
+    fn method_handle(&mut self, tokens: &mut Tokens) -> Result<u16> {
+        let kind_name = tokens.word("方法句柄引用类型")?;
+        let kind = reference_kind_by_name(&kind_name).ok_or_else(|| syntax_error(&format!("未知的方法句柄引用类型{}", kind_name)))?;
+        let interface = tokens.accept("interface");
+        let member = tokens.word("方法句柄成员")?;
+        let index = if (kind as _bindgen_ty_4) <= JVM_REF_putStatic {
+            let (owner, name) = split_member(&member)?;
+            let descriptor = tokens.word("字段描述符")?;
+            self.pool().add_method_handle(kind, &MemberRef { owner, name, descriptor: &descriptor }, interface)
+        } else {
+            let (owner, name, descriptor) = split_method(&member)?;
+            self.pool().add_method_handle(kind, &MemberRef { owner, name, descriptor }, interface)
+        };
+        Ok(index)
+    }
+
+    /// 相同的引导方法只保留一个
+    fn bootstrap_method(&mut self, method_ref: u16, arguments: Vec<u16>) -> Result<u16> {
+        let position = self.bootstrap_methods.iter()
+            .position(|method| method.method_ref == method_ref && method.arguments == arguments);
+        let index = match position {
+            Some(index) => index,
+            None => {
+                self.bootstrap_methods.push(BootstrapMethod { method_ref, arguments });
+                self.bootstrap_methods.len() - 1
+            }
+        };
+        u16::try_from(index).map_err(|_| syntax_error("引导方法过多"))
+    }
+
+    fn finish(mut self) -> Result<JClassInfo> {
+        if self.method.is_some() {
+            return Err(syntax_error("方法缺少.end method"));
+        }
+        if !self.declared {
+            return Err(syntax_error("缺少.class"));
+        }
+        if let Some(super_class) = self.super_class.take() {
+            self.class.superclass_index = self.pool().add_class(&super_class);
+        }
+        if let Some(source) = self.source {
+            let attribute = AttributeInfo::SourceFile(source).to_origin(self.pool())?;
+            self.class.attributes.push(attribute);
+        }
+        if let Some(signature) = self.signature {
+            let attribute = AttributeInfo::Signature(signature).to_origin(self.pool())?;
+            self.class.attributes.push(attribute);
+        }
+        if !self.bootstrap_methods.is_empty() {
+            let methods = std::mem::take(&mut self.bootstrap_methods);
+            let attribute = AttributeInfo::BootstrapMethods(methods).to_origin(self.pool())?;
+            self.class.attributes.push(attribute);
+        }
+        Ok(self.class)
+    }
+}
+
+/// 按最后一个/拆分为所属类与成员名
+fn split_member(member: &str) -> Result<(&str, &str)> {
+    match member.rfind('/') {
+        Some(split) if split > 0 && split + 1 < member.len() => Ok((&member[..split], &member[split + 1..])),
+        _ => Err(syntax_error(&format!("成员引用应写作owner/name: {}", member))),
+    }
+}
+
+fn split_method(member: &str) -> Result<(&str, &str, &str)> {
+    let split = member.find('(').ok_or_else(|| syntax_error(&format!("缺少方法描述符: {}", member)))?;
+    let (owner, name) = split_member(&member[..split])?;
+    Ok((owner, name, &member[split..]))
+}
+
+/// 数值字面量：L后缀为long，f后缀为float，d后缀或含小数点、指数为double，0x前缀为十六进制整数
+fn parse_number(word: &str) -> Result<ConstantValue> {
+    let invalid = || syntax_error(&format!("无效的常量{}", word));
+    let (negative, digits) = match word.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, word),
+    };
+    if let Some(hex) = digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")) {
+        let (hex, long) = match hex.strip_suffix(['L', 'l']) {
+            Some(hex) => (hex, true),
+            None => (hex, false),
+        };
+        let value = u64::from_str_radix(hex, 16).map_err(|_| invalid())?;
+        let value = if negative { (value as i64).wrapping_neg() } else { value as i64 };
+        return if long {
+            Ok(ConstantValue::ConstantLong(value))
+        } else {
+            // 十六进制允许写出无符号形式，如0xFFFFFFFF
+            let value = i32::try_from(value).or_else(|_| u32::try_from(value).map(|value| value as i32)).map_err(|_| invalid())?;
+            Ok(ConstantValue::ConstantInteger(value))
+        };
+    }
+    if let Some(value) = word.strip_suffix(['L', 'l']) {
+        return value.parse().map(ConstantValue::ConstantLong).map_err(|_| invalid());
+    }
+    if let Some(value) = word.strip_suffix(['F', 'f']) {
+        return value.parse().map(ConstantValue::ConstantFloat).map_err(|_| invalid());
+    }
+    if let Some(value) = word.strip_suffix(['D', 'd']) {
+        return value.parse().map(ConstantValue::ConstantDouble).map_err(|_| invalid());
+    }
+    if word.contains(['.', 'e', 'E']) {
+        return word.parse().map(ConstantValue::ConstantDouble).map_err(|_| invalid());
+    }
+    word.parse().map(ConstantValue::ConstantInteger).map_err(|_| invalid())
+}
+
+const REFERENCE_KIND_NAMES: [&str; 9] = [
+    "getfield", "getstatic", "putfield", "putstatic",
+    "invokevirtual", "invokestatic", "invokespecial", "newinvokespecial", "invokeinterface",
+];
+
+fn reference_kind_by_name(name: &str) -> Option<u8> {
+    REFERENCE_KIND_NAMES.iter().position(|kind| *kind == name).map(|kind| kind as u8 + 1)
+}
+
+const ARRAY_TYPE_NAMES: [(u8, &str); 8] = [
+    (array_type::T_BOOLEAN, "boolean"),
+    (array_type::T_CHAR, "char"),
+    (array_type::T_FLOAT, "float"),
+    (array_type::T_DOUBLE, "double"),
+    (array_type::T_BYTE, "byte"),
+    (array_type::T_SHORT, "short"),
+    (array_type::T_INT, "int"),
+    (array_type::T_LONG, "long"),
+];
+
+fn array_type_by_name(name: &str) -> Option<u8> {
+    ARRAY_TYPE_NAMES.iter().find(|(_, type_name)| *type_name == name).map(|(atype, _)| *atype)
+}
+
+fn flag_keywords(names: &[&str]) -> String {
+    names.iter().map(|name| name[4..].to_ascii_lowercase()).collect::<Vec<_>>().join(" ")
+}
+
+/// 以flags开头的声明，flags为空时不留多余空格
+fn declaration(directive: &str, flags: &str, rest: &str) -> String {
+    if flags.is_empty() { format!("{} {}", directive, rest) } else { format!("{} {} {}", directive, flags, rest) }
+}
+
+struct Emitter<'a> {
+    class: &'a JClassInfo,
+    pool: &'a ConstantPool,
+    out: String,
+}
+
+impl Emitter<'_> {
+    fn line(&mut self, indent: usize, text: &str) {
+        let _ = writeln!(self.out, "{:indent$}{}", "", text, indent = indent);
+    }
+
+    /// 按属性名查找并解析
+    fn find<'b>(&self, attributes: &'b [OriginAttribute], tag: &str) -> Result<Option<&'b AttributeInfo>> {
+        for attribute in attributes {
+            if self.pool.utf8(attribute.name)? == tag {
+                return Ok(Some(attribute.info(self.pool)?));
+            }
+        }
+        Ok(None)
+    }
+
+    fn run(mut self) -> Result<String> {
+        let class = self.class;
+        self.line(0, &format!(".version {} {}", class.major_version, class.minor_version));
+        self.line(0, &declaration(".class", &flag_keywords(&class.access().flag_names()), class.this_class_name()?));
+        self.line(0, &format!(".super {}", class.super_class_name()?.unwrap_or("none")));
+        for name in class.interface_names()? {
+            self.line(0, &format!(".implements {}", name));
+        }
+        if let Some(AttributeInfo::SourceFile(index)) = self.find(&class.attributes, SOURCE_FILE_TAG)? {
+            self.line(0, &format!(".source {}", quote(self.pool.utf8(*index)?)));
+        }
+        if let Some(AttributeInfo::Signature(index)) = self.find(&class.attributes, SIGNATURE_TAG)? {
+            self.line(0, &format!(".signature {}", quote(self.pool.utf8(*index)?)));
+        }
+        for field in &class.fields {
+            self.line(0, "");
+            let mut text = declaration(".field", &flag_keywords(&field.access().flag_names()),
+                &format!("{} {}", field.get_name(self.pool)?, field.get_descriptor(self.pool)?));
+            if let Some(AttributeInfo::ConstantValue(index)) = self.find(&field.attributes, CONSTANT_VALUE_TAG)? {
+                let _ = write!(text, " = {}", self.constant(*index)?);
+            }
+            self.line(0, &text);
+            if let Some(AttributeInfo::Signature(index)) = self.find(&field.attributes, SIGNATURE_TAG)? {
+                self.line(0, &format!(".signature {}", quote(self.pool.utf8(*index)?)));
+            }
+        }
+        for method in &class.methods {
+            self.line(0, "");
+            self.method(method)?;
+        }
+        Ok(self.out)
+    }
+
+    fn method(&mut self, method: &MethodInfo) -> Result<()> {
+        let pool = self.pool;
+        self.line(0, &declaration(".method", &flag_keywords(&method.access().flag_names()),
+            &format!("{}{}", method.get_name(pool)?, method.get_descriptor(pool)?)));
+        if let Some(AttributeInfo::Signature(index)) = self.find(&method.attributes, SIGNATURE_TAG)? {
+            self.line(4, &format!(".signature {}", quote(pool.utf8(*index)?)));
+        }
+        if let Some(AttributeInfo::Exceptions(exceptions)) = self.find(&method.attributes, EXCEPTIONS_TAG)? {
+            for index in exceptions {
+                self.line(4, &format!(".throws {}", pool.class_name(*index)?));
+            }
+        }
+        if let Some(AttributeInfo::Code(code)) = self.find(&method.attributes, CODE_TAG)? {
+            self.code(code)?;
+        }
+        self.line(0, ".end method");
+        Ok(())
+    }
+
+    fn code(&mut self, code: &CodeAttribute) -> Result<()> {
+        self.line(4, &format!(".limit stack {}", code.max_stack));
+        self.line(4, &format!(".limit locals {}", code.max_locals));
+        let instructions = Instructions::new(&code.codes).collect::<Result<Vec<_>>>()?;
+        let mut labels = BTreeSet::new();
+        for (_, instruction) in &instructions {
+            labels.extend(instruction.branch_targets());
+        }
+        for entry in &code.exceptions.entries {
+            labels.extend([entry.start_pc as u32, entry.end_pc as u32, entry.handler_pc as u32]);
+        }
+        let mut lines: BTreeMap<u32, Vec<u16>> = BTreeMap::new();
+        if let Some(AttributeInfo::LineNumberTable(numbers)) = self.find(&code.attributes, LINE_NUMBER_TABLE_TAG)? {
+            for number in numbers {
+                lines.entry(number.start_pc as u32).or_default().push(number.line_number);
+            }
+        }
+        for (pc, instruction) in &instructions {
+            if labels.contains(pc) {
+                self.line(0, &format!("L{}:", pc));
+            }
+            for line in lines.get(pc).into_iter().flatten() {
+                self.line(4, &format!(".line {}", line));
+            }
+            self.instruction(instruction)?;
+        }
+        let end = code.codes.len() as u32;
+        if labels.contains(&end) {
+            self.line(0, &format!("L{}:", end));
+        }
+        for entry in &code.exceptions.entries {
+            let catch_type = if entry.catch_type == 0 { "all" } else { self.pool.class_name(entry.catch_type)? };
+            self.line(4, &format!(".catch {} from L{} to L{} using L{}", catch_type, entry.start_pc, entry.end_pc, entry.handler_pc));
+        }
+        Ok(())
+    }
+
+    fn instruction(&mut self, instruction: &Instruction) -> Result<()> {
+        let pool = self.pool;
+        let opcode = match instruction {
+            Instruction::WideLocal(opcode, _) => *opcode,
+            Instruction::WideIinc(..) => IINC,
+            instruction => instruction.opcode(),
+        };
+        let mnemonic = opcode_name(opcode).unwrap_or_default();
+        let operands = match instruction {
+            Instruction::Simple(_) => String::new(),
+            Instruction::Bipush(value) => value.to_string(),
+            Instruction::Sipush(value) => value.to_string(),
+            Instruction::Ldc(index) | Instruction::LdcW(index) | Instruction::Ldc2W(index) => self.constant(*index)?,
+            Instruction::Local(_, index) => index.to_string(),
+            Instruction::WideLocal(_, index) => index.to_string(),
+            Instruction::Iinc(index, value) => format!("{} {}", index, value),
+            Instruction::WideIinc(index, value) => format!("{} {}", index, value),
+            Instruction::Branch(_, target) | Instruction::BranchW(_, target) => format!("L{}", target),
+            Instruction::TableSwitch { default, low, targets } => {
+                self.line(4, &format!("{} {}", mnemonic, low));
+                for target in targets {
+                    self.line(8, &format!("L{}", target));
+                }
+                self.line(8, &format!("default : L{}", default));
+                return Ok(());
+            }
+            Instruction::LookupSwitch { default, pairs } => {
+                self.line(4, mnemonic);
+                for (key, target) in pairs {
+                    self.line(8, &format!("{} : L{}", key, target));
+                }
+                self.line(8, &format!("default : L{}", default));
+                return Ok(());
+            }
+            Instruction::Field(_, index) => {
+                let member = pool.member_ref(*index)?;
+                format!("{}/{} {}", member.owner, member.name, member.descriptor)
+            }
+            Instruction::Invoke(_, index) => {
+                let member = pool.member_ref(*index)?;
+                let interface = if matches!(pool.get(*index)?, ConstantValue::ConstantInterfaceMethodref(..)) { "interface " } else { "" };
+                format!("{}{}/{}{}", interface, member.owner, member.name, member.descriptor)
+            }
+            Instruction::InvokeInterface(index, count) => {
+                let member = pool.member_ref(*index)?;
+                format!("{}/{}{} {}", member.owner, member.name, member.descriptor, count)
+            }
+            Instruction::InvokeDynamic(index) => self.invoke_dynamic(*index)?,
+            Instruction::Type(_, index) => pool.class_name(*index)?.to_string(),
+            Instruction::NewArray(atype) => {
+                let name = ARRAY_TYPE_NAMES.iter().find(|(value, _)| value == atype).map(|(_, name)| *name);
+                name.ok_or_else(|| MessageError::of(ErrorKind::InvalidBytecode, &format!("未知的数组元素类型{}", atype)))?.to_string()
+            }
+            Instruction::MultiANewArray(index, dimensions) => format!("{} {}", pool.class_name(*index)?, dimensions),
+        };
+        if operands.is_empty() {
+            self.line(4, mnemonic);
+        } else {
+            self.line(4, &format!("{} {}", mnemonic, operands));
+        }
+        Ok(())
+    }
+
+    fn invoke_dynamic(&self, index: u16) -> Result<String> {
+        let pool = self.pool;
+        let (bootstrap, name_type_index) = match pool.get(index)? {
+            ConstantValue::ConstantInvokeDynamic(bootstrap, name_type_index) => (*bootstrap, *name_type_index),
+            value => return Err(MessageError::of(ErrorKind::ConstantMismatch, &format!("常量[{}]应为InvokeDynamic，实际为{}", index, value.kind_name()))),
+        };
+        let (name, descriptor) = pool.name_and_type(name_type_index)?;
+        let method = match self.find(&self.class.attributes, BOOTSTRAP_METHODS_TAG)? {
+            Some(AttributeInfo::BootstrapMethods(methods)) => methods.get(bootstrap as usize),
+            _ => None,
+        };
+        let method = method.ok_or_else(|| MessageError::of(ErrorKind::BadIndex, &format!("引导方法[{}]不存在", bootstrap)))?;
+        let mut text = format!("{}{} {}", name, descriptor, self.method_handle(method.method_ref)?);
+        for argument in &method.arguments {
+            let _ = write!(text, " {}", self.constant(*argument)?);
+        }
+        Ok(text)
+    }
+
+    /// 引用类型后接成员，不含handle前缀
+    fn method_handle(&self, index: u16) -> Result<String> {
+        let pool = self.pool;
+        let handle = pool.method_handle(index)?;
+        let kind = REFERENCE_KIND_NAMES.get((handle.kind as usize).wrapping_sub(1))
+            .ok_or_else(|| MessageError::of(ErrorKind::InvalidConstantTag, &format!("未知的方法句柄引用类型{}", handle.kind)))?;
+        let member = &handle.member;
+        let ref_index = match pool.get(index)? {
+            ConstantValue::ConstantMethodHandle(_, ref_index) => *ref_index,
+            _ => 0,
+        };
+        Ok(if (handle.kind as _bindgen_ty_4) <= JVM_REF_putStatic {
+            format!("{} {}/{} {}", kind, member.owner, member.name, member.descriptor)
+        } else {
+            let interface = handle.kind as _bindgen_ty_4 != JVM_REF_invokeInterface
+                && matches!(pool.get(ref_index)?, ConstantValue::ConstantInterfaceMethodref(..));
+            format!("{} {}{}/{}{}", kind, if interface { "interface " } else { "" }, member.owner, member.name, member.descriptor)
+        })
+    }
+
+    /// 与assemble的常量字面量对应
+    fn constant(&self, index: u16) -> Result<String> {
+        let pool = self.pool;
+        Ok(match pool.get(index)? {
+            ConstantValue::ConstantInteger(value) => value.to_string(),
+            ConstantValue::ConstantFloat(value) => format!("{:?}f", value),
+            ConstantValue::ConstantLong(value) => format!("{}L", value),
+            ConstantValue::ConstantDouble(value) => format!("{:?}d", value),
+            ConstantValue::ConstantString(utf8) => match pool.get(*utf8)? {
+                ConstantValue::ConstantUtf8Raw(bytes) => quote_bytes(bytes),
+                _ => quote(pool.utf8(*utf8)?),
+            },
+            ConstantValue::ConstantClass(name) => format!("class {}", pool.utf8(*name)?),
+            ConstantValue::ConstantMethodType(descriptor) => format!("methodtype {}", pool.utf8(*descriptor)?),
+            ConstantValue::ConstantMethodHandle(..) => format!("handle {}", self.method_handle(index)?),
+            value => {
+                return Err(MessageError::of(ErrorKind::ConstantMismatch, &format!("常量[{}]为{}，无法以汇编文本表示", index, value.kind_name())));
+            }
+        })
+    }
+}
@@ -1,14 +1,24 @@
-use crate::common::error::Result;
+use crate::access_flags::InnerClassAccessFlags;
+use crate::classfile_constants::{JVM_ITEM_Double, JVM_ITEM_Float, JVM_ITEM_Integer, JVM_ITEM_Long, JVM_ITEM_Null, JVM_ITEM_Object, JVM_ITEM_Top, JVM_ITEM_Uninitialized, JVM_ITEM_UninitializedThis};
+use crate::common::constants::{ANNOTATION_DEFAULT_TAG, BOOTSTRAP_METHODS_TAG, CODE_TAG, CONSTANT_VALUE_TAG, DEPRECATED_TAG, ENCLOSING_METHOD_TAG, EXCEPTIONS_TAG, INNER_CLASSES_TAG, LINE_NUMBER_TABLE_TAG, LOCAL_VARIABLE_TABLE_TAG, LOCAL_VARIABLE_TYPE_TABLE_TYPE_TAG, METHOD_PARAMETERS_TAG, NEST_HOST_TAG, NEST_MEMBERS_TAG, RUNTIME_INVISIBLE_ANNOTATIONS_INVISIBLE_TAG, RUNTIME_INVISIBLE_PARAMETER_ANNOTATIONS_INVISIBLE_TAG, RUNTIME_INVISIBLE_TYPE_ANNOTATIONS_INVISIBLE_TAG, RUNTIME_VISIBLE_ANNOTATIONS_VISIBLE_TAG, RUNTIME_VISIBLE_PARAMETER_ANNOTATIONS_VISIBLE_TAG, RUNTIME_VISIBLE_TYPE_ANNOTATIONS_VISIBLE_TAG, SIGNATURE_TAG, SOURCE_FILE_TAG, STACK_MAP_TABLE_TAG, STACK_MAP_TAG, SYNTHETIC_TAG};
+use crate::common::error::{ErrorKind, MessageError, Result};
+use crate::common::options::ParseOptions;
+use crate::code_analysis;
+use crate::instruction::{Instruction, Instructions};
+use crate::constant_pool::{ConstantPool, ConstantValue};
 use crate::support::data_reader::{DataReader, DataWriter, WriteFromType};
 use crate::support::data_reader::ReadToType;
-use std::io::{BufWriter, Cursor, Read, Write};
+use crate::support::lazy_value::LazyValue;
+use std::io::{Cursor, Read, Write};
 
 #[derive(Clone, Debug)]
 pub struct OriginAttribute {
     pub name: u16,
+    /// 属性的原始数据。经info_mut或set_info修改后，写出时以修改后的内容为准，
+    /// 直接修改data不会生效，需先sync写回或调用reset_info丢弃修改
     pub data: Vec<u8>,
-    // 专为JGLauncher提供，仅需用到CodeAttribute，所以咱不实现所有Attribute的解析
-    // info: LazyValue<AttributeInfo>,
+    // 首次访问时才解析，修改后写出时重新编码，未修改则原样写出data
+    info: LazyValue<Box<AttributeInfo>>,
 }
 
 #[derive(Clone, Debug)]
@@ -33,39 +43,687 @@ pub struct ExceptionTableEntry {
     pub catch_type: u16,
 }
 
+/// 已知属性的结构化内容，属性名索引由外层的OriginAttribute保存
+#[derive(Clone, Debug)]
+pub enum AttributeInfo {
+    AnnotationDefault(ElementValue),
+    BootstrapMethods(Vec<BootstrapMethod>),
+    Code(CodeAttribute),
+    // constant value index
+    ConstantValue(u16),
+    Deprecated,
+    EnclosingMethod(EnclosingMethod),
+    // class index
+    Exceptions(Vec<u16>),
+    InnerClasses(Vec<InnerClass>),
+    LineNumberTable(Vec<LineNumber>),
+    LocalVariableTable(Vec<LocalVariable>),
+    // descriptor_index 为签名索引
+    LocalVariableTypeTable(Vec<LocalVariable>),
+    MethodParameters(Vec<MethodParameter>),
+    // class index
+    NestHost(u16),
+    // class index
+    NestMembers(Vec<u16>),
+    RuntimeVisibleAnnotations(Vec<Annotation>),
+    RuntimeInvisibleAnnotations(Vec<Annotation>),
+    // 按参数顺序排列的注解
+    RuntimeVisibleParameterAnnotations(Vec<Vec<Annotation>>),
+    RuntimeInvisibleParameterAnnotations(Vec<Vec<Annotation>>),
+    RuntimeVisibleTypeAnnotations(Vec<TypeAnnotation>),
+    RuntimeInvisibleTypeAnnotations(Vec<TypeAnnotation>),
+    // signature index
+    Signature(u16),
+    // source file name index
+    SourceFile(u16),
+    StackMap(Vec<StackMapEntry>),
+    StackMapTable(Vec<StackMapFrame>),
+    Synthetic,
+    // 未知属性，原样保留
+    OriginAttribute(OriginAttribute),
+}
+
+#[derive(Clone, Debug)]
+pub struct BootstrapMethod {
+    pub method_ref: u16,
+    pub arguments: Vec<u16>,
+}
+
+#[derive(Clone, Debug)]
+pub struct EnclosingMethod {
+    pub class_index: u16,
+    pub method_index: u16,
+}
+
+#[derive(Clone, Debug)]
+pub struct InnerClass {
+    pub inner_class_info_index: u16,
+    pub outer_class_info_index: u16,
+    pub inner_name_index: u16,
+    pub inner_class_access_flags: u16,
+}
+
+#[derive(Clone, Debug)]
+pub struct LineNumber {
+    pub start_pc: u16,
+    pub line_number: u16,
+}
+
+#[derive(Clone, Debug)]
+pub struct LocalVariable {
+    pub start_pc: u16,
+    pub length: u16,
+    pub name_index: u16,
+    pub descriptor_index: u16,
+    pub index: u16,
+}
+
+#[derive(Clone, Debug)]
+pub struct MethodParameter {
+    pub name_index: u16,
+    pub access_flags: u16,
+}
+
+#[derive(Clone, Debug)]
+pub struct Annotation {
+    pub type_index: u16,
+    pub element_value_pairs: Vec<ElementValuePair>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ElementValuePair {
+    pub name_index: u16,
+    pub value: ElementValue,
+}
+
+#[derive(Clone, Debug)]
+pub enum ElementValue {
+    // tag: B C D F I J S Z s
+    Const(u8, u16),
+    // type name index, const name index
+    Enum(u16, u16),
+    // class info index
+    Class(u16),
+    Annotation(Annotation),
+    Array(Vec<ElementValue>),
+}
+
+#[derive(Clone, Debug)]
+pub struct TypeAnnotation {
+    pub target_type: u8,
+    pub target_info: TargetInfo,
+    pub type_path: Vec<TypePathEntry>,
+    pub annotation: Annotation,
+}
+
+#[derive(Clone, Debug)]
+pub enum TargetInfo {
+    // type parameter index
+    TypeParameter(u8),
+    // supertype index
+    Supertype(u16),
+    // type parameter index, bound index
+    TypeParameterBound(u8, u8),
+    Empty,
+    // formal parameter index
+    FormalParameter(u8),
+    // throws type index
+    Throws(u16),
+    LocalVar(Vec<LocalVarTarget>),
+    // exception table index
+    Catch(u16),
+    // offset
+    Offset(u16),
+    // offset, type argument index
+    TypeArgument(u16, u8),
+}
+
+#[derive(Clone, Debug)]
+pub struct LocalVarTarget {
+    pub start_pc: u16,
+    pub length: u16,
+    pub index: u16,
+}
+
+#[derive(Clone, Debug)]
+pub struct TypePathEntry {
+    pub type_path_kind: u8,
+    pub type_argument_index: u8,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VerificationTypeInfo {
+    Top,
+    Integer,
+    Float,
+    Double,
+    Long,
+    Null,
+    UninitializedThis,
+    // class index
+    Object(u16),
+    // offset
+    Uninitialized(u16),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StackMapFrame {
+    // 0-63
+    SameFrame { offset_delta: u16 },
+    // 64-127
+    SameLocals1StackItemFrame { offset_delta: u16, stack: VerificationTypeInfo },
+    // 247
+    SameLocals1StackItemFrameExtended { offset_delta: u16, stack: VerificationTypeInfo },
+    // 248-250, k为移除的局部变量数
+    ChopFrame { k: u8, offset_delta: u16 },
+    // 251
+    SameFrameExtended { offset_delta: u16 },
+    // 252-254
+    AppendFrame { offset_delta: u16, locals: Vec<VerificationTypeInfo> },
+    // 255
+    FullFrame { offset_delta: u16, locals: Vec<VerificationTypeInfo>, stack: Vec<VerificationTypeInfo> },
+}
+
+/// CLDC中使用的StackMap属性项
+#[derive(Clone, Debug)]
+pub struct StackMapEntry {
+    pub offset: u16,
+    pub locals: Vec<VerificationTypeInfo>,
+    pub stack: Vec<VerificationTypeInfo>,
+}
+
 impl OriginAttribute {
+    pub fn new(name: u16, data: Vec<u8>) -> OriginAttribute {
+        OriginAttribute {
+            name,
+            data,
+            info: LazyValue::new(),
+        }
+    }
+
     pub fn new_from_reader<T: Read>(reader: &mut DataReader<T>) -> Result<OriginAttribute> {
         let name_index: u16 = reader.read_to("属性名")?;
-        let len: i32 = reader.read_to("属性数据长度")?;
+        let len: u32 = reader.read_to("属性数据长度")?;
         let len = len as usize;
-        let mut data = Vec::with_capacity(len);
-        unsafe {
-            data.set_len(len);
-        }
+        reader.check_len("属性数据", len, reader.options().max_attribute_size)?;
+        let mut data = vec![0; len];
         reader.read_bytes("属性数据", &mut data)?;
-        Ok(OriginAttribute {
-            name: name_index,
-            data,
-            // info: LazyValue::UnLoad
-        })
+        Ok(OriginAttribute::new(name_index, data))
+    }
+
+    /// 使用已解析的属性内容构建，name为属性名在常量池中的索引
+    pub fn new_with_info(name: u16, info: &AttributeInfo) -> Result<OriginAttribute> {
+        if let AttributeInfo::OriginAttribute(origin) = info {
+            return Ok(OriginAttribute::new(name, origin.data.clone()));
+        }
+        Ok(OriginAttribute::new(name, info.to_bytes()?))
+    }
+
+    /// 按属性名解析为结构化内容，未知属性返回AttributeInfo::OriginAttribute，解析结果与info共用缓存
+    pub fn to_attribute_info(&self, pool: &ConstantPool) -> Result<AttributeInfo> {
+        self.info(pool).cloned()
+    }
+
+    #[inline]
+    fn decode(&self, pool: &ConstantPool) -> Result<AttributeInfo> {
+        Self::decode_data(self.name, &self.data, pool)
+    }
+
+    fn decode_data(name: u16, data: &[u8], pool: &ConstantPool) -> Result<AttributeInfo> {
+        let info = match pool.get_constant_item(name) {
+            ConstantValue::ConstantUtf8(tag) => AttributeInfo::new_with_data(tag, data)?,
+            _ => None,
+        };
+        Ok(info.unwrap_or_else(|| AttributeInfo::OriginAttribute(OriginAttribute::new(name, data.to_vec()))))
+    }
+
+    /// 解析后的属性内容，首次访问时解析并缓存
+    pub fn info(&self, pool: &ConstantPool) -> Result<&AttributeInfo> {
+        self.info.get_or_try_load(|| self.decode(pool).map(Box::new)).map(|info| &**info)
+    }
+
+    /// 可修改的属性内容，写出时将重新编码
+    pub fn info_mut(&mut self, pool: &ConstantPool) -> Result<&mut AttributeInfo> {
+        let (name, data) = (self.name, &self.data);
+        let info = self.info.get_mut_or_try_load(|| Self::decode_data(name, data, pool).map(Box::new))?;
+        Ok(&mut **info)
+    }
+
+    pub fn set_info(&mut self, info: AttributeInfo) {
+        self.info.set(Box::new(info));
+    }
+
+    #[inline]
+    pub fn is_modified(&self) -> bool {
+        self.info.is_modified()
+    }
+
+    /// 将修改后的属性内容重新编码到data
+    pub fn sync(&mut self) -> Result<()> {
+        if let (true, Some(info)) = (self.info.is_modified(), self.info.get()) {
+            self.data = info.to_bytes()?;
+            self.info.mark_synced();
+        }
+        Ok(())
+    }
+
+    /// 属性名及属性内容中引用的常量索引，只解析不修改属性
+    pub fn constant_indices(&self, pool: &ConstantPool) -> Result<Vec<u16>> {
+        let mut indices = self.info(pool)?.constant_indices(pool)?;
+        indices.push(self.name);
+        Ok(indices)
+    }
+
+    /// 改写属性名及属性内容中的常量索引，属性内容会被解析并在写出时重新编码
+    pub fn remap_constant_indices(&mut self, pool: &ConstantPool, remap: &mut dyn FnMut(u16) -> u16) -> Result<()> {
+        self.info_mut(pool)?.remap_constant_indices(pool, remap)?;
+        self.name = remap(self.name);
+        Ok(())
+    }
+
+    /// 直接修改data后调用，丢弃已缓存的解析结果及未sync的修改
+    pub fn reset_info(&mut self) {
+        self.info.reset();
     }
 
     pub fn write_to<T: Write>(&self, writer: &mut DataWriter<T>) -> Result<()> {
         writer.write_from("属性名", self.name)?;
-        writer.write_from("属性数据长度", self.data.len() as i32)?;
-        writer.write_bytes("属性数据", &self.data)
+        match (self.info.is_modified(), self.info.get()) {
+            (true, Some(info)) => {
+                writer.write_from("属性数据长度", info.byte_size() as i32)?;
+                info.write_to(writer)
+            }
+            _ => {
+                writer.write_from("属性数据长度", self.data.len() as i32)?;
+                writer.write_bytes("属性数据", &self.data)
+            }
+        }
     }
 
     #[inline]
     pub fn byte_size(&self) -> usize {
         size_of::<u16>() + size_of::<i32>() // data长度
-            + self.data.len()
+            + match (self.info.is_modified(), self.info.get()) {
+                (true, Some(info)) => info.byte_size(),
+                _ => self.data.len(),
+            }
     }
 }
 
+impl AttributeInfo {
+    /// 按属性名解析属性数据，属性名未知时返回None，错误中的偏移相对于属性数据
+    pub fn new_with_data(name: &str, data: &[u8]) -> Result<Option<AttributeInfo>> {
+        Self::new_with_data_and_options(name, data, ParseOptions::default())
+    }
+
+    /// 使用指定的解析限制解析属性数据，如限制Code属性中的字节码长度
+    pub fn new_with_data_and_options(name: &str, data: &[u8], options: ParseOptions) -> Result<Option<AttributeInfo>> {
+        Self::read_data(name, data, options).map_err(|e| e.in_path(name))
+    }
+
+    fn read_data(name: &str, data: &[u8], options: ParseOptions) -> Result<Option<AttributeInfo>> {
+        let reader = &mut DataReader::from_bytes(data).with_options(options);
+        let info = match name {
+            ANNOTATION_DEFAULT_TAG => AttributeInfo::AnnotationDefault(ElementValue::new_with_reader(reader)?),
+            BOOTSTRAP_METHODS_TAG => AttributeInfo::BootstrapMethods(read_list(reader, "引导方法数量", BootstrapMethod::new_with_reader)?),
+            CODE_TAG => AttributeInfo::Code(CodeAttribute::new_with_reader(reader)?),
+            CONSTANT_VALUE_TAG => AttributeInfo::ConstantValue(reader.read_to("常量值索引")?),
+            DEPRECATED_TAG => AttributeInfo::Deprecated,
+            ENCLOSING_METHOD_TAG => AttributeInfo::EnclosingMethod(EnclosingMethod::new_with_reader(reader)?),
+            EXCEPTIONS_TAG => AttributeInfo::Exceptions(read_list(reader, "异常数量", |reader| reader.read_to("异常类索引"))?),
+            INNER_CLASSES_TAG => AttributeInfo::InnerClasses(read_list(reader, "内部类数量", InnerClass::new_with_reader)?),
+            LINE_NUMBER_TABLE_TAG => AttributeInfo::LineNumberTable(read_list(reader, "行号表大小", LineNumber::new_with_reader)?),
+            LOCAL_VARIABLE_TABLE_TAG => AttributeInfo::LocalVariableTable(read_list(reader, "局部变量表大小", LocalVariable::new_with_reader)?),
+            LOCAL_VARIABLE_TYPE_TABLE_TYPE_TAG => AttributeInfo::LocalVariableTypeTable(read_list(reader, "局部变量类型表大小", LocalVariable::new_with_reader)?),
+            METHOD_PARAMETERS_TAG => {
+                let count: u8 = reader.read_to("方法参数数量")?;
+                let mut parameters = Vec::with_capacity(count as usize);
+                for _ in 0..count {
+                    parameters.push(MethodParameter::new_with_reader(reader)?);
+                }
+                AttributeInfo::MethodParameters(parameters)
+            }
+            NEST_HOST_TAG => AttributeInfo::NestHost(reader.read_to("嵌套宿主类索引")?),
+            NEST_MEMBERS_TAG => AttributeInfo::NestMembers(read_list(reader, "嵌套成员数量", |reader| reader.read_to("嵌套成员类索引"))?),
+            RUNTIME_VISIBLE_ANNOTATIONS_VISIBLE_TAG => AttributeInfo::RuntimeVisibleAnnotations(read_list(reader, "注解数量", Annotation::new_with_reader)?),
+            RUNTIME_INVISIBLE_ANNOTATIONS_INVISIBLE_TAG => AttributeInfo::RuntimeInvisibleAnnotations(read_list(reader, "注解数量", Annotation::new_with_reader)?),
+            RUNTIME_VISIBLE_PARAMETER_ANNOTATIONS_VISIBLE_TAG => AttributeInfo::RuntimeVisibleParameterAnnotations(read_parameter_annotations(reader)?),
+            RUNTIME_INVISIBLE_PARAMETER_ANNOTATIONS_INVISIBLE_TAG => AttributeInfo::RuntimeInvisibleParameterAnnotations(read_parameter_annotations(reader)?),
+            RUNTIME_VISIBLE_TYPE_ANNOTATIONS_VISIBLE_TAG => AttributeInfo::RuntimeVisibleTypeAnnotations(read_list(reader, "类型注解数量", TypeAnnotation::new_with_reader)?),
+            RUNTIME_INVISIBLE_TYPE_ANNOTATIONS_INVISIBLE_TAG => AttributeInfo::RuntimeInvisibleTypeAnnotations(read_list(reader, "类型注解数量", TypeAnnotation::new_with_reader)?),
+            SIGNATURE_TAG => AttributeInfo::Signature(reader.read_to("签名索引")?),
+            SOURCE_FILE_TAG => AttributeInfo::SourceFile(reader.read_to("源文件名索引")?),
+            STACK_MAP_TAG => AttributeInfo::StackMap(read_list(reader, "栈映射数量", StackMapEntry::new_with_reader)?),
+            STACK_MAP_TABLE_TAG => AttributeInfo::StackMapTable(read_list(reader, "栈映射帧数量", StackMapFrame::new_with_reader)?),
+            SYNTHETIC_TAG => AttributeInfo::Synthetic,
+            _ => return Ok(None),
+        };
+        if reader.position() != data.len() {
+            return Err(MessageError::of(ErrorKind::InvalidAttribute, &format!("{name}属性数据未完全解析: {}/{}", reader.position(), data.len())).at(reader.position()));
+        }
+        Ok(Some(info))
+    }
+
+    /// 转为原始属性，属性名从常量池中查找或追加
+    pub fn to_origin(&self, pool: &mut ConstantPool) -> Result<OriginAttribute> {
+        match self {
+            AttributeInfo::OriginAttribute(origin) => Ok(origin.clone()),
+            _ => {
+                let name = pool.add_constant(ConstantValue::ConstantUtf8(self.tag().to_string()));
+                OriginAttribute::new_with_info(name, self)
+            }
+        }
+    }
+
+    /// 属性名，未知属性返回空字符串
+    pub fn tag(&self) -> &'static str {
+        match self {
+            AttributeInfo::AnnotationDefault(_) => ANNOTATION_DEFAULT_TAG,
+            AttributeInfo::BootstrapMethods(_) => BOOTSTRAP_METHODS_TAG,
+            AttributeInfo::Code(_) => CODE_TAG,
+            AttributeInfo::ConstantValue(_) => CONSTANT_VALUE_TAG,
+            AttributeInfo::Deprecated => DEPRECATED_TAG,
+            AttributeInfo::EnclosingMethod(_) => ENCLOSING_METHOD_TAG,
+            AttributeInfo::Exceptions(_) => EXCEPTIONS_TAG,
+            AttributeInfo::InnerClasses(_) => INNER_CLASSES_TAG,
+            AttributeInfo::LineNumberTable(_) => LINE_NUMBER_TABLE_TAG,
+            AttributeInfo::LocalVariableTable(_) => LOCAL_VARIABLE_TABLE_TAG,
+            AttributeInfo::LocalVariableTypeTable(_) => LOCAL_VARIABLE_TYPE_TABLE_TYPE_TAG,
+            AttributeInfo::MethodParameters(_) => METHOD_PARAMETERS_TAG,
+            AttributeInfo::NestHost(_) => NEST_HOST_TAG,
+            AttributeInfo::NestMembers(_) => NEST_MEMBERS_TAG,
+            AttributeInfo::RuntimeVisibleAnnotations(_) => RUNTIME_VISIBLE_ANNOTATIONS_VISIBLE_TAG,
+            AttributeInfo::RuntimeInvisibleAnnotations(_) => RUNTIME_INVISIBLE_ANNOTATIONS_INVISIBLE_TAG,
+            AttributeInfo::RuntimeVisibleParameterAnnotations(_) => RUNTIME_VISIBLE_PARAMETER_ANNOTATIONS_VISIBLE_TAG,
+            AttributeInfo::RuntimeInvisibleParameterAnnotations(_) => RUNTIME_INVISIBLE_PARAMETER_ANNOTATIONS_INVISIBLE_TAG,
+            AttributeInfo::RuntimeVisibleTypeAnnotations(_) => RUNTIME_VISIBLE_TYPE_ANNOTATIONS_VISIBLE_TAG,
+            AttributeInfo::RuntimeInvisibleTypeAnnotations(_) => RUNTIME_INVISIBLE_TYPE_ANNOTATIONS_INVISIBLE_TAG,
+            AttributeInfo::Signature(_) => SIGNATURE_TAG,
+            AttributeInfo::SourceFile(_) => SOURCE_FILE_TAG,
+            AttributeInfo::StackMap(_) => STACK_MAP_TAG,
+            AttributeInfo::StackMapTable(_) => STACK_MAP_TABLE_TAG,
+            AttributeInfo::Synthetic => SYNTHETIC_TAG,
+            AttributeInfo::OriginAttribute(_) => "",
+        }
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut data = Vec::with_capacity(self.byte_size());
+        self.write_to(&mut DataWriter::from(&mut data))?;
+        Ok(data)
+    }
+
+    /// 写出属性数据，不含属性名与长度
+    pub fn write_to<T: Write>(&self, writer: &mut DataWriter<T>) -> Result<()> {
+        match self {
+            AttributeInfo::AnnotationDefault(value) => value.write_to(writer),
+            AttributeInfo::BootstrapMethods(methods) => write_list(writer, "引导方法数量", methods, BootstrapMethod::write_to),
+            AttributeInfo::Code(code) => code.write_to(writer),
+            AttributeInfo::ConstantValue(index) => writer.write_from("常量值索引", *index),
+            AttributeInfo::Deprecated | AttributeInfo::Synthetic => Ok(()),
+            AttributeInfo::EnclosingMethod(method) => method.write_to(writer),
+            AttributeInfo::Exceptions(exceptions) => write_list(writer, "异常数量", exceptions, |index, writer| writer.write_from("异常类索引", *index)),
+            AttributeInfo::InnerClasses(classes) => write_list(writer, "内部类数量", classes, InnerClass::write_to),
+            AttributeInfo::LineNumberTable(lines) => write_list(writer, "行号表大小", lines, LineNumber::write_to),
+            AttributeInfo::LocalVariableTable(variables) => write_list(writer, "局部变量表大小", variables, LocalVariable::write_to),
+            AttributeInfo::LocalVariableTypeTable(variables) => write_list(writer, "局部变量类型表大小", variables, LocalVariable::write_to),
+            AttributeInfo::MethodParameters(parameters) => {
+                writer.write_from("方法参数数量", parameters.len() as u8)?;
+                for parameter in parameters {
+                    parameter.write_to(writer)?;
+                }
+                Ok(())
+            }
+            AttributeInfo::NestHost(index) => writer.write_from("嵌套宿主类索引", *index),
+            AttributeInfo::NestMembers(members) => write_list(writer, "嵌套成员数量", members, |index, writer| writer.write_from("嵌套成员类索引", *index)),
+            AttributeInfo::RuntimeVisibleAnnotations(annotations) |
+            AttributeInfo::RuntimeInvisibleAnnotations(annotations) => write_list(writer, "注解数量", annotations, Annotation::write_to),
+            AttributeInfo::RuntimeVisibleParameterAnnotations(parameters) |
+            AttributeInfo::RuntimeInvisibleParameterAnnotations(parameters) => {
+                writer.write_from("参数数量", parameters.len() as u8)?;
+                for annotations in parameters {
+                    write_list(writer, "注解数量", annotations, Annotation::write_to)?;
+                }
+                Ok(())
+            }
+            AttributeInfo::RuntimeVisibleTypeAnnotations(annotations) |
+            AttributeInfo::RuntimeInvisibleTypeAnnotations(annotations) => write_list(writer, "类型注解数量", annotations, TypeAnnotation::write_to),
+            AttributeInfo::Signature(index) => writer.write_from("签名索引", *index),
+            AttributeInfo::SourceFile(index) => writer.write_from("源文件名索引", *index),
+            AttributeInfo::StackMap(entries) => write_list(writer, "栈映射数量", entries, StackMapEntry::write_to),
+            AttributeInfo::StackMapTable(frames) => write_list(writer, "栈映射帧数量", frames, StackMapFrame::write_to),
+            AttributeInfo::OriginAttribute(origin) => writer.write_bytes("属性数据", &origin.data),
+        }
+    }
+
+    /// 属性数据长度，不含属性名与长度
+    pub fn byte_size(&self) -> usize {
+        match self {
+            AttributeInfo::AnnotationDefault(value) => value.byte_size(),
+            AttributeInfo::BootstrapMethods(methods) => list_size(methods, BootstrapMethod::byte_size),
+            AttributeInfo::Code(code) => code.byte_size(),
+            AttributeInfo::ConstantValue(_) | AttributeInfo::NestHost(_) |
+            AttributeInfo::Signature(_) | AttributeInfo::SourceFile(_) => size_of::<u16>(),
+            AttributeInfo::Deprecated | AttributeInfo::Synthetic => 0,
+            AttributeInfo::EnclosingMethod(_) => size_of::<[u16;2]>(),
+            AttributeInfo::Exceptions(indexes) | AttributeInfo::NestMembers(indexes) => size_of::<u16>() * (indexes.len() + 1),
+            AttributeInfo::InnerClasses(classes) => size_of::<u16>() + size_of::<[u16;4]>() * classes.len(),
+            AttributeInfo::LineNumberTable(lines) => size_of::<u16>() + size_of::<[u16;2]>() * lines.len(),
+            AttributeInfo::LocalVariableTable(variables) |
+            AttributeInfo::LocalVariableTypeTable(variables) => size_of::<u16>() + size_of::<[u16;5]>() * variables.len(),
+            AttributeInfo::MethodParameters(parameters) => size_of::<u8>() + size_of::<[u16;2]>() * parameters.len(),
+            AttributeInfo::RuntimeVisibleAnnotations(annotations) |
+            AttributeInfo::RuntimeInvisibleAnnotations(annotations) => list_size(annotations, Annotation::byte_size),
+            AttributeInfo::RuntimeVisibleParameterAnnotations(parameters) |
+            AttributeInfo::RuntimeInvisibleParameterAnnotations(parameters) => {
+                size_of::<u8>() + parameters.iter().map(|annotations| list_size(annotations, Annotation::byte_size)).sum::<usize>()
+            }
+            AttributeInfo::RuntimeVisibleTypeAnnotations(annotations) |
+            AttributeInfo::RuntimeInvisibleTypeAnnotations(annotations) => list_size(annotations, TypeAnnotation::byte_size),
+            AttributeInfo::StackMap(entries) => list_size(entries, StackMapEntry::byte_size),
+            AttributeInfo::StackMapTable(frames) => list_size(frames, StackMapFrame::byte_size),
+            AttributeInfo::OriginAttribute(origin) => origin.data.len(),
+        }
+    }
+
+    /// 属性内容中引用的常量索引，不含值为0的可选索引，未知属性返回错误
+    pub fn constant_indices(&self, pool: &ConstantPool) -> Result<Vec<u16>> {
+        if let AttributeInfo::Code(code) = self {
+            return code.constant_indices(pool);
+        }
+        let mut indices = Vec::new();
+        self.clone().remap_constant_indices(pool, &mut |index| {
+            indices.push(index);
+            index
+        })?;
+        Ok(indices)
+    }
+
+    /// 通过remap改写属性内容中的所有常量索引，值为0的可选索引不会传入remap，
+    /// 未知属性无法确定其中的常量索引，返回错误
+    pub fn remap_constant_indices(&mut self, pool: &ConstantPool, remap: &mut dyn FnMut(u16) -> u16) -> Result<()> {
+        match self {
+            AttributeInfo::AnnotationDefault(value) => remap_element_value(value, remap),
+            AttributeInfo::BootstrapMethods(methods) => {
+                for method in methods {
+                    remap_index(&mut method.method_ref, remap);
+                    method.arguments.iter_mut().for_each(|argument| remap_index(argument, remap));
+                }
+            }
+            AttributeInfo::Code(code) => code.remap_constant_indices(pool, remap)?,
+            AttributeInfo::ConstantValue(index) | AttributeInfo::NestHost(index) |
+            AttributeInfo::Signature(index) | AttributeInfo::SourceFile(index) => remap_index(index, remap),
+            AttributeInfo::Deprecated | AttributeInfo::Synthetic | AttributeInfo::LineNumberTable(_) => {}
+            AttributeInfo::EnclosingMethod(method) => {
+                remap_index(&mut method.class_index, remap);
+                remap_index(&mut method.method_index, remap);
+            }
+            AttributeInfo::Exceptions(indexes) | AttributeInfo::NestMembers(indexes) => {
+                indexes.iter_mut().for_each(|index| remap_index(index, remap));
+            }
+            AttributeInfo::InnerClasses(classes) => {
+                for class in classes {
+                    remap_index(&mut class.inner_class_info_index, remap);
+                    remap_index(&mut class.outer_class_info_index, remap);
+                    remap_index(&mut class.inner_name_index, remap);
+                }
+            }
+            AttributeInfo::LocalVariableTable(variables) |
+            AttributeInfo::LocalVariableTypeTable(variables) => {
+                for variable in variables {
+                    remap_index(&mut variable.name_index, remap);
+                    remap_index(&mut variable.descriptor_index, remap);
+                }
+            }
+            AttributeInfo::MethodParameters(parameters) => {
+                parameters.iter_mut().for_each(|parameter| remap_index(&mut parameter.name_index, remap));
+            }
+            AttributeInfo::RuntimeVisibleAnnotations(annotations) |
+            AttributeInfo::RuntimeInvisibleAnnotations(annotations) => {
+                annotations.iter_mut().for_each(|annotation| remap_annotation(annotation, remap));
+            }
+            AttributeInfo::RuntimeVisibleParameterAnnotations(parameters) |
+            AttributeInfo::RuntimeInvisibleParameterAnnotations(parameters) => {
+                parameters.iter_mut().flatten().for_each(|annotation| remap_annotation(annotation, remap));
+            }
+            AttributeInfo::RuntimeVisibleTypeAnnotations(annotations) |
+            AttributeInfo::RuntimeInvisibleTypeAnnotations(annotations) => {
+                annotations.iter_mut().for_each(|annotation| remap_annotation(&mut annotation.annotation, remap));
+            }
+            AttributeInfo::StackMap(entries) => {
+                for entry in entries {
+                    entry.locals.iter_mut().chain(entry.stack.iter_mut())
+                        .for_each(|verification_type| remap_verification_type(verification_type, remap));
+                }
+            }
+            AttributeInfo::StackMapTable(frames) => {
+                for frame in frames {
+                    match frame {
+                        StackMapFrame::SameFrame { .. } | StackMapFrame::ChopFrame { .. } |
+                        StackMapFrame::SameFrameExtended { .. } => {}
+                        StackMapFrame::SameLocals1StackItemFrame { stack, .. } |
+                        StackMapFrame::SameLocals1StackItemFrameExtended { stack, .. } => remap_verification_type(stack, remap),
+                        StackMapFrame::AppendFrame { locals, .. } => {
+                            locals.iter_mut().for_each(|verification_type| remap_verification_type(verification_type, remap));
+                        }
+                        StackMapFrame::FullFrame { locals, stack, .. } => {
+                            locals.iter_mut().chain(stack.iter_mut())
+                                .for_each(|verification_type| remap_verification_type(verification_type, remap));
+                        }
+                    }
+                }
+            }
+            AttributeInfo::OriginAttribute(origin) => {
+                let name = pool.utf8(origin.name).unwrap_or_default();
+                return Err(MessageError::of(ErrorKind::UnsupportedAttribute, &format!("未知属性[{}]无法重映射常量索引", name)));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 属性名是否为name，名称常量无效时视为不匹配
+fn is_named(attribute: &OriginAttribute, pool: &ConstantPool, name: &str) -> bool {
+    pool.utf8(attribute.name).is_ok_and(|tag| tag == name)
+}
+
+pub(crate) fn find_attribute<'a>(attributes: &'a [OriginAttribute], pool: &ConstantPool, name: &str) -> Option<&'a OriginAttribute> {
+    attributes.iter().find(|attribute| is_named(attribute, pool, name))
+}
+
+pub(crate) fn find_attribute_mut<'a>(attributes: &'a mut [OriginAttribute], pool: &ConstantPool, name: &str) -> Option<&'a mut OriginAttribute> {
+    attributes.iter_mut().find(|attribute| is_named(attribute, pool, name))
+}
+
+pub(crate) fn attributes_named<'a>(attributes: &'a [OriginAttribute], pool: &'a ConstantPool, name: &'a str) -> impl Iterator<Item = &'a OriginAttribute> + 'a {
+    attributes.iter().filter(move |attribute| is_named(attribute, pool, name))
+}
+
+/// 替换第一个同名属性并移除其余同名属性，不存在时追加到末尾
+pub(crate) fn set_attribute(attributes: &mut Vec<OriginAttribute>, pool: &mut ConstantPool, name: &str, data: Vec<u8>) {
+    let attribute = OriginAttribute::new(pool.add_utf8(name), data);
+    let position = attributes.iter().position(|attribute| is_named(attribute, pool, name));
+    attributes.retain(|attribute| !is_named(attribute, pool, name));
+    attributes.insert(position.unwrap_or(attributes.len()), attribute);
+}
+
+/// 移除所有同名属性并按原顺序返回
+pub(crate) fn remove_attribute(attributes: &mut Vec<OriginAttribute>, pool: &ConstantPool, name: &str) -> Vec<OriginAttribute> {
+    let (removed, kept) = std::mem::take(attributes).into_iter().partition(|attribute| is_named(attribute, pool, name));
+    *attributes = kept;
+    removed
+}
+
+#[inline]
+fn remap_index(index: &mut u16, remap: &mut dyn FnMut(u16) -> u16) {
+    if *index != 0 {
+        *index = remap(*index);
+    }
+}
+
+fn remap_annotation(annotation: &mut Annotation, remap: &mut dyn FnMut(u16) -> u16) {
+    remap_index(&mut annotation.type_index, remap);
+    for pair in &mut annotation.element_value_pairs {
+        remap_index(&mut pair.name_index, remap);
+        remap_element_value(&mut pair.value, remap);
+    }
+}
+
+fn remap_element_value(value: &mut ElementValue, remap: &mut dyn FnMut(u16) -> u16) {
+    match value {
+        ElementValue::Const(_, index) | ElementValue::Class(index) => remap_index(index, remap),
+        ElementValue::Enum(type_name, const_name) => {
+            remap_index(type_name, remap);
+            remap_index(const_name, remap);
+        }
+        ElementValue::Annotation(annotation) => remap_annotation(annotation, remap),
+        ElementValue::Array(values) => values.iter_mut().for_each(|value| remap_element_value(value, remap)),
+    }
+}
+
+#[inline]
+fn remap_verification_type(verification_type: &mut VerificationTypeInfo, remap: &mut dyn FnMut(u16) -> u16) {
+    if let VerificationTypeInfo::Object(index) = verification_type {
+        remap_index(index, remap);
+    }
+}
+
+#[inline]
+fn read_parameter_annotations<T: Read>(reader: &mut DataReader<T>) -> Result<Vec<Vec<Annotation>>> {
+    let count: u8 = reader.read_to("参数数量")?;
+    let mut parameters = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        parameters.push(read_list(reader, "注解数量", Annotation::new_with_reader)?);
+    }
+    Ok(parameters)
+}
+
+fn read_list<T: Read, V>(reader: &mut DataReader<T>, name: &str, read: impl Fn(&mut DataReader<T>) -> Result<V>) -> Result<Vec<V>> {
+    let count: u16 = reader.read_to(name)?;
+    let mut values = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        values.push(read(reader)?);
+    }
+    Ok(values)
+}
+
+#[inline]
+fn write_list<T: Write, V>(writer: &mut DataWriter<T>, name: &str, values: &[V], write: impl Fn(&V, &mut DataWriter<T>) -> Result<()>) -> Result<()> {
+    writer.write_from(name, values.len() as u16)?;
+    for value in values {
+        write(value, writer)?;
+    }
+    Ok(())
+}
+
+#[inline]
+fn list_size<V>(values: &[V], size: impl Fn(&V) -> usize) -> usize {
+    size_of::<u16>() + values.iter().map(size).sum::<usize>()
+}
+
 impl CodeAttribute {
     pub fn new_with_data(data: &[u8]) -> Result<CodeAttribute> {
-        Self::new_with_reader(&mut Cursor::new(data).into())
+        Self::new_with_reader(&mut DataReader::from_bytes(data))
     }
 
     pub fn new_with_reader<T: Read>(reader: &mut DataReader<T>) -> Result<CodeAttribute> {
@@ -73,12 +731,10 @@ impl CodeAttribute {
         let max_stack: u16 = reader.read_to("操作栈最大深度")?;
         let max_locals: u16 = reader.read_to("局部变量最大槽数")?;
 
-        let code_len: i32 = reader.read_to("字节码长度")?;
+        let code_len: u32 = reader.read_to("字节码长度")?;
         let code_len = code_len as usize;
-        let mut codes = Vec::with_capacity(code_len);
-        unsafe {
-            codes.set_len(code_len);
-        }
+        reader.check_len("字节码", code_len, reader.options().max_code_size)?;
+        let mut codes = vec![0; code_len];
         reader.read_bytes("字节码", &mut codes)?;
 
         let exceptions = ExceptionTable::new_with_reader(reader)?;
@@ -86,7 +742,7 @@ impl CodeAttribute {
         let attr_size: u16 = reader.read_to("属性数量")?;
         let mut attributes = Vec::with_capacity(attr_size as usize);
         for i in 0..attr_size {
-            attributes.push(OriginAttribute::new_from_reader(reader)?);
+            attributes.push(OriginAttribute::new_from_reader(reader).map_err(|e| e.in_path(&format!("attributes[{}]", i)))?);
         }
 
         Ok(CodeAttribute {
@@ -98,30 +754,109 @@ impl CodeAttribute {
         })
     }
 
+    /// 第一个名为name的子属性，如LineNumberTable、StackMapTable
+    pub fn attribute(&self, pool: &ConstantPool, name: &str) -> Option<&OriginAttribute> {
+        find_attribute(&self.attributes, pool, name)
+    }
+
+    pub fn attributes_named<'a>(&'a self, pool: &'a ConstantPool, name: &'a str) -> impl Iterator<Item = &'a OriginAttribute> + 'a {
+        attributes_named(&self.attributes, pool, name)
+    }
+
+    /// 以data作为名为name的子属性内容，替换已有的同名属性
+    pub fn set_attribute(&mut self, pool: &mut ConstantPool, name: &str, data: Vec<u8>) {
+        set_attribute(&mut self.attributes, pool, name, data)
+    }
+
+    /// 移除所有名为name的子属性并返回
+    pub fn remove_attribute(&mut self, pool: &ConstantPool, name: &str) -> Vec<OriginAttribute> {
+        remove_attribute(&mut self.attributes, pool, name)
+    }
+
+    /// 按顺序解码字节码，返回(pc, 指令)
+    pub fn instructions(&self) -> Instructions<'_> {
+        Instructions::new(&self.codes)
+    }
+
+    /// 重新计算max_stack与max_locals，argument_slots为参数占用的槽数（实例方法包含this）
+    pub fn compute_maxs(&mut self, pool: &ConstantPool, argument_slots: u16) -> Result<()> {
+        (self.max_stack, self.max_locals) = code_analysis::compute_maxs(self, pool, argument_slots)?;
+        Ok(())
+    }
+
     pub fn to_bytes(&self) -> Result<Vec<u8>> {
         let data_size = self.byte_size();
         let mut data = Vec::with_capacity(data_size);
-        {
-            let mut writer = DataWriter::from(BufWriter::new(&mut data));
-            writer.write_from("操作栈最大深度", self.max_stack)?;
-            writer.write_from("局部变量最大槽数", self.max_locals)?;
-            writer.write_from("字节码长度", self.codes.len() as i32)?;
-            writer.write_bytes("字节码", &self.codes)?;
-            self.exceptions.write_to(&mut writer)?;
-            writer.write_from("属性数量", self.attributes.len() as u16)?;
-            for attr in &self.attributes {
-                attr.write_to(&mut writer)?;
-            }
-        }
+        self.write_to(&mut DataWriter::from(&mut data))?;
         Ok(data)
     }
 
+    pub fn write_to<T: Write>(&self, writer: &mut DataWriter<T>) -> Result<()> {
+        writer.write_from("操作栈最大深度", self.max_stack)?;
+        writer.write_from("局部变量最大槽数", self.max_locals)?;
+        writer.write_from("字节码长度", self.codes.len() as i32)?;
+        writer.write_bytes("字节码", &self.codes)?;
+        self.exceptions.write_to(writer)?;
+        writer.write_from("属性数量", self.attributes.len() as u16)?;
+        for attr in &self.attributes {
+            attr.write_to(writer)?;
+        }
+        Ok(())
+    }
+
     pub fn byte_size(&self) -> usize {
         let mut attrs_size = 0;
         for attr in &self.attributes {
             attrs_size += attr.byte_size();
         }
-        self.codes.len() + size_of::<[u16;2]>() + self.exceptions.byte_size() + attrs_size
+        size_of::<[u16;2]>() + size_of::<i32>() + self.codes.len() + self.exceptions.byte_size()
+            + size_of::<u16>() // 属性数量
+            + attrs_size
+    }
+
+    /// 异常表、字节码操作数及子属性中引用的常量索引，子属性的解析结果缓存在原属性中
+    pub fn constant_indices(&self, pool: &ConstantPool) -> Result<Vec<u16>> {
+        let mut code = CodeAttribute {
+            codes: self.codes.clone(),
+            max_stack: self.max_stack,
+            max_locals: self.max_locals,
+            exceptions: self.exceptions.clone(),
+            attributes: Vec::new(),
+        };
+        let mut indices = Vec::new();
+        code.remap_constant_indices(pool, &mut |index| {
+            indices.push(index);
+            index
+        })?;
+        for (i, attribute) in self.attributes.iter().enumerate() {
+            indices.extend(attribute.constant_indices(pool).map_err(|e| e.in_path(&format!("attributes[{}]", i)))?);
+        }
+        Ok(indices)
+    }
+
+    /// 改写异常表、字节码操作数及子属性中的常量索引
+    pub fn remap_constant_indices(&mut self, pool: &ConstantPool, remap: &mut dyn FnMut(u16) -> u16) -> Result<()> {
+        for entry in &mut self.exceptions.entries {
+            remap_index(&mut entry.catch_type, remap);
+        }
+        let instructions = Instructions::new(&self.codes).collect::<Result<Vec<_>>>()?;
+        for (pc, mut instruction) in instructions {
+            let index = match &mut instruction {
+                Instruction::Ldc(index) | Instruction::LdcW(index) | Instruction::Ldc2W(index) |
+                Instruction::Field(_, index) | Instruction::Invoke(_, index) | Instruction::InvokeInterface(index, _) |
+                Instruction::InvokeDynamic(index) | Instruction::Type(_, index) | Instruction::MultiANewArray(index, _) => index,
+                _ => continue,
+            };
+            *index = remap(*index);
+            // 操作数长度不变，原位覆盖；ldc索引超出单字节范围时编码失败
+            let mut bytes = Vec::with_capacity(5);
+            instruction.encode_to(pc, &mut bytes)?;
+            self.codes[pc as usize..pc as usize + bytes.len()].copy_from_slice(&bytes);
+        }
+        for (i, attribute) in self.attributes.iter_mut().enumerate() {
+            attribute.remap_constant_indices(pool, remap).map_err(|e| e.in_path(&format!("attributes[{}]", i)))?;
+        }
+        Ok(())
     }
 }
 
@@ -148,7 +883,7 @@ impl ExceptionTable {
 
     #[inline]
     pub fn byte_size(&self) -> usize {
-        self.entries.len() * ExceptionTableEntry::byte_size()
+        size_of::<u16>() + self.entries.len() * ExceptionTableEntry::byte_size()
     }
 }
 
@@ -180,30 +915,557 @@ impl ExceptionTableEntry {
     }
 }
 
-// 专为JGLauncher提供，仅需用到CodeAttribute，所以暂不实现所有Attribute的解析
-// #[derive(Clone, Debug)]
-// pub enum AttributeInfo {
-//     AnnotationDefaultAttribute,
-//     BootstrapMethodsAttribute,
-//     CodeAttribute,
-//     ConstantAttribute,
-//     DeprecatedAttribute,
-//     EnclosingMethodAttribute,
-//     ExceptionsAttribute,
-//     InnerClassesAttribute,
-//     LineNumberAttribute,
-//     LocalVariableAttribute,
-//     LocalVariableTypeAttribute,
-//     MethodParametersAttribute,
-//     NestHostAttribute,
-//     NestMembersAttribute,
-//     AnnotationsAttribute,
-//     ParameterAnnotationsAttribute,
-//     TypeAnnotationsAttribute,
-//     SignatureAttribute,
-//     SourceFileAttribute,
-//     SyntheticAttribute,
-//     StackMap,
-//     StackMapTable,
-//     OriginAttribute,
-// }
\ No newline at end of file
+impl BootstrapMethod {
+    pub fn new_with_reader<T: Read>(reader: &mut DataReader<T>) -> Result<BootstrapMethod> {
+        let method_ref = reader.read_to("引导方法句柄索引")?;
+        let arguments = read_list(reader, "引导方法参数数量", |reader| reader.read_to("引导方法参数索引"))?;
+        Ok(BootstrapMethod {
+            method_ref,
+            arguments,
+        })
+    }
+
+    pub fn write_to<T: Write>(&self, writer: &mut DataWriter<T>) -> Result<()> {
+        writer.write_from("引导方法句柄索引", self.method_ref)?;
+        write_list(writer, "引导方法参数数量", &self.arguments, |index, writer| writer.write_from("引导方法参数索引", *index))
+    }
+
+    #[inline]
+    pub fn byte_size(&self) -> usize {
+        size_of::<[u16;2]>() + size_of::<u16>() * self.arguments.len()
+    }
+}
+
+impl EnclosingMethod {
+    pub fn new_with_reader<T: Read>(reader: &mut DataReader<T>) -> Result<EnclosingMethod> {
+        let class_index = reader.read_to("外围类索引")?;
+        let method_index = reader.read_to("外围方法索引")?;
+        Ok(EnclosingMethod {
+            class_index,
+            method_index,
+        })
+    }
+
+    pub fn write_to<T: Write>(&self, writer: &mut DataWriter<T>) -> Result<()> {
+        writer.write_from("外围类索引", self.class_index)?;
+        writer.write_from("外围方法索引", self.method_index)
+    }
+}
+
+impl InnerClass {
+    pub fn new_with_reader<T: Read>(reader: &mut DataReader<T>) -> Result<InnerClass> {
+        let inner_class_info_index = reader.read_to("内部类索引")?;
+        let outer_class_info_index = reader.read_to("外部类索引")?;
+        let inner_name_index = reader.read_to("内部类名索引")?;
+        let inner_class_access_flags = reader.read_to("内部类访问标识")?;
+        Ok(InnerClass {
+            inner_class_info_index,
+            outer_class_info_index,
+            inner_name_index,
+            inner_class_access_flags,
+        })
+    }
+
+    pub fn write_to<T: Write>(&self, writer: &mut DataWriter<T>) -> Result<()> {
+        writer.write_from("内部类索引", self.inner_class_info_index)?;
+        writer.write_from("外部类索引", self.outer_class_info_index)?;
+        writer.write_from("内部类名索引", self.inner_name_index)?;
+        writer.write_from("内部类访问标识", self.inner_class_access_flags)
+    }
+
+    pub fn access(&self) -> InnerClassAccessFlags {
+        InnerClassAccessFlags::from_bits(self.inner_class_access_flags)
+    }
+
+    pub fn set_access(&mut self, access: InnerClassAccessFlags) {
+        self.inner_class_access_flags = access.bits();
+    }
+}
+
+impl LineNumber {
+    pub fn new_with_reader<T: Read>(reader: &mut DataReader<T>) -> Result<LineNumber> {
+        let start_pc = reader.read_to("行号起始PC")?;
+        let line_number = reader.read_to("行号")?;
+        Ok(LineNumber {
+            start_pc,
+            line_number,
+        })
+    }
+
+    pub fn write_to<T: Write>(&self, writer: &mut DataWriter<T>) -> Result<()> {
+        writer.write_from("行号起始PC", self.start_pc)?;
+        writer.write_from("行号", self.line_number)
+    }
+}
+
+impl LocalVariable {
+    pub fn new_with_reader<T: Read>(reader: &mut DataReader<T>) -> Result<LocalVariable> {
+        let start_pc = reader.read_to("局部变量起始PC")?;
+        let length = reader.read_to("局部变量作用长度")?;
+        let name_index = reader.read_to("局部变量名索引")?;
+        let descriptor_index = reader.read_to("局部变量描述索引")?;
+        let index = reader.read_to("局部变量槽位")?;
+        Ok(LocalVariable {
+            start_pc,
+            length,
+            name_index,
+            descriptor_index,
+            index,
+        })
+    }
+
+    pub fn write_to<T: Write>(&self, writer: &mut DataWriter<T>) -> Result<()> {
+        writer.write_from("局部变量起始PC", self.start_pc)?;
+        writer.write_from("局部变量作用长度", self.length)?;
+        writer.write_from("局部变量名索引", self.name_index)?;
+        writer.write_from("局部变量描述索引", self.descriptor_index)?;
+        writer.write_from("局部变量槽位", self.index)
+    }
+}
+
+impl MethodParameter {
+    pub fn new_with_reader<T: Read>(reader: &mut DataReader<T>) -> Result<MethodParameter> {
+        let name_index = reader.read_to("方法参数名索引")?;
+        let access_flags = reader.read_to("方法参数访问标识")?;
+        Ok(MethodParameter {
+            name_index,
+            access_flags,
+        })
+    }
+
+    pub fn write_to<T: Write>(&self, writer: &mut DataWriter<T>) -> Result<()> {
+        writer.write_from("方法参数名索引", self.name_index)?;
+        writer.write_from("方法参数访问标识", self.access_flags)
+    }
+}
+
+impl Annotation {
+    pub fn new_with_reader<T: Read>(reader: &mut DataReader<T>) -> Result<Annotation> {
+        let type_index = reader.read_to("注解类型索引")?;
+        let element_value_pairs = read_list(reader, "注解元素数量", ElementValuePair::new_with_reader)?;
+        Ok(Annotation {
+            type_index,
+            element_value_pairs,
+        })
+    }
+
+    pub fn write_to<T: Write>(&self, writer: &mut DataWriter<T>) -> Result<()> {
+        writer.write_from("注解类型索引", self.type_index)?;
+        write_list(writer, "注解元素数量", &self.element_value_pairs, ElementValuePair::write_to)
+    }
+
+    pub fn byte_size(&self) -> usize {
+        size_of::<u16>() + list_size(&self.element_value_pairs, ElementValuePair::byte_size)
+    }
+}
+
+impl ElementValuePair {
+    pub fn new_with_reader<T: Read>(reader: &mut DataReader<T>) -> Result<ElementValuePair> {
+        let name_index = reader.read_to("注解元素名索引")?;
+        let value = ElementValue::new_with_reader(reader)?;
+        Ok(ElementValuePair {
+            name_index,
+            value,
+        })
+    }
+
+    pub fn write_to<T: Write>(&self, writer: &mut DataWriter<T>) -> Result<()> {
+        writer.write_from("注解元素名索引", self.name_index)?;
+        self.value.write_to(writer)
+    }
+
+    #[inline]
+    pub fn byte_size(&self) -> usize {
+        size_of::<u16>() + self.value.byte_size()
+    }
+}
+
+impl ElementValue {
+    pub fn new_with_reader<T: Read>(reader: &mut DataReader<T>) -> Result<ElementValue> {
+        let tag: u8 = reader.read_to("注解元素类型")?;
+        Ok(match tag {
+            b'B' | b'C' | b'D' | b'F' | b'I' | b'J' | b'S' | b'Z' | b's' => {
+                ElementValue::Const(tag, reader.read_to("注解常量值索引")?)
+            }
+            b'e' => {
+                let type_name_index = reader.read_to("注解枚举类型索引")?;
+                let const_name_index = reader.read_to("注解枚举常量名索引")?;
+                ElementValue::Enum(type_name_index, const_name_index)
+            }
+            b'c' => ElementValue::Class(reader.read_to("注解类索引")?),
+            b'@' => ElementValue::Annotation(Annotation::new_with_reader(reader)?),
+            b'[' => ElementValue::Array(read_list(reader, "注解数组长度", ElementValue::new_with_reader)?),
+            _ => {
+                return Err(MessageError::of(ErrorKind::InvalidAttribute, &format!("无效的注解元素类型[{}]", tag)).at(reader.position() - 1));
+            }
+        })
+    }
+
+    pub fn write_to<T: Write>(&self, writer: &mut DataWriter<T>) -> Result<()> {
+        writer.write_from("注解元素类型", self.tag())?;
+        match self {
+            ElementValue::Const(_, index) => writer.write_from("注解常量值索引", *index),
+            ElementValue::Enum(type_name_index, const_name_index) => {
+                writer.write_from("注解枚举类型索引", *type_name_index)?;
+                writer.write_from("注解枚举常量名索引", *const_name_index)
+            }
+            ElementValue::Class(index) => writer.write_from("注解类索引", *index),
+            ElementValue::Annotation(annotation) => annotation.write_to(writer),
+            ElementValue::Array(values) => write_list(writer, "注解数组长度", values, ElementValue::write_to),
+        }
+    }
+
+    pub fn tag(&self) -> u8 {
+        match self {
+            ElementValue::Const(tag, _) => *tag,
+            ElementValue::Enum(_, _) => b'e',
+            ElementValue::Class(_) => b'c',
+            ElementValue::Annotation(_) => b'@',
+            ElementValue::Array(_) => b'[',
+        }
+    }
+
+    pub fn byte_size(&self) -> usize {
+        size_of::<u8>() + match self {
+            ElementValue::Const(_, _) | ElementValue::Class(_) => size_of::<u16>(),
+            ElementValue::Enum(_, _) => size_of::<[u16;2]>(),
+            ElementValue::Annotation(annotation) => annotation.byte_size(),
+            ElementValue::Array(values) => list_size(values, ElementValue::byte_size),
+        }
+    }
+}
+
+impl TypeAnnotation {
+    pub fn new_with_reader<T: Read>(reader: &mut DataReader<T>) -> Result<TypeAnnotation> {
+        let target_type: u8 = reader.read_to("类型注解目标类型")?;
+        let target_info = TargetInfo::new_with_reader(target_type, reader)?;
+        let path_length: u8 = reader.read_to("类型路径长度")?;
+        let mut type_path = Vec::with_capacity(path_length as usize);
+        for _ in 0..path_length {
+            let type_path_kind = reader.read_to("类型路径类别")?;
+            let type_argument_index = reader.read_to("类型参数索引")?;
+            type_path.push(TypePathEntry {
+                type_path_kind,
+                type_argument_index,
+            });
+        }
+        let annotation = Annotation::new_with_reader(reader)?;
+        Ok(TypeAnnotation {
+            target_type,
+            target_info,
+            type_path,
+            annotation,
+        })
+    }
+
+    pub fn write_to<T: Write>(&self, writer: &mut DataWriter<T>) -> Result<()> {
+        writer.write_from("类型注解目标类型", self.target_type)?;
+        self.target_info.write_to(writer)?;
+        writer.write_from("类型路径长度", self.type_path.len() as u8)?;
+        for entry in &self.type_path {
+            writer.write_from("类型路径类别", entry.type_path_kind)?;
+            writer.write_from("类型参数索引", entry.type_argument_index)?;
+        }
+        self.annotation.write_to(writer)
+    }
+
+    pub fn byte_size(&self) -> usize {
+        size_of::<u8>() + self.target_info.byte_size()
+            + size_of::<u8>() + size_of::<[u8;2]>() * self.type_path.len()
+            + self.annotation.byte_size()
+    }
+}
+
+impl TargetInfo {
+    pub fn new_with_reader<T: Read>(target_type: u8, reader: &mut DataReader<T>) -> Result<TargetInfo> {
+        Ok(match target_type {
+            0x00 | 0x01 => TargetInfo::TypeParameter(reader.read_to("类型参数索引")?),
+            0x10 => TargetInfo::Supertype(reader.read_to("父类型索引")?),
+            0x11 | 0x12 => TargetInfo::TypeParameterBound(reader.read_to("类型参数索引")?, reader.read_to("类型参数边界索引")?),
+            0x13..=0x15 => TargetInfo::Empty,
+            0x16 => TargetInfo::FormalParameter(reader.read_to("形参索引")?),
+            0x17 => TargetInfo::Throws(reader.read_to("抛出类型索引")?),
+            0x40 | 0x41 => {
+                TargetInfo::LocalVar(read_list(reader, "局部变量目标数量", |reader| {
+                    Ok(LocalVarTarget {
+                        start_pc: reader.read_to("局部变量起始PC")?,
+                        length: reader.read_to("局部变量作用长度")?,
+                        index: reader.read_to("局部变量槽位")?,
+                    })
+                })?)
+            }
+            0x42 => TargetInfo::Catch(reader.read_to("异常表索引")?),
+            0x43..=0x46 => TargetInfo::Offset(reader.read_to("字节码偏移")?),
+            0x47..=0x4B => TargetInfo::TypeArgument(reader.read_to("字节码偏移")?, reader.read_to("类型实参索引")?),
+            _ => {
+                return Err(MessageError::of(ErrorKind::InvalidAttribute, &format!("无效的类型注解目标类型[{:#x}]", target_type)).at(reader.position() - 1));
+            }
+        })
+    }
+
+    pub fn write_to<T: Write>(&self, writer: &mut DataWriter<T>) -> Result<()> {
+        match self {
+            TargetInfo::TypeParameter(index) | TargetInfo::FormalParameter(index) => writer.write_from("类型参数索引", *index),
+            TargetInfo::Supertype(index) | TargetInfo::Throws(index) |
+            TargetInfo::Catch(index) | TargetInfo::Offset(index) => writer.write_from("类型注解目标索引", *index),
+            TargetInfo::TypeParameterBound(index, bound_index) => {
+                writer.write_from("类型参数索引", *index)?;
+                writer.write_from("类型参数边界索引", *bound_index)
+            }
+            TargetInfo::Empty => Ok(()),
+            TargetInfo::LocalVar(targets) => write_list(writer, "局部变量目标数量", targets, |target, writer| {
+                writer.write_from("局部变量起始PC", target.start_pc)?;
+                writer.write_from("局部变量作用长度", target.length)?;
+                writer.write_from("局部变量槽位", target.index)
+            }),
+            TargetInfo::TypeArgument(offset, index) => {
+                writer.write_from("字节码偏移", *offset)?;
+                writer.write_from("类型实参索引", *index)
+            }
+        }
+    }
+
+    pub fn byte_size(&self) -> usize {
+        match self {
+            TargetInfo::TypeParameter(_) | TargetInfo::FormalParameter(_) => size_of::<u8>(),
+            TargetInfo::Supertype(_) | TargetInfo::Throws(_) |
+            TargetInfo::Catch(_) | TargetInfo::Offset(_) => size_of::<u16>(),
+            TargetInfo::TypeParameterBound(_, _) => size_of::<[u8;2]>(),
+            TargetInfo::Empty => 0,
+            TargetInfo::LocalVar(targets) => size_of::<u16>() + size_of::<[u16;3]>() * targets.len(),
+            TargetInfo::TypeArgument(_, _) => size_of::<u16>() + size_of::<u8>(),
+        }
+    }
+}
+
+impl VerificationTypeInfo {
+    pub fn new_with_reader<T: Read>(reader: &mut DataReader<T>) -> Result<VerificationTypeInfo> {
+        let tag: u8 = reader.read_to("校验类型")?;
+        Ok(match tag as i32 {
+            JVM_ITEM_Top => VerificationTypeInfo::Top,
+            JVM_ITEM_Integer => VerificationTypeInfo::Integer,
+            JVM_ITEM_Float => VerificationTypeInfo::Float,
+            JVM_ITEM_Double => VerificationTypeInfo::Double,
+            JVM_ITEM_Long => VerificationTypeInfo::Long,
+            JVM_ITEM_Null => VerificationTypeInfo::Null,
+            JVM_ITEM_UninitializedThis => VerificationTypeInfo::UninitializedThis,
+            JVM_ITEM_Object => VerificationTypeInfo::Object(reader.read_to("校验类型类索引")?),
+            JVM_ITEM_Uninitialized => VerificationTypeInfo::Uninitialized(reader.read_to("校验类型偏移")?),
+            _ => {
+                return Err(MessageError::of(ErrorKind::InvalidAttribute, &format!("无效的校验类型[{}]", tag)).at(reader.position() - 1));
+            }
+        })
+    }
+
+    pub fn write_to<T: Write>(&self, writer: &mut DataWriter<T>) -> Result<()> {
+        writer.write_from("校验类型", self.tag())?;
+        match self {
+            VerificationTypeInfo::Object(index) => writer.write_from("校验类型类索引", *index),
+            VerificationTypeInfo::Uninitialized(offset) => writer.write_from("校验类型偏移", *offset),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn tag(&self) -> u8 {
+        (match self {
+            VerificationTypeInfo::Top => JVM_ITEM_Top,
+            VerificationTypeInfo::Integer => JVM_ITEM_Integer,
+            VerificationTypeInfo::Float => JVM_ITEM_Float,
+            VerificationTypeInfo::Double => JVM_ITEM_Double,
+            VerificationTypeInfo::Long => JVM_ITEM_Long,
+            VerificationTypeInfo::Null => JVM_ITEM_Null,
+            VerificationTypeInfo::UninitializedThis => JVM_ITEM_UninitializedThis,
+            VerificationTypeInfo::Object(_) => JVM_ITEM_Object,
+            VerificationTypeInfo::Uninitialized(_) => JVM_ITEM_Uninitialized,
+        }) as u8
+    }
+
+    #[inline]
+    pub fn byte_size(&self) -> usize {
+        match self {
+            VerificationTypeInfo::Object(_) | VerificationTypeInfo::Uninitialized(_) => size_of::<u8>() + size_of::<u16>(),
+            _ => size_of::<u8>(),
+        }
+    }
+}
+
+impl StackMapFrame {
+    pub fn new_with_reader<T: Read>(reader: &mut DataReader<T>) -> Result<StackMapFrame> {
+        let frame_type: u8 = reader.read_to("栈映射帧类型")?;
+        Ok(match frame_type {
+            0..=63 => StackMapFrame::SameFrame { offset_delta: frame_type as u16 },
+            64..=127 => StackMapFrame::SameLocals1StackItemFrame {
+                offset_delta: (frame_type - 64) as u16,
+                stack: VerificationTypeInfo::new_with_reader(reader)?,
+            },
+            247 => StackMapFrame::SameLocals1StackItemFrameExtended {
+                offset_delta: reader.read_to("栈映射帧偏移")?,
+                stack: VerificationTypeInfo::new_with_reader(reader)?,
+            },
+            248..=250 => StackMapFrame::ChopFrame {
+                k: 251 - frame_type,
+                offset_delta: reader.read_to("栈映射帧偏移")?,
+            },
+            251 => StackMapFrame::SameFrameExtended { offset_delta: reader.read_to("栈映射帧偏移")? },
+            252..=254 => {
+                let offset_delta = reader.read_to("栈映射帧偏移")?;
+                let mut locals = Vec::with_capacity((frame_type - 251) as usize);
+                for _ in 251..frame_type {
+                    locals.push(VerificationTypeInfo::new_with_reader(reader)?);
+                }
+                StackMapFrame::AppendFrame {
+                    offset_delta,
+                    locals,
+                }
+            }
+            255 => StackMapFrame::FullFrame {
+                offset_delta: reader.read_to("栈映射帧偏移")?,
+                locals: read_list(reader, "局部变量数量", VerificationTypeInfo::new_with_reader)?,
+                stack: read_list(reader, "操作栈数量", VerificationTypeInfo::new_with_reader)?,
+            },
+            _ => {
+                return Err(MessageError::of(ErrorKind::InvalidAttribute, &format!("无效的栈映射帧类型[{}]", frame_type)).at(reader.position() - 1));
+            }
+        })
+    }
+
+    pub fn write_to<T: Write>(&self, writer: &mut DataWriter<T>) -> Result<()> {
+        writer.write_from("栈映射帧类型", self.frame_type()?)?;
+        match self {
+            StackMapFrame::SameFrame { .. } => Ok(()),
+            StackMapFrame::SameLocals1StackItemFrame { stack, .. } => stack.write_to(writer),
+            StackMapFrame::SameLocals1StackItemFrameExtended { offset_delta, stack } => {
+                writer.write_from("栈映射帧偏移", *offset_delta)?;
+                stack.write_to(writer)
+            }
+            StackMapFrame::ChopFrame { offset_delta, .. } |
+            StackMapFrame::SameFrameExtended { offset_delta } => writer.write_from("栈映射帧偏移", *offset_delta),
+            StackMapFrame::AppendFrame { offset_delta, locals } => {
+                writer.write_from("栈映射帧偏移", *offset_delta)?;
+                for local in locals {
+                    local.write_to(writer)?;
+                }
+                Ok(())
+            }
+            StackMapFrame::FullFrame { offset_delta, locals, stack } => {
+                writer.write_from("栈映射帧偏移", *offset_delta)?;
+                write_list(writer, "局部变量数量", locals, VerificationTypeInfo::write_to)?;
+                write_list(writer, "操作栈数量", stack, VerificationTypeInfo::write_to)
+            }
+        }
+    }
+
+    /// 帧类型字节，偏移或局部变量数量超出该帧类型的表示范围时返回错误
+    pub fn frame_type(&self) -> Result<u8> {
+        match self {
+            StackMapFrame::SameFrame { offset_delta } if *offset_delta <= 63 => Ok(*offset_delta as u8),
+            StackMapFrame::SameLocals1StackItemFrame { offset_delta, .. } if *offset_delta <= 63 => Ok(64 + *offset_delta as u8),
+            StackMapFrame::SameLocals1StackItemFrameExtended { .. } => Ok(247),
+            StackMapFrame::ChopFrame { k, .. } if (1..=3).contains(k) => Ok(251 - *k),
+            StackMapFrame::SameFrameExtended { .. } => Ok(251),
+            StackMapFrame::AppendFrame { locals, .. } if (1..=3).contains(&locals.len()) => Ok(251 + locals.len() as u8),
+            StackMapFrame::FullFrame { .. } => Ok(255),
+            _ => Err(MessageError::of(ErrorKind::InvalidAttribute, &format!("无效的栈映射帧: {:?}", self))),
+        }
+    }
+
+    pub fn offset_delta(&self) -> u16 {
+        match self {
+            StackMapFrame::SameFrame { offset_delta } |
+            StackMapFrame::SameLocals1StackItemFrame { offset_delta, .. } |
+            StackMapFrame::SameLocals1StackItemFrameExtended { offset_delta, .. } |
+            StackMapFrame::ChopFrame { offset_delta, .. } |
+            StackMapFrame::SameFrameExtended { offset_delta } |
+            StackMapFrame::AppendFrame { offset_delta, .. } |
+            StackMapFrame::FullFrame { offset_delta, .. } => *offset_delta,
+        }
+    }
+
+    /// 修改偏移增量，same与same_locals_1_stack_item帧在超出63时改用扩展形式，其余帧保持原形式
+    pub fn set_offset_delta(&mut self, delta: u16) {
+        match self {
+            StackMapFrame::SameFrame { .. } if delta > 63 => *self = StackMapFrame::SameFrameExtended { offset_delta: delta },
+            StackMapFrame::SameLocals1StackItemFrame { stack, .. } if delta > 63 => {
+                *self = StackMapFrame::SameLocals1StackItemFrameExtended { offset_delta: delta, stack: stack.clone() };
+            }
+            StackMapFrame::SameFrame { offset_delta } |
+            StackMapFrame::SameLocals1StackItemFrame { offset_delta, .. } |
+            StackMapFrame::SameLocals1StackItemFrameExtended { offset_delta, .. } |
+            StackMapFrame::ChopFrame { offset_delta, .. } |
+            StackMapFrame::SameFrameExtended { offset_delta } |
+            StackMapFrame::AppendFrame { offset_delta, .. } |
+            StackMapFrame::FullFrame { offset_delta, .. } => *offset_delta = delta,
+        }
+    }
+
+    /// 各帧对应的绝对pc，第一帧的pc为其偏移增量，之后每帧为前一帧pc + 偏移增量 + 1
+    pub fn absolute_pcs(frames: &[StackMapFrame]) -> Vec<u32> {
+        let mut pcs = Vec::with_capacity(frames.len());
+        for frame in frames {
+            let delta = frame.offset_delta() as u32;
+            pcs.push(pcs.last().map_or(delta, |pc| pc + delta + 1));
+        }
+        pcs
+    }
+
+    /// 按绝对pc重新设置各帧的偏移增量，pcs须与frames一一对应且严格递增
+    pub fn set_absolute_pcs(frames: &mut [StackMapFrame], pcs: &[u32]) -> Result<()> {
+        if frames.len() != pcs.len() {
+            let msg = format!("栈映射帧数量{}与pc数量{}不一致", frames.len(), pcs.len());
+            return Err(MessageError::of(ErrorKind::InvalidAttribute, &msg));
+        }
+        let mut previous: Option<u32> = None;
+        for (frame, pc) in frames.iter_mut().zip(pcs) {
+            let delta = match previous {
+                None => Some(*pc),
+                Some(previous) => pc.checked_sub(previous + 1),
+            };
+            match delta.and_then(|delta| u16::try_from(delta).ok()) {
+                Some(delta) => frame.set_offset_delta(delta),
+                None => return Err(MessageError::of(ErrorKind::InvalidAttribute, &format!("栈映射帧pc[{}]无效", pc))),
+            }
+            previous = Some(*pc);
+        }
+        Ok(())
+    }
+
+    pub fn byte_size(&self) -> usize {
+        size_of::<u8>() + match self {
+            StackMapFrame::SameFrame { .. } => 0,
+            StackMapFrame::SameLocals1StackItemFrame { stack, .. } => stack.byte_size(),
+            StackMapFrame::SameLocals1StackItemFrameExtended { stack, .. } => size_of::<u16>() + stack.byte_size(),
+            StackMapFrame::ChopFrame { .. } | StackMapFrame::SameFrameExtended { .. } => size_of::<u16>(),
+            StackMapFrame::AppendFrame { locals, .. } => {
+                size_of::<u16>() + locals.iter().map(VerificationTypeInfo::byte_size).sum::<usize>()
+            }
+            StackMapFrame::FullFrame { locals, stack, .. } => {
+                size_of::<u16>() + list_size(locals, VerificationTypeInfo::byte_size)
+                    + list_size(stack, VerificationTypeInfo::byte_size)
+            }
+        }
+    }
+}
+
+impl StackMapEntry {
+    pub fn new_with_reader<T: Read>(reader: &mut DataReader<T>) -> Result<StackMapEntry> {
+        let offset = reader.read_to("栈映射偏移")?;
+        let locals = read_list(reader, "局部变量数量", VerificationTypeInfo::new_with_reader)?;
+        let stack = read_list(reader, "操作栈数量", VerificationTypeInfo::new_with_reader)?;
+        Ok(StackMapEntry {
+            offset,
+            locals,
+            stack,
+        })
+    }
+
+    pub fn write_to<T: Write>(&self, writer: &mut DataWriter<T>) -> Result<()> {
+        writer.write_from("栈映射偏移", self.offset)?;
+        write_list(writer, "局部变量数量", &self.locals, VerificationTypeInfo::write_to)?;
+        write_list(writer, "操作栈数量", &self.stack, VerificationTypeInfo::write_to)
+    }
+
+    pub fn byte_size(&self) -> usize {
+        size_of::<u16>() + list_size(&self.locals, VerificationTypeInfo::byte_size)
+            + list_size(&self.stack, VerificationTypeInfo::byte_size)
+    }
+}
@@ -0,0 +1,410 @@
+use crate::access_flags::{ClassAccessFlags, FieldAccessFlags, MethodAccessFlags};
+use crate::attribute_info::AttributeInfo;
+use crate::common::error::{ErrorKind, MessageError, Result};
+use crate::common::opcode::opcodes::*;
+use crate::constant_pool::{ConstantPool, ConstantValue};
+use crate::descriptor::MethodDescriptor;
+use crate::field_info::FieldInfo;
+use crate::instruction::Instruction;
+use crate::instruction_list::{Label, MethodBody};
+use crate::jclass_info::{JClassInfo, DEFAULT_MAJOR_VERSION, JCLASS_MAGIC};
+use crate::method_info::MethodInfo;
+use crate::stack_map::{regenerate_stack_map_table, ClassHierarchy, SimpleClassHierarchy};
+use crate::verifier::STACK_MAP_MIN_VERSION;
+
+/// 可放入常量池的字面量，用于字段的ConstantValue及ldc
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    String(String),
+}
+
+impl From<i32> for Literal {
+    fn from(value: i32) -> Self {
+        Literal::Int(value)
+    }
+}
+
+impl From<i64> for Literal {
+    fn from(value: i64) -> Self {
+        Literal::Long(value)
+    }
+}
+
+impl From<f32> for Literal {
+    fn from(value: f32) -> Self {
+        Literal::Float(value)
+    }
+}
+
+impl From<f64> for Literal {
+    fn from(value: f64) -> Self {
+        Literal::Double(value)
+    }
+}
+
+impl From<&str> for Literal {
+    fn from(value: &str) -> Self {
+        Literal::String(value.to_string())
+    }
+}
+
+impl From<String> for Literal {
+    fn from(value: String) -> Self {
+        Literal::String(value)
+    }
+}
+
+fn add_literal(pool: &mut ConstantPool, literal: &Literal) -> u16 {
+    match literal {
+        Literal::Int(value) => pool.add_constant(ConstantValue::ConstantInteger(*value)),
+        Literal::Long(value) => pool.add_constant(ConstantValue::ConstantLong(*value)),
+        Literal::Float(value) => pool.add_constant(ConstantValue::ConstantFloat(*value)),
+        Literal::Double(value) => pool.add_constant(ConstantValue::ConstantDouble(*value)),
+        Literal::String(value) => pool.add_string(value),
+    }
+}
+
+/// 从零构建类，常量池按需分配
+///
+/// 默认版本为52，访问标志为public super，父类为java/lang/Object。类名与描述符均为内部形式
+///
+/// ```ignore
+/// let mut builder = ClassBuilder::new("sample/Hello");
+/// builder.interface("java/lang/Runnable")
+///     .constant_field(FieldAccessFlags::PUBLIC | FieldAccessFlags::STATIC | FieldAccessFlags::FINAL, "GREETING", "Ljava/lang/String;", "hello")?;
+/// let mut run = builder.method(MethodAccessFlags::PUBLIC, "run", "()V");
+/// run.field(GETSTATIC, "java/lang/System", "out", "Ljava/io/PrintStream;")
+///     .ldc("hello")
+///     .invoke(INVOKEVIRTUAL, "java/io/PrintStream", "println", "(Ljava/lang/String;)V")?
+///     .op(RETURN);
+/// run.finish()?;
+/// let class = builder.build()?;
+/// ```
+pub struct ClassBuilder {
+    class: JClassInfo,
+    source: Option<u16>,
+    signature: Option<u16>,
+}
+
+impl ClassBuilder {
+    pub fn new(name: &str) -> ClassBuilder {
+        let mut class = JClassInfo {
+            magic: JCLASS_MAGIC,
+            major_version: DEFAULT_MAJOR_VERSION,
+            constant_pool: ConstantPool::new(1),
+            access_flags: (ClassAccessFlags::PUBLIC | ClassAccessFlags::SUPER).bits(),
+            ..Default::default()
+        };
+        class.class_index = class.constant_pool.add_class(name);
+        class.superclass_index = class.constant_pool.add_class("java/lang/Object");
+        ClassBuilder { class, source: None, signature: None }
+    }
+
+    pub fn version(&mut self, major: u16, minor: u16) -> &mut Self {
+        self.class.major_version = major;
+        self.class.minor_version = minor;
+        self
+    }
+
+    pub fn access(&mut self, access: ClassAccessFlags) -> &mut Self {
+        self.class.set_access(access);
+        self
+    }
+
+    /// 为None时不设置父类，仅用于java/lang/Object及module-info
+    pub fn super_class(&mut self, name: Option<&str>) -> &mut Self {
+        self.class.superclass_index = match name {
+            Some(name) => self.class.constant_pool.add_class(name),
+            None => 0,
+        };
+        self
+    }
+
+    pub fn interface(&mut self, name: &str) -> &mut Self {
+        let index = self.class.constant_pool.add_class(name);
+        if !self.class.interfaces.contains(&index) {
+            self.class.interfaces.push(index);
+        }
+        self
+    }
+
+    pub fn source_file(&mut self, name: &str) -> &mut Self {
+        self.source = Some(self.class.constant_pool.add_utf8(name));
+        self
+    }
+
+    pub fn signature(&mut self, signature: &str) -> &mut Self {
+        self.signature = Some(self.class.constant_pool.add_utf8(signature));
+        self
+    }
+
+    pub fn constant_pool(&mut self) -> &mut ConstantPool {
+        &mut self.class.constant_pool
+    }
+
+    pub fn field(&mut self, access: FieldAccessFlags, name: &str, descriptor: &str) -> &mut Self {
+        let pool = &mut self.class.constant_pool;
+        let field = FieldInfo {
+            access_flags: access.bits(),
+            name: pool.add_utf8(name),
+            descriptor: pool.add_utf8(descriptor),
+            attributes: Vec::new(),
+        };
+        self.class.fields.push(field);
+        self
+    }
+
+    /// 带ConstantValue属性的字段，value的类型需与描述符一致：I、S、C、B、Z对应Int，J、F、D分别对应Long、Float、Double，
+    /// Ljava/lang/String;对应String
+    pub fn constant_field(&mut self, access: FieldAccessFlags, name: &str, descriptor: &str, value: impl Into<Literal>) -> Result<&mut Self> {
+        self.field(access, name, descriptor);
+        let pool = &mut self.class.constant_pool;
+        let index = add_literal(pool, &value.into());
+        let attribute = AttributeInfo::ConstantValue(index).to_origin(pool)?;
+        self.class.fields.last_mut().unwrap().attributes.push(attribute);
+        Ok(self)
+    }
+
+    /// 开始构建方法，调用MethodBuilder::finish后加入类中
+    pub fn method(&mut self, access: MethodAccessFlags, name: &str, descriptor: &str) -> MethodBuilder<'_> {
+        let pool = &mut self.class.constant_pool;
+        let info = MethodInfo {
+            access_flags: access.bits(),
+            name: pool.add_utf8(name),
+            descriptor: pool.add_utf8(descriptor),
+            attributes: Vec::new(),
+        };
+        MethodBuilder {
+            builder: self,
+            info,
+            descriptor: descriptor.to_string(),
+            body: MethodBody::new(),
+        }
+    }
+
+    /// 生成JClassInfo，版本不低于50时为各方法生成StackMapTable
+    ///
+    /// 类层次中只登记了正在构建的类，其余类视为java/lang/Object的直接子类。
+    /// 分支合并处出现不同引用类型时，应使用build_with_hierarchy提供准确的类层次
+    pub fn build(self) -> Result<JClassInfo> {
+        let mut hierarchy = SimpleClassHierarchy::new();
+        hierarchy.add_class(&self.class)?;
+        self.build_with_hierarchy(&hierarchy)
+    }
+
+    pub fn build_with_hierarchy(mut self, hierarchy: &dyn ClassHierarchy) -> Result<JClassInfo> {
+        let pool = &mut self.class.constant_pool;
+        if let Some(source) = self.source {
+            let attribute = AttributeInfo::SourceFile(source).to_origin(pool)?;
+            self.class.attributes.push(attribute);
+        }
+        if let Some(signature) = self.signature {
+            let attribute = AttributeInfo::Signature(signature).to_origin(pool)?;
+            self.class.attributes.push(attribute);
+        }
+        if self.class.major_version >= STACK_MAP_MIN_VERSION {
+            for i in 0..self.class.methods.len() {
+                regenerate_stack_map_table(&mut self.class, i, hierarchy)?;
+            }
+        }
+        Ok(self.class)
+    }
+}
+
+/// 构建单个方法的Code、Exceptions与Signature属性
+///
+/// 未指定max_stack或max_locals时按指令计算；没有指令且未指定二者的方法视为抽象或本地方法，不生成Code属性
+pub struct MethodBuilder<'a> {
+    builder: &'a mut ClassBuilder,
+    info: MethodInfo,
+    descriptor: String,
+    body: MethodBody,
+}
+
+impl<'a> MethodBuilder<'a> {
+    pub fn constant_pool(&mut self) -> &mut ConstantPool {
+        &mut self.builder.class.constant_pool
+    }
+
+    pub fn new_label(&mut self) -> Label {
+        self.body.list.new_label()
+    }
+
+    pub fn place_label(&mut self, label: Label) -> &mut Self {
+        self.body.list.place_label(label);
+        self
+    }
+
+    pub fn push(&mut self, instruction: Instruction) -> &mut Self {
+        self.body.list.push(instruction);
+        self
+    }
+
+    /// 无操作数的指令
+    pub fn op(&mut self, opcode: u8) -> &mut Self {
+        self.push(Instruction::Simple(opcode))
+    }
+
+    /// xload、xstore与ret，索引超过255时使用wide
+    pub fn local(&mut self, opcode: u8, index: u16) -> &mut Self {
+        match u8::try_from(index) {
+            Ok(index) => self.push(Instruction::Local(opcode, index)),
+            Err(_) => self.push(Instruction::WideLocal(opcode, index)),
+        }
+    }
+
+    /// 索引或增量超出单字节范围时使用wide
+    pub fn iinc(&mut self, index: u16, value: i16) -> &mut Self {
+        match (u8::try_from(index), i8::try_from(value)) {
+            (Ok(index), Ok(value)) => self.push(Instruction::Iinc(index, value)),
+            _ => self.push(Instruction::WideIinc(index, value)),
+        }
+    }
+
+    /// 按取值选择iconst、bipush、sipush或ldc
+    pub fn push_int(&mut self, value: i32) -> &mut Self {
+        match value {
+            -1..=5 => self.op((ICONST_0 as i32 + value) as u8),
+            _ => match (i8::try_from(value), i16::try_from(value)) {
+                (Ok(value), _) => self.push(Instruction::Bipush(value)),
+                (_, Ok(value)) => self.push(Instruction::Sipush(value)),
+                _ => self.ldc(value),
+            },
+        }
+    }
+
+    /// 按常量类型及索引选择ldc、ldc_w或ldc2_w
+    pub fn ldc(&mut self, value: impl Into<Literal>) -> &mut Self {
+        let value = value.into();
+        let index = add_literal(self.constant_pool(), &value);
+        let instruction = match value {
+            Literal::Long(_) | Literal::Double(_) => Instruction::Ldc2W(index),
+            _ if index <= u8::MAX as u16 => Instruction::Ldc(index),
+            _ => Instruction::LdcW(index),
+        };
+        self.push(instruction)
+    }
+
+    /// 加载Class常量
+    pub fn ldc_class(&mut self, name: &str) -> &mut Self {
+        let index = self.constant_pool().add_class(name);
+        match u8::try_from(index) {
+            Ok(_) => self.push(Instruction::Ldc(index)),
+            Err(_) => self.push(Instruction::LdcW(index)),
+        }
+    }
+
+    /// getstatic、putstatic、getfield与putfield
+    pub fn field(&mut self, opcode: u8, owner: &str, name: &str, descriptor: &str) -> &mut Self {
+        let index = self.constant_pool().add_fieldref(owner, name, descriptor);
+        self.push(Instruction::Field(opcode, index))
+    }
+
+    /// invokevirtual、invokespecial、invokestatic与invokeinterface，invokeinterface引用InterfaceMethodref，其余引用Methodref
+    pub fn invoke(&mut self, opcode: u8, owner: &str, name: &str, descriptor: &str) -> Result<&mut Self> {
+        if opcode == INVOKEINTERFACE {
+            return self.invoke_interface(opcode, owner, name, descriptor);
+        }
+        let index = self.constant_pool().add_methodref(owner, name, descriptor);
+        Ok(self.push(Instruction::Invoke(opcode, index)))
+    }
+
+    /// 调用接口中的方法，引用InterfaceMethodref，用于invokeinterface以及接口上的invokestatic、invokespecial
+    pub fn invoke_interface(&mut self, opcode: u8, owner: &str, name: &str, descriptor: &str) -> Result<&mut Self> {
+        let count = if opcode == INVOKEINTERFACE {
+            u8::try_from(MethodDescriptor::parse(descriptor)?.argument_slots(false))
+                .map_err(|_| MessageError::of(ErrorKind::LimitExceeded, "invokeinterface的参数过多"))?
+        } else {
+            0
+        };
+        let index = self.constant_pool().add_interface_methodref(owner, name, descriptor);
+        let instruction = match opcode {
+            INVOKEINTERFACE => Instruction::InvokeInterface(index, count),
+            _ => Instruction::Invoke(opcode, index),
+        };
+        Ok(self.push(instruction))
+    }
+
+    /// new、anewarray、checkcast与instanceof
+    pub fn type_op(&mut self, opcode: u8, class_name: &str) -> &mut Self {
+        let index = self.constant_pool().add_class(class_name);
+        self.push(Instruction::Type(opcode, index))
+    }
+
+    /// newarray，元素类型见array_type
+    pub fn new_array(&mut self, atype: u8) -> &mut Self {
+        self.push(Instruction::NewArray(atype))
+    }
+
+    pub fn multi_new_array(&mut self, descriptor: &str, dimensions: u8) -> &mut Self {
+        let index = self.constant_pool().add_class(descriptor);
+        self.push(Instruction::MultiANewArray(index, dimensions))
+    }
+
+    pub fn jump(&mut self, opcode: u8, target: Label) -> &mut Self {
+        self.body.list.jump(opcode, target);
+        self
+    }
+
+    pub fn table_switch(&mut self, low: i32, default: Label, targets: Vec<Label>) -> &mut Self {
+        self.body.list.table_switch(low, default, targets);
+        self
+    }
+
+    pub fn lookup_switch(&mut self, default: Label, pairs: Vec<(i32, Label)>) -> &mut Self {
+        self.body.list.lookup_switch(default, pairs);
+        self
+    }
+
+    /// 异常处理器，catch_type为None时捕获所有异常
+    pub fn try_catch(&mut self, start: Label, end: Label, handler: Label, catch_type: Option<&str>) -> &mut Self {
+        let catch_type = match catch_type {
+            Some(name) => self.constant_pool().add_class(name),
+            None => 0,
+        };
+        self.body.list.add_exception_handler(start, end, handler, catch_type);
+        self
+    }
+
+    /// 之后的指令属于源码的第line行
+    pub fn line(&mut self, line: u16) -> &mut Self {
+        let label = self.body.list.new_label();
+        self.body.list.place_label(label);
+        self.body.lines.push((label, line));
+        self
+    }
+
+    pub fn max_stack(&mut self, max_stack: u16) -> &mut Self {
+        self.body.max_stack = Some(max_stack);
+        self
+    }
+
+    pub fn max_locals(&mut self, max_locals: u16) -> &mut Self {
+        self.body.max_locals = Some(max_locals);
+        self
+    }
+
+    pub fn throws(&mut self, class_name: &str) -> &mut Self {
+        let index = self.constant_pool().add_class(class_name);
+        self.body.throws.push(index);
+        self
+    }
+
+    pub fn signature(&mut self, signature: &str) -> &mut Self {
+        self.body.signature = Some(self.constant_pool().add_utf8(signature));
+        self
+    }
+
+    /// 生成方法的属性并加入类中
+    pub fn finish(self) -> Result<()> {
+        let MethodBuilder { builder, mut info, descriptor, body } = self;
+        let argument_slots = MethodDescriptor::parse(&descriptor)?.argument_slots(info.access().contains(MethodAccessFlags::STATIC));
+        info.attributes = body.into_attributes(&mut builder.class.constant_pool, argument_slots)?;
+        builder.class.methods.push(info);
+        Ok(())
+    }
+}
@@ -0,0 +1,165 @@
+use crate::attribute_info::CodeAttribute;
+use crate::common::error::{ErrorKind, MessageError, Result};
+use crate::common::opcode::{opcodes::*, STACK_GROW};
+use crate::constant_pool::{ConstantPool, ConstantValue};
+use crate::descriptor::{FieldType, MethodDescriptor};
+use crate::instruction::Instruction;
+
+/// 解码后的字节码，可按pc查找指令
+pub(crate) struct DecodedCode {
+    pub instructions: Vec<(u32, Instruction)>,
+    /// pc对应的指令下标，非指令起始位置为None
+    indices: Vec<Option<usize>>,
+}
+
+impl DecodedCode {
+    pub fn new(codes: &[u8]) -> Result<DecodedCode> {
+        let mut instructions = Vec::new();
+        let mut indices = vec![None; codes.len()];
+        for item in crate::instruction::Instructions::new(codes) {
+            let (pc, instruction) = item?;
+            indices[pc as usize] = Some(instructions.len());
+            instructions.push((pc, instruction));
+        }
+        Ok(DecodedCode { instructions, indices })
+    }
+
+    /// pc对应的指令下标，from为引用该pc的指令位置，用于错误偏移
+    pub fn index_of(&self, pc: u32, from: u32) -> Result<usize> {
+        match self.indices.get(pc as usize) {
+            Some(Some(index)) => Ok(*index),
+            _ => Err(invalid(from, &format!("目标[{}]不是指令起始位置", pc))),
+        }
+    }
+
+    /// 顺序执行的下一条指令下标
+    pub fn next_index(&self, index: usize) -> Result<usize> {
+        if index + 1 < self.instructions.len() {
+            Ok(index + 1)
+        } else {
+            Err(invalid(self.instructions[index].0, "执行越过字节码末尾"))
+        }
+    }
+}
+
+/// 通过数据流分析计算操作栈最大深度与局部变量最大槽数
+///
+/// argument_slots为参数占用的槽数，实例方法包含this，见MethodDescriptor::argument_slots
+pub fn compute_maxs(code: &CodeAttribute, pool: &ConstantPool, argument_slots: u16) -> Result<(u16, u16)> {
+    let decoded = DecodedCode::new(&code.codes)?;
+    if decoded.instructions.is_empty() {
+        return Ok((0, argument_slots));
+    }
+
+    let mut max_locals = argument_slots as u32;
+    for (_, instruction) in &decoded.instructions {
+        if let Some((index, size)) = local_slot(instruction) {
+            max_locals = max_locals.max(index as u32 + size as u32);
+        }
+    }
+
+    let mut depths: Vec<Option<i32>> = vec![None; decoded.instructions.len()];
+    let mut pending = vec![(0, 0)];
+    for entry in &code.exceptions.entries {
+        // 异常处理器入口的操作栈只有异常对象
+        pending.push((decoded.index_of(entry.handler_pc as u32, entry.handler_pc as u32)?, 1));
+    }
+    let mut max_stack = 0;
+    while let Some((index, depth)) = pending.pop() {
+        let (pc, instruction) = &decoded.instructions[index];
+        match depths[index] {
+            Some(existing) if existing == depth => continue,
+            Some(existing) => return Err(invalid(*pc, &format!("操作栈深度不一致[{}, {}]", existing, depth))),
+            None => depths[index] = Some(depth),
+        }
+        // 异常处理器入口的深度不来自前驱指令，需单独计入
+        max_stack = max_stack.max(depth);
+        let after = depth + stack_effect(instruction, pool).map_err(|e| e.at(*pc as usize))?;
+        if after < 0 {
+            return Err(invalid(*pc, "操作栈下溢"));
+        }
+        max_stack = max_stack.max(after);
+        let opcode = instruction.opcode();
+        for target in instruction.branch_targets() {
+            pending.push((decoded.index_of(target, *pc)?, after));
+        }
+        match opcode {
+            // jsr压入的返回地址由子程序消费，ret返回到jsr之后
+            JSR | JSR_W => pending.push((decoded.next_index(index)?, depth)),
+            GOTO | GOTO_W | RET | TABLESWITCH | LOOKUPSWITCH | IRETURN..=RETURN | ATHROW => {}
+            _ => pending.push((decoded.next_index(index)?, after)),
+        }
+    }
+    let max_stack = u16::try_from(max_stack).map_err(|_| MessageError::of(ErrorKind::LimitExceeded, "操作栈深度超出65535"))?;
+    let max_locals = u16::try_from(max_locals).map_err(|_| MessageError::of(ErrorKind::LimitExceeded, "局部变量槽数超出65535"))?;
+    Ok((max_stack, max_locals))
+}
+
+/// 指令对操作栈深度的净影响，字段访问与方法调用按常量池中的描述符计算
+pub fn stack_effect(instruction: &Instruction, pool: &ConstantPool) -> Result<i32> {
+    let effect = match instruction {
+        Instruction::Field(opcode, index) => {
+            let size = FieldType::parse(pool.member_ref(*index)?.descriptor)?.slot_size() as i32;
+            match *opcode {
+                GETSTATIC => size,
+                PUTSTATIC => -size,
+                GETFIELD => size - 1,
+                _ => -size - 1,
+            }
+        }
+        Instruction::Invoke(opcode, index) => {
+            let descriptor = MethodDescriptor::parse(pool.member_ref(*index)?.descriptor)?;
+            descriptor.return_slots() as i32 - descriptor.argument_slots(*opcode == INVOKESTATIC) as i32
+        }
+        Instruction::InvokeInterface(index, _) => {
+            let descriptor = MethodDescriptor::parse(pool.member_ref(*index)?.descriptor)?;
+            descriptor.return_slots() as i32 - descriptor.argument_slots(false) as i32
+        }
+        Instruction::InvokeDynamic(index) => {
+            let name_and_type = match pool.get(*index)? {
+                ConstantValue::ConstantInvokeDynamic(_, name_and_type) => *name_and_type,
+                value => {
+                    let msg = format!("常量[{}]应为InvokeDynamic，实际为{}", index, value.kind_name());
+                    return Err(MessageError::of(ErrorKind::ConstantMismatch, &msg));
+                }
+            };
+            let descriptor = MethodDescriptor::parse(pool.name_and_type(name_and_type)?.1)?;
+            descriptor.return_slots() as i32 - descriptor.argument_slots(true) as i32
+        }
+        Instruction::MultiANewArray(_, dimensions) => 1 - *dimensions as i32,
+        Instruction::WideLocal(opcode, _) => STACK_GROW[*opcode as usize] as i32,
+        Instruction::WideIinc(_, _) => 0,
+        instruction => STACK_GROW[instruction.opcode() as usize] as i32,
+    };
+    Ok(effect)
+}
+
+/// 指令访问的局部变量索引及槽数
+pub(crate) fn local_slot(instruction: &Instruction) -> Option<(u16, u16)> {
+    let (opcode, index) = match instruction {
+        Instruction::Local(opcode, index) => (*opcode, *index as u16),
+        Instruction::WideLocal(opcode, index) => (*opcode, *index),
+        Instruction::Iinc(index, _) => return Some((*index as u16, 1)),
+        Instruction::WideIinc(index, _) => return Some((*index, 1)),
+        // xload_n与xstore_n按i、l、f、d、a分组，每组4个
+        Instruction::Simple(opcode @ ILOAD_0..=ALOAD_3) => {
+            let offset = opcode - ILOAD_0;
+            return Some(((offset % 4) as u16, if matches!(offset / 4, 1 | 3) { 2 } else { 1 }));
+        }
+        Instruction::Simple(opcode @ ISTORE_0..=ASTORE_3) => {
+            let offset = opcode - ISTORE_0;
+            return Some(((offset % 4) as u16, if matches!(offset / 4, 1 | 3) { 2 } else { 1 }));
+        }
+        _ => return None,
+    };
+    let size = match opcode {
+        LLOAD | DLOAD | LSTORE | DSTORE => 2,
+        _ => 1,
+    };
+    Some((index, size))
+}
+
+#[inline]
+fn invalid(pc: u32, msg: &str) -> MessageError {
+    MessageError::of(ErrorKind::InvalidBytecode, msg).at(pc as usize)
+}
@@ -2,6 +2,8 @@
 pub const ANNOTATION_DEFAULT_TAG: &str = "AnnotationDefault";
 pub const RUNTIME_VISIBLE_ANNOTATIONS_VISIBLE_TAG: &str = "RuntimeVisibleAnnotations";
 pub const RUNTIME_INVISIBLE_ANNOTATIONS_INVISIBLE_TAG: &str = "RuntimeInvisibleAnnotations";
+pub const RUNTIME_VISIBLE_PARAMETER_ANNOTATIONS_VISIBLE_TAG: &str = "RuntimeVisibleParameterAnnotations";
+pub const RUNTIME_INVISIBLE_PARAMETER_ANNOTATIONS_INVISIBLE_TAG: &str = "RuntimeInvisibleParameterAnnotations";
 pub const BOOTSTRAP_METHODS_TAG: &str = "BootstrapMethods";
 pub const CODE_TAG: &str = "Code";
 pub const CONSTANT_VALUE_TAG: &str = "ConstantValue";
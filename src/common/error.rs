@@ -1,8 +1,46 @@
+use std::any::Any;
 use std::fmt::{Debug, Display, Formatter};
 
+/// 错误类型，用于区分截断的输入与格式错误的输入
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// 输入提前结束，通常为截断的文件
+    UnexpectedEof,
+    /// 读写时的其他IO错误
+    Io,
+    BadMagic,
+    InvalidConstantTag,
+    /// 常量索引越界或指向占位项
+    BadIndex,
+    /// 常量类型与期望不符
+    ConstantMismatch,
+    InvalidUtf8,
+    /// 超出解析限制
+    LimitExceeded,
+    InvalidAttribute,
+    /// 属性内容无法识别，无法进行需要理解其内容的操作
+    UnsupportedAttribute,
+    InvalidBytecode,
+    /// 指令合法但不支持分析，如jsr与ret子程序
+    UnsupportedInstruction,
+    InvalidDescriptor,
+    InvalidSignature,
+    InvalidAccessFlags,
+    /// 汇编文本语法错误
+    Syntax,
+    #[default]
+    Other,
+}
+
+/// msg为错误描述，offset为出错位置在输入中的字节偏移（延迟解析的属性为相对属性数据的偏移），
+/// path为出错的结构路径，如methods[3].attributes[1].Code
 #[derive(Debug, Clone)]
 pub struct MessageError {
-    pub msg: String
+    pub msg: String,
+    pub kind: ErrorKind,
+    pub offset: Option<usize>,
+    pub path: String,
 }
 
 #[macro_export]
@@ -10,28 +48,87 @@ macro_rules! with_message {
     ($result:expr, $msg:expr) => {
         match $result {
             Ok(t) => Ok(t),
-            Err(e) => Err(MessageError::new(&format!("{}: {}", $msg, e))),
+            Err(e) => Err($crate::common::error::MessageError::with_source($msg, &e)),
         }
     };
 }
 
 impl MessageError {
     pub fn new(msg: &str) -> MessageError {
+        Self::of(ErrorKind::Other, msg)
+    }
+
+    pub fn of(kind: ErrorKind, msg: &str) -> MessageError {
         MessageError {
-            msg: String::from(msg)
+            msg: String::from(msg),
+            kind,
+            offset: None,
+            path: String::new(),
+        }
+    }
+
+    /// 在source的描述前加上msg，source为io::Error或MessageError时保留其错误类型
+    pub fn with_source<E: Display + 'static>(msg: &str, source: &E) -> MessageError {
+        let any = source as &dyn Any;
+        if let Some(error) = any.downcast_ref::<MessageError>() {
+            let mut error = error.clone();
+            error.msg = format!("{}: {}", msg, error.msg);
+            return error;
+        }
+        let kind = match any.downcast_ref::<std::io::Error>() {
+            Some(error) if error.kind() == std::io::ErrorKind::UnexpectedEof => ErrorKind::UnexpectedEof,
+            Some(_) => ErrorKind::Io,
+            None => ErrorKind::Other,
+        };
+        Self::of(kind, &format!("{}: {}", msg, source))
+    }
+
+    /// 记录出错的字节偏移，已记录时保留更内层的偏移
+    pub fn at(mut self, offset: usize) -> MessageError {
+        if self.offset.is_none() {
+            self.offset = Some(offset);
         }
+        self
+    }
+
+    /// 在结构路径前加上外层路径，内层路径以[开头时直接拼接
+    pub fn in_path(mut self, segment: &str) -> MessageError {
+        self.path = match (self.path.is_empty(), self.path.starts_with('[')) {
+            (true, _) => segment.to_string(),
+            (false, true) => format!("{}{}", segment, self.path),
+            (false, false) => format!("{}.{}", segment, self.path),
+        };
+        self
+    }
+
+    #[inline]
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// 输入提前结束导致的错误
+    #[inline]
+    pub fn is_eof(&self) -> bool {
+        self.kind == ErrorKind::UnexpectedEof
     }
 }
 
-impl<T> Into<Result<T>> for MessageError {
-    fn into(self) -> Result<T> {
-        Err(self)
+impl<T> From<MessageError> for Result<T> {
+    fn from(value: MessageError) -> Self {
+        Err(value)
     }
 }
 
 impl Display for MessageError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        f.write_str(&self.msg)
+        if !self.path.is_empty() {
+            write!(f, "{}: ", self.path)?;
+        }
+        f.write_str(&self.msg)?;
+        if let Some(offset) = self.offset {
+            write!(f, " (偏移{})", offset)?;
+        }
+        Ok(())
     }
 }
 
@@ -40,6 +137,50 @@ impl std::error::Error for MessageError {
 }
 
 pub type Result<T> = core::result::Result<T, MessageError>;
+
+/// 常量池访问错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConstantError {
+    /// 索引为0或超出常量数量
+    BadIndex { index: u16, count: u16 },
+    /// Long/Double常量之后不可用的占位索引
+    PhantomIndex { index: u16 },
+    /// 常量类型与期望不符
+    KindMismatch { index: u16, expected: &'static str, found: &'static str },
+    /// Long/Double与其他常量占用的槽数不同，无法原位替换
+    SlotMismatch { index: u16 },
+    /// Utf8常量的内容不是合法的modified UTF-8，无法作为字符串使用
+    InvalidUtf8 { index: u16 },
+}
+
+impl Display for ConstantError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConstantError::BadIndex { index, count } => write!(f, "无效的常量索引[{index}]，常量数量为{count}"),
+            ConstantError::PhantomIndex { index } => write!(f, "常量索引[{index}]为Long/Double之后的占位项"),
+            ConstantError::KindMismatch { index, expected, found } => write!(f, "常量[{index}]类型不符，期望{expected}，实际为{found}"),
+            ConstantError::SlotMismatch { index } => write!(f, "常量[{index}]替换前后占用的槽数不同"),
+            ConstantError::InvalidUtf8 { index } => write!(f, "常量[{index}]不是合法的modified UTF-8"),
+        }
+    }
+}
+
+impl std::error::Error for ConstantError {
+
+}
+
+impl From<ConstantError> for MessageError {
+    fn from(value: ConstantError) -> Self {
+        let kind = match value {
+            ConstantError::BadIndex { .. } | ConstantError::PhantomIndex { .. } => ErrorKind::BadIndex,
+            ConstantError::KindMismatch { .. } | ConstantError::SlotMismatch { .. } => ErrorKind::ConstantMismatch,
+            ConstantError::InvalidUtf8 { .. } => ErrorKind::InvalidUtf8,
+        };
+        MessageError::of(kind, &value.to_string())
+    }
+}
+
+pub type ConstantResult<T> = core::result::Result<T, ConstantError>;
 //
 // pub trait ToResult<T> {
 //     fn with_message(self, msg: &str) -> Result<T>;
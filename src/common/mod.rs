@@ -1,3 +1,4 @@
 pub mod error;
 pub mod constants;
 pub mod opcode;
+pub mod options;
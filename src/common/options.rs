@@ -0,0 +1,35 @@
+/// 解析限制，防止恶意构造的class文件触发超大内存分配
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// 单个属性数据的最大字节数
+    pub max_attribute_size: usize,
+    /// Code属性中字节码的最大字节数，JVMS规定小于65536
+    pub max_code_size: usize,
+    /// 常量池数量（constant_pool_count）的最大值
+    pub max_constant_count: usize,
+    /// 输入数据的最大字节数
+    pub max_input_size: usize,
+}
+
+impl ParseOptions {
+    /// 不限制大小，仅用于可信的输入
+    pub const fn unlimited() -> ParseOptions {
+        ParseOptions {
+            max_attribute_size: usize::MAX,
+            max_code_size: usize::MAX,
+            max_constant_count: usize::MAX,
+            max_input_size: usize::MAX,
+        }
+    }
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            max_attribute_size: 64 * 1024 * 1024,
+            max_code_size: u16::MAX as usize,
+            max_constant_count: u16::MAX as usize,
+            max_input_size: 256 * 1024 * 1024,
+        }
+    }
+}
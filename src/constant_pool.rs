@@ -1,5 +1,5 @@
-use crate::classfile_constants::{JVM_CONSTANT_Class, JVM_CONSTANT_Double, JVM_CONSTANT_Dynamic, JVM_CONSTANT_Fieldref, JVM_CONSTANT_Float, JVM_CONSTANT_Integer, JVM_CONSTANT_InterfaceMethodref, JVM_CONSTANT_InvokeDynamic, JVM_CONSTANT_Long, JVM_CONSTANT_MethodHandle, JVM_CONSTANT_MethodType, JVM_CONSTANT_Methodref, JVM_CONSTANT_Module, JVM_CONSTANT_NameAndType, JVM_CONSTANT_Package, JVM_CONSTANT_String, JVM_CONSTANT_Utf8};
-use crate::common::error::{MessageError, Result};
+use crate::classfile_constants::{JVM_CONSTANT_Class, JVM_CONSTANT_Double, JVM_CONSTANT_Dynamic, JVM_CONSTANT_Fieldref, JVM_CONSTANT_Float, JVM_CONSTANT_Integer, JVM_CONSTANT_InterfaceMethodref, JVM_CONSTANT_InvokeDynamic, JVM_CONSTANT_Long, JVM_CONSTANT_MethodHandle, JVM_CONSTANT_MethodType, JVM_CONSTANT_Methodref, JVM_CONSTANT_Module, JVM_CONSTANT_NameAndType, JVM_CONSTANT_Package, JVM_CONSTANT_String, JVM_CONSTANT_Utf8, JVM_REF_getField, JVM_REF_invokeInterface, JVM_REF_putStatic, _bindgen_ty_4};
+use crate::common::error::{ConstantError, ConstantResult, ErrorKind, MessageError, Result};
 use crate::support::data_reader::{DataReader, DataWriter, ReadToType, WriteFromType};
 use crate::with_message;
 use std::cmp::Ordering;
@@ -7,6 +7,7 @@ use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
 use std::io::{Read, Write};
 use crate::jclass_info::JClassInfo;
+use crate::util::mutf8;
 
 #[derive(Clone, Debug)]
 pub struct RefInfo {
@@ -14,6 +15,9 @@ pub struct RefInfo {
     name_type_index: u16,
 }
 
+// ConstantUtf8Raw的判别值，不对应任何JVM常量标记
+const UTF8_RAW_DISCRIMINANT: u8 = 0xFF;
+
 #[repr(u8)]
 #[derive(Clone, Debug)]
 pub enum ConstantValue {
@@ -32,6 +36,8 @@ pub enum ConstantValue {
     // name index, type index
     ConstantNameAndType(u16, u16) = JVM_CONSTANT_NameAndType as u8,
     ConstantUtf8(String) = JVM_CONSTANT_Utf8 as u8,
+    // 无法按modified UTF-8解码的原始字节，写出时仍为Utf8常量
+    ConstantUtf8Raw(Vec<u8>) = UTF8_RAW_DISCRIMINANT,
     // ref kind, ref index
     ConstantMethodHandle(u8, u16) = JVM_CONSTANT_MethodHandle as u8,
     // descriptor
@@ -46,6 +52,21 @@ pub enum ConstantValue {
     ConstantPackage(u16) = JVM_CONSTANT_Package as u8,
 }
 
+/// 解析后的字段或方法引用
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemberRef<'a> {
+    pub owner: &'a str,
+    pub name: &'a str,
+    pub descriptor: &'a str,
+}
+
+/// 解析后的方法句柄，kind为JVM_REF_*引用类型
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MethodHandleRef<'a> {
+    pub kind: u8,
+    pub member: MemberRef<'a>,
+}
+
 #[derive(Debug, Clone)]
 pub struct ConstantItem {
     index: u16,
@@ -117,8 +138,10 @@ impl ConstantValue {
             }
             JVM_CONSTANT_Utf8 => {
                 let str_bytes = reader.read_bytes_with_pre_size("UTF8字符串常量")?;
-                let string = with_message!(String::from_utf8(str_bytes), "UTF8常量读取出错")?;
-                ConstantValue::ConstantUtf8(string)
+                match mutf8::decode(&str_bytes) {
+                    Some(string) => ConstantValue::ConstantUtf8(string),
+                    None => ConstantValue::ConstantUtf8Raw(str_bytes),
+                }
             }
             JVM_CONSTANT_MethodHandle => {
                 let kind_byte: u8 = reader.read_to("Method Handle常量")?;
@@ -147,7 +170,7 @@ impl ConstantValue {
                 ConstantValue::ConstantPackage(index)
             }
             _ => {
-                return Err(MessageError::new(&format!("无效的常量类型[{}]", const_type)));
+                return Err(MessageError::of(ErrorKind::InvalidConstantTag, &format!("无效的常量类型[{}]", const_type)).at(reader.position() - 1));
             }
         })
     }
@@ -156,7 +179,7 @@ impl ConstantValue {
         if let ConstantValue::Null = self {
             return Ok(())
         }
-        let const_type = self.value();
+        let const_type = self.tag();
         writer.write_from("常量类型", const_type)?;
         match self {
             ConstantValue::Null => {
@@ -197,7 +220,10 @@ impl ConstantValue {
                 writer.write_from("名字和描述符常量", *type_index)
             }
             ConstantValue::ConstantUtf8(val) => {
-                writer.write_bytes_with_pre_size("UTF8字符串常量", val.as_bytes())
+                writer.write_bytes_with_pre_size("UTF8字符串常量", &mutf8::encode(val))
+            }
+            ConstantValue::ConstantUtf8Raw(val) => {
+                writer.write_bytes_with_pre_size("UTF8字符串常量", val)
             }
             ConstantValue::ConstantMethodHandle(kind_byte, ref_index) => {
                 writer.write_from("Method Handle常量", *kind_byte)?;
@@ -254,16 +280,94 @@ impl ConstantValue {
                 size_of::<f64>()
             }
             ConstantValue::ConstantUtf8(val) => {
-                val.as_bytes().len() + size_of::<u16>()
+                mutf8::encoded_len(val) + size_of::<u16>()
+            }
+            ConstantValue::ConstantUtf8Raw(val) => {
+                val.len() + size_of::<u16>()
             }
         }
     }
 
+    /// class文件中的常量类型标记，与tag相同
+    #[inline]
     pub fn value(&self) -> u8 {
+        self.tag()
+    }
+
+    // 枚举判别值，用于区分ConstantUtf8与ConstantUtf8Raw
+    fn discriminant(&self) -> u8 {
         unsafe {
             *(self as *const ConstantValue as *const u8)
         }
     }
+
+    /// 常量类型名称，用于错误信息
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            ConstantValue::Null => "Null",
+            ConstantValue::ConstantClass(_) => "Class",
+            ConstantValue::ConstantFieldref(_, _) => "Fieldref",
+            ConstantValue::ConstantMethodref(_, _) => "Methodref",
+            ConstantValue::ConstantInterfaceMethodref(_, _) => "InterfaceMethodref",
+            ConstantValue::ConstantString(_) => "String",
+            ConstantValue::ConstantInteger(_) => "Integer",
+            ConstantValue::ConstantFloat(_) => "Float",
+            ConstantValue::ConstantLong(_) => "Long",
+            ConstantValue::ConstantDouble(_) => "Double",
+            ConstantValue::ConstantNameAndType(_, _) => "NameAndType",
+            ConstantValue::ConstantUtf8(_) | ConstantValue::ConstantUtf8Raw(_) => "Utf8",
+            ConstantValue::ConstantMethodHandle(_, _) => "MethodHandle",
+            ConstantValue::ConstantMethodType(_) => "MethodType",
+            ConstantValue::ConstantDynamic(_, _) => "Dynamic",
+            ConstantValue::ConstantInvokeDynamic(_, _) => "InvokeDynamic",
+            ConstantValue::ConstantModule(_) => "Module",
+            ConstantValue::ConstantPackage(_) => "Package",
+        }
+    }
+
+    /// Long与Double占用两个常量槽
+    #[inline]
+    pub fn is_wide(&self) -> bool {
+        matches!(self, ConstantValue::ConstantLong(_) | ConstantValue::ConstantDouble(_))
+    }
+
+    /// 常量中引用的其他常量索引，不含引导方法索引
+    pub fn constant_indices(&self) -> Vec<u16> {
+        let mut indices = Vec::with_capacity(2);
+        self.clone().remap_constant_indices(&mut |index| {
+            indices.push(index);
+            index
+        });
+        indices
+    }
+
+    /// 改写常量中引用的其他常量索引
+    pub fn remap_constant_indices(&mut self, remap: &mut dyn FnMut(u16) -> u16) {
+        match self {
+            ConstantValue::ConstantClass(index) | ConstantValue::ConstantString(index) |
+            ConstantValue::ConstantMethodHandle(_, index) | ConstantValue::ConstantMethodType(index) |
+            ConstantValue::ConstantModule(index) | ConstantValue::ConstantPackage(index) |
+            ConstantValue::ConstantDynamic(_, index) | ConstantValue::ConstantInvokeDynamic(_, index) => {
+                *index = remap(*index);
+            }
+            ConstantValue::ConstantFieldref(class_index, name_type_index) |
+            ConstantValue::ConstantMethodref(class_index, name_type_index) |
+            ConstantValue::ConstantInterfaceMethodref(class_index, name_type_index) |
+            ConstantValue::ConstantNameAndType(class_index, name_type_index) => {
+                *class_index = remap(*class_index);
+                *name_type_index = remap(*name_type_index);
+            }
+            _ => {}
+        }
+    }
+
+    /// class文件中的常量类型标记
+    pub fn tag(&self) -> u8 {
+        match self {
+            ConstantValue::ConstantUtf8Raw(_) => JVM_CONSTANT_Utf8 as u8,
+            _ => self.discriminant(),
+        }
+    }
     // pub fn byte(&self) -> u8 {
     //     let result = match self {
     //         ConstantValue::Null => 0,
@@ -307,11 +411,16 @@ impl ConstantPool {
     pub fn new_with_reader<T: Read>(reader: &mut DataReader<T>) -> Result<ConstantPool> {
         let name = "常量池";
         let pool_count: u16 = reader.read_to(name)?;
+        let max_count = reader.options().max_constant_count;
+        if pool_count as usize > max_count {
+            let msg = format!("常量池数量{}超出限制{}", pool_count, max_count);
+            return Err(MessageError::of(ErrorKind::LimitExceeded, &msg).at(reader.position() - size_of::<u16>()));
+        }
         let mut pool = ConstantPool::new(pool_count);
         let mut i = 1;
         while i < pool_count {
         // for _ in 1..pool_count {
-            let value = ConstantValue::new_with_reader(reader)?;
+            let value = ConstantValue::new_with_reader(reader).map_err(|e| e.in_path(&format!("constant_pool[{}]", i)))?;
             match &value {
                 ConstantValue::ConstantLong(_) | ConstantValue::ConstantDouble(_) => {
                     pool.add_constant_force(value);
@@ -349,7 +458,9 @@ impl ConstantPool {
             None => {
                 let mut cache = HashMap::with_capacity(self.count as usize);
                 for item in &self.values {
-                    cache.insert(item.value.clone(), item.index);
+                    if !matches!(item.value, ConstantValue::Null) {
+                        cache.entry(item.value.clone()).or_insert(item.index);
+                    }
                 }
                 self.cache = Some(cache);
                 self.cache()
@@ -363,11 +474,17 @@ impl ConstantPool {
     //     self.count
     // }
 
+    /// 添加常量，已存在相同常量时返回其索引，Long/Double会同时占用其后的占位项
     pub fn add_constant(&mut self, value: ConstantValue) -> u16 {
         if let Some(index) = self.cache().get(&value) {
             return *index;
         }
-        self.add_constant_force(value)
+        let wide = value.is_wide();
+        let index = self.add_constant_force(value);
+        if wide {
+            self.add_constant_force(ConstantValue::Null);
+        }
+        index
     }
 
     #[inline]
@@ -377,15 +494,93 @@ impl ConstantPool {
             index: self.count,
             value
         };
-        if let Some(cache) = &mut self.cache {
+        if let (Some(cache), false) = (&mut self.cache, matches!(item.value, ConstantValue::Null)) {
             cache.insert(item.value.clone(), self.count);
         }
         self.values.push(item);
         self.count
     }
 
+    /// 查找已存在的常量，不会添加
+    pub fn find(&self, value: &ConstantValue) -> Option<u16> {
+        if matches!(value, ConstantValue::Null) {
+            return None;
+        }
+        match &self.cache {
+            Some(cache) => cache.get(value).copied(),
+            None => self.values.iter()
+                .find(|item| item.value == *value)
+                .map(|item| item.index),
+        }
+    }
+
+    pub fn add_utf8(&mut self, value: &str) -> u16 {
+        self.add_constant(ConstantValue::ConstantUtf8(value.to_string()))
+    }
+
+    /// name为内部形式的类名，如java/lang/String
+    pub fn add_class(&mut self, name: &str) -> u16 {
+        let name_index = self.add_utf8(name);
+        self.add_constant(ConstantValue::ConstantClass(name_index))
+    }
+
+    pub fn add_string(&mut self, value: &str) -> u16 {
+        let utf8_index = self.add_utf8(value);
+        self.add_constant(ConstantValue::ConstantString(utf8_index))
+    }
+
+    pub fn add_name_and_type(&mut self, name: &str, descriptor: &str) -> u16 {
+        let name_index = self.add_utf8(name);
+        let type_index = self.add_utf8(descriptor);
+        self.add_constant(ConstantValue::ConstantNameAndType(name_index, type_index))
+    }
+
+    pub fn add_fieldref(&mut self, owner: &str, name: &str, descriptor: &str) -> u16 {
+        let (class_index, name_type_index) = self.add_member(owner, name, descriptor);
+        self.add_constant(ConstantValue::ConstantFieldref(class_index, name_type_index))
+    }
+
+    pub fn add_methodref(&mut self, owner: &str, name: &str, descriptor: &str) -> u16 {
+        let (class_index, name_type_index) = self.add_member(owner, name, descriptor);
+        self.add_constant(ConstantValue::ConstantMethodref(class_index, name_type_index))
+    }
+
+    pub fn add_interface_methodref(&mut self, owner: &str, name: &str, descriptor: &str) -> u16 {
+        let (class_index, name_type_index) = self.add_member(owner, name, descriptor);
+        self.add_constant(ConstantValue::ConstantInterfaceMethodref(class_index, name_type_index))
+    }
+
+    #[inline]
+    fn add_member(&mut self, owner: &str, name: &str, descriptor: &str) -> (u16, u16) {
+        (self.add_class(owner), self.add_name_and_type(name, descriptor))
+    }
+
+    /// kind为JVM_REF_*引用类型，字段操作引用Fieldref，invokeInterface引用InterfaceMethodref，
+    /// invokeStatic与invokeSpecial在interface为true时引用InterfaceMethodref
+    pub fn add_method_handle(&mut self, kind: u8, member: &MemberRef, interface: bool) -> u16 {
+        let ref_index = match kind as _bindgen_ty_4 {
+            JVM_REF_getField..=JVM_REF_putStatic => self.add_fieldref(member.owner, member.name, member.descriptor),
+            JVM_REF_invokeInterface => self.add_interface_methodref(member.owner, member.name, member.descriptor),
+            _ if interface => self.add_interface_methodref(member.owner, member.name, member.descriptor),
+            _ => self.add_methodref(member.owner, member.name, member.descriptor),
+        };
+        self.add_constant(ConstantValue::ConstantMethodHandle(kind, ref_index))
+    }
+
+    pub fn add_method_type(&mut self, descriptor: &str) -> u16 {
+        let descriptor_index = self.add_utf8(descriptor);
+        self.add_constant(ConstantValue::ConstantMethodType(descriptor_index))
+    }
+
+    /// bootstrap_method为BootstrapMethods属性中的引导方法序号
+    pub fn add_invoke_dynamic(&mut self, bootstrap_method: u16, name: &str, descriptor: &str) -> u16 {
+        let name_type_index = self.add_name_and_type(name, descriptor);
+        self.add_constant(ConstantValue::ConstantInvokeDynamic(bootstrap_method, name_type_index))
+    }
+
+    /// 未检查的访问，索引无效时返回ConstantValue::Null
     pub fn get_constant_item(&self, index: u16) -> &ConstantValue {
-        if index >= self.count {
+        if index > self.count {
             &self.values[0].value
         } else {
             &self.values[index as usize].value
@@ -395,11 +590,152 @@ impl ConstantPool {
     pub fn get_constant_count(&self) -> u16 {
         self.count
     }
+
+    /// 检查索引的访问，索引越界或为占位项时返回错误
+    pub fn get(&self, index: u16) -> ConstantResult<&ConstantValue> {
+        if index == 0 || index > self.count {
+            return Err(ConstantError::BadIndex { index, count: self.count });
+        }
+        match &self.values[index as usize].value {
+            ConstantValue::Null => Err(ConstantError::PhantomIndex { index }),
+            value => Ok(value),
+        }
+    }
+
+    /// 遍历所有有效常量，跳过索引0与Long/Double之后的占位项
+    pub fn iter(&self) -> impl Iterator<Item = (u16, &ConstantValue)> {
+        self.values.iter()
+            .filter(|item| !matches!(item.value, ConstantValue::Null))
+            .map(|item| (item.index, &item.value))
+    }
+
+    /// 原位替换常量并返回原常量，替换前后占用的槽数须相同
+    pub fn replace(&mut self, index: u16, value: ConstantValue) -> ConstantResult<ConstantValue> {
+        if self.get(index)?.is_wide() != value.is_wide() {
+            return Err(ConstantError::SlotMismatch { index });
+        }
+        self.cache = None;
+        Ok(std::mem::replace(&mut self.values[index as usize].value, value))
+    }
+
+    /// retain(used)产生的原索引到新索引的映射，不修改常量池
+    pub fn retain_mapping(&self, used: &[bool]) -> Vec<u16> {
+        let mut keep = vec![false; self.values.len()];
+        let mut pending: Vec<u16> = (1..self.values.len() as u16)
+            .filter(|index| used.get(*index as usize).copied().unwrap_or(false))
+            .collect();
+        while let Some(index) = pending.pop() {
+            match keep.get_mut(index as usize) {
+                Some(keep) if !*keep => *keep = true,
+                _ => continue,
+            }
+            pending.extend(self.values[index as usize].value.constant_indices());
+        }
+
+        let mut mapping = vec![0; self.values.len()];
+        let mut next = 1;
+        for item in &self.values {
+            if !keep[item.index as usize] || matches!(item.value, ConstantValue::Null) {
+                continue;
+            }
+            mapping[item.index as usize] = next;
+            next += if item.value.is_wide() { 2 } else { 1 };
+        }
+        mapping
+    }
+
+    /// 只保留used中标记的常量及其依赖的常量，按原顺序重新编号，
+    /// 返回原索引到新索引的映射，被移除的常量映射为0
+    pub fn retain(&mut self, used: &[bool]) -> Vec<u16> {
+        let mapping = self.retain_mapping(used);
+        let mut pool = ConstantPool::new(self.count);
+        for item in &self.values {
+            if mapping[item.index as usize] == 0 {
+                continue;
+            }
+            pool.add_constant_force(item.value.clone());
+            if item.value.is_wide() {
+                pool.add_constant_force(ConstantValue::Null);
+            }
+        }
+        for item in &mut pool.values {
+            item.value.remap_constant_indices(&mut |index| mapping.get(index as usize).copied().unwrap_or(0));
+        }
+        *self = pool;
+        mapping
+    }
+
+    #[inline]
+    fn mismatch<T>(&self, index: u16, expected: &'static str, value: &ConstantValue) -> ConstantResult<T> {
+        Err(ConstantError::KindMismatch { index, expected, found: value.kind_name() })
+    }
+
+    pub fn utf8(&self, index: u16) -> ConstantResult<&str> {
+        match self.get(index)? {
+            ConstantValue::ConstantUtf8(value) => Ok(value),
+            ConstantValue::ConstantUtf8Raw(_) => Err(ConstantError::InvalidUtf8 { index }),
+            value => self.mismatch(index, "Utf8", value),
+        }
+    }
+
+    /// Class常量对应的类名（内部形式，如java/lang/Object）
+    pub fn class_name(&self, index: u16) -> ConstantResult<&str> {
+        match self.get(index)? {
+            ConstantValue::ConstantClass(name_index) => self.utf8(*name_index),
+            value => self.mismatch(index, "Class", value),
+        }
+    }
+
+    /// NameAndType常量对应的名称与描述符
+    pub fn name_and_type(&self, index: u16) -> ConstantResult<(&str, &str)> {
+        match self.get(index)? {
+            ConstantValue::ConstantNameAndType(name_index, type_index) => {
+                Ok((self.utf8(*name_index)?, self.utf8(*type_index)?))
+            }
+            value => self.mismatch(index, "NameAndType", value),
+        }
+    }
+
+    /// Fieldref、Methodref或InterfaceMethodref常量对应的所属类、名称与描述符
+    pub fn member_ref(&self, index: u16) -> ConstantResult<MemberRef<'_>> {
+        match self.get(index)? {
+            ConstantValue::ConstantFieldref(class_index, name_type_index) |
+            ConstantValue::ConstantMethodref(class_index, name_type_index) |
+            ConstantValue::ConstantInterfaceMethodref(class_index, name_type_index) => {
+                let owner = self.class_name(*class_index)?;
+                let (name, descriptor) = self.name_and_type(*name_type_index)?;
+                Ok(MemberRef {
+                    owner,
+                    name,
+                    descriptor,
+                })
+            }
+            value => self.mismatch(index, "Fieldref/Methodref/InterfaceMethodref", value),
+        }
+    }
+
+    /// String常量对应的字符串
+    pub fn string(&self, index: u16) -> ConstantResult<&str> {
+        match self.get(index)? {
+            ConstantValue::ConstantString(utf8_index) => self.utf8(*utf8_index),
+            value => self.mismatch(index, "String", value),
+        }
+    }
+
+    pub fn method_handle(&self, index: u16) -> ConstantResult<MethodHandleRef<'_>> {
+        match self.get(index)? {
+            ConstantValue::ConstantMethodHandle(kind, ref_index) => Ok(MethodHandleRef {
+                kind: *kind,
+                member: self.member_ref(*ref_index)?,
+            }),
+            value => self.mismatch(index, "MethodHandle", value),
+        }
+    }
 }
 
 impl Hash for ConstantValue {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        self.value().hash(state);
+        self.discriminant().hash(state);
         match self {
             ConstantValue::Null => {}
             ConstantValue::ConstantClass(v)|
@@ -413,6 +749,7 @@ impl Hash for ConstantValue {
             ConstantValue::ConstantLong(v) => v.hash(state),
             ConstantValue::ConstantDouble(v) => v.to_bits().hash(state),
             ConstantValue::ConstantUtf8(v) => v.hash(state),
+            ConstantValue::ConstantUtf8Raw(v) => v.hash(state),
             ConstantValue::ConstantFieldref(a, b)|
             ConstantValue::ConstantMethodref(a, b)|
             ConstantValue::ConstantInterfaceMethodref(a, b)|
@@ -447,7 +784,7 @@ impl PartialOrd<Self> for ConstantValue {
 
 impl Ord for ConstantValue {
     fn cmp(&self, other: &Self) -> Ordering {
-        let ordering = self.value().cmp(&other.value());
+        let ordering = self.discriminant().cmp(&other.discriminant());
         if ordering != Ordering::Equal {
             return ordering;
         }
@@ -468,6 +805,8 @@ impl Ord for ConstantValue {
                 => v1.total_cmp(v2),
             (ConstantValue::ConstantUtf8(v1), ConstantValue::ConstantUtf8(v2))
                 => v1.cmp(v2),
+            (ConstantValue::ConstantUtf8Raw(v1), ConstantValue::ConstantUtf8Raw(v2))
+                => v1.cmp(v2),
             (ConstantValue::ConstantFieldref(a1, b1), ConstantValue::ConstantFieldref(a2, b2))|
             (ConstantValue::ConstantMethodref(a1, b1), ConstantValue::ConstantMethodref(a2, b2))|
             (ConstantValue::ConstantInterfaceMethodref(a1, b1), ConstantValue::ConstantInterfaceMethodref(a2, b2))|
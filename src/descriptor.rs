@@ -0,0 +1,243 @@
+use crate::classfile_constants::{JVM_SIGNATURE_ARRAY, JVM_SIGNATURE_BOOLEAN, JVM_SIGNATURE_BYTE, JVM_SIGNATURE_CHAR, JVM_SIGNATURE_CLASS, JVM_SIGNATURE_DOUBLE, JVM_SIGNATURE_ENDCLASS, JVM_SIGNATURE_ENDFUNC, JVM_SIGNATURE_FLOAT, JVM_SIGNATURE_FUNC, JVM_SIGNATURE_INT, JVM_SIGNATURE_LONG, JVM_SIGNATURE_SHORT, JVM_SIGNATURE_VOID, _bindgen_ty_6};
+use crate::common::error::{ErrorKind, MessageError, Result};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// 字段描述符，类名为内部形式，如java/lang/String
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FieldType {
+    Byte,
+    Char,
+    Double,
+    Float,
+    Int,
+    Long,
+    Short,
+    Boolean,
+    Object(String),
+    Array(Box<FieldType>),
+}
+
+/// 方法描述符，return_type为None表示void
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MethodDescriptor {
+    pub parameters: Vec<FieldType>,
+    pub return_type: Option<FieldType>,
+}
+
+impl FieldType {
+    pub fn parse(descriptor: &str) -> Result<FieldType> {
+        let mut index = 0;
+        let field_type = Self::read_from(descriptor, &mut index)?;
+        if index != descriptor.len() {
+            return Err(invalid_descriptor(descriptor));
+        }
+        Ok(field_type)
+    }
+
+    fn read_from(descriptor: &str, index: &mut usize) -> Result<FieldType> {
+        let bytes = descriptor.as_bytes();
+        let tag = match bytes.get(*index) {
+            Some(tag) => *tag,
+            None => return Err(invalid_descriptor(descriptor)),
+        };
+        *index += 1;
+        let field_type = match tag as _bindgen_ty_6 {
+            JVM_SIGNATURE_BYTE => FieldType::Byte,
+            JVM_SIGNATURE_CHAR => FieldType::Char,
+            JVM_SIGNATURE_DOUBLE => FieldType::Double,
+            JVM_SIGNATURE_FLOAT => FieldType::Float,
+            JVM_SIGNATURE_INT => FieldType::Int,
+            JVM_SIGNATURE_LONG => FieldType::Long,
+            JVM_SIGNATURE_SHORT => FieldType::Short,
+            JVM_SIGNATURE_BOOLEAN => FieldType::Boolean,
+            JVM_SIGNATURE_CLASS => {
+                let start = *index;
+                let end = match bytes[start..].iter().position(|b| *b as _bindgen_ty_6 == JVM_SIGNATURE_ENDCLASS) {
+                    Some(len) if len > 0 => start + len,
+                    _ => return Err(invalid_descriptor(descriptor)),
+                };
+                *index = end + 1;
+                FieldType::Object(descriptor[start..end].to_string())
+            }
+            JVM_SIGNATURE_ARRAY => FieldType::Array(Box::new(Self::read_from(descriptor, index)?)),
+            _ => return Err(invalid_descriptor(descriptor)),
+        };
+        Ok(field_type)
+    }
+
+    /// 作为局部变量或操作数时占用的槽数，long与double为2
+    pub fn slot_size(&self) -> u16 {
+        match self {
+            FieldType::Long | FieldType::Double => 2,
+            _ => 1,
+        }
+    }
+
+    pub fn is_primitive(&self) -> bool {
+        !matches!(self, FieldType::Object(_) | FieldType::Array(_))
+    }
+
+    pub fn is_reference(&self) -> bool {
+        !self.is_primitive()
+    }
+
+    /// 数组维数，非数组为0
+    pub fn dimensions(&self) -> usize {
+        match self {
+            FieldType::Array(component) => 1 + component.dimensions(),
+            _ => 0,
+        }
+    }
+
+    /// 多维数组的最内层元素类型，非数组返回自身
+    pub fn element_type(&self) -> &FieldType {
+        match self {
+            FieldType::Array(component) => component.element_type(),
+            field_type => field_type,
+        }
+    }
+
+    /// Java源码形式的类型名，如int[]、java.lang.String
+    pub fn java_name(&self) -> String {
+        match self {
+            FieldType::Byte => "byte".to_string(),
+            FieldType::Char => "char".to_string(),
+            FieldType::Double => "double".to_string(),
+            FieldType::Float => "float".to_string(),
+            FieldType::Int => "int".to_string(),
+            FieldType::Long => "long".to_string(),
+            FieldType::Short => "short".to_string(),
+            FieldType::Boolean => "boolean".to_string(),
+            FieldType::Object(name) => internal_to_binary(name),
+            FieldType::Array(component) => format!("{}[]", component.java_name()),
+        }
+    }
+
+    fn tag(&self) -> char {
+        let tag = match self {
+            FieldType::Byte => JVM_SIGNATURE_BYTE,
+            FieldType::Char => JVM_SIGNATURE_CHAR,
+            FieldType::Double => JVM_SIGNATURE_DOUBLE,
+            FieldType::Float => JVM_SIGNATURE_FLOAT,
+            FieldType::Int => JVM_SIGNATURE_INT,
+            FieldType::Long => JVM_SIGNATURE_LONG,
+            FieldType::Short => JVM_SIGNATURE_SHORT,
+            FieldType::Boolean => JVM_SIGNATURE_BOOLEAN,
+            FieldType::Object(_) => JVM_SIGNATURE_CLASS,
+            FieldType::Array(_) => JVM_SIGNATURE_ARRAY,
+        };
+        tag as u8 as char
+    }
+}
+
+impl Display for FieldType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.tag())?;
+        match self {
+            FieldType::Object(name) => write!(f, "{}{}", name, JVM_SIGNATURE_ENDCLASS as u8 as char),
+            FieldType::Array(component) => component.fmt(f),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl FromStr for FieldType {
+    type Err = MessageError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        FieldType::parse(s)
+    }
+}
+
+impl MethodDescriptor {
+    pub fn new(parameters: Vec<FieldType>, return_type: Option<FieldType>) -> MethodDescriptor {
+        MethodDescriptor {
+            parameters,
+            return_type,
+        }
+    }
+
+    pub fn parse(descriptor: &str) -> Result<MethodDescriptor> {
+        let bytes = descriptor.as_bytes();
+        if bytes.first().map(|b| *b as _bindgen_ty_6) != Some(JVM_SIGNATURE_FUNC) {
+            return Err(invalid_descriptor(descriptor));
+        }
+        let mut index = 1;
+        let mut parameters = Vec::new();
+        loop {
+            match bytes.get(index) {
+                Some(b) if *b as _bindgen_ty_6 == JVM_SIGNATURE_ENDFUNC => break,
+                Some(_) => parameters.push(FieldType::read_from(descriptor, &mut index)?),
+                None => return Err(invalid_descriptor(descriptor)),
+            }
+        }
+        index += 1;
+        let return_type = match bytes.get(index) {
+            Some(b) if *b as _bindgen_ty_6 == JVM_SIGNATURE_VOID => {
+                index += 1;
+                None
+            }
+            _ => Some(FieldType::read_from(descriptor, &mut index)?),
+        };
+        if index != descriptor.len() {
+            return Err(invalid_descriptor(descriptor));
+        }
+        Ok(MethodDescriptor {
+            parameters,
+            return_type,
+        })
+    }
+
+    /// 参数占用的局部变量槽数，不含实例方法的this
+    pub fn parameter_slots(&self) -> u16 {
+        self.parameters.iter().map(FieldType::slot_size).sum()
+    }
+
+    /// 方法调用时的局部变量槽数，实例方法包含this
+    pub fn argument_slots(&self, is_static: bool) -> u16 {
+        self.parameter_slots() + if is_static { 0 } else { 1 }
+    }
+
+    /// 返回值占用的操作栈槽数，void为0
+    pub fn return_slots(&self) -> u16 {
+        self.return_type.as_ref().map_or(0, FieldType::slot_size)
+    }
+}
+
+impl Display for MethodDescriptor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", JVM_SIGNATURE_FUNC as u8 as char)?;
+        for parameter in &self.parameters {
+            parameter.fmt(f)?;
+        }
+        write!(f, "{}", JVM_SIGNATURE_ENDFUNC as u8 as char)?;
+        match &self.return_type {
+            Some(return_type) => return_type.fmt(f),
+            None => write!(f, "{}", JVM_SIGNATURE_VOID as u8 as char),
+        }
+    }
+}
+
+impl FromStr for MethodDescriptor {
+    type Err = MessageError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        MethodDescriptor::parse(s)
+    }
+}
+
+/// 内部形式类名转为二进制名，如java/lang/String转为java.lang.String
+pub fn internal_to_binary(name: &str) -> String {
+    name.replace('/', ".")
+}
+
+/// 二进制名转为内部形式类名，如java.lang.String转为java/lang/String
+pub fn binary_to_internal(name: &str) -> String {
+    name.replace('.', "/")
+}
+
+#[inline]
+fn invalid_descriptor(descriptor: &str) -> MessageError {
+    MessageError::of(ErrorKind::InvalidDescriptor, &format!("无效的描述符[{}]", descriptor))
+}
@@ -0,0 +1,803 @@
+use crate::access_flags::{ClassAccessFlags, InnerClassAccessFlags, MethodAccessFlags};
+use crate::attribute_info::{Annotation, AttributeInfo, CodeAttribute, ElementValue, LocalVariable, OriginAttribute, StackMapFrame, VerificationTypeInfo};
+use crate::common::constants::{CODE_TAG, EXCEPTIONS_TAG, SOURCE_FILE_TAG};
+use crate::common::error::Result;
+use crate::common::opcode::{array_type, opcode_name, opcodes::IINC};
+use crate::constant_pool::{ConstantPool, ConstantValue};
+use crate::descriptor::{internal_to_binary, FieldType, MethodDescriptor};
+use crate::field_info::FieldInfo;
+use crate::instruction::{Instruction, Instructions};
+use crate::jclass_info::JClassInfo;
+use crate::method_info::MethodInfo;
+use crate::signature::{find_signature, ClassSignature, ClassTypeSignature, MethodSignature, TypeArgument, TypeParameter, TypeSignature};
+use std::fmt::Write;
+
+/// 按javap -c -p的形式输出类声明、成员声明及字节码
+///
+/// 与javap一致，类、字段及方法存在Signature属性时按签名输出泛型类型，如`private volatile java.util.List<T> items;`，
+/// 否则使用描述符中的擦除类型。详细模式下额外以Signature属性列出签名原文
+pub fn disassemble(class: &JClassInfo) -> Result<String> {
+    Disassembler::new(class, false).run()
+}
+
+/// 按javap -c -v -p的形式输出，额外包含版本、常量池、访问标志及各属性的内容
+pub fn disassemble_verbose(class: &JClassInfo) -> Result<String> {
+    Disassembler::new(class, true).run()
+}
+
+struct Disassembler<'a> {
+    class: &'a JClassInfo,
+    pool: &'a ConstantPool,
+    this_class: &'a str,
+    verbose: bool,
+    out: String,
+}
+
+impl<'a> Disassembler<'a> {
+    fn new(class: &'a JClassInfo, verbose: bool) -> Disassembler<'a> {
+        Disassembler {
+            class,
+            pool: &class.constant_pool,
+            this_class: "",
+            verbose,
+            out: String::new(),
+        }
+    }
+
+    fn run(mut self) -> Result<String> {
+        let class = self.class;
+        self.this_class = class.this_class_name()?;
+        for attribute in &class.attributes {
+            if self.pool.utf8(attribute.name)? == SOURCE_FILE_TAG {
+                if let AttributeInfo::SourceFile(index) = attribute.info(self.pool)? {
+                    let indent = if self.verbose { 2 } else { 0 };
+                    self.line(indent, &format!("Compiled from \"{}\"", self.pool.utf8(*index)?));
+                }
+            }
+        }
+        let declaration = self.class_declaration()?;
+        if self.verbose {
+            self.line(0, &declaration);
+            self.line(2, &format!("minor version: {}", class.minor_version));
+            self.line(2, &format!("major version: {}", class.major_version));
+            self.line(2, &format!("flags: {}", flags(class.access_flags, &class.access().flag_names())));
+            self.line(2, &format!("{:<40}// {}", format!("this_class: #{}", class.class_index), self.this_class));
+            if let Some(name) = class.super_class_name()? {
+                self.line(2, &format!("{:<40}// {}", format!("super_class: #{}", class.superclass_index), name));
+            }
+            self.line(2, &format!("interfaces: {}, fields: {}, methods: {}, attributes: {}",
+                class.interfaces.len(), class.fields.len(), class.methods.len(), class.attributes.len()));
+            self.constant_pool()?;
+            self.line(0, "{");
+        } else {
+            self.line(0, &format!("{} {{", declaration));
+        }
+        let mut first = true;
+        for field in &class.fields {
+            if !first {
+                self.line(0, "");
+            }
+            self.field(field)?;
+            first = false;
+        }
+        for method in &class.methods {
+            if !first {
+                self.line(0, "");
+            }
+            self.method(method)?;
+            first = false;
+        }
+        self.line(0, "}");
+        if self.verbose {
+            for attribute in &class.attributes {
+                self.attribute(0, attribute, None)?;
+            }
+        }
+        Ok(self.out)
+    }
+
+    fn line(&mut self, indent: usize, text: &str) {
+        let _ = writeln!(self.out, "{:indent$}{}", "", text, indent = indent);
+    }
+
+    fn class_declaration(&self) -> Result<String> {
+        let class = self.class;
+        let access = class.access();
+        let mut declaration = modifiers(&access.to_string());
+        let is_interface = access.contains(ClassAccessFlags::INTERFACE);
+        declaration.push_str(if is_interface { "interface " } else { "class " });
+        declaration.push_str(&internal_to_binary(self.this_class));
+        let (super_class, interfaces) = match find_signature(&class.attributes, self.pool)? {
+            Some(signature) => {
+                let signature = ClassSignature::parse(signature)?;
+                declaration.push_str(&type_parameters(&signature.type_parameters));
+                // 与javap一致，详细模式下带签名的类总是输出父类
+                let super_class = Some(signature.superclass.erased_name())
+                    .filter(|name| self.verbose || name != "java/lang/Object")
+                    .map(|_| class_type(&signature.superclass));
+                (super_class, signature.interfaces.iter().map(class_type).collect())
+            }
+            None => {
+                let super_class = class.super_class_name()?
+                    .filter(|name| *name != "java/lang/Object")
+                    .map(internal_to_binary);
+                (super_class, class.interface_names()?.into_iter().map(internal_to_binary).collect::<Vec<_>>())
+            }
+        };
+        if is_interface {
+            if !interfaces.is_empty() {
+                let _ = write!(declaration, " extends {}", interfaces.join(", "));
+            }
+        } else {
+            if let Some(super_class) = super_class {
+                let _ = write!(declaration, " extends {}", super_class);
+            }
+            if !interfaces.is_empty() {
+                let _ = write!(declaration, " implements {}", interfaces.join(", "));
+            }
+        }
+        Ok(declaration)
+    }
+
+    fn constant_pool(&mut self) -> Result<()> {
+        self.line(0, "Constant pool:");
+        let width = format!("#{}", self.pool.get_constant_count()).len() + 2;
+        for (index, value) in self.pool.iter() {
+            let kind = value.kind_name();
+            let (operands, comment) = self.constant_entry(value)?;
+            let entry = format!("{:>width$} = {:<18} {}", format!("#{}", index), kind, operands, width = width);
+            match comment {
+                Some(comment) => self.line(0, &format!("{:<41} // {}", entry, comment)),
+                None => self.line(0, &entry),
+            }
+        }
+        Ok(())
+    }
+
+    /// 常量池项的操作数及注释
+    fn constant_entry(&self, value: &ConstantValue) -> Result<(String, Option<String>)> {
+        let pool = self.pool;
+        Ok(match value {
+            ConstantValue::ConstantClass(index) => (format!("#{}", index), Some(class_comment(pool.utf8(*index)?))),
+            ConstantValue::ConstantFieldref(class_index, name_type_index) |
+            ConstantValue::ConstantMethodref(class_index, name_type_index) |
+            ConstantValue::ConstantInterfaceMethodref(class_index, name_type_index) => {
+                let (name, descriptor) = pool.name_and_type(*name_type_index)?;
+                let comment = format!("{}.{}:{}", pool.class_name(*class_index)?, quote_name(name), descriptor);
+                (format!("#{}.#{}", class_index, name_type_index), Some(comment))
+            }
+            ConstantValue::ConstantString(index) => (format!("#{}", index), Some(self.text(*index)?)),
+            ConstantValue::ConstantNameAndType(name_index, type_index) => {
+                let comment = format!("{}:{}", quote_name(pool.utf8(*name_index)?), pool.utf8(*type_index)?);
+                (format!("#{}:#{}", name_index, type_index), Some(comment))
+            }
+            ConstantValue::ConstantMethodHandle(kind, index) => {
+                let member = pool.member_ref(*index)?;
+                let comment = format!("{} {}.{}:{}", reference_kind_name(*kind), member.owner, quote_name(member.name), member.descriptor);
+                (format!("{}:#{}", kind, index), Some(comment))
+            }
+            ConstantValue::ConstantMethodType(index) => (format!("#{}", index), Some(pool.utf8(*index)?.to_string())),
+            ConstantValue::ConstantDynamic(bootstrap, name_type_index) |
+            ConstantValue::ConstantInvokeDynamic(bootstrap, name_type_index) => {
+                let (name, descriptor) = pool.name_and_type(*name_type_index)?;
+                (format!("#{}:#{}", bootstrap, name_type_index), Some(format!("#{}:{}:{}", bootstrap, quote_name(name), descriptor)))
+            }
+            ConstantValue::ConstantModule(index) | ConstantValue::ConstantPackage(index) => {
+                (format!("#{}", index), Some(pool.utf8(*index)?.to_string()))
+            }
+            ConstantValue::ConstantUtf8Raw(bytes) => (escape(&String::from_utf8_lossy(bytes)), None),
+            value => (constant_literal(value).unwrap_or_default(), None),
+        })
+    }
+
+    /// 转义后的字符串常量，无法按MUTF-8解码的内容以替换字符输出
+    fn text(&self, index: u16) -> Result<String> {
+        Ok(match self.pool.get(index)? {
+            ConstantValue::ConstantUtf8Raw(bytes) => escape(&String::from_utf8_lossy(bytes)),
+            _ => escape(self.pool.utf8(index)?),
+        })
+    }
+
+    /// ldc及ConstantValue中的常量，如String abc、long 1l
+    fn constant_value(&self, index: u16) -> Result<String> {
+        let pool = self.pool;
+        let value = pool.get(index)?;
+        let literal = || constant_literal(value).unwrap_or_default();
+        Ok(match value {
+            ConstantValue::ConstantString(utf8) => format!("String {}", self.text(*utf8)?),
+            ConstantValue::ConstantInteger(_) => format!("int {}", literal()),
+            ConstantValue::ConstantFloat(_) => format!("float {}", literal()),
+            ConstantValue::ConstantLong(_) => format!("long {}", literal()),
+            ConstantValue::ConstantDouble(_) => format!("double {}", literal()),
+            ConstantValue::ConstantClass(name) => format!("class {}", class_comment(pool.utf8(*name)?)),
+            value => {
+                let (_, comment) = self.constant_entry(value)?;
+                format!("{} {}", value.kind_name(), comment.unwrap_or_default())
+            }
+        })
+    }
+
+    fn field(&mut self, field: &FieldInfo) -> Result<()> {
+        let pool = self.pool;
+        let access = field.access();
+        let descriptor = field.get_descriptor(pool)?;
+        let field_type = match find_signature(&field.attributes, pool)? {
+            Some(signature) => java_type(&TypeSignature::parse(signature)?),
+            None => FieldType::parse(descriptor)?.java_name(),
+        };
+        self.line(2, &format!("{}{} {};", modifiers(&access.to_string()), field_type, field.get_name(pool)?));
+        if self.verbose {
+            self.line(4, &format!("descriptor: {}", descriptor));
+            self.line(4, &format!("flags: {}", flags(field.access_flags, &access.flag_names())));
+            for attribute in &field.attributes {
+                self.attribute(4, attribute, None)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn method(&mut self, method: &MethodInfo) -> Result<()> {
+        let pool = self.pool;
+        let access = method.access();
+        let name = method.get_name(pool)?;
+        let descriptor = MethodDescriptor::parse(method.get_descriptor(pool)?)?;
+        let mut declaration = modifiers(&access.to_string());
+        if name == "<clinit>" {
+            declaration.push_str("static {}");
+        } else {
+            // 有签名时按泛型签名输出，否则使用描述符中的类型
+            let (generics, mut parameters, return_type, mut throws) = match find_signature(&method.attributes, pool)? {
+                Some(signature) => {
+                    let signature = MethodSignature::parse(signature)?;
+                    (
+                        type_parameters(&signature.type_parameters),
+                        signature.parameters.iter().map(java_type).collect::<Vec<_>>(),
+                        signature.return_type.as_ref().map_or("void".to_string(), java_type),
+                        signature.throws.iter().map(java_type).collect::<Vec<_>>(),
+                    )
+                }
+                None => (
+                    String::new(),
+                    descriptor.parameters.iter().map(FieldType::java_name).collect(),
+                    descriptor.return_type.as_ref().map_or("void".to_string(), FieldType::java_name),
+                    Vec::new(),
+                ),
+            };
+            if access.contains(MethodAccessFlags::VARARGS) {
+                if let Some(last) = parameters.last_mut().filter(|last| last.ends_with("[]")) {
+                    last.replace_range(last.len() - 2.., "...");
+                }
+            }
+            if throws.is_empty() {
+                for attribute in &method.attributes {
+                    if pool.utf8(attribute.name)? == EXCEPTIONS_TAG {
+                        if let AttributeInfo::Exceptions(exceptions) = attribute.info(pool)? {
+                            for index in exceptions {
+                                throws.push(internal_to_binary(pool.class_name(*index)?));
+                            }
+                        }
+                    }
+                }
+            }
+            if !generics.is_empty() {
+                let _ = write!(declaration, "{} ", generics);
+            }
+            if name == "<init>" {
+                declaration.push_str(&internal_to_binary(self.this_class));
+            } else {
+                let _ = write!(declaration, "{} {}", return_type, name);
+            }
+            let _ = write!(declaration, "({})", parameters.join(", "));
+            if !throws.is_empty() {
+                let _ = write!(declaration, " throws {}", throws.join(", "));
+            }
+        }
+        declaration.push(';');
+        self.line(2, &declaration);
+        // 与javap一致，args_size为参数个数（含this）而非槽数
+        let args_size = descriptor.parameters.len() as u16 + u16::from(!access.contains(MethodAccessFlags::STATIC));
+        if self.verbose {
+            self.line(4, &format!("descriptor: {}", descriptor));
+            self.line(4, &format!("flags: {}", flags(method.access_flags, &access.flag_names())));
+        }
+        for attribute in &method.attributes {
+            if self.verbose || pool.utf8(attribute.name)? == CODE_TAG {
+                self.attribute(4, attribute, Some(args_size))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 输出属性，args_size为方法参数个数（非静态方法含this），仅用于Code属性
+    fn attribute(&mut self, indent: usize, attribute: &OriginAttribute, args_size: Option<u16>) -> Result<()> {
+        let pool = self.pool;
+        let name = pool.utf8(attribute.name)?;
+        match attribute.info(pool)? {
+            AttributeInfo::Code(code) => self.code(indent, code, args_size.unwrap_or_default())?,
+            AttributeInfo::ConstantValue(index) => self.line(indent, &format!("{}: {}", name, self.constant_value(*index)?)),
+            AttributeInfo::Deprecated | AttributeInfo::Synthetic => self.line(indent, &format!("{}: true", name)),
+            AttributeInfo::Signature(index) => {
+                self.line(indent, &format!("{:<40}// {}", format!("{}: #{}", name, index), pool.utf8(*index)?));
+            }
+            AttributeInfo::SourceFile(index) => self.line(indent, &format!("{}: \"{}\"", name, pool.utf8(*index)?)),
+            AttributeInfo::Exceptions(exceptions) => {
+                self.line(indent, &format!("{}:", name));
+                let names = exceptions.iter()
+                    .map(|index| Ok(internal_to_binary(pool.class_name(*index)?)))
+                    .collect::<Result<Vec<_>>>()?;
+                self.line(indent + 2, &format!("throws {}", names.join(", ")));
+            }
+            AttributeInfo::LineNumberTable(lines) => {
+                self.line(indent, &format!("{}:", name));
+                for line in lines {
+                    self.line(indent + 2, &format!("line {}: {}", line.line_number, line.start_pc));
+                }
+            }
+            AttributeInfo::LocalVariableTable(variables) | AttributeInfo::LocalVariableTypeTable(variables) => {
+                self.line(indent, &format!("{}:", name));
+                self.local_variables(indent + 2, variables)?;
+            }
+            AttributeInfo::StackMapTable(frames) => {
+                self.line(indent, &format!("{}: number_of_entries = {}", name, frames.len()));
+                for frame in frames {
+                    self.stack_map_frame(indent + 2, frame)?;
+                }
+            }
+            AttributeInfo::InnerClasses(classes) => {
+                self.line(indent, &format!("{}:", name));
+                for inner in classes {
+                    let access = InnerClassAccessFlags::from_bits(inner.inner_class_access_flags);
+                    let mut refs = String::new();
+                    let mut comment = String::new();
+                    if inner.inner_name_index != 0 {
+                        let _ = write!(refs, "#{}= ", inner.inner_name_index);
+                        let _ = write!(comment, "{}=", pool.utf8(inner.inner_name_index)?);
+                    }
+                    let _ = write!(refs, "#{}", inner.inner_class_info_index);
+                    let _ = write!(comment, "class {}", pool.class_name(inner.inner_class_info_index)?);
+                    if inner.outer_class_info_index != 0 {
+                        let _ = write!(refs, " of #{}", inner.outer_class_info_index);
+                        let _ = write!(comment, " of class {}", pool.class_name(inner.outer_class_info_index)?);
+                    }
+                    self.line(indent + 2, &format!("{:<39} // {}", format!("{}{};", modifiers(&access.to_string()), refs), comment));
+                }
+            }
+            AttributeInfo::EnclosingMethod(enclosing) => {
+                let mut comment = internal_to_binary(pool.class_name(enclosing.class_index)?);
+                if enclosing.method_index != 0 {
+                    let (method, _) = pool.name_and_type(enclosing.method_index)?;
+                    let _ = write!(comment, ".{}", quote_name(method));
+                }
+                let refs = format!("#{}.#{}", enclosing.class_index, enclosing.method_index);
+                self.line(indent, &format!("{:<39} // {}", format!("{}: {}", name, refs), comment));
+            }
+            AttributeInfo::NestHost(index) => self.line(indent, &format!("{}: class {}", name, pool.class_name(*index)?)),
+            AttributeInfo::NestMembers(members) => {
+                self.line(indent, &format!("{}:", name));
+                for member in members {
+                    self.line(indent + 2, pool.class_name(*member)?);
+                }
+            }
+            AttributeInfo::BootstrapMethods(methods) => {
+                self.line(indent, &format!("{}:", name));
+                for (i, method) in methods.iter().enumerate() {
+                    let (_, comment) = self.constant_entry(pool.get(method.method_ref)?)?;
+                    self.line(indent + 2, &format!("{}: #{} {}", i, method.method_ref, comment.unwrap_or_default()));
+                    self.line(indent + 4, "Method arguments:");
+                    for argument in &method.arguments {
+                        let value = pool.get(*argument)?;
+                        let (operands, comment) = self.constant_entry(value)?;
+                        self.line(indent + 6, &format!("#{} {}", argument, comment.unwrap_or(operands)));
+                    }
+                }
+            }
+            AttributeInfo::MethodParameters(parameters) => {
+                self.line(indent, &format!("{}:", name));
+                self.line(indent + 2, &format!("{:<30} {}", "Name", "Flags"));
+                for parameter in parameters {
+                    let parameter_name = if parameter.name_index == 0 { "<no name>" } else { pool.utf8(parameter.name_index)? };
+                    let names: Vec<_> = [(0x0010, "final"), (0x1000, "synthetic"), (0x8000, "mandated")].iter()
+                        .filter(|(flag, _)| parameter.access_flags & flag != 0)
+                        .map(|(_, name)| *name)
+                        .collect();
+                    self.line(indent + 2, format!("{:<30} {}", parameter_name, names.join(" ")).trim_end());
+                }
+            }
+            AttributeInfo::RuntimeVisibleAnnotations(annotations) | AttributeInfo::RuntimeInvisibleAnnotations(annotations) => {
+                self.line(indent, &format!("{}:", name));
+                for (i, annotation) in annotations.iter().enumerate() {
+                    self.line(indent + 2, &format!("{}: {}", i, annotation_refs(annotation)));
+                    self.annotation(indent + 4, annotation)?;
+                }
+            }
+            AttributeInfo::RuntimeVisibleParameterAnnotations(parameters) | AttributeInfo::RuntimeInvisibleParameterAnnotations(parameters) => {
+                self.line(indent, &format!("{}:", name));
+                for (i, annotations) in parameters.iter().enumerate() {
+                    self.line(indent + 2, &format!("parameter {}:", i));
+                    for (j, annotation) in annotations.iter().enumerate() {
+                        self.line(indent + 4, &format!("{}: {}", j, annotation_refs(annotation)));
+                        self.annotation(indent + 6, annotation)?;
+                    }
+                }
+            }
+            AttributeInfo::AnnotationDefault(value) => {
+                self.line(indent, &format!("{}:", name));
+                self.line(indent + 2, &format!("default_value: {}", element_value_refs(value)));
+            }
+            _ => self.line(indent, &format!("{}: length = 0x{:X}", name, attribute.data.len())),
+        }
+        Ok(())
+    }
+
+    fn code(&mut self, indent: usize, code: &CodeAttribute, args_size: u16) -> Result<()> {
+        self.line(indent, &format!("{}:", CODE_TAG));
+        // javap中pc列的宽度，详细模式比普通模式多两列
+        let pc_width = if self.verbose {
+            self.line(indent + 2, &format!("stack={}, locals={}, args_size={}", code.max_stack, code.max_locals, args_size));
+            indent + 6
+        } else {
+            indent + 4
+        };
+        for item in Instructions::new(&code.codes) {
+            let (pc, instruction) = item?;
+            self.instruction(pc_width, pc, &instruction)?;
+        }
+        // 普通模式下异常表与Code对齐
+        let table_indent = if self.verbose { indent + 2 } else { indent };
+        if !code.exceptions.entries.is_empty() {
+            self.line(table_indent, "Exception table:");
+            self.line(table_indent, "   from    to  target type");
+            for entry in &code.exceptions.entries {
+                let catch_type = match entry.catch_type {
+                    0 => "any".to_string(),
+                    index => format!("Class {}", self.pool.class_name(index)?),
+                };
+                self.line(table_indent, &format!("{:>8}{:>6}{:>6}   {}", entry.start_pc, entry.end_pc, entry.handler_pc, catch_type));
+            }
+        }
+        if !self.verbose {
+            return Ok(());
+        }
+        for attribute in &code.attributes {
+            self.attribute(indent + 2, attribute, None)?;
+        }
+        Ok(())
+    }
+
+    fn instruction(&mut self, pc_width: usize, pc: u32, instruction: &Instruction) -> Result<()> {
+        let pool = self.pool;
+        let opcode = instruction.opcode();
+        let mnemonic = opcode_name(opcode).unwrap_or("<illegal>");
+        let (operand, comment) = match instruction {
+            Instruction::Simple(_) => (None, None),
+            Instruction::Bipush(value) => (Some(value.to_string()), None),
+            Instruction::Sipush(value) => (Some(value.to_string()), None),
+            Instruction::Ldc(index) | Instruction::LdcW(index) | Instruction::Ldc2W(index) => {
+                (Some(format!("#{}", index)), Some(self.constant_value(*index)?))
+            }
+            Instruction::Local(_, index) => (Some(index.to_string()), None),
+            Instruction::Iinc(index, value) => (Some(format!("{}, {}", index, value)), None),
+            Instruction::WideLocal(_, index) => (Some(index.to_string()), None),
+            Instruction::WideIinc(index, value) => (Some(format!("{}, {}", index, value)), None),
+            Instruction::Branch(_, target) | Instruction::BranchW(_, target) => (Some(target.to_string()), None),
+            Instruction::TableSwitch { default, low, targets } => {
+                let high = *low as i64 + targets.len() as i64 - 1;
+                self.line(0, &format!("{:>pc_width$}: {:<13} {{ // {} to {}", pc, mnemonic, low, high, pc_width = pc_width));
+                for (i, target) in targets.iter().enumerate() {
+                    self.switch_case(pc_width, &(*low as i64 + i as i64).to_string(), *target);
+                }
+                self.switch_case(pc_width, "default", *default);
+                self.line(pc_width + 2, "}");
+                return Ok(());
+            }
+            Instruction::LookupSwitch { default, pairs } => {
+                self.line(0, &format!("{:>pc_width$}: {:<13} {{ // {}", pc, mnemonic, pairs.len(), pc_width = pc_width));
+                for (key, target) in pairs {
+                    self.switch_case(pc_width, &key.to_string(), *target);
+                }
+                self.switch_case(pc_width, "default", *default);
+                self.line(pc_width + 2, "}");
+                return Ok(());
+            }
+            Instruction::Field(_, index) | Instruction::Invoke(_, index) => (Some(format!("#{}", index)), Some(self.member_comment(*index)?)),
+            Instruction::InvokeInterface(index, count) => (Some(format!("#{},  {}", index, count)), Some(self.member_comment(*index)?)),
+            Instruction::InvokeDynamic(index) => {
+                let (_, comment) = self.constant_entry(pool.get(*index)?)?;
+                (Some(format!("#{},  0", index)), Some(format!("InvokeDynamic {}", comment.unwrap_or_default())))
+            }
+            Instruction::Type(_, index) => (Some(format!("#{}", index)), Some(format!("class {}", class_comment(pool.class_name(*index)?)))),
+            Instruction::NewArray(atype) => (Some(array_type_name(*atype).to_string()), None),
+            Instruction::MultiANewArray(index, dimensions) => {
+                (Some(format!("#{},  {}", index, dimensions)), Some(format!("class {}", class_comment(pool.class_name(*index)?))))
+            }
+        };
+        // wide修饰的指令与javap一致以_w后缀表示
+        let mnemonic = match instruction {
+            Instruction::WideLocal(opcode, _) => format!("{}_w", opcode_name(*opcode).unwrap_or("<illegal>")),
+            Instruction::WideIinc(..) => format!("{}_w", opcode_name(IINC).unwrap_or("<illegal>")),
+            _ => mnemonic.to_string(),
+        };
+        let text = match (operand, comment) {
+            (None, _) => mnemonic,
+            (Some(operand), None) => format!("{:<13} {}", mnemonic, operand),
+            (Some(operand), Some(comment)) => format!("{:<34}// {}", format!("{:<13} {}", mnemonic, operand), comment),
+        };
+        self.line(0, &format!("{:>pc_width$}: {}", pc, text, pc_width = pc_width));
+        Ok(())
+    }
+
+    fn switch_case(&mut self, pc_width: usize, key: &str, target: u32) {
+        self.line(0, &format!("{:>width$}: {}", key, target, width = pc_width + 14));
+    }
+
+    /// 字段或方法引用的注释，所属类为当前类时省略类名
+    fn member_comment(&self, index: u16) -> Result<String> {
+        let pool = self.pool;
+        let kind = match pool.get(index)? {
+            ConstantValue::ConstantFieldref(..) => "Field",
+            ConstantValue::ConstantInterfaceMethodref(..) => "InterfaceMethod",
+            _ => "Method",
+        };
+        let member = pool.member_ref(index)?;
+        let owner = if member.owner == self.this_class { String::new() } else { format!("{}.", member.owner) };
+        Ok(format!("{} {}{}:{}", kind, owner, quote_name(member.name), member.descriptor))
+    }
+
+    /// 注解的类型名及元素值，如sample.Sample$Keep(value="field")
+    fn annotation(&mut self, indent: usize, annotation: &Annotation) -> Result<()> {
+        let type_name = FieldType::parse(self.pool.utf8(annotation.type_index)?)?.java_name();
+        if annotation.element_value_pairs.is_empty() {
+            self.line(indent, &type_name);
+            return Ok(());
+        }
+        self.line(indent, &format!("{}(", type_name));
+        for pair in &annotation.element_value_pairs {
+            let value = self.element_value(&pair.value)?;
+            self.line(indent + 2, &format!("{}={}", self.pool.utf8(pair.name_index)?, value));
+        }
+        self.line(indent, ")");
+        Ok(())
+    }
+
+    fn element_value(&self, value: &ElementValue) -> Result<String> {
+        let pool = self.pool;
+        Ok(match value {
+            ElementValue::Const(b's', index) => format!("\"{}\"", self.text(*index)?),
+            ElementValue::Const(tag, index) => {
+                let literal = constant_literal(pool.get(*index)?).unwrap_or_default();
+                match (tag, pool.get(*index)?) {
+                    (b'Z', ConstantValue::ConstantInteger(value)) => (*value != 0).to_string(),
+                    (b'C', ConstantValue::ConstantInteger(value)) => {
+                        format!("'{}'", escape(&char::from_u32(*value as u32).unwrap_or(char::REPLACEMENT_CHARACTER).to_string()))
+                    }
+                    _ => literal,
+                }
+            }
+            ElementValue::Enum(type_index, name_index) => format!("{}.{}", pool.utf8(*type_index)?, pool.utf8(*name_index)?),
+            ElementValue::Class(index) => format!("class {}", pool.utf8(*index)?),
+            ElementValue::Annotation(annotation) => {
+                let mut pairs = Vec::with_capacity(annotation.element_value_pairs.len());
+                for pair in &annotation.element_value_pairs {
+                    pairs.push(format!("{}={}", pool.utf8(pair.name_index)?, self.element_value(&pair.value)?));
+                }
+                let type_name = FieldType::parse(pool.utf8(annotation.type_index)?)?.java_name();
+                format!("@{}({})", type_name, pairs.join(","))
+            }
+            ElementValue::Array(values) => {
+                let values = values.iter().map(|value| self.element_value(value)).collect::<Result<Vec<_>>>()?;
+                format!("[{}]", values.join(","))
+            }
+        })
+    }
+
+    fn local_variables(&mut self, indent: usize, variables: &[LocalVariable]) -> Result<()> {
+        self.line(indent, "Start  Length  Slot  Name   Signature");
+        for variable in variables {
+            let name = self.pool.utf8(variable.name_index)?;
+            let descriptor = self.pool.utf8(variable.descriptor_index)?;
+            self.line(indent, &format!("{:>5} {:>7} {:>5} {:>5}   {}", variable.start_pc, variable.length, variable.index, name, descriptor));
+        }
+        Ok(())
+    }
+
+    fn stack_map_frame(&mut self, indent: usize, frame: &StackMapFrame) -> Result<()> {
+        let kind = match frame {
+            StackMapFrame::SameFrame { .. } => "same",
+            StackMapFrame::SameLocals1StackItemFrame { .. } => "same_locals_1_stack_item",
+            StackMapFrame::SameLocals1StackItemFrameExtended { .. } => "same_locals_1_stack_item_frame_extended",
+            StackMapFrame::ChopFrame { .. } => "chop",
+            StackMapFrame::SameFrameExtended { .. } => "same_frame_extended",
+            StackMapFrame::AppendFrame { .. } => "append",
+            StackMapFrame::FullFrame { .. } => "full_frame",
+        };
+        self.line(indent, &format!("frame_type = {} /* {} */", frame.frame_type()?, kind));
+        let indent = indent + 2;
+        if !matches!(frame, StackMapFrame::SameFrame { .. } | StackMapFrame::SameLocals1StackItemFrame { .. }) {
+            self.line(indent, &format!("offset_delta = {}", frame.offset_delta()));
+        }
+        match frame {
+            StackMapFrame::SameLocals1StackItemFrame { stack, .. } |
+            StackMapFrame::SameLocals1StackItemFrameExtended { stack, .. } => {
+                let stack = self.verification_types(std::slice::from_ref(stack))?;
+                self.line(indent, &format!("stack = {}", stack));
+            }
+            StackMapFrame::AppendFrame { locals, .. } => {
+                let locals = self.verification_types(locals)?;
+                self.line(indent, &format!("locals = {}", locals));
+            }
+            StackMapFrame::FullFrame { locals, stack, .. } => {
+                let (locals, stack) = (self.verification_types(locals)?, self.verification_types(stack)?);
+                self.line(indent, &format!("locals = {}", locals));
+                self.line(indent, &format!("stack = {}", stack));
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn verification_types(&self, types: &[VerificationTypeInfo]) -> Result<String> {
+        if types.is_empty() {
+            return Ok("[]".to_string());
+        }
+        let mut names = Vec::with_capacity(types.len());
+        for info in types {
+            names.push(match info {
+                VerificationTypeInfo::Top => "top".to_string(),
+                VerificationTypeInfo::Integer => "int".to_string(),
+                VerificationTypeInfo::Float => "float".to_string(),
+                VerificationTypeInfo::Double => "double".to_string(),
+                VerificationTypeInfo::Long => "long".to_string(),
+                VerificationTypeInfo::Null => "null".to_string(),
+                VerificationTypeInfo::UninitializedThis => "this".to_string(),
+                VerificationTypeInfo::Object(index) => format!("class {}", class_comment(self.pool.class_name(*index)?)),
+                VerificationTypeInfo::Uninitialized(offset) => format!("uninitialized {}", offset),
+            });
+        }
+        Ok(format!("[ {} ]", names.join(", ")))
+    }
+}
+
+/// 签名中类型的Java语法形式，如java.util.Map<java.lang.String, ? extends T>
+fn java_type(signature: &TypeSignature) -> String {
+    match signature {
+        TypeSignature::Base(field_type) => field_type.java_name(),
+        TypeSignature::Class(class) => class_type(class),
+        TypeSignature::TypeVariable(name) => name.clone(),
+        TypeSignature::Array(component) => format!("{}[]", java_type(component)),
+    }
+}
+
+fn class_type(class: &ClassTypeSignature) -> String {
+    let mut name = internal_to_binary(&class.name);
+    name.push_str(&type_arguments(&class.type_arguments));
+    for inner in &class.inner {
+        let _ = write!(name, ".{}{}", inner.name, type_arguments(&inner.type_arguments));
+    }
+    name
+}
+
+fn type_arguments(arguments: &[TypeArgument]) -> String {
+    if arguments.is_empty() {
+        return String::new();
+    }
+    let arguments: Vec<_> = arguments.iter().map(|argument| match argument {
+        TypeArgument::Any => "?".to_string(),
+        TypeArgument::Exact(signature) => java_type(signature),
+        TypeArgument::Extends(signature) => format!("? extends {}", java_type(signature)),
+        TypeArgument::Super(signature) => format!("? super {}", java_type(signature)),
+    }).collect();
+    format!("<{}>", arguments.join(", "))
+}
+
+/// 类型参数及其上界，与javap一致总是输出上界
+fn type_parameters(parameters: &[TypeParameter]) -> String {
+    if parameters.is_empty() {
+        return String::new();
+    }
+    let parameters: Vec<_> = parameters.iter().map(|parameter| {
+        let bounds: Vec<_> = parameter.class_bound.iter().chain(&parameter.interface_bounds).map(java_type).collect();
+        format!("{} extends {}", parameter.name, bounds.join(" & "))
+    }).collect();
+    format!("<{}>", parameters.join(", "))
+}
+
+/// 访问标志关键字，非空时末尾带空格
+fn modifiers(keywords: &str) -> String {
+    if keywords.is_empty() { String::new() } else { format!("{} ", keywords) }
+}
+
+/// javap -v形式的访问标志，如(0x0021) ACC_PUBLIC, ACC_SUPER
+fn flags(bits: u16, names: &[&str]) -> String {
+    format!("(0x{:04x}) {}", bits, names.join(", ")).trim_end().to_string()
+}
+
+/// 数组类名加引号，与javap一致
+fn class_comment(name: &str) -> String {
+    if name.starts_with('[') { format!("\"{}\"", name) } else { name.to_string() }
+}
+
+/// <init>与<clinit>加引号
+fn quote_name(name: &str) -> String {
+    if name.starts_with('<') { format!("\"{}\"", name) } else { name.to_string() }
+}
+
+/// 数值常量的字面量，与javap一致带类型后缀
+fn constant_literal(value: &ConstantValue) -> Option<String> {
+    Some(match value {
+        ConstantValue::ConstantInteger(value) => value.to_string(),
+        ConstantValue::ConstantFloat(value) => format!("{:?}f", value),
+        ConstantValue::ConstantLong(value) => format!("{}l", value),
+        ConstantValue::ConstantDouble(value) => format!("{:?}d", value),
+        ConstantValue::ConstantUtf8(value) => escape(value),
+        _ => return None,
+    })
+}
+
+/// 转义控制字符，其余字符原样输出
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\u{8}' => escaped.push_str("\\b"),
+            '\u{c}' => escaped.push_str("\\f"),
+            c if c.is_control() => {
+                let _ = write!(escaped, "\\u{:04x}", c as u32);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn reference_kind_name(kind: u8) -> &'static str {
+    match kind {
+        1 => "REF_getField",
+        2 => "REF_getStatic",
+        3 => "REF_putField",
+        4 => "REF_putStatic",
+        5 => "REF_invokeVirtual",
+        6 => "REF_invokeStatic",
+        7 => "REF_invokeSpecial",
+        8 => "REF_newInvokeSpecial",
+        9 => "REF_invokeInterface",
+        _ => "REF_???",
+    }
+}
+
+fn array_type_name(atype: u8) -> &'static str {
+    match atype {
+        array_type::T_BOOLEAN => "boolean",
+        array_type::T_CHAR => "char",
+        array_type::T_FLOAT => "float",
+        array_type::T_DOUBLE => "double",
+        array_type::T_BYTE => "byte",
+        array_type::T_SHORT => "short",
+        array_type::T_INT => "int",
+        array_type::T_LONG => "long",
+        _ => "<illegal>",
+    }
+}
+
+/// 注解的常量索引形式，如#109(#110=s#111)
+fn annotation_refs(annotation: &Annotation) -> String {
+    let pairs: Vec<_> = annotation.element_value_pairs.iter()
+        .map(|pair| format!("#{}={}", pair.name_index, element_value_refs(&pair.value)))
+        .collect();
+    format!("#{}({})", annotation.type_index, pairs.join(","))
+}
+
+fn element_value_refs(value: &ElementValue) -> String {
+    match value {
+        ElementValue::Const(tag, index) => format!("{}#{}", *tag as char, index),
+        ElementValue::Enum(type_index, name_index) => format!("e#{}.#{}", type_index, name_index),
+        ElementValue::Class(index) => format!("c#{}", index),
+        ElementValue::Annotation(annotation) => format!("@{}", annotation_refs(annotation)),
+        ElementValue::Array(values) => {
+            let values: Vec<_> = values.iter().map(element_value_refs).collect();
+            format!("[{}]", values.join(","))
+        }
+    }
+}
@@ -1,6 +1,9 @@
-use crate::attribute_info::OriginAttribute;
+use crate::annotation::Annotations;
+use crate::attribute_info::{self, OriginAttribute};
+use crate::access_flags::FieldAccessFlags;
 use crate::support::data_reader::{DataReader, DataWriter, ReadToType, WriteFromType};
 use crate::common::error::Result;
+use crate::constant_pool::ConstantPool;
 use std::io::{Read, Write};
 
 #[derive(Clone, Debug)]
@@ -18,8 +21,8 @@ impl FieldInfo {
         let descriptor: u16 = reader.read_to("字段描述")?;
         let attribute_size: u16 = reader.read_to("字段属性数量")?;
         let mut attributes = Vec::with_capacity(attribute_size as usize);
-        for _ in 0..attribute_size {
-            attributes.push(OriginAttribute::new_from_reader(reader)?)
+        for i in 0..attribute_size {
+            attributes.push(OriginAttribute::new_from_reader(reader).map_err(|e| e.in_path(&format!("attributes[{}]", i)))?)
         }
         Ok(FieldInfo {
             access_flags,
@@ -40,6 +43,69 @@ impl FieldInfo {
         Ok(())
     }
 
+    pub fn access(&self) -> FieldAccessFlags {
+        FieldAccessFlags::from_bits(self.access_flags)
+    }
+
+    pub fn set_access(&mut self, access: FieldAccessFlags) {
+        self.access_flags = access.bits();
+    }
+
+    pub fn get_name<'a>(&self, pool: &'a ConstantPool) -> Result<&'a str> {
+        Ok(pool.utf8(self.name)?)
+    }
+
+    pub fn get_descriptor<'a>(&self, pool: &'a ConstantPool) -> Result<&'a str> {
+        Ok(pool.utf8(self.descriptor)?)
+    }
+
+    /// 第一个名为name的属性
+    pub fn attribute(&self, pool: &ConstantPool, name: &str) -> Option<&OriginAttribute> {
+        attribute_info::find_attribute(&self.attributes, pool, name)
+    }
+
+    pub fn attributes_named<'a>(&'a self, pool: &'a ConstantPool, name: &'a str) -> impl Iterator<Item = &'a OriginAttribute> + 'a {
+        attribute_info::attributes_named(&self.attributes, pool, name)
+    }
+
+    /// 以data作为名为name的属性内容，替换已有的同名属性，属性名按需加入常量池
+    pub fn set_attribute(&mut self, pool: &mut ConstantPool, name: &str, data: Vec<u8>) {
+        attribute_info::set_attribute(&mut self.attributes, pool, name, data)
+    }
+
+    /// 移除所有名为name的属性并返回
+    pub fn remove_attribute(&mut self, pool: &ConstantPool, name: &str) -> Vec<OriginAttribute> {
+        attribute_info::remove_attribute(&mut self.attributes, pool, name)
+    }
+
+    /// 字段上的可见与不可见注解
+    pub fn annotations(&self, pool: &ConstantPool) -> Result<Annotations> {
+        Annotations::read(&self.attributes, pool)
+    }
+
+    pub fn set_annotations(&mut self, pool: &mut ConstantPool, annotations: &Annotations) -> Result<()> {
+        annotations.write(&mut self.attributes, pool)
+    }
+
+    /// 名称、描述符及属性中引用的常量索引
+    pub fn constant_indices(&self, pool: &ConstantPool) -> Result<Vec<u16>> {
+        let mut indices = vec![self.name, self.descriptor];
+        for (i, attribute) in self.attributes.iter().enumerate() {
+            indices.extend(attribute.constant_indices(pool).map_err(|e| e.in_path(&format!("attributes[{}]", i)))?);
+        }
+        Ok(indices)
+    }
+
+    /// 改写名称、描述符及属性中的常量索引
+    pub fn remap_constant_indices(&mut self, pool: &ConstantPool, remap: &mut dyn FnMut(u16) -> u16) -> Result<()> {
+        self.name = remap(self.name);
+        self.descriptor = remap(self.descriptor);
+        for (i, attribute) in self.attributes.iter_mut().enumerate() {
+            attribute.remap_constant_indices(pool, remap).map_err(|e| e.in_path(&format!("attributes[{}]", i)))?;
+        }
+        Ok(())
+    }
+
     //noinspection DuplicatedCode
     pub fn byte_size(&self) -> usize {
         let mut attrs_size = 0;
@@ -0,0 +1,330 @@
+use crate::common::error::{ErrorKind, MessageError, Result};
+use crate::common::opcode::opcodes::*;
+
+/// 解码后的字节码指令，跳转目标均为绝对pc
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Instruction {
+    /// 无操作数的指令，如aload_0、iadd、return
+    Simple(u8),
+    Bipush(i8),
+    Sipush(i16),
+    /// ldc，常量池索引不超过255
+    Ldc(u16),
+    LdcW(u16),
+    Ldc2W(u16),
+    /// xload、xstore与ret，操作码及局部变量索引
+    Local(u8, u8),
+    /// wide修饰的xload、xstore与ret
+    WideLocal(u8, u16),
+    /// iinc，局部变量索引及增量
+    Iinc(u8, i8),
+    WideIinc(u16, i16),
+    /// if系列条件跳转、goto与jsr，操作码及跳转目标
+    Branch(u8, u32),
+    /// goto_w与jsr_w
+    BranchW(u8, u32),
+    TableSwitch {
+        default: u32,
+        low: i32,
+        targets: Vec<u32>,
+    },
+    LookupSwitch {
+        default: u32,
+        pairs: Vec<(i32, u32)>,
+    },
+    /// getstatic、putstatic、getfield与putfield，操作码及Fieldref索引
+    Field(u8, u16),
+    /// invokevirtual、invokespecial与invokestatic，操作码及方法引用索引
+    Invoke(u8, u16),
+    /// InterfaceMethodref索引及参数槽数（count）
+    InvokeInterface(u16, u8),
+    InvokeDynamic(u16),
+    /// new、anewarray、checkcast与instanceof，操作码及Class索引
+    Type(u8, u16),
+    /// newarray，元素类型见array_type
+    NewArray(u8),
+    /// Class索引及维数
+    MultiANewArray(u16, u8),
+}
+
+impl Instruction {
+    /// 解码pc处的指令
+    pub fn decode(codes: &[u8], pc: u32) -> Result<Instruction> {
+        let at = pc as usize;
+        let opcode = match codes.get(at) {
+            Some(opcode) => *opcode,
+            None => return Err(eof(pc)),
+        };
+        let u8_at = |offset: usize| codes.get(at + offset).copied().ok_or_else(|| eof(pc));
+        let u16_at = |offset: usize| -> Result<u16> { Ok(u16::from_be_bytes([u8_at(offset)?, u8_at(offset + 1)?])) };
+        let i32_at = |offset: usize| -> Result<i32> { Ok(i32::from_be_bytes([u8_at(offset)?, u8_at(offset + 1)?, u8_at(offset + 2)?, u8_at(offset + 3)?])) };
+        let target = |offset: i32| -> Result<u32> {
+            u32::try_from(pc as i64 + offset as i64).map_err(|_| invalid(pc, &format!("跳转目标越界[{}]", offset)))
+        };
+        let instruction = match opcode {
+            BIPUSH => Instruction::Bipush(u8_at(1)? as i8),
+            SIPUSH => Instruction::Sipush(u16_at(1)? as i16),
+            LDC => Instruction::Ldc(u8_at(1)? as u16),
+            LDC_W => Instruction::LdcW(u16_at(1)?),
+            LDC2_W => Instruction::Ldc2W(u16_at(1)?),
+            ILOAD | LLOAD | FLOAD | DLOAD | ALOAD | ISTORE | LSTORE | FSTORE | DSTORE | ASTORE | RET => Instruction::Local(opcode, u8_at(1)?),
+            IINC => Instruction::Iinc(u8_at(1)?, u8_at(2)? as i8),
+            IFEQ..=JSR | IFNULL | IFNONNULL => Instruction::Branch(opcode, target(u16_at(1)? as i16 as i32)?),
+            GOTO_W | JSR_W => Instruction::BranchW(opcode, target(i32_at(1)?)?),
+            WIDE => match u8_at(1)? {
+                IINC => Instruction::WideIinc(u16_at(2)?, u16_at(4)? as i16),
+                ILOAD | LLOAD | FLOAD | DLOAD | ALOAD | ISTORE | LSTORE | FSTORE | DSTORE | ASTORE | RET => {
+                    Instruction::WideLocal(u8_at(1)?, u16_at(2)?)
+                }
+                modified => return Err(invalid(pc, &format!("wide不能修饰操作码[{}]", modified))),
+            },
+            TABLESWITCH => {
+                let start = padded_start(pc) as usize - at;
+                let default = target(i32_at(start)?)?;
+                let (low, high) = (i32_at(start + 4)?, i32_at(start + 8)?);
+                if high < low {
+                    return Err(invalid(pc, &format!("tableswitch范围无效[{}, {}]", low, high)));
+                }
+                let count = (high as i64 - low as i64 + 1) as usize;
+                if at + start + 12 + count * 4 > codes.len() {
+                    return Err(eof(pc));
+                }
+                let mut targets = Vec::with_capacity(count);
+                for i in 0..count {
+                    targets.push(target(i32_at(start + 12 + i * 4)?)?);
+                }
+                Instruction::TableSwitch { default, low, targets }
+            }
+            LOOKUPSWITCH => {
+                let start = padded_start(pc) as usize - at;
+                let default = target(i32_at(start)?)?;
+                let count = i32_at(start + 4)?;
+                if count < 0 {
+                    return Err(invalid(pc, &format!("lookupswitch匹配数无效[{}]", count)));
+                }
+                let count = count as usize;
+                if at + start + 8 + count * 8 > codes.len() {
+                    return Err(eof(pc));
+                }
+                let mut pairs = Vec::with_capacity(count);
+                for i in 0..count {
+                    let offset = start + 8 + i * 8;
+                    pairs.push((i32_at(offset)?, target(i32_at(offset + 4)?)?));
+                }
+                Instruction::LookupSwitch { default, pairs }
+            }
+            GETSTATIC..=PUTFIELD => Instruction::Field(opcode, u16_at(1)?),
+            INVOKEVIRTUAL..=INVOKESTATIC => Instruction::Invoke(opcode, u16_at(1)?),
+            INVOKEINTERFACE => Instruction::InvokeInterface(u16_at(1)?, u8_at(3)?),
+            INVOKEDYNAMIC => Instruction::InvokeDynamic(u16_at(1)?),
+            NEW | ANEWARRAY | CHECKCAST | INSTANCEOF => Instruction::Type(opcode, u16_at(1)?),
+            NEWARRAY => Instruction::NewArray(u8_at(1)?),
+            MULTIANEWARRAY => Instruction::MultiANewArray(u16_at(1)?, u8_at(3)?),
+            // 其余带操作数的操作码均已在上方列出
+            _ if opcode <= JSR_W => Instruction::Simple(opcode),
+            _ => return Err(invalid(pc, &format!("无效的操作码[{}]", opcode))),
+        };
+        Ok(instruction)
+    }
+
+    pub fn opcode(&self) -> u8 {
+        match self {
+            Instruction::Simple(opcode) | Instruction::Local(opcode, _) | Instruction::Branch(opcode, _) |
+            Instruction::BranchW(opcode, _) | Instruction::Field(opcode, _) | Instruction::Invoke(opcode, _) |
+            Instruction::Type(opcode, _) => *opcode,
+            Instruction::Bipush(_) => BIPUSH,
+            Instruction::Sipush(_) => SIPUSH,
+            Instruction::Ldc(_) => LDC,
+            Instruction::LdcW(_) => LDC_W,
+            Instruction::Ldc2W(_) => LDC2_W,
+            Instruction::WideLocal(_, _) | Instruction::WideIinc(_, _) => WIDE,
+            Instruction::Iinc(_, _) => IINC,
+            Instruction::TableSwitch { .. } => TABLESWITCH,
+            Instruction::LookupSwitch { .. } => LOOKUPSWITCH,
+            Instruction::InvokeInterface(_, _) => INVOKEINTERFACE,
+            Instruction::InvokeDynamic(_) => INVOKEDYNAMIC,
+            Instruction::NewArray(_) => NEWARRAY,
+            Instruction::MultiANewArray(_, _) => MULTIANEWARRAY,
+        }
+    }
+
+    /// 指令位于pc时的字节长度，switch指令的填充与pc有关
+    pub fn byte_size(&self, pc: u32) -> usize {
+        match self {
+            Instruction::Simple(_) => 1,
+            Instruction::Bipush(_) | Instruction::Ldc(_) | Instruction::Local(_, _) | Instruction::NewArray(_) => 2,
+            Instruction::Sipush(_) | Instruction::LdcW(_) | Instruction::Ldc2W(_) | Instruction::Iinc(_, _) |
+            Instruction::Branch(_, _) | Instruction::Field(_, _) | Instruction::Invoke(_, _) | Instruction::Type(_, _) => 3,
+            Instruction::MultiANewArray(_, _) | Instruction::WideLocal(_, _) => 4,
+            Instruction::BranchW(_, _) | Instruction::InvokeInterface(_, _) | Instruction::InvokeDynamic(_) => 5,
+            Instruction::WideIinc(_, _) => 6,
+            Instruction::TableSwitch { targets, .. } => (padded_start(pc) - pc) as usize + 12 + targets.len() * 4,
+            Instruction::LookupSwitch { pairs, .. } => (padded_start(pc) - pc) as usize + 8 + pairs.len() * 8,
+        }
+    }
+
+    /// 按位于pc编码追加到codes，跳转偏移或操作数超出范围时返回错误
+    pub fn encode_to(&self, pc: u32, codes: &mut Vec<u8>) -> Result<()> {
+        let offset16 = |target: u32| -> Result<[u8; 2]> {
+            i16::try_from(target as i64 - pc as i64)
+                .map(i16::to_be_bytes)
+                .map_err(|_| invalid(pc, &format!("跳转目标[{}]超出16位偏移范围", target)))
+        };
+        let offset32 = |target: u32| -> Result<[u8; 4]> {
+            i32::try_from(target as i64 - pc as i64)
+                .map(i32::to_be_bytes)
+                .map_err(|_| invalid(pc, &format!("跳转目标[{}]超出32位偏移范围", target)))
+        };
+        let opcode = self.opcode();
+        codes.push(opcode);
+        match self {
+            Instruction::Simple(opcode) => {
+                if !is_simple(*opcode) {
+                    return Err(invalid(pc, &format!("操作码[{}]需要操作数", opcode)));
+                }
+            }
+            Instruction::Bipush(value) => codes.push(*value as u8),
+            Instruction::Sipush(value) => codes.extend_from_slice(&value.to_be_bytes()),
+            Instruction::Ldc(index) => match u8::try_from(*index) {
+                Ok(index) => codes.push(index),
+                Err(_) => return Err(invalid(pc, &format!("ldc常量索引[{}]超出单字节范围", index))),
+            },
+            Instruction::LdcW(index) | Instruction::Ldc2W(index) | Instruction::Field(_, index) |
+            Instruction::Invoke(_, index) | Instruction::Type(_, index) => codes.extend_from_slice(&index.to_be_bytes()),
+            Instruction::Local(_, index) => codes.push(*index),
+            Instruction::WideLocal(opcode, index) => {
+                codes.push(*opcode);
+                codes.extend_from_slice(&index.to_be_bytes());
+            }
+            Instruction::Iinc(index, value) => codes.extend_from_slice(&[*index, *value as u8]),
+            Instruction::WideIinc(index, value) => {
+                codes.push(IINC);
+                codes.extend_from_slice(&index.to_be_bytes());
+                codes.extend_from_slice(&value.to_be_bytes());
+            }
+            Instruction::Branch(_, target) => codes.extend_from_slice(&offset16(*target)?),
+            Instruction::BranchW(_, target) => codes.extend_from_slice(&offset32(*target)?),
+            Instruction::TableSwitch { default, low, targets } => {
+                codes.resize(codes.len() + (padded_start(pc) - pc - 1) as usize, 0);
+                let high = *low as i64 + targets.len() as i64 - 1;
+                let high = i32::try_from(high).map_err(|_| invalid(pc, "tableswitch跳转表过长"))?;
+                codes.extend_from_slice(&offset32(*default)?);
+                codes.extend_from_slice(&low.to_be_bytes());
+                codes.extend_from_slice(&high.to_be_bytes());
+                for target in targets {
+                    codes.extend_from_slice(&offset32(*target)?);
+                }
+            }
+            Instruction::LookupSwitch { default, pairs } => {
+                codes.resize(codes.len() + (padded_start(pc) - pc - 1) as usize, 0);
+                codes.extend_from_slice(&offset32(*default)?);
+                codes.extend_from_slice(&(pairs.len() as i32).to_be_bytes());
+                for (key, target) in pairs {
+                    codes.extend_from_slice(&key.to_be_bytes());
+                    codes.extend_from_slice(&offset32(*target)?);
+                }
+            }
+            Instruction::InvokeInterface(index, count) => {
+                codes.extend_from_slice(&index.to_be_bytes());
+                codes.extend_from_slice(&[*count, 0]);
+            }
+            Instruction::InvokeDynamic(index) => {
+                codes.extend_from_slice(&index.to_be_bytes());
+                codes.extend_from_slice(&[0, 0]);
+            }
+            Instruction::NewArray(atype) => codes.push(*atype),
+            Instruction::MultiANewArray(index, dimensions) => {
+                codes.extend_from_slice(&index.to_be_bytes());
+                codes.push(*dimensions);
+            }
+        }
+        Ok(())
+    }
+
+    /// 跳转目标，不含顺序执行的下一条指令
+    pub fn branch_targets(&self) -> Vec<u32> {
+        match self {
+            Instruction::Branch(_, target) | Instruction::BranchW(_, target) => vec![*target],
+            Instruction::TableSwitch { default, targets, .. } => {
+                let mut all = vec![*default];
+                all.extend_from_slice(targets);
+                all
+            }
+            Instruction::LookupSwitch { default, pairs } => {
+                let mut all = vec![*default];
+                all.extend(pairs.iter().map(|(_, target)| *target));
+                all
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// 按顺序解码字节码，遇到错误时返回该错误后结束
+#[derive(Debug, Clone)]
+pub struct Instructions<'a> {
+    codes: &'a [u8],
+    pc: u32,
+    failed: bool,
+}
+
+impl<'a> Instructions<'a> {
+    pub fn new(codes: &'a [u8]) -> Instructions<'a> {
+        Instructions {
+            codes,
+            pc: 0,
+            failed: false,
+        }
+    }
+}
+
+impl Iterator for Instructions<'_> {
+    type Item = Result<(u32, Instruction)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || self.pc as usize >= self.codes.len() {
+            return None;
+        }
+        let pc = self.pc;
+        match Instruction::decode(self.codes, pc) {
+            Ok(instruction) => {
+                self.pc += instruction.byte_size(pc) as u32;
+                Some(Ok((pc, instruction)))
+            }
+            Err(e) => {
+                self.failed = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// 依次编码指令，指令的pc按前面指令的长度累计
+pub fn encode_instructions(instructions: &[Instruction]) -> Result<Vec<u8>> {
+    let mut codes = Vec::new();
+    for instruction in instructions {
+        instruction.encode_to(codes.len() as u32, &mut codes)?;
+    }
+    Ok(codes)
+}
+
+/// switch指令操作码之后按4字节对齐的起始位置
+#[inline]
+fn padded_start(pc: u32) -> u32 {
+    (pc + 4) & !3
+}
+
+#[inline]
+fn is_simple(opcode: u8) -> bool {
+    matches!(Instruction::decode(&[opcode], 0), Ok(Instruction::Simple(_)))
+}
+
+#[inline]
+fn eof(pc: u32) -> MessageError {
+    MessageError::of(ErrorKind::UnexpectedEof, "字节码指令不完整").at(pc as usize)
+}
+
+#[inline]
+fn invalid(pc: u32, msg: &str) -> MessageError {
+    MessageError::of(ErrorKind::InvalidBytecode, msg).at(pc as usize)
+}
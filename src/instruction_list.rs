@@ -0,0 +1,367 @@
+use crate::attribute_info::{AttributeInfo, CodeAttribute, ExceptionTable, ExceptionTableEntry, LineNumber, OriginAttribute};
+use crate::code_analysis::compute_maxs;
+use crate::common::error::{ErrorKind, MessageError, Result};
+use crate::common::opcode::opcodes::*;
+use crate::constant_pool::ConstantPool;
+use crate::instruction::Instruction;
+
+/// 指令列表中的位置标签，由InstructionList::new_label创建
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Label(u32);
+
+/// 指令列表中的一项，跳转目标使用标签
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListItem {
+    Label(Label),
+    /// 不含跳转目标的指令
+    Instruction(Instruction),
+    /// if系列条件跳转、goto、jsr、goto_w与jsr_w
+    Jump(u8, Label),
+    TableSwitch {
+        default: Label,
+        low: i32,
+        targets: Vec<Label>,
+    },
+    LookupSwitch {
+        default: Label,
+        pairs: Vec<(i32, Label)>,
+    },
+}
+
+/// 异常处理器，覆盖[start, end)范围，catch_type为0时捕获所有异常
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExceptionHandler {
+    pub start: Label,
+    pub end: Label,
+    pub handler: Label,
+    pub catch_type: u16,
+}
+
+/// 使用标签的指令列表，汇编时计算跳转偏移与switch填充
+///
+/// 超出16位偏移的goto与jsr改为goto_w与jsr_w，条件跳转改为反向条件跳过其后的goto_w
+#[derive(Debug, Clone, Default)]
+pub struct InstructionList {
+    items: Vec<ListItem>,
+    handlers: Vec<ExceptionHandler>,
+    label_count: u32,
+}
+
+/// 汇编结果，可查询标签对应的pc
+#[derive(Debug, Clone)]
+pub struct AssembledCode {
+    pub codes: Vec<u8>,
+    pub exceptions: ExceptionTable,
+    labels: Vec<Option<u32>>,
+}
+
+/// 方法的指令列表及Code以外的附加信息，用于生成方法的属性
+#[derive(Debug, Clone, Default)]
+pub struct MethodBody {
+    pub list: InstructionList,
+    /// 未指定时按指令计算
+    pub max_stack: Option<u16>,
+    pub max_locals: Option<u16>,
+    /// 源码行号及其起始位置的标签
+    pub lines: Vec<(Label, u16)>,
+    /// Exceptions属性中的类常量索引
+    pub throws: Vec<u16>,
+    /// Signature属性的Utf8常量索引
+    pub signature: Option<u16>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum JumpForm {
+    Short,
+    Wide,
+    /// 反向条件跳转(3字节)后接goto_w(5字节)
+    Inverted,
+}
+
+impl JumpForm {
+    fn size(self) -> u32 {
+        match self {
+            JumpForm::Short => 3,
+            JumpForm::Wide => 5,
+            JumpForm::Inverted => 8,
+        }
+    }
+}
+
+impl InstructionList {
+    pub fn new() -> InstructionList {
+        Self::default()
+    }
+
+    pub fn new_label(&mut self) -> Label {
+        let label = Label(self.label_count);
+        self.label_count += 1;
+        label
+    }
+
+    /// 将标签放置在下一条指令处，每个标签只能放置一次
+    pub fn place_label(&mut self, label: Label) -> &mut Self {
+        self.items.push(ListItem::Label(label));
+        self
+    }
+
+    pub fn push(&mut self, instruction: Instruction) -> &mut Self {
+        self.items.push(ListItem::Instruction(instruction));
+        self
+    }
+
+    pub fn jump(&mut self, opcode: u8, target: Label) -> &mut Self {
+        self.items.push(ListItem::Jump(opcode, target));
+        self
+    }
+
+    pub fn table_switch(&mut self, low: i32, default: Label, targets: Vec<Label>) -> &mut Self {
+        self.items.push(ListItem::TableSwitch { default, low, targets });
+        self
+    }
+
+    pub fn lookup_switch(&mut self, default: Label, pairs: Vec<(i32, Label)>) -> &mut Self {
+        self.items.push(ListItem::LookupSwitch { default, pairs });
+        self
+    }
+
+    pub fn add_exception_handler(&mut self, start: Label, end: Label, handler: Label, catch_type: u16) -> &mut Self {
+        self.handlers.push(ExceptionHandler { start, end, handler, catch_type });
+        self
+    }
+
+    pub fn items(&self) -> &[ListItem] {
+        &self.items
+    }
+
+    pub fn handlers(&self) -> &[ExceptionHandler] {
+        &self.handlers
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// 计算跳转形式与标签位置并编码
+    pub fn assemble(&self) -> Result<AssembledCode> {
+        let mut forms = Vec::with_capacity(self.items.len());
+        for item in &self.items {
+            let form = match item {
+                ListItem::Jump(GOTO_W | JSR_W, _) => JumpForm::Wide,
+                ListItem::Jump(IFEQ..=JSR | IFNULL | IFNONNULL, _) => JumpForm::Short,
+                ListItem::Jump(opcode, _) => return Err(invalid(&format!("操作码[{}]不是跳转指令", opcode))),
+                ListItem::Instruction(instruction) if !instruction.branch_targets().is_empty() => {
+                    return Err(invalid(&format!("跳转指令[{}]须使用标签", instruction.opcode())));
+                }
+                _ => JumpForm::Short,
+            };
+            forms.push(form);
+        }
+        // 跳转只会变长，重复计算直到所有短跳转的偏移都在范围内
+        let (pcs, labels) = loop {
+            let (pcs, labels) = self.layout(&forms)?;
+            let mut changed = false;
+            for (i, item) in self.items.iter().enumerate() {
+                if let (ListItem::Jump(opcode, target), JumpForm::Short) = (item, forms[i]) {
+                    let offset = label_pc(&labels, *target)? as i64 - pcs[i] as i64;
+                    if i16::try_from(offset).is_err() {
+                        forms[i] = if is_conditional(*opcode) { JumpForm::Inverted } else { JumpForm::Wide };
+                        changed = true;
+                    }
+                }
+            }
+            if !changed {
+                break (pcs, labels);
+            }
+        };
+
+        let mut codes = Vec::new();
+        for (i, item) in self.items.iter().enumerate() {
+            let pc = pcs[i];
+            let instruction = match item {
+                ListItem::Label(_) => continue,
+                ListItem::Instruction(instruction) => instruction.clone(),
+                ListItem::Jump(opcode, target) => {
+                    let target = label_pc(&labels, *target)?;
+                    match forms[i] {
+                        JumpForm::Short => Instruction::Branch(*opcode, target),
+                        JumpForm::Wide => Instruction::BranchW(wide_opcode(*opcode), target),
+                        JumpForm::Inverted => {
+                            Instruction::Branch(inverse_condition(*opcode), pc + JumpForm::Inverted.size()).encode_to(pc, &mut codes)?;
+                            Instruction::BranchW(GOTO_W, target).encode_to(pc + JumpForm::Short.size(), &mut codes)?;
+                            continue;
+                        }
+                    }
+                }
+                ListItem::TableSwitch { default, low, targets } => Instruction::TableSwitch {
+                    default: label_pc(&labels, *default)?,
+                    low: *low,
+                    targets: targets.iter().map(|target| label_pc(&labels, *target)).collect::<Result<_>>()?,
+                },
+                ListItem::LookupSwitch { default, pairs } => Instruction::LookupSwitch {
+                    default: label_pc(&labels, *default)?,
+                    pairs: pairs.iter().map(|(key, target)| Ok((*key, label_pc(&labels, *target)?))).collect::<Result<_>>()?,
+                },
+            };
+            instruction.encode_to(pc, &mut codes)?;
+        }
+        if codes.len() > u16::MAX as usize {
+            let msg = format!("字节码长度{}超出限制{}", codes.len(), u16::MAX);
+            return Err(MessageError::of(ErrorKind::LimitExceeded, &msg));
+        }
+
+        let mut entries = Vec::with_capacity(self.handlers.len());
+        for handler in &self.handlers {
+            let (start_pc, end_pc) = (label_pc(&labels, handler.start)?, label_pc(&labels, handler.end)?);
+            if start_pc >= end_pc {
+                return Err(invalid(&format!("异常处理范围无效[{}, {})", start_pc, end_pc)));
+            }
+            entries.push(ExceptionTableEntry {
+                start_pc: start_pc as u16,
+                end_pc: end_pc as u16,
+                handler_pc: label_pc(&labels, handler.handler)? as u16,
+                catch_type: handler.catch_type,
+            });
+        }
+        Ok(AssembledCode {
+            codes,
+            exceptions: ExceptionTable { entries },
+            labels,
+        })
+    }
+
+    /// 汇编为Code属性，不含子属性
+    pub fn build(&self, max_stack: u16, max_locals: u16) -> Result<CodeAttribute> {
+        let assembled = self.assemble()?;
+        Ok(CodeAttribute {
+            codes: assembled.codes,
+            max_stack,
+            max_locals,
+            exceptions: assembled.exceptions,
+            attributes: Vec::new(),
+        })
+    }
+
+    /// 按当前跳转形式计算每项的pc及标签位置
+    fn layout(&self, forms: &[JumpForm]) -> Result<(Vec<u32>, Vec<Option<u32>>)> {
+        let mut pcs = Vec::with_capacity(self.items.len());
+        let mut labels = vec![None; self.label_count as usize];
+        let mut pc = 0u32;
+        for (i, item) in self.items.iter().enumerate() {
+            pcs.push(pc);
+            pc += match item {
+                ListItem::Label(label) => {
+                    match labels.get_mut(label.0 as usize) {
+                        Some(slot @ None) => *slot = Some(pc),
+                        Some(Some(_)) => return Err(invalid(&format!("标签[{}]重复放置", label.0))),
+                        None => return Err(invalid(&format!("标签[{}]不属于该指令列表", label.0))),
+                    }
+                    0
+                }
+                ListItem::Instruction(instruction) => instruction.byte_size(pc) as u32,
+                ListItem::Jump(_, _) => forms[i].size(),
+                ListItem::TableSwitch { targets, .. } => {
+                    Instruction::TableSwitch { default: 0, low: 0, targets: vec![0; targets.len()] }.byte_size(pc) as u32
+                }
+                ListItem::LookupSwitch { pairs, .. } => {
+                    Instruction::LookupSwitch { default: 0, pairs: vec![(0, 0); pairs.len()] }.byte_size(pc) as u32
+                }
+            };
+        }
+        Ok((pcs, labels))
+    }
+}
+
+impl MethodBody {
+    pub fn new() -> MethodBody {
+        Self::default()
+    }
+
+    /// 生成方法的Code、Exceptions与Signature属性，argument_slots见MethodDescriptor::argument_slots
+    ///
+    /// 没有指令且未指定max_stack与max_locals时视为抽象或本地方法，不生成Code属性
+    pub fn into_attributes(self, pool: &mut ConstantPool, argument_slots: u16) -> Result<Vec<OriginAttribute>> {
+        let mut attributes = Vec::new();
+        if !self.list.is_empty() || self.max_stack.is_some() || self.max_locals.is_some() {
+            let assembled = self.list.assemble()?;
+            let mut code = CodeAttribute {
+                codes: assembled.codes.clone(),
+                max_stack: 0,
+                max_locals: 0,
+                exceptions: assembled.exceptions.clone(),
+                attributes: Vec::new(),
+            };
+            (code.max_stack, code.max_locals) = match (self.max_stack, self.max_locals) {
+                (Some(max_stack), Some(max_locals)) => (max_stack, max_locals),
+                (max_stack, max_locals) => {
+                    let (stack, locals) = compute_maxs(&code, pool, argument_slots)?;
+                    (max_stack.unwrap_or(stack), max_locals.unwrap_or(locals))
+                }
+            };
+            if !self.lines.is_empty() {
+                let lines = self.lines.iter()
+                    .filter_map(|(label, line_number)| Some(LineNumber {
+                        start_pc: assembled.label_pc(*label)? as u16,
+                        line_number: *line_number,
+                    }))
+                    .collect();
+                code.attributes.push(AttributeInfo::LineNumberTable(lines).to_origin(pool)?);
+            }
+            attributes.push(AttributeInfo::Code(code).to_origin(pool)?);
+        }
+        if !self.throws.is_empty() {
+            attributes.push(AttributeInfo::Exceptions(self.throws).to_origin(pool)?);
+        }
+        if let Some(signature) = self.signature {
+            attributes.push(AttributeInfo::Signature(signature).to_origin(pool)?);
+        }
+        Ok(attributes)
+    }
+}
+
+impl AssembledCode {
+    /// 标签对应的pc，未放置的标签返回None
+    pub fn label_pc(&self, label: Label) -> Option<u32> {
+        self.labels.get(label.0 as usize).copied().flatten()
+    }
+}
+
+/// 条件跳转的反向条件，如ifeq与ifne
+pub fn inverse_condition(opcode: u8) -> u8 {
+    match opcode {
+        IFEQ..=IF_ACMPNE => ((opcode - IFEQ) ^ 1) + IFEQ,
+        IFNULL => IFNONNULL,
+        IFNONNULL => IFNULL,
+        _ => opcode,
+    }
+}
+
+#[inline]
+fn is_conditional(opcode: u8) -> bool {
+    matches!(opcode, IFEQ..=IF_ACMPNE | IFNULL | IFNONNULL)
+}
+
+#[inline]
+fn wide_opcode(opcode: u8) -> u8 {
+    match opcode {
+        JSR | JSR_W => JSR_W,
+        _ => GOTO_W,
+    }
+}
+
+#[inline]
+fn label_pc(labels: &[Option<u32>], label: Label) -> Result<u32> {
+    match labels.get(label.0 as usize) {
+        Some(Some(pc)) => Ok(*pc),
+        _ => Err(invalid(&format!("标签[{}]未放置", label.0))),
+    }
+}
+
+#[inline]
+fn invalid(msg: &str) -> MessageError {
+    MessageError::of(ErrorKind::InvalidBytecode, msg)
+}
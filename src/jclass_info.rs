@@ -1,12 +1,19 @@
-use crate::attribute_info::OriginAttribute;
-use crate::common::error::{MessageError, Result};
-use crate::constant_pool::ConstantPool;
+use crate::annotation::Annotations;
+use crate::attribute_info::{self, OriginAttribute};
+use crate::access_flags::{ClassAccessFlags, MethodAccessFlags};
+use crate::common::error::{ConstantError, ConstantResult, ErrorKind, MessageError, Result};
+use crate::common::options::ParseOptions;
+use crate::constant_pool::{ConstantPool, ConstantValue};
+use crate::descriptor::MethodDescriptor;
 use crate::field_info::FieldInfo;
+use crate::instruction::{Instruction, Instructions};
 use crate::method_info::MethodInfo;
 use crate::support::data_reader::{DataReader, DataWriter, ReadToType, WriteFromType};
 use std::io::{Read, Write};
 
 pub const JCLASS_MAGIC: u32 = 0xCAFEBABE;
+/// 汇编器与ClassBuilder未指定版本时使用的类文件主版本（Java 8）
+pub const DEFAULT_MAJOR_VERSION: u16 = 52;
 
 #[derive(Debug, Clone, Default)]
 pub struct JClassInfo {
@@ -24,10 +31,24 @@ pub struct JClassInfo {
 }
 
 impl JClassInfo {
+    /// 使用默认的解析限制从内存数据解析
+    pub fn from_bytes(data: &[u8]) -> Result<JClassInfo> {
+        Self::from_bytes_with_options(data, ParseOptions::default())
+    }
+
+    /// 使用指定的解析限制从内存数据解析，长度字段会在分配前与剩余数据比较
+    pub fn from_bytes_with_options(data: &[u8], options: ParseOptions) -> Result<JClassInfo> {
+        if data.len() > options.max_input_size {
+            let msg = format!("输入长度{}超出限制{}", data.len(), options.max_input_size);
+            return Err(MessageError::of(ErrorKind::LimitExceeded, &msg));
+        }
+        Self::from_reader(&mut DataReader::from_bytes(data).with_options(options))
+    }
+
     pub fn from_reader<T: Read>(reader: &mut DataReader<T>) -> Result<JClassInfo> {
         let magic: u32 = reader.read_to("魔术头")?;
         if magic != JCLASS_MAGIC {
-            return Err(MessageError::new("解析数据非class文件"));
+            return Err(MessageError::of(ErrorKind::BadMagic, "解析数据非class文件").at(0));
         }
         let minor_version: u16 = reader.read_to("次版本")?;
         let major_version: u16 = reader.read_to("主版本")?;
@@ -44,22 +65,22 @@ impl JClassInfo {
 
         let field_count: u16 = reader.read_to("字段数量")?;
         let mut fields = Vec::with_capacity(field_count as usize);
-        for _ in 0..field_count {
-            let field_info = FieldInfo::new_from_reader(reader)?;
+        for i in 0..field_count {
+            let field_info = FieldInfo::new_from_reader(reader).map_err(|e| e.in_path(&format!("fields[{}]", i)))?;
             fields.push(field_info);
         }
 
         let method_count: u16 = reader.read_to("方法数量")?;
         let mut methods = Vec::with_capacity(method_count as usize);
-        for _ in 0..method_count {
-            let method_info = MethodInfo::new_from_reader(reader)?;
+        for i in 0..method_count {
+            let method_info = MethodInfo::new_from_reader(reader).map_err(|e| e.in_path(&format!("methods[{}]", i)))?;
             methods.push(method_info);
         }
 
         let attribute_count: u16 = reader.read_to("属性数量")?;
         let mut attributes = Vec::with_capacity(attribute_count as usize);
-        for _ in 0..attribute_count {
-            let attribute = OriginAttribute::new_from_reader(reader)?;
+        for i in 0..attribute_count {
+            let attribute = OriginAttribute::new_from_reader(reader).map_err(|e| e.in_path(&format!("attributes[{}]", i)))?;
             attributes.push(attribute);
         }
         Ok(JClassInfo {
@@ -108,6 +129,242 @@ impl JClassInfo {
         Ok(())
     }
 
+    pub fn access(&self) -> ClassAccessFlags {
+        ClassAccessFlags::from_bits(self.access_flags)
+    }
+
+    pub fn set_access(&mut self, access: ClassAccessFlags) {
+        self.access_flags = access.bits();
+    }
+
+    pub fn this_class_name(&self) -> Result<&str> {
+        Ok(self.constant_pool.class_name(self.class_index)?)
+    }
+
+    /// 父类名，java/lang/Object与module-info没有父类，返回None
+    pub fn super_class_name(&self) -> Result<Option<&str>> {
+        if self.superclass_index == 0 {
+            return Ok(None);
+        }
+        Ok(Some(self.constant_pool.class_name(self.superclass_index)?))
+    }
+
+    pub fn interface_names(&self) -> Result<Vec<&str>> {
+        Ok(self.interfaces.iter()
+            .map(|index| self.constant_pool.class_name(*index))
+            .collect::<ConstantResult<_>>()?)
+    }
+
+    /// 第一个名为name的类属性，如SourceFile、InnerClasses
+    pub fn attribute(&self, name: &str) -> Option<&OriginAttribute> {
+        attribute_info::find_attribute(&self.attributes, &self.constant_pool, name)
+    }
+
+    pub fn attributes_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a OriginAttribute> + 'a {
+        attribute_info::attributes_named(&self.attributes, &self.constant_pool, name)
+    }
+
+    /// 以data作为名为name的类属性内容，替换已有的同名属性，属性名按需加入常量池
+    pub fn set_attribute(&mut self, name: &str, data: Vec<u8>) {
+        attribute_info::set_attribute(&mut self.attributes, &mut self.constant_pool, name, data)
+    }
+
+    /// 移除所有名为name的类属性并返回
+    pub fn remove_attribute(&mut self, name: &str) -> Vec<OriginAttribute> {
+        attribute_info::remove_attribute(&mut self.attributes, &self.constant_pool, name)
+    }
+
+    /// 类上的可见与不可见注解
+    pub fn annotations(&self) -> Result<Annotations> {
+        Annotations::read(&self.attributes, &self.constant_pool)
+    }
+
+    pub fn set_annotations(&mut self, annotations: &Annotations) -> Result<()> {
+        annotations.write(&mut self.attributes, &mut self.constant_pool)
+    }
+
+    /// 按名称与描述符查找方法
+    pub fn find_method(&self, name: &str, descriptor: &str) -> Option<&MethodInfo> {
+        let pool = &self.constant_pool;
+        self.methods.iter().find(|method| is_member(pool, method.name, method.descriptor, name, descriptor))
+    }
+
+    pub fn find_method_mut(&mut self, name: &str, descriptor: &str) -> Option<&mut MethodInfo> {
+        let pool = &self.constant_pool;
+        self.methods.iter_mut().find(|method| is_member(pool, method.name, method.descriptor, name, descriptor))
+    }
+
+    /// 按名称与描述符查找字段
+    pub fn find_field(&self, name: &str, descriptor: &str) -> Option<&FieldInfo> {
+        let pool = &self.constant_pool;
+        self.fields.iter().find(|field| is_member(pool, field.name, field.descriptor, name, descriptor))
+    }
+
+    pub fn find_field_mut(&mut self, name: &str, descriptor: &str) -> Option<&mut FieldInfo> {
+        let pool = &self.constant_pool;
+        self.fields.iter_mut().find(|field| is_member(pool, field.name, field.descriptor, name, descriptor))
+    }
+
+    /// 名称为name的所有方法，包括重载方法
+    pub fn methods_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a MethodInfo> + 'a {
+        self.methods.iter().filter(move |method| self.constant_pool.utf8(method.name).is_ok_and(|n| n == name))
+    }
+
+    /// 加入没有属性的方法并返回，名称与描述符按需加入常量池。同名同描述符的方法已存在时返回错误
+    pub fn add_method(&mut self, access: MethodAccessFlags, name: &str, descriptor: &str) -> Result<&mut MethodInfo> {
+        MethodDescriptor::parse(descriptor)?;
+        if self.find_method(name, descriptor).is_some() {
+            return Err(MessageError::new(&format!("方法{}{}已存在", name, descriptor)));
+        }
+        let method = MethodInfo {
+            access_flags: access.bits(),
+            name: self.constant_pool.add_utf8(name),
+            descriptor: self.constant_pool.add_utf8(descriptor),
+            attributes: Vec::new(),
+        };
+        self.methods.push(method);
+        Ok(self.methods.last_mut().unwrap())
+    }
+
+    /// 移除方法并返回，未找到时返回None
+    ///
+    /// 常量池保持不变，其他位置对该方法的引用仍然有效；仅被该方法使用的常量可通过compact_constant_pool移除
+    pub fn remove_method(&mut self, name: &str, descriptor: &str) -> Option<MethodInfo> {
+        let pool = &self.constant_pool;
+        let index = self.methods.iter().position(|method| is_member(pool, method.name, method.descriptor, name, descriptor))?;
+        Some(self.methods.remove(index))
+    }
+
+    /// 重命名字段或方法（按描述符是否以(开头区分），并改写常量池中所属类为本类的对应成员引用，
+    /// 使类内的字段访问、方法调用及方法句柄指向新名称
+    ///
+    /// 原名称的Utf8常量可能被其他常量共享，因此不会原位修改，而是为新名称加入常量
+    pub fn rename_member(&mut self, name: &str, descriptor: &str, new_name: &str) -> Result<()> {
+        let is_method = descriptor.starts_with('(');
+        let exists = |class: &JClassInfo, name: &str| match is_method {
+            true => class.find_method(name, descriptor).is_some(),
+            false => class.find_field(name, descriptor).is_some(),
+        };
+        if !exists(self, name) {
+            return Err(MessageError::new(&format!("未找到成员{}:{}", name, descriptor)));
+        }
+        if name == new_name {
+            return Ok(());
+        }
+        if exists(self, new_name) {
+            return Err(MessageError::new(&format!("成员{}:{}已存在", new_name, descriptor)));
+        }
+
+        let this_class = self.this_class_name()?.to_string();
+        let pool = &self.constant_pool;
+        let references: Vec<(u16, ConstantValue)> = pool.iter()
+            .filter(|(_, value)| match value {
+                ConstantValue::ConstantFieldref(..) => !is_method,
+                ConstantValue::ConstantMethodref(..) | ConstantValue::ConstantInterfaceMethodref(..) => is_method,
+                _ => false,
+            })
+            .filter(|(index, _)| pool.member_ref(*index)
+                .is_ok_and(|member| member.owner == this_class && member.name == name && member.descriptor == descriptor))
+            .map(|(index, value)| (index, value.clone()))
+            .collect();
+
+        let name_index = self.constant_pool.add_utf8(new_name);
+        if is_method {
+            self.find_method_mut(name, descriptor).unwrap().name = name_index;
+        } else {
+            self.find_field_mut(name, descriptor).unwrap().name = name_index;
+        }
+        if references.is_empty() {
+            return Ok(());
+        }
+        let name_type = self.constant_pool.add_name_and_type(new_name, descriptor);
+        for (index, value) in references {
+            let value = match value {
+                ConstantValue::ConstantFieldref(class, _) => ConstantValue::ConstantFieldref(class, name_type),
+                ConstantValue::ConstantMethodref(class, _) => ConstantValue::ConstantMethodref(class, name_type),
+                ConstantValue::ConstantInterfaceMethodref(class, _) => ConstantValue::ConstantInterfaceMethodref(class, name_type),
+                _ => unreachable!(),
+            };
+            self.constant_pool.replace(index, value)?;
+        }
+        Ok(())
+    }
+
+    /// 常量池以外所有位置引用的常量索引，只解析不修改属性
+    pub fn constant_indices(&self) -> Result<Vec<u16>> {
+        let pool = &self.constant_pool;
+        let mut indices = vec![self.class_index];
+        if self.superclass_index != 0 {
+            indices.push(self.superclass_index);
+        }
+        indices.extend(&self.interfaces);
+        for (i, field) in self.fields.iter().enumerate() {
+            indices.extend(field.constant_indices(pool).map_err(|e| e.in_path(&format!("fields[{}]", i)))?);
+        }
+        for (i, method) in self.methods.iter().enumerate() {
+            indices.extend(method.constant_indices(pool).map_err(|e| e.in_path(&format!("methods[{}]", i)))?);
+        }
+        for (i, attribute) in self.attributes.iter().enumerate() {
+            indices.extend(attribute.constant_indices(pool).map_err(|e| e.in_path(&format!("attributes[{}]", i)))?);
+        }
+        Ok(indices)
+    }
+
+    /// 改写常量池以外所有位置的常量索引，包括已知属性与字节码操作数
+    pub fn remap_constant_indices(&mut self, remap: &mut dyn FnMut(u16) -> u16) -> Result<()> {
+        let pool = &self.constant_pool;
+        self.class_index = remap(self.class_index);
+        if self.superclass_index != 0 {
+            self.superclass_index = remap(self.superclass_index);
+        }
+        for interface in &mut self.interfaces {
+            *interface = remap(*interface);
+        }
+        for (i, field) in self.fields.iter_mut().enumerate() {
+            field.remap_constant_indices(pool, remap).map_err(|e| e.in_path(&format!("fields[{}]", i)))?;
+        }
+        for (i, method) in self.methods.iter_mut().enumerate() {
+            method.remap_constant_indices(pool, remap).map_err(|e| e.in_path(&format!("methods[{}]", i)))?;
+        }
+        for (i, attribute) in self.attributes.iter_mut().enumerate() {
+            attribute.remap_constant_indices(pool, remap).map_err(|e| e.in_path(&format!("attributes[{}]", i)))?;
+        }
+        Ok(())
+    }
+
+    /// 移除未被引用的常量并重新编号，同时改写所有引用处的索引
+    ///
+    /// 存在无法识别的属性或ldc索引无法容纳时返回错误，此时类不会被修改
+    pub fn compact_constant_pool(&mut self) -> Result<()> {
+        let count = self.constant_pool.get_constant_count();
+        let mut used = vec![false; count as usize + 1];
+        // 只读遍历，无法识别的属性等错误在修改之前返回
+        for index in self.constant_indices()? {
+            match used.get_mut(index as usize) {
+                Some(used) => *used = true,
+                None => return Err(ConstantError::BadIndex { index, count }.into()),
+            }
+        }
+        let mapping = self.constant_pool.retain_mapping(&used);
+        let pool = &self.constant_pool;
+        for (i, method) in self.methods.iter().enumerate() {
+            if let Some(code) = method.code(pool).map_err(|e| e.in_path(&format!("methods[{}]", i)))? {
+                for item in Instructions::new(&code.codes) {
+                    if let (pc, Instruction::Ldc(index)) = item? {
+                        if mapping[index as usize] > u8::MAX as u16 {
+                            let msg = format!("ldc常量索引[{}]超出单字节范围", mapping[index as usize]);
+                            return Err(MessageError::of(ErrorKind::InvalidBytecode, &msg).at(pc as usize).in_path(&format!("methods[{}]", i)));
+                        }
+                    }
+                }
+            }
+        }
+        // 先按原常量池解析属性并改写索引，再重新编号常量池
+        self.remap_constant_indices(&mut |index| mapping[index as usize])?;
+        self.constant_pool.retain(&used);
+        Ok(())
+    }
+
     pub fn byte_size(&self) -> usize {
         size_of::<u32>() +
         size_of::<u16>() +
@@ -125,4 +382,8 @@ impl JClassInfo {
         size_of::<u16>() + // 属性数量
         self.attributes.iter().map(OriginAttribute::byte_size).sum::<usize>()
     }
-}
\ No newline at end of file
+}
+
+fn is_member(pool: &ConstantPool, name_index: u16, descriptor_index: u16, name: &str, descriptor: &str) -> bool {
+    pool.utf8(name_index).is_ok_and(|n| n == name) && pool.utf8(descriptor_index).is_ok_and(|d| d == descriptor)
+}
@@ -0,0 +1,263 @@
+use crate::attribute_info::{AttributeInfo, OriginAttribute};
+use crate::classfile_constants::{JVM_CONSTANT_Class, JVM_CONSTANT_Double, JVM_CONSTANT_Dynamic, JVM_CONSTANT_Fieldref, JVM_CONSTANT_Float, JVM_CONSTANT_Integer, JVM_CONSTANT_InterfaceMethodref, JVM_CONSTANT_InvokeDynamic, JVM_CONSTANT_Long, JVM_CONSTANT_MethodHandle, JVM_CONSTANT_MethodType, JVM_CONSTANT_Methodref, JVM_CONSTANT_Module, JVM_CONSTANT_NameAndType, JVM_CONSTANT_Package, JVM_CONSTANT_String, JVM_CONSTANT_Utf8, _bindgen_ty_3};
+use crate::common::error::{ErrorKind, MessageError, Result};
+use crate::constant_pool::ConstantValue;
+use crate::jclass_info::{JClassInfo, JCLASS_MAGIC};
+use crate::util::class_scan::{get_u16_from_data, get_u32_from_data};
+use crate::support::data_reader::DataReader;
+use crate::util::mutf8;
+use std::borrow::Cow;
+
+/// 直接引用输入数据的class结构，常量与属性内容均不复制，
+/// 介于JClassInfo的完整解析与fast_scan_class之间
+#[derive(Debug, Clone)]
+pub struct JClassInfoRef<'a> {
+    pub data: &'a [u8],
+    pub magic: u32,
+    pub minor_version: u16,
+    pub major_version: u16,
+    pub constant_pool: ConstantPoolRef<'a>,
+    pub access_flags: u16,
+    pub class_index: u16,
+    pub superclass_index: u16,
+    pub interfaces: Vec<u16>,
+    pub fields: Vec<FieldInfoRef<'a>>,
+    pub methods: Vec<MethodInfoRef<'a>>,
+    pub attributes: Vec<AttributeRef<'a>>,
+}
+
+/// offsets 中每个元素为对应常量（含类型标记）在输入数据中的起始位置，索引0与Long/Double后的占位项为0
+#[derive(Debug, Clone)]
+pub struct ConstantPoolRef<'a> {
+    data: &'a [u8],
+    offsets: Vec<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MemberInfoRef<'a> {
+    pub access_flags: u16,
+    pub name: u16,
+    pub descriptor: u16,
+    pub attributes: Vec<AttributeRef<'a>>,
+}
+
+pub type FieldInfoRef<'a> = MemberInfoRef<'a>;
+pub type MethodInfoRef<'a> = MemberInfoRef<'a>;
+
+#[derive(Debug, Clone)]
+pub struct AttributeRef<'a> {
+    pub name: u16,
+    pub data: &'a [u8],
+}
+
+impl<'a> JClassInfoRef<'a> {
+    pub fn from_bytes(data: &'a [u8]) -> Result<JClassInfoRef<'a>> {
+        let mut index = 0;
+        let magic = get_u32_from_data(data, &mut index)?;
+        if magic != JCLASS_MAGIC {
+            return Err(MessageError::of(ErrorKind::BadMagic, "解析数据非class文件").at(0));
+        }
+        let minor_version = get_u16_from_data(data, &mut index)?;
+        let major_version = get_u16_from_data(data, &mut index)?;
+        let constant_pool = ConstantPoolRef::new_with_data(data, &mut index)?;
+        let access_flags = get_u16_from_data(data, &mut index)?;
+        let class_index = get_u16_from_data(data, &mut index)?;
+        let superclass_index = get_u16_from_data(data, &mut index)?;
+        let interface_count = get_u16_from_data(data, &mut index)?;
+        let mut interfaces = Vec::with_capacity(interface_count as usize);
+        for _ in 0..interface_count {
+            interfaces.push(get_u16_from_data(data, &mut index)?);
+        }
+        let fields = MemberInfoRef::new_list_with_data(data, &mut index, "fields")?;
+        let methods = MemberInfoRef::new_list_with_data(data, &mut index, "methods")?;
+        let attributes = AttributeRef::new_list_with_data(data, &mut index).map_err(|e| e.in_path("attributes"))?;
+        Ok(JClassInfoRef {
+            data,
+            magic,
+            minor_version,
+            major_version,
+            constant_pool,
+            access_flags,
+            class_index,
+            superclass_index,
+            interfaces,
+            fields,
+            methods,
+            attributes,
+        })
+    }
+
+    /// 完整解析为可编辑的JClassInfo
+    pub fn to_class_info(&self) -> Result<JClassInfo> {
+        JClassInfo::from_bytes(self.data)
+    }
+
+    /// 查找指定名称的类属性
+    pub fn find_attribute(&self, name: &str) -> Option<&AttributeRef<'a>> {
+        find_attribute(&self.attributes, &self.constant_pool, name)
+    }
+}
+
+impl<'a> ConstantPoolRef<'a> {
+    fn new_with_data(data: &'a [u8], index: &mut usize) -> Result<ConstantPoolRef<'a>> {
+        let pool_count = get_u16_from_data(data, index)? as usize;
+        let mut offsets = vec![0; pool_count.max(1)];
+        let mut i = 1;
+        while i < pool_count {
+            offsets[i] = *index;
+            let tag = match data.get(*index) {
+                None => return Err(MessageError::of(ErrorKind::UnexpectedEof, "读取常量类型时越界").at(*index)),
+                Some(tag) => *tag,
+            };
+            *index += 1;
+            *index += match tag as _bindgen_ty_3 {
+                JVM_CONSTANT_Utf8 => get_u16_from_data(data, index)? as usize,
+                JVM_CONSTANT_Integer | JVM_CONSTANT_Float => size_of::<i32>(),
+                JVM_CONSTANT_Long | JVM_CONSTANT_Double => {
+                    // 占位项
+                    i += 1;
+                    size_of::<i64>()
+                }
+                JVM_CONSTANT_Class | JVM_CONSTANT_String | JVM_CONSTANT_Module |
+                JVM_CONSTANT_Package | JVM_CONSTANT_MethodType => size_of::<u16>(),
+                JVM_CONSTANT_Fieldref | JVM_CONSTANT_Methodref |
+                JVM_CONSTANT_InterfaceMethodref | JVM_CONSTANT_NameAndType |
+                JVM_CONSTANT_Dynamic | JVM_CONSTANT_InvokeDynamic => size_of::<[u16;2]>(),
+                JVM_CONSTANT_MethodHandle => size_of::<u16>() + size_of::<u8>(),
+                _ => {
+                    let error = MessageError::of(ErrorKind::InvalidConstantTag, &format!("无效的常量类型[{}]", tag));
+                    return Err(error.at(*index - 1).in_path(&format!("constant_pool[{}]", i)));
+                }
+            };
+            i += 1;
+        }
+        if *index > data.len() {
+            return Err(MessageError::of(ErrorKind::UnexpectedEof, "读取常量池越界").at(data.len()));
+        }
+        Ok(ConstantPoolRef {
+            data,
+            offsets,
+        })
+    }
+
+    pub fn get_constant_count(&self) -> u16 {
+        (self.offsets.len() - 1) as u16
+    }
+
+    /// 按需解码指定常量，Utf8常量会复制字符串内容
+    pub fn get_constant_item(&self, index: u16) -> Result<ConstantValue> {
+        match self.offsets.get(index as usize) {
+            Some(offset) if *offset != 0 => {
+                ConstantValue::new_with_reader(&mut DataReader::from_bytes(&self.data[*offset..]))
+            }
+            _ => Ok(ConstantValue::Null),
+        }
+    }
+
+    /// Utf8常量的原始字节（modified UTF-8编码）
+    pub fn utf8_bytes(&self, index: u16) -> Result<&'a [u8]> {
+        let mut offset = match self.offsets.get(index as usize) {
+            Some(offset) if *offset != 0 => *offset,
+            _ => return Err(MessageError::of(ErrorKind::BadIndex, &format!("无效的常量索引[{}]", index))),
+        };
+        if self.data[offset] as _bindgen_ty_3 != JVM_CONSTANT_Utf8 {
+            return Err(MessageError::of(ErrorKind::ConstantMismatch, &format!("常量[{}]不是Utf8常量", index)).at(offset));
+        }
+        offset += 1;
+        let len = get_u16_from_data(self.data, &mut offset)? as usize;
+        Ok(&self.data[offset..offset + len])
+    }
+
+    /// Utf8常量内容，可直接按UTF-8读取时不复制
+    ///
+    /// 与完整解析一致，标准UTF-8的四字节序列不是合法的modified UTF-8
+    pub fn utf8(&self, index: u16) -> Result<Cow<'a, str>> {
+        let bytes = self.utf8_bytes(index)?;
+        match std::str::from_utf8(bytes) {
+            Ok(string) if bytes.iter().all(|b| *b != 0 && *b < 0xF0) => Ok(Cow::Borrowed(string)),
+            _ => match mutf8::decode(bytes) {
+                Some(string) => Ok(Cow::Owned(string)),
+                None => Err(MessageError::of(ErrorKind::InvalidUtf8, &format!("常量[{}]不是合法的modified UTF-8", index))
+                    .at(self.offsets[index as usize])),
+            },
+        }
+    }
+
+    /// Class常量对应的类名
+    pub fn class_name(&self, index: u16) -> Result<Cow<'a, str>> {
+        match self.get_constant_item(index)? {
+            ConstantValue::ConstantClass(name_index) => self.utf8(name_index),
+            _ => Err(MessageError::of(ErrorKind::ConstantMismatch, &format!("常量[{}]不是Class常量", index))),
+        }
+    }
+}
+
+impl<'a> MemberInfoRef<'a> {
+    fn new_list_with_data(data: &'a [u8], index: &mut usize, path: &str) -> Result<Vec<MemberInfoRef<'a>>> {
+        let count = get_u16_from_data(data, index).map_err(|e| e.in_path(path))?;
+        let mut members = Vec::with_capacity(count as usize);
+        for i in 0..count {
+            let member = Self::new_with_data(data, index).map_err(|e| e.in_path(&format!("{}[{}]", path, i)))?;
+            members.push(member);
+        }
+        Ok(members)
+    }
+
+    fn new_with_data(data: &'a [u8], index: &mut usize) -> Result<MemberInfoRef<'a>> {
+        let access_flags = get_u16_from_data(data, index)?;
+        let name = get_u16_from_data(data, index)?;
+        let descriptor = get_u16_from_data(data, index)?;
+        let attributes = AttributeRef::new_list_with_data(data, index).map_err(|e| e.in_path("attributes"))?;
+        Ok(MemberInfoRef {
+            access_flags,
+            name,
+            descriptor,
+            attributes,
+        })
+    }
+
+    /// 查找指定名称的属性
+    pub fn find_attribute(&self, pool: &ConstantPoolRef<'a>, name: &str) -> Option<&AttributeRef<'a>> {
+        find_attribute(&self.attributes, pool, name)
+    }
+}
+
+impl<'a> AttributeRef<'a> {
+    fn new_list_with_data(data: &'a [u8], index: &mut usize) -> Result<Vec<AttributeRef<'a>>> {
+        let count = get_u16_from_data(data, index)?;
+        let mut attributes = Vec::with_capacity(count as usize);
+        for i in 0..count {
+            let name = get_u16_from_data(data, index)?;
+            let len = get_u32_from_data(data, index)? as usize;
+            let start = *index;
+            *index += len;
+            if *index > data.len() {
+                return Err(MessageError::of(ErrorKind::UnexpectedEof, "读取属性数据越界").at(start).in_path(&format!("[{}]", i)));
+            }
+            attributes.push(AttributeRef {
+                name,
+                data: &data[start..*index],
+            });
+        }
+        Ok(attributes)
+    }
+
+    pub fn to_origin(&self) -> OriginAttribute {
+        OriginAttribute::new(self.name, self.data.to_vec())
+    }
+
+    /// 按属性名解析为结构化内容，未知属性返回AttributeInfo::OriginAttribute
+    pub fn to_attribute_info(&self, pool: &ConstantPoolRef<'a>) -> Result<AttributeInfo> {
+        let info = match pool.utf8(self.name) {
+            Ok(name) => AttributeInfo::new_with_data(&name, self.data)?,
+            Err(_) => None,
+        };
+        Ok(info.unwrap_or_else(|| AttributeInfo::OriginAttribute(self.to_origin())))
+    }
+}
+
+fn find_attribute<'a, 'b>(attributes: &'b [AttributeRef<'a>], pool: &ConstantPoolRef<'a>, name: &str) -> Option<&'b AttributeRef<'a>> {
+    attributes.iter().find(|attribute| {
+        pool.utf8_bytes(attribute.name).is_ok_and(|bytes| bytes == name.as_bytes())
+    })
+}
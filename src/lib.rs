@@ -1,12 +1,25 @@
 #![allow(unused_attributes, unused, non_upper_case_globals)]
 
+pub mod access_flags;
+pub mod annotation;
+pub mod assembler;
 pub mod common;
 pub mod classfile_constants;
+pub mod code_analysis;
 pub mod constant_pool;
+pub mod descriptor;
+pub mod disassembler;
 pub mod jclass_info;
+pub mod jclass_info_ref;
 pub mod util;
 pub mod field_info;
+pub mod instruction;
+pub mod instruction_list;
 pub mod attribute_info;
+pub mod class_builder;
 pub mod method_info;
+pub mod signature;
+pub mod stack_map;
+pub mod verifier;
 mod support;
 
@@ -1,6 +1,10 @@
-use crate::attribute_info::OriginAttribute;
+use crate::annotation::{self, Annotations, ParameterAnnotations, ResolvedElementValue};
+use crate::attribute_info::{self, AttributeInfo, CodeAttribute, OriginAttribute};
+use crate::common::constants::CODE_TAG;
+use crate::access_flags::MethodAccessFlags;
 use crate::support::data_reader::{DataReader, DataWriter, ReadToType, WriteFromType};
 use crate::common::error::Result;
+use crate::constant_pool::ConstantPool;
 use std::io::{Read, Write};
 
 #[derive(Clone, Debug)]
@@ -18,8 +22,8 @@ impl MethodInfo {
         let descriptor: u16 = reader.read_to("方法描述")?;
         let attribute_size: u16 = reader.read_to("方法属性数量")?;
         let mut attributes = Vec::with_capacity(attribute_size as usize);
-        for _ in 0..attribute_size {
-            attributes.push(OriginAttribute::new_from_reader(reader)?)
+        for i in 0..attribute_size {
+            attributes.push(OriginAttribute::new_from_reader(reader).map_err(|e| e.in_path(&format!("attributes[{}]", i)))?)
         }
         Ok(MethodInfo {
             access_flags,
@@ -40,6 +44,111 @@ impl MethodInfo {
         Ok(())
     }
 
+    pub fn access(&self) -> MethodAccessFlags {
+        MethodAccessFlags::from_bits(self.access_flags)
+    }
+
+    pub fn set_access(&mut self, access: MethodAccessFlags) {
+        self.access_flags = access.bits();
+    }
+
+    pub fn get_name<'a>(&self, pool: &'a ConstantPool) -> Result<&'a str> {
+        Ok(pool.utf8(self.name)?)
+    }
+
+    pub fn get_descriptor<'a>(&self, pool: &'a ConstantPool) -> Result<&'a str> {
+        Ok(pool.utf8(self.descriptor)?)
+    }
+
+    /// 第一个名为name的属性
+    pub fn attribute(&self, pool: &ConstantPool, name: &str) -> Option<&OriginAttribute> {
+        attribute_info::find_attribute(&self.attributes, pool, name)
+    }
+
+    pub fn attributes_named<'a>(&'a self, pool: &'a ConstantPool, name: &'a str) -> impl Iterator<Item = &'a OriginAttribute> + 'a {
+        attribute_info::attributes_named(&self.attributes, pool, name)
+    }
+
+    /// 以data作为名为name的属性内容，替换已有的同名属性，属性名按需加入常量池
+    pub fn set_attribute(&mut self, pool: &mut ConstantPool, name: &str, data: Vec<u8>) {
+        attribute_info::set_attribute(&mut self.attributes, pool, name, data)
+    }
+
+    /// 移除所有名为name的属性并返回
+    pub fn remove_attribute(&mut self, pool: &ConstantPool, name: &str) -> Vec<OriginAttribute> {
+        attribute_info::remove_attribute(&mut self.attributes, pool, name)
+    }
+
+    /// 解析后的Code属性，抽象与本地方法返回None
+    pub fn code<'a>(&'a self, pool: &ConstantPool) -> Result<Option<&'a CodeAttribute>> {
+        match self.attribute(pool, CODE_TAG) {
+            Some(attribute) => match attribute.info(pool)? {
+                AttributeInfo::Code(code) => Ok(Some(code)),
+                _ => Ok(None),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// 可修改的Code属性，写出时将重新编码
+    pub fn code_mut<'a>(&'a mut self, pool: &ConstantPool) -> Result<Option<&'a mut CodeAttribute>> {
+        let attribute = match attribute_info::find_attribute_mut(&mut self.attributes, pool, CODE_TAG) {
+            Some(attribute) => attribute,
+            None => return Ok(None),
+        };
+        match attribute.info_mut(pool)? {
+            AttributeInfo::Code(code) => Ok(Some(code)),
+            _ => Ok(None),
+        }
+    }
+
+    /// 方法上的可见与不可见注解
+    pub fn annotations(&self, pool: &ConstantPool) -> Result<Annotations> {
+        Annotations::read(&self.attributes, pool)
+    }
+
+    pub fn set_annotations(&mut self, pool: &mut ConstantPool, annotations: &Annotations) -> Result<()> {
+        annotations.write(&mut self.attributes, pool)
+    }
+
+    /// 各参数上的可见与不可见注解
+    pub fn parameter_annotations(&self, pool: &ConstantPool) -> Result<ParameterAnnotations> {
+        ParameterAnnotations::read(&self.attributes, pool)
+    }
+
+    pub fn set_parameter_annotations(&mut self, pool: &mut ConstantPool, annotations: &ParameterAnnotations) -> Result<()> {
+        annotations.write(&mut self.attributes, pool)
+    }
+
+    /// 注解类型中元素的默认值，即AnnotationDefault属性
+    pub fn annotation_default(&self, pool: &ConstantPool) -> Result<Option<ResolvedElementValue>> {
+        annotation::read_default(&self.attributes, pool)
+    }
+
+    /// value为None时移除默认值
+    pub fn set_annotation_default(&mut self, pool: &mut ConstantPool, value: Option<&ResolvedElementValue>) -> Result<()> {
+        annotation::write_default(&mut self.attributes, pool, value)
+    }
+
+    /// 名称、描述符及属性中引用的常量索引
+    pub fn constant_indices(&self, pool: &ConstantPool) -> Result<Vec<u16>> {
+        let mut indices = vec![self.name, self.descriptor];
+        for (i, attribute) in self.attributes.iter().enumerate() {
+            indices.extend(attribute.constant_indices(pool).map_err(|e| e.in_path(&format!("attributes[{}]", i)))?);
+        }
+        Ok(indices)
+    }
+
+    /// 改写名称、描述符及属性中的常量索引
+    pub fn remap_constant_indices(&mut self, pool: &ConstantPool, remap: &mut dyn FnMut(u16) -> u16) -> Result<()> {
+        self.name = remap(self.name);
+        self.descriptor = remap(self.descriptor);
+        for (i, attribute) in self.attributes.iter_mut().enumerate() {
+            attribute.remap_constant_indices(pool, remap).map_err(|e| e.in_path(&format!("attributes[{}]", i)))?;
+        }
+        Ok(())
+    }
+
     //noinspection DuplicatedCode
     pub fn byte_size(&self) -> usize {
         let mut attrs_size = 0;
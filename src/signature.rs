@@ -0,0 +1,509 @@
+use crate::attribute_info::{AttributeInfo, OriginAttribute};
+use crate::classfile_constants::{JVM_SIGNATURE_ARRAY, JVM_SIGNATURE_CLASS, JVM_SIGNATURE_DOT, JVM_SIGNATURE_ENDCLASS, JVM_SIGNATURE_ENDFUNC, JVM_SIGNATURE_ENDSPECIAL, JVM_SIGNATURE_FUNC, JVM_SIGNATURE_SLASH, JVM_SIGNATURE_SPECIAL, JVM_SIGNATURE_VOID};
+use crate::common::error::{ErrorKind, MessageError, Result};
+use crate::constant_pool::ConstantPool;
+use crate::descriptor::FieldType;
+use std::fmt::{Display, Formatter};
+
+const CLASS: u8 = JVM_SIGNATURE_CLASS as u8;
+const END_CLASS: u8 = JVM_SIGNATURE_ENDCLASS as u8;
+const ARRAY: u8 = JVM_SIGNATURE_ARRAY as u8;
+const FUNC: u8 = JVM_SIGNATURE_FUNC as u8;
+const END_FUNC: u8 = JVM_SIGNATURE_ENDFUNC as u8;
+const SPECIAL: u8 = JVM_SIGNATURE_SPECIAL as u8;
+const END_SPECIAL: u8 = JVM_SIGNATURE_ENDSPECIAL as u8;
+const DOT: u8 = JVM_SIGNATURE_DOT as u8;
+const SLASH: u8 = JVM_SIGNATURE_SLASH as u8;
+const VOID: u8 = JVM_SIGNATURE_VOID as u8;
+const TYPE_VARIABLE: u8 = b'T';
+const BOUND: u8 = b':';
+const THROWS: u8 = b'^';
+const WILDCARD_ANY: u8 = b'*';
+const WILDCARD_EXTENDS: u8 = b'+';
+const WILDCARD_SUPER: u8 = b'-';
+
+/// JavaTypeSignature
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TypeSignature {
+    // 仅为基本类型
+    Base(FieldType),
+    Class(ClassTypeSignature),
+    // 类型变量名
+    TypeVariable(String),
+    Array(Box<TypeSignature>),
+}
+
+/// 字段签名为引用类型签名
+pub type FieldSignature = TypeSignature;
+
+/// name为最外层类的内部形式类名，inner为依次嵌套的内部类
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ClassTypeSignature {
+    pub name: String,
+    pub type_arguments: Vec<TypeArgument>,
+    pub inner: Vec<SimpleClassTypeSignature>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SimpleClassTypeSignature {
+    pub name: String,
+    pub type_arguments: Vec<TypeArgument>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TypeArgument {
+    // *
+    Any,
+    Exact(TypeSignature),
+    // ? extends
+    Extends(TypeSignature),
+    // ? super
+    Super(TypeSignature),
+}
+
+/// class_bound为None时表示仅有接口上界
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TypeParameter {
+    pub name: String,
+    pub class_bound: Option<TypeSignature>,
+    pub interface_bounds: Vec<TypeSignature>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ClassSignature {
+    pub type_parameters: Vec<TypeParameter>,
+    pub superclass: ClassTypeSignature,
+    pub interfaces: Vec<ClassTypeSignature>,
+}
+
+/// return_type为None表示void
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MethodSignature {
+    pub type_parameters: Vec<TypeParameter>,
+    pub parameters: Vec<TypeSignature>,
+    pub return_type: Option<TypeSignature>,
+    pub throws: Vec<TypeSignature>,
+}
+
+struct SignatureParser<'a> {
+    text: &'a str,
+    bytes: &'a [u8],
+    index: usize,
+}
+
+impl<'a> SignatureParser<'a> {
+    fn new(text: &'a str) -> SignatureParser<'a> {
+        SignatureParser {
+            text,
+            bytes: text.as_bytes(),
+            index: 0,
+        }
+    }
+
+    fn error(&self) -> MessageError {
+        MessageError::of(ErrorKind::InvalidSignature, &format!("无效的泛型签名[{}]", self.text)).at(self.index)
+    }
+
+    #[inline]
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.index).copied()
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<()> {
+        if self.peek() != Some(expected) {
+            return Err(self.error());
+        }
+        self.index += 1;
+        Ok(())
+    }
+
+    fn finish<T>(&self, value: T) -> Result<T> {
+        if self.index != self.bytes.len() {
+            return Err(self.error());
+        }
+        Ok(value)
+    }
+
+    /// 读取到任一结束字符为止，结果不能为空
+    fn read_until(&mut self, ends: &[u8]) -> Result<String> {
+        let start = self.index;
+        while let Some(b) = self.peek() {
+            if ends.contains(&b) {
+                break;
+            }
+            self.index += 1;
+        }
+        if start == self.index {
+            return Err(self.error());
+        }
+        Ok(self.text[start..self.index].to_string())
+    }
+
+    fn identifier(&mut self) -> Result<String> {
+        self.read_until(&[DOT, END_CLASS, ARRAY, SLASH, SPECIAL, END_SPECIAL, BOUND])
+    }
+
+    fn type_parameters(&mut self) -> Result<Vec<TypeParameter>> {
+        let mut parameters = Vec::new();
+        if self.peek() != Some(SPECIAL) {
+            return Ok(parameters);
+        }
+        self.index += 1;
+        loop {
+            let name = self.identifier()?;
+            self.expect(BOUND)?;
+            let class_bound = match self.peek() {
+                Some(BOUND) => None,
+                _ => Some(self.reference_type()?),
+            };
+            let mut interface_bounds = Vec::new();
+            while self.peek() == Some(BOUND) {
+                self.index += 1;
+                interface_bounds.push(self.reference_type()?);
+            }
+            parameters.push(TypeParameter {
+                name,
+                class_bound,
+                interface_bounds,
+            });
+            if self.peek() == Some(END_SPECIAL) {
+                self.index += 1;
+                return Ok(parameters);
+            }
+        }
+    }
+
+    fn type_arguments(&mut self) -> Result<Vec<TypeArgument>> {
+        let mut arguments = Vec::new();
+        if self.peek() != Some(SPECIAL) {
+            return Ok(arguments);
+        }
+        self.index += 1;
+        loop {
+            let argument = match self.peek() {
+                Some(WILDCARD_ANY) => {
+                    self.index += 1;
+                    TypeArgument::Any
+                }
+                Some(WILDCARD_EXTENDS) => {
+                    self.index += 1;
+                    TypeArgument::Extends(self.reference_type()?)
+                }
+                Some(WILDCARD_SUPER) => {
+                    self.index += 1;
+                    TypeArgument::Super(self.reference_type()?)
+                }
+                _ => TypeArgument::Exact(self.reference_type()?),
+            };
+            arguments.push(argument);
+            if self.peek() == Some(END_SPECIAL) {
+                self.index += 1;
+                return Ok(arguments);
+            }
+        }
+    }
+
+    fn class_type(&mut self) -> Result<ClassTypeSignature> {
+        self.expect(CLASS)?;
+        let name = self.read_until(&[DOT, END_CLASS, SPECIAL])?;
+        let type_arguments = self.type_arguments()?;
+        let mut inner = Vec::new();
+        while self.peek() == Some(DOT) {
+            self.index += 1;
+            let name = self.identifier()?;
+            let type_arguments = self.type_arguments()?;
+            inner.push(SimpleClassTypeSignature {
+                name,
+                type_arguments,
+            });
+        }
+        self.expect(END_CLASS)?;
+        Ok(ClassTypeSignature {
+            name,
+            type_arguments,
+            inner,
+        })
+    }
+
+    fn reference_type(&mut self) -> Result<TypeSignature> {
+        match self.peek() {
+            Some(CLASS) => Ok(TypeSignature::Class(self.class_type()?)),
+            Some(TYPE_VARIABLE) => {
+                self.index += 1;
+                let name = self.identifier()?;
+                self.expect(END_CLASS)?;
+                Ok(TypeSignature::TypeVariable(name))
+            }
+            Some(ARRAY) => {
+                self.index += 1;
+                Ok(TypeSignature::Array(Box::new(self.java_type()?)))
+            }
+            _ => Err(self.error()),
+        }
+    }
+
+    fn java_type(&mut self) -> Result<TypeSignature> {
+        match self.peek() {
+            Some(b'B' | b'C' | b'D' | b'F' | b'I' | b'J' | b'S' | b'Z') => {
+                let base = FieldType::parse(&self.text[self.index..self.index + 1])?;
+                self.index += 1;
+                Ok(TypeSignature::Base(base))
+            }
+            _ => self.reference_type(),
+        }
+    }
+}
+
+impl TypeSignature {
+    /// 解析字段签名或任意Java类型签名
+    pub fn parse(signature: &str) -> Result<TypeSignature> {
+        let mut parser = SignatureParser::new(signature);
+        let value = parser.java_type()?;
+        parser.finish(value)
+    }
+
+    /// 通过rename修改签名中的类名，rename参数与返回值均为内部形式类名，返回None表示不修改
+    pub fn rename_classes(&mut self, rename: &mut dyn FnMut(&str) -> Option<String>) {
+        match self {
+            TypeSignature::Base(_) | TypeSignature::TypeVariable(_) => {}
+            TypeSignature::Class(class) => class.rename_classes(rename),
+            TypeSignature::Array(component) => component.rename_classes(rename),
+        }
+    }
+}
+
+impl ClassTypeSignature {
+    pub fn new(name: &str) -> ClassTypeSignature {
+        ClassTypeSignature {
+            name: name.to_string(),
+            type_arguments: Vec::new(),
+            inner: Vec::new(),
+        }
+    }
+
+    /// 擦除泛型后的内部形式类名，内部类以$连接，如java/util/Map$Entry
+    pub fn erased_name(&self) -> String {
+        let mut name = self.name.clone();
+        for inner in &self.inner {
+            name.push('$');
+            name.push_str(&inner.name);
+        }
+        name
+    }
+
+    /// 内部类以外层类名加$的完整类名传入rename，重命名结果以新外层类名加$开头时取其后部分作为内部类名
+    pub fn rename_classes(&mut self, rename: &mut dyn FnMut(&str) -> Option<String>) {
+        let mut old_name = self.name.clone();
+        if let Some(name) = rename(&self.name) {
+            self.name = name;
+        }
+        let mut new_name = self.name.clone();
+        rename_arguments(&mut self.type_arguments, rename);
+        for inner in &mut self.inner {
+            old_name = format!("{}${}", old_name, inner.name);
+            if let Some(name) = rename(&old_name) {
+                inner.name = match name.strip_prefix(&format!("{}$", new_name)) {
+                    Some(simple) => simple.to_string(),
+                    None => name.rsplit(['$', '/']).next().unwrap_or(&name).to_string(),
+                };
+            }
+            new_name = format!("{}${}", new_name, inner.name);
+            rename_arguments(&mut inner.type_arguments, rename);
+        }
+    }
+}
+
+impl ClassSignature {
+    pub fn parse(signature: &str) -> Result<ClassSignature> {
+        let mut parser = SignatureParser::new(signature);
+        let type_parameters = parser.type_parameters()?;
+        let superclass = parser.class_type()?;
+        let mut interfaces = Vec::new();
+        while parser.peek().is_some() {
+            interfaces.push(parser.class_type()?);
+        }
+        parser.finish(ClassSignature {
+            type_parameters,
+            superclass,
+            interfaces,
+        })
+    }
+
+    pub fn rename_classes(&mut self, rename: &mut dyn FnMut(&str) -> Option<String>) {
+        rename_parameters(&mut self.type_parameters, rename);
+        self.superclass.rename_classes(rename);
+        for interface in &mut self.interfaces {
+            interface.rename_classes(rename);
+        }
+    }
+}
+
+impl MethodSignature {
+    pub fn parse(signature: &str) -> Result<MethodSignature> {
+        let mut parser = SignatureParser::new(signature);
+        let type_parameters = parser.type_parameters()?;
+        parser.expect(FUNC)?;
+        let mut parameters = Vec::new();
+        while parser.peek() != Some(END_FUNC) {
+            parameters.push(parser.java_type()?);
+        }
+        parser.index += 1;
+        let return_type = match parser.peek() {
+            Some(VOID) => {
+                parser.index += 1;
+                None
+            }
+            _ => Some(parser.java_type()?),
+        };
+        let mut throws = Vec::new();
+        while parser.peek() == Some(THROWS) {
+            parser.index += 1;
+            let exception = match parser.peek() {
+                Some(CLASS) | Some(TYPE_VARIABLE) => parser.reference_type()?,
+                _ => return Err(parser.error()),
+            };
+            throws.push(exception);
+        }
+        parser.finish(MethodSignature {
+            type_parameters,
+            parameters,
+            return_type,
+            throws,
+        })
+    }
+
+    pub fn rename_classes(&mut self, rename: &mut dyn FnMut(&str) -> Option<String>) {
+        rename_parameters(&mut self.type_parameters, rename);
+        self.parameters.iter_mut()
+            .chain(self.return_type.iter_mut())
+            .chain(self.throws.iter_mut())
+            .for_each(|signature| signature.rename_classes(rename));
+    }
+}
+
+fn rename_arguments(arguments: &mut [TypeArgument], rename: &mut dyn FnMut(&str) -> Option<String>) {
+    for argument in arguments {
+        match argument {
+            TypeArgument::Any => {}
+            TypeArgument::Exact(signature) | TypeArgument::Extends(signature) |
+            TypeArgument::Super(signature) => signature.rename_classes(rename),
+        }
+    }
+}
+
+fn rename_parameters(parameters: &mut [TypeParameter], rename: &mut dyn FnMut(&str) -> Option<String>) {
+    for parameter in parameters {
+        parameter.class_bound.iter_mut()
+            .chain(parameter.interface_bounds.iter_mut())
+            .for_each(|bound| bound.rename_classes(rename));
+    }
+}
+
+/// 查找Signature属性并返回签名字符串
+pub fn find_signature<'a>(attributes: &[OriginAttribute], pool: &'a ConstantPool) -> Result<Option<&'a str>> {
+    for attribute in attributes {
+        if let AttributeInfo::Signature(index) = attribute.info(pool)? {
+            return Ok(Some(pool.utf8(*index)?));
+        }
+    }
+    Ok(None)
+}
+
+impl Display for TypeSignature {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TypeSignature::Base(base) => base.fmt(f),
+            TypeSignature::Class(class) => class.fmt(f),
+            TypeSignature::TypeVariable(name) => write!(f, "{}{}{}", TYPE_VARIABLE as char, name, END_CLASS as char),
+            TypeSignature::Array(component) => write!(f, "{}{}", ARRAY as char, component),
+        }
+    }
+}
+
+impl Display for ClassTypeSignature {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", CLASS as char, self.name)?;
+        write_arguments(f, &self.type_arguments)?;
+        for inner in &self.inner {
+            write!(f, "{}{}", DOT as char, inner.name)?;
+            write_arguments(f, &inner.type_arguments)?;
+        }
+        write!(f, "{}", END_CLASS as char)
+    }
+}
+
+impl Display for TypeArgument {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TypeArgument::Any => write!(f, "{}", WILDCARD_ANY as char),
+            TypeArgument::Exact(signature) => signature.fmt(f),
+            TypeArgument::Extends(signature) => write!(f, "{}{}", WILDCARD_EXTENDS as char, signature),
+            TypeArgument::Super(signature) => write!(f, "{}{}", WILDCARD_SUPER as char, signature),
+        }
+    }
+}
+
+impl Display for TypeParameter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.name, BOUND as char)?;
+        if let Some(bound) = &self.class_bound {
+            bound.fmt(f)?;
+        }
+        for bound in &self.interface_bounds {
+            write!(f, "{}{}", BOUND as char, bound)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for ClassSignature {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write_parameters(f, &self.type_parameters)?;
+        self.superclass.fmt(f)?;
+        for interface in &self.interfaces {
+            interface.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for MethodSignature {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write_parameters(f, &self.type_parameters)?;
+        write!(f, "{}", FUNC as char)?;
+        for parameter in &self.parameters {
+            parameter.fmt(f)?;
+        }
+        write!(f, "{}", END_FUNC as char)?;
+        match &self.return_type {
+            Some(return_type) => return_type.fmt(f)?,
+            None => write!(f, "{}", VOID as char)?,
+        }
+        for exception in &self.throws {
+            write!(f, "{}{}", THROWS as char, exception)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_arguments(f: &mut Formatter<'_>, arguments: &[TypeArgument]) -> std::fmt::Result {
+    if arguments.is_empty() {
+        return Ok(());
+    }
+    write!(f, "{}", SPECIAL as char)?;
+    for argument in arguments {
+        argument.fmt(f)?;
+    }
+    write!(f, "{}", END_SPECIAL as char)
+}
+
+fn write_parameters(f: &mut Formatter<'_>, parameters: &[TypeParameter]) -> std::fmt::Result {
+    if parameters.is_empty() {
+        return Ok(());
+    }
+    write!(f, "{}", SPECIAL as char)?;
+    for parameter in parameters {
+        parameter.fmt(f)?;
+    }
+    write!(f, "{}", END_SPECIAL as char)
+}
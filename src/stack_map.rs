@@ -0,0 +1,1086 @@
+use crate::attribute_info::{AttributeInfo, CodeAttribute, OriginAttribute, StackMapFrame, VerificationTypeInfo};
+use crate::code_analysis::DecodedCode;
+use crate::common::constants::{CODE_TAG, STACK_MAP_TABLE_TAG};
+use crate::common::error::{ErrorKind, MessageError, Result};
+use crate::common::opcode::{array_type, opcodes::*};
+use crate::constant_pool::{ConstantPool, ConstantValue};
+use crate::descriptor::{FieldType, MethodDescriptor};
+use crate::instruction::Instruction;
+use crate::jclass_info::JClassInfo;
+use crate::method_info::MethodInfo;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+pub const OBJECT_CLASS: &str = "java/lang/Object";
+pub const THROWABLE_CLASS: &str = "java/lang/Throwable";
+
+/// 类层次查询，合并不同分支上的引用类型时使用
+///
+/// 类名均为内部形式，数组类名为描述符形式，如[Ljava/lang/String;
+pub trait ClassHierarchy {
+    /// 父类名，java/lang/Object返回None
+    fn super_class(&self, name: &str) -> Option<String>;
+
+    fn is_interface(&self, name: &str) -> bool;
+
+    /// 两个类最近的公共父类，任一为接口时为java/lang/Object
+    fn common_super_class(&self, a: &str, b: &str) -> String {
+        if a == b {
+            return a.to_string();
+        }
+        if self.is_interface(a) || self.is_interface(b) {
+            return OBJECT_CLASS.to_string();
+        }
+        let mut ancestors = HashSet::new();
+        let mut current = Some(a.to_string());
+        while let Some(name) = current {
+            current = self.super_class(&name);
+            ancestors.insert(name);
+        }
+        let mut current = Some(b.to_string());
+        while let Some(name) = current {
+            if ancestors.contains(&name) {
+                return name;
+            }
+            current = self.super_class(&name);
+        }
+        OBJECT_CLASS.to_string()
+    }
+
+    /// source类型的值能否赋给target类型，target为接口时总是可以
+    fn is_assignable(&self, target: &str, source: &str) -> bool {
+        if target == source || target == OBJECT_CLASS || self.is_interface(target) {
+            return true;
+        }
+        let mut current = self.super_class(source);
+        while let Some(name) = current {
+            if name == target {
+                return true;
+            }
+            current = self.super_class(&name);
+        }
+        false
+    }
+}
+
+/// 基于显式登记信息的类层次，未登记的类视为java/lang/Object的直接子类
+#[derive(Debug, Clone, Default)]
+pub struct SimpleClassHierarchy {
+    classes: HashMap<String, (Option<String>, bool)>,
+}
+
+impl SimpleClassHierarchy {
+    pub fn new() -> SimpleClassHierarchy {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, name: &str, super_class: Option<&str>, is_interface: bool) {
+        self.classes.insert(name.to_string(), (super_class.map(str::to_string), is_interface));
+    }
+
+    /// 登记class文件中的类
+    pub fn add_class(&mut self, class: &JClassInfo) -> Result<()> {
+        let name = class.this_class_name()?.to_string();
+        let super_class = class.super_class_name()?.map(str::to_string);
+        let is_interface = class.access().contains(crate::access_flags::ClassAccessFlags::INTERFACE);
+        self.classes.insert(name, (super_class, is_interface));
+        Ok(())
+    }
+}
+
+impl ClassHierarchy for SimpleClassHierarchy {
+    fn super_class(&self, name: &str) -> Option<String> {
+        if name == OBJECT_CLASS {
+            return None;
+        }
+        match self.classes.get(name) {
+            Some((super_class, _)) => super_class.clone(),
+            None => Some(OBJECT_CLASS.to_string()),
+        }
+    }
+
+    fn is_interface(&self, name: &str) -> bool {
+        self.classes.get(name).is_some_and(|(_, is_interface)| *is_interface)
+    }
+}
+
+/// 帧中的值类型，Uninitialized为创建该对象的new指令的pc
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FrameType {
+    Top,
+    Integer,
+    Float,
+    Long,
+    Double,
+    Null,
+    UninitializedThis,
+    Uninitialized(u32),
+    Object(String),
+}
+
+/// 指令执行前的局部变量与操作栈状态
+///
+/// 局部变量按槽存放，long与double之后的槽为Top；操作栈每个值占一项
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Frame {
+    pub locals: Vec<FrameType>,
+    pub stack: Vec<FrameType>,
+}
+
+impl FrameType {
+    pub fn from_field_type(field_type: &FieldType) -> FrameType {
+        match field_type {
+            FieldType::Byte | FieldType::Char | FieldType::Short | FieldType::Boolean | FieldType::Int => FrameType::Integer,
+            FieldType::Float => FrameType::Float,
+            FieldType::Long => FrameType::Long,
+            FieldType::Double => FrameType::Double,
+            FieldType::Object(name) => FrameType::Object(name.clone()),
+            array => FrameType::Object(array.to_string()),
+        }
+    }
+
+    pub fn from_verification_type(info: &VerificationTypeInfo, pool: &ConstantPool) -> Result<FrameType> {
+        Ok(match info {
+            VerificationTypeInfo::Top => FrameType::Top,
+            VerificationTypeInfo::Integer => FrameType::Integer,
+            VerificationTypeInfo::Float => FrameType::Float,
+            VerificationTypeInfo::Double => FrameType::Double,
+            VerificationTypeInfo::Long => FrameType::Long,
+            VerificationTypeInfo::Null => FrameType::Null,
+            VerificationTypeInfo::UninitializedThis => FrameType::UninitializedThis,
+            VerificationTypeInfo::Object(index) => FrameType::Object(pool.class_name(*index)?.to_string()),
+            VerificationTypeInfo::Uninitialized(offset) => FrameType::Uninitialized(*offset as u32),
+        })
+    }
+
+    /// 转为校验类型，类名按需加入常量池
+    pub fn to_verification_type(&self, pool: &mut ConstantPool) -> VerificationTypeInfo {
+        match self {
+            FrameType::Top => VerificationTypeInfo::Top,
+            FrameType::Integer => VerificationTypeInfo::Integer,
+            FrameType::Float => VerificationTypeInfo::Float,
+            FrameType::Long => VerificationTypeInfo::Long,
+            FrameType::Double => VerificationTypeInfo::Double,
+            FrameType::Null => VerificationTypeInfo::Null,
+            FrameType::UninitializedThis => VerificationTypeInfo::UninitializedThis,
+            FrameType::Uninitialized(pc) => VerificationTypeInfo::Uninitialized(*pc as u16),
+            FrameType::Object(name) => VerificationTypeInfo::Object(pool.add_class(name)),
+        }
+    }
+
+    pub fn is_wide(&self) -> bool {
+        matches!(self, FrameType::Long | FrameType::Double)
+    }
+
+    pub fn is_reference(&self) -> bool {
+        matches!(self, FrameType::Null | FrameType::UninitializedThis | FrameType::Uninitialized(_) | FrameType::Object(_))
+    }
+
+    /// source类型的值能否赋给当前类型，Top可接受任意值，未初始化的对象只能赋给相同的类型
+    pub fn is_assignable_from(&self, source: &FrameType, hierarchy: &dyn ClassHierarchy) -> bool {
+        match (self, source) {
+            (FrameType::Top, _) => true,
+            (a, b) if a == b => true,
+            (FrameType::Object(_), FrameType::Null) => true,
+            (FrameType::Object(target), FrameType::Object(source)) => is_class_assignable(target, source, hierarchy),
+            _ => false,
+        }
+    }
+
+    /// 两个类型合并后的类型，不兼容时为Top
+    pub fn merge(&self, other: &FrameType, hierarchy: &dyn ClassHierarchy) -> FrameType {
+        match (self, other) {
+            (a, b) if a == b => a.clone(),
+            (FrameType::Null, FrameType::Object(_)) => other.clone(),
+            (FrameType::Object(_), FrameType::Null) => self.clone(),
+            (FrameType::Object(a), FrameType::Object(b)) => FrameType::Object(merge_class(a, b, hierarchy)),
+            _ => FrameType::Top,
+        }
+    }
+}
+
+impl Frame {
+    /// 方法入口处的帧，构造方法中的this为UninitializedThis
+    pub fn initial(class_name: &str, method_name: &str, descriptor: &MethodDescriptor, is_static: bool) -> Frame {
+        let mut frame = Frame::default();
+        if !is_static {
+            frame.locals.push(if method_name == "<init>" && class_name != OBJECT_CLASS {
+                FrameType::UninitializedThis
+            } else {
+                FrameType::Object(class_name.to_string())
+            });
+        }
+        for parameter in &descriptor.parameters {
+            frame.push_local(FrameType::from_field_type(parameter));
+        }
+        frame
+    }
+
+    /// 转为StackMapTable使用的局部变量与操作栈，long与double只占一项，去除末尾的Top
+    pub fn to_verification_types(&self, pool: &mut ConstantPool) -> (Vec<VerificationTypeInfo>, Vec<VerificationTypeInfo>) {
+        let mut locals = Vec::new();
+        let mut i = 0;
+        while i < self.locals.len() {
+            locals.push(self.locals[i].to_verification_type(pool));
+            i += if self.locals[i].is_wide() { 2 } else { 1 };
+        }
+        while locals.last() == Some(&VerificationTypeInfo::Top) {
+            locals.pop();
+        }
+        let stack = self.stack.iter().map(|value| value.to_verification_type(pool)).collect();
+        (locals, stack)
+    }
+
+    /// 由StackMapTable中的局部变量与操作栈还原
+    pub fn from_verification_types(locals: &[VerificationTypeInfo], stack: &[VerificationTypeInfo], pool: &ConstantPool) -> Result<Frame> {
+        let mut frame = Frame::default();
+        for local in locals {
+            frame.push_local(FrameType::from_verification_type(local, pool)?);
+        }
+        for value in stack {
+            frame.stack.push(FrameType::from_verification_type(value, pool)?);
+        }
+        Ok(frame)
+    }
+
+    fn push_local(&mut self, value: FrameType) {
+        let wide = value.is_wide();
+        self.locals.push(value);
+        if wide {
+            self.locals.push(FrameType::Top);
+        }
+    }
+
+    /// 操作栈占用的槽数
+    pub fn stack_slots(&self) -> usize {
+        self.stack.iter().map(|value| if value.is_wide() { 2 } else { 1 }).sum()
+    }
+
+    /// source帧能否作为当前帧使用，缺少的局部变量视为Top，操作栈须等长
+    pub fn is_assignable_from(&self, source: &Frame, hierarchy: &dyn ClassHierarchy) -> bool {
+        self.stack.len() == source.stack.len()
+            && self.stack.iter().zip(&source.stack).all(|(target, value)| target.is_assignable_from(value, hierarchy))
+            && self.locals.iter().enumerate()
+                .all(|(i, target)| target.is_assignable_from(source.locals.get(i).unwrap_or(&FrameType::Top), hierarchy))
+    }
+
+    /// 将other合并到当前帧，返回是否有变化，操作栈不兼容时返回错误
+    pub fn merge(&mut self, other: &Frame, hierarchy: &dyn ClassHierarchy) -> std::result::Result<bool, String> {
+        if self.stack.len() != other.stack.len() {
+            return Err(format!("操作栈深度不一致[{}, {}]", self.stack.len(), other.stack.len()));
+        }
+        let mut changed = false;
+        for (value, incoming) in self.stack.iter_mut().zip(&other.stack) {
+            let merged = value.merge(incoming, hierarchy);
+            if merged == FrameType::Top {
+                return Err(format!("操作栈类型不一致[{:?}, {:?}]", value, incoming));
+            }
+            if merged != *value {
+                *value = merged;
+                changed = true;
+            }
+        }
+        // 缺少的局部变量视为Top
+        if self.locals.len() > other.locals.len() {
+            changed |= self.locals[other.locals.len()..].iter().any(|value| *value != FrameType::Top);
+            self.locals.truncate(other.locals.len());
+        }
+        for (i, value) in self.locals.iter_mut().enumerate() {
+            let merged = value.merge(&other.locals[i], hierarchy);
+            if merged != *value {
+                *value = merged;
+                changed = true;
+            }
+        }
+        // long或double的前半部分被合并为Top时，后半部分不再有意义
+        for i in 0..self.locals.len() {
+            if self.locals[i].is_wide() && self.locals.get(i + 1) != Some(&FrameType::Top) {
+                self.locals[i] = FrameType::Top;
+                changed = true;
+            }
+        }
+        Ok(changed)
+    }
+}
+
+/// 计算方法中需要栈映射帧的位置及其帧，code可以是修改后的Code属性
+///
+/// 跳转目标、异常处理器入口及无条件跳转之后的指令需要帧，不支持jsr与ret
+pub fn compute_frames(class: &JClassInfo, method: &MethodInfo, code: &CodeAttribute, hierarchy: &dyn ClassHierarchy) -> Result<BTreeMap<u32, Frame>> {
+    let pool = &class.constant_pool;
+    let descriptor = MethodDescriptor::parse(method.get_descriptor(pool)?)?;
+    let is_static = method.access().contains(crate::access_flags::MethodAccessFlags::STATIC);
+    let initial = Frame::initial(class.this_class_name()?, method.get_name(pool)?, &descriptor, is_static);
+    let analyzer = Analyzer::new(code, pool, class.this_class_name()?, hierarchy)?;
+    let frames = analyzer.run(initial)?;
+
+    let mut result = BTreeMap::new();
+    for index in analyzer.frame_indices()? {
+        let pc = analyzer.decoded.instructions[index].0;
+        match &frames[index] {
+            Some(frame) => result.insert(pc, frame.clone()),
+            None => return Err(invalid(pc, "不可达的字节码")),
+        };
+    }
+    Ok(result)
+}
+
+/// 将帧压缩为StackMapTable中的帧，initial为方法入口处的帧
+pub fn encode_frames(initial: &Frame, frames: &BTreeMap<u32, Frame>, pool: &mut ConstantPool) -> Vec<StackMapFrame> {
+    let mut result = Vec::with_capacity(frames.len());
+    let (mut previous_locals, _) = initial.to_verification_types(pool);
+    let mut previous_pc: Option<u32> = None;
+    for (pc, frame) in frames {
+        let offset_delta = match previous_pc {
+            None => *pc as u16,
+            Some(previous) => (pc - previous - 1) as u16,
+        };
+        let (locals, mut stack) = frame.to_verification_types(pool);
+        let encoded = if stack.is_empty() && locals == previous_locals {
+            if offset_delta <= 63 {
+                StackMapFrame::SameFrame { offset_delta }
+            } else {
+                StackMapFrame::SameFrameExtended { offset_delta }
+            }
+        } else if stack.len() == 1 && locals == previous_locals {
+            let stack = stack.pop().unwrap();
+            if offset_delta <= 63 {
+                StackMapFrame::SameLocals1StackItemFrame { offset_delta, stack }
+            } else {
+                StackMapFrame::SameLocals1StackItemFrameExtended { offset_delta, stack }
+            }
+        } else if stack.is_empty() && locals.len() < previous_locals.len() && previous_locals.len() - locals.len() <= 3
+            && previous_locals.starts_with(&locals) {
+            StackMapFrame::ChopFrame { k: (previous_locals.len() - locals.len()) as u8, offset_delta }
+        } else if stack.is_empty() && locals.len() > previous_locals.len() && locals.len() - previous_locals.len() <= 3
+            && locals.starts_with(&previous_locals) {
+            StackMapFrame::AppendFrame { offset_delta, locals: locals[previous_locals.len()..].to_vec() }
+        } else {
+            StackMapFrame::FullFrame { offset_delta, locals: locals.clone(), stack }
+        };
+        result.push(encoded);
+        previous_locals = locals;
+        previous_pc = Some(*pc);
+    }
+    result
+}
+
+/// 将StackMapTable展开为按绝对pc索引的帧，initial为方法入口处的帧
+pub fn expand_frames(initial: &Frame, table: &[StackMapFrame], pool: &ConstantPool) -> Result<BTreeMap<u32, Frame>> {
+    let mut locals = trim_locals(&initial.locals);
+    let mut frames = BTreeMap::new();
+    for (i, (frame, pc)) in table.iter().zip(StackMapFrame::absolute_pcs(table)).enumerate() {
+        let mut stack = Vec::new();
+        match frame {
+            StackMapFrame::SameFrame { .. } | StackMapFrame::SameFrameExtended { .. } => {}
+            StackMapFrame::SameLocals1StackItemFrame { stack: value, .. } |
+            StackMapFrame::SameLocals1StackItemFrameExtended { stack: value, .. } => {
+                stack.push(FrameType::from_verification_type(value, pool)?);
+            }
+            StackMapFrame::ChopFrame { k, .. } => {
+                for _ in 0..*k {
+                    // long与double占两个槽，一并移除
+                    if locals.len() >= 2 && locals[locals.len() - 2].is_wide() {
+                        locals.pop();
+                    }
+                    if locals.pop().is_none() {
+                        let msg = format!("移除的局部变量数{}超出已有数量", k);
+                        return Err(MessageError::of(ErrorKind::InvalidAttribute, &msg).in_path(&format!("[{}]", i)));
+                    }
+                }
+            }
+            StackMapFrame::AppendFrame { locals: appended, .. } => {
+                let mut frame = Frame { locals, stack: Vec::new() };
+                for local in appended {
+                    frame.push_local(FrameType::from_verification_type(local, pool)?);
+                }
+                locals = frame.locals;
+            }
+            StackMapFrame::FullFrame { locals: full, stack: values, .. } => {
+                let frame = Frame::from_verification_types(full, values, pool)?;
+                locals = frame.locals;
+                stack = frame.stack;
+            }
+        }
+        frames.insert(pc, Frame { locals: locals.clone(), stack });
+    }
+    Ok(frames)
+}
+
+/// 去除末尾未使用的局部变量槽，保留long与double的后半部分
+fn trim_locals(locals: &[FrameType]) -> Vec<FrameType> {
+    let mut len = locals.len();
+    while len > 0 && locals[len - 1] == FrameType::Top && !(len >= 2 && locals[len - 2].is_wide()) {
+        len -= 1;
+    }
+    locals[..len].to_vec()
+}
+
+/// 重新生成指定方法Code属性中的StackMapTable，没有需要帧的位置时移除该属性
+pub fn regenerate_stack_map_table(class: &mut JClassInfo, method_index: usize, hierarchy: &dyn ClassHierarchy) -> Result<()> {
+    let method = class.methods.get(method_index)
+        .ok_or_else(|| MessageError::new(&format!("方法下标[{}]越界", method_index)))?;
+    let pool = &class.constant_pool;
+    let (attribute_index, mut code) = match method.attributes.iter().enumerate()
+        .find(|(_, attribute)| pool.utf8(attribute.name).is_ok_and(|name| name == CODE_TAG)) {
+        Some((i, attribute)) => match attribute.info(pool)? {
+            AttributeInfo::Code(code) => (i, code.clone()),
+            _ => return Ok(()),
+        },
+        None => return Ok(()),
+    };
+    let frames = compute_frames(class, method, &code, hierarchy)
+        .map_err(|e| e.in_path(&format!("methods[{}]", method_index)))?;
+    let descriptor = MethodDescriptor::parse(method.get_descriptor(pool)?)?;
+    let is_static = method.access().contains(crate::access_flags::MethodAccessFlags::STATIC);
+    let initial = Frame::initial(class.this_class_name()?, method.get_name(pool)?, &descriptor, is_static);
+
+    let pool = &mut class.constant_pool;
+    code.remove_attribute(pool, STACK_MAP_TABLE_TAG);
+    if !frames.is_empty() {
+        let table = AttributeInfo::StackMapTable(encode_frames(&initial, &frames, pool));
+        let name = pool.add_utf8(STACK_MAP_TABLE_TAG);
+        code.attributes.push(OriginAttribute::new_with_info(name, &table)?);
+    }
+    class.methods[method_index].attributes[attribute_index].set_info(AttributeInfo::Code(code));
+    Ok(())
+}
+
+/// 按指令模拟帧的变化并在控制流合并处合并帧
+pub(crate) struct Analyzer<'a> {
+    pub decoded: DecodedCode,
+    code: &'a CodeAttribute,
+    pool: &'a ConstantPool,
+    class_name: &'a str,
+    hierarchy: &'a dyn ClassHierarchy,
+    /// 是否检查引用类型的赋值关系
+    strict: bool,
+}
+
+impl<'a> Analyzer<'a> {
+    pub fn new(code: &'a CodeAttribute, pool: &'a ConstantPool, class_name: &'a str, hierarchy: &'a dyn ClassHierarchy) -> Result<Analyzer<'a>> {
+        Ok(Analyzer {
+            decoded: DecodedCode::new(&code.codes)?,
+            code,
+            pool,
+            class_name,
+            hierarchy,
+            strict: false,
+        })
+    }
+
+    /// 检查方法参数、字段值、接收者及athrow操作数的赋值关系
+    pub fn strict(mut self) -> Analyzer<'a> {
+        self.strict = true;
+        self
+    }
+
+    /// 需要栈映射帧的指令下标，按pc排序
+    pub fn frame_indices(&self) -> Result<Vec<usize>> {
+        let mut indices = HashSet::new();
+        for entry in &self.code.exceptions.entries {
+            indices.insert(self.decoded.index_of(entry.handler_pc as u32, entry.handler_pc as u32)?);
+        }
+        for (index, (pc, instruction)) in self.decoded.instructions.iter().enumerate() {
+            for target in instruction.branch_targets() {
+                indices.insert(self.decoded.index_of(target, *pc)?);
+            }
+            if ends_block(instruction.opcode()) && index + 1 < self.decoded.instructions.len() {
+                indices.insert(index + 1);
+            }
+        }
+        let mut indices: Vec<_> = indices.into_iter().collect();
+        indices.sort();
+        Ok(indices)
+    }
+
+    /// 数据流分析，返回每条指令执行前的帧，不可达的指令为None
+    pub fn run(&self, initial: Frame) -> Result<Vec<Option<Frame>>> {
+        let count = self.decoded.instructions.len();
+        let mut frames: Vec<Option<Frame>> = vec![None; count];
+        if count == 0 {
+            return Ok(frames);
+        }
+        let mut handlers = Vec::with_capacity(self.code.exceptions.entries.len());
+        for entry in &self.code.exceptions.entries {
+            if entry.start_pc >= entry.end_pc || entry.end_pc as usize > self.code.codes.len() {
+                return Err(invalid(entry.start_pc as u32, &format!("异常处理范围无效[{}, {})", entry.start_pc, entry.end_pc)));
+            }
+            let catch_type = match entry.catch_type {
+                0 => THROWABLE_CLASS.to_string(),
+                index => self.pool.class_name(index)?.to_string(),
+            };
+            let handler = self.decoded.index_of(entry.handler_pc as u32, entry.handler_pc as u32)?;
+            handlers.push((entry.start_pc as u32, entry.end_pc as u32, handler, catch_type));
+        }
+
+        frames[0] = Some(initial);
+        let mut pending = vec![0];
+        let mut queued = vec![false; count];
+        queued[0] = true;
+        while let Some(index) = pending.pop() {
+            queued[index] = false;
+            let frame = frames[index].clone().unwrap();
+            let (pc, instruction) = &self.decoded.instructions[index];
+            let mut successors = Vec::new();
+            let mut after = frame.clone();
+            self.execute(&mut after, *pc, instruction)
+                .map_err(|e| e.at(*pc as usize))?;
+            // 指令执行前后都可能抛出异常，处理器入口需合并两者的局部变量
+            for (start, end, handler, catch_type) in &handlers {
+                if (*start..*end).contains(pc) {
+                    for locals in [&frame.locals, &after.locals] {
+                        let handler_frame = Frame {
+                            locals: locals.clone(),
+                            stack: vec![FrameType::Object(catch_type.clone())],
+                        };
+                        successors.push((*handler, handler_frame));
+                    }
+                }
+            }
+            for target in instruction.branch_targets() {
+                successors.push((self.decoded.index_of(target, *pc)?, after.clone()));
+            }
+            if !ends_block(instruction.opcode()) {
+                successors.push((self.decoded.next_index(index)?, after));
+            }
+            for (successor, incoming) in successors {
+                let changed = match &mut frames[successor] {
+                    Some(existing) => existing.merge(&incoming, self.hierarchy)
+                        .map_err(|msg| invalid(self.decoded.instructions[successor].0, &msg))?,
+                    slot => {
+                        *slot = Some(incoming);
+                        true
+                    }
+                };
+                if changed && !queued[successor] {
+                    queued[successor] = true;
+                    pending.push(successor);
+                }
+            }
+        }
+        Ok(frames)
+    }
+
+    /// 模拟执行一条指令，frame由执行前变为执行后
+    pub fn execute(&self, frame: &mut Frame, pc: u32, instruction: &Instruction) -> Result<()> {
+        let opcode = instruction.opcode();
+        match instruction {
+            Instruction::Simple(_) => self.execute_simple(frame, opcode)?,
+            Instruction::Bipush(_) | Instruction::Sipush(_) => frame.stack.push(FrameType::Integer),
+            Instruction::Ldc(index) | Instruction::LdcW(index) | Instruction::Ldc2W(index) => {
+                let value = match self.pool.get(*index)? {
+                    ConstantValue::ConstantInteger(_) => FrameType::Integer,
+                    ConstantValue::ConstantFloat(_) => FrameType::Float,
+                    ConstantValue::ConstantLong(_) => FrameType::Long,
+                    ConstantValue::ConstantDouble(_) => FrameType::Double,
+                    ConstantValue::ConstantString(_) => FrameType::Object("java/lang/String".to_string()),
+                    ConstantValue::ConstantClass(_) => FrameType::Object("java/lang/Class".to_string()),
+                    ConstantValue::ConstantMethodType(_) => FrameType::Object("java/lang/invoke/MethodType".to_string()),
+                    ConstantValue::ConstantMethodHandle(_, _) => FrameType::Object("java/lang/invoke/MethodHandle".to_string()),
+                    ConstantValue::ConstantDynamic(_, name_and_type) => {
+                        FrameType::from_field_type(&FieldType::parse(self.pool.name_and_type(*name_and_type)?.1)?)
+                    }
+                    value => return Err(error(&format!("ldc不能加载{}常量", value.kind_name()))),
+                };
+                if value.is_wide() != (opcode == LDC2_W) {
+                    return Err(error("ldc与ldc_w只能加载单槽常量，ldc2_w只能加载long或double"));
+                }
+                frame.stack.push(value);
+            }
+            Instruction::Local(_, index) => self.execute_local(frame, opcode, *index as u16)?,
+            Instruction::WideLocal(opcode, index) => self.execute_local(frame, *opcode, *index)?,
+            Instruction::Iinc(index, _) => expect_local(frame, *index as u16, &FrameType::Integer)?,
+            Instruction::WideIinc(index, _) => expect_local(frame, *index, &FrameType::Integer)?,
+            Instruction::Branch(opcode, _) | Instruction::BranchW(opcode, _) => match *opcode {
+                IFEQ..=IFLE => pop_expect(frame, &FrameType::Integer)?,
+                IF_ICMPEQ..=IF_ICMPLE => {
+                    pop_expect(frame, &FrameType::Integer)?;
+                    pop_expect(frame, &FrameType::Integer)?;
+                }
+                IF_ACMPEQ | IF_ACMPNE => {
+                    pop_reference(frame)?;
+                    pop_reference(frame)?;
+                }
+                IFNULL | IFNONNULL => {
+                    pop_reference(frame)?;
+                }
+                GOTO | GOTO_W => {}
+                _ => return Err(unsupported_subroutine()),
+            },
+            Instruction::TableSwitch { .. } | Instruction::LookupSwitch { .. } => pop_expect(frame, &FrameType::Integer)?,
+            Instruction::Field(opcode, index) => {
+                let member = self.pool.member_ref(*index)?;
+                let value = FrameType::from_field_type(&FieldType::parse(member.descriptor)?);
+                match *opcode {
+                    GETSTATIC => frame.stack.push(value),
+                    PUTSTATIC => self.pop_assignable(frame, &value)?,
+                    GETFIELD => {
+                        self.pop_object(frame, member.owner)?;
+                        frame.stack.push(value);
+                    }
+                    _ => {
+                        self.pop_assignable(frame, &value)?;
+                        // 构造方法中可以在调用父类构造方法前给本类字段赋值
+                        if frame.stack.last() == Some(&FrameType::UninitializedThis) && member.owner == self.class_name {
+                            frame.stack.pop();
+                        } else {
+                            self.pop_object(frame, member.owner)?;
+                        }
+                    }
+                }
+            }
+            Instruction::Invoke(_, index) | Instruction::InvokeInterface(index, _) => {
+                let member = self.pool.member_ref(*index)?;
+                let descriptor = MethodDescriptor::parse(member.descriptor)?;
+                self.pop_arguments(frame, &descriptor)?;
+                if opcode != INVOKESTATIC {
+                    if member.name == "<init>" {
+                        let receiver = pop_reference(frame)?;
+                        let initialized = match &receiver {
+                            FrameType::UninitializedThis => FrameType::Object(self.class_name.to_string()),
+                            FrameType::Uninitialized(_) => FrameType::Object(member.owner.to_string()),
+                            _ => return Err(error("<init>的接收者已初始化")),
+                        };
+                        for value in frame.locals.iter_mut().chain(frame.stack.iter_mut()) {
+                            if *value == receiver {
+                                *value = initialized.clone();
+                            }
+                        }
+                    } else if opcode == INVOKEINTERFACE {
+                        // 接口类型按java/lang/Object校验
+                        self.pop_object(frame, OBJECT_CLASS)?;
+                    } else {
+                        self.pop_object(frame, member.owner)?;
+                    }
+                }
+                push_return(frame, &descriptor);
+            }
+            Instruction::InvokeDynamic(index) => {
+                let name_and_type = match self.pool.get(*index)? {
+                    ConstantValue::ConstantInvokeDynamic(_, name_and_type) => *name_and_type,
+                    value => return Err(MessageError::of(ErrorKind::ConstantMismatch, &format!("常量[{}]应为InvokeDynamic，实际为{}", index, value.kind_name()))),
+                };
+                let descriptor = MethodDescriptor::parse(self.pool.name_and_type(name_and_type)?.1)?;
+                self.pop_arguments(frame, &descriptor)?;
+                push_return(frame, &descriptor);
+            }
+            Instruction::Type(opcode, index) => {
+                let name = self.pool.class_name(*index)?;
+                match *opcode {
+                    NEW => frame.stack.push(FrameType::Uninitialized(pc)),
+                    ANEWARRAY => {
+                        pop_expect(frame, &FrameType::Integer)?;
+                        let component = if name.starts_with('[') { name.to_string() } else { format!("L{};", name) };
+                        frame.stack.push(FrameType::Object(format!("[{}", component)));
+                    }
+                    CHECKCAST => {
+                        pop_reference(frame)?;
+                        frame.stack.push(FrameType::Object(name.to_string()));
+                    }
+                    _ => {
+                        pop_reference(frame)?;
+                        frame.stack.push(FrameType::Integer);
+                    }
+                }
+            }
+            Instruction::NewArray(atype) => {
+                pop_expect(frame, &FrameType::Integer)?;
+                let name = match *atype {
+                    array_type::T_BOOLEAN => "[Z",
+                    array_type::T_CHAR => "[C",
+                    array_type::T_FLOAT => "[F",
+                    array_type::T_DOUBLE => "[D",
+                    array_type::T_BYTE => "[B",
+                    array_type::T_SHORT => "[S",
+                    array_type::T_INT => "[I",
+                    array_type::T_LONG => "[J",
+                    _ => return Err(error(&format!("无效的数组类型[{}]", atype))),
+                };
+                frame.stack.push(FrameType::Object(name.to_string()));
+            }
+            Instruction::MultiANewArray(index, dimensions) => {
+                let name = self.pool.class_name(*index)?;
+                if *dimensions == 0 || name.bytes().take_while(|b| *b == b'[').count() < *dimensions as usize {
+                    return Err(error(&format!("multianewarray维数[{}]无效", dimensions)));
+                }
+                for _ in 0..*dimensions {
+                    pop_expect(frame, &FrameType::Integer)?;
+                }
+                frame.stack.push(FrameType::Object(name.to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    fn execute_local(&self, frame: &mut Frame, opcode: u8, index: u16) -> Result<()> {
+        match opcode {
+            ILOAD => load(frame, index, FrameType::Integer),
+            LLOAD => load(frame, index, FrameType::Long),
+            FLOAD => load(frame, index, FrameType::Float),
+            DLOAD => load(frame, index, FrameType::Double),
+            ALOAD => {
+                let value = frame.locals.get(index as usize).cloned().unwrap_or(FrameType::Top);
+                if !value.is_reference() {
+                    return Err(error(&format!("局部变量[{}]不是引用类型: {:?}", index, value)));
+                }
+                frame.stack.push(value);
+                Ok(())
+            }
+            ISTORE => store(frame, index, Some(FrameType::Integer)),
+            LSTORE => store(frame, index, Some(FrameType::Long)),
+            FSTORE => store(frame, index, Some(FrameType::Float)),
+            DSTORE => store(frame, index, Some(FrameType::Double)),
+            ASTORE => store(frame, index, None),
+            _ => Err(unsupported_subroutine()),
+        }
+    }
+
+    fn execute_simple(&self, frame: &mut Frame, opcode: u8) -> Result<()> {
+        const INTEGER: FrameType = FrameType::Integer;
+        const FLOAT: FrameType = FrameType::Float;
+        const LONG: FrameType = FrameType::Long;
+        const DOUBLE: FrameType = FrameType::Double;
+        // 按i、l、f、d顺序排列的运算指令
+        const TYPES: [FrameType; 4] = [INTEGER, LONG, FLOAT, DOUBLE];
+        match opcode {
+            NOP => {}
+            ACONST_NULL => frame.stack.push(FrameType::Null),
+            ICONST_M1..=ICONST_5 => frame.stack.push(INTEGER),
+            LCONST_0 | LCONST_1 => frame.stack.push(LONG),
+            FCONST_0..=FCONST_2 => frame.stack.push(FLOAT),
+            DCONST_0 | DCONST_1 => frame.stack.push(DOUBLE),
+            ILOAD_0..=ALOAD_3 => {
+                let offset = opcode - ILOAD_0;
+                self.execute_local(frame, [ILOAD, LLOAD, FLOAD, DLOAD, ALOAD][offset as usize / 4], (offset % 4) as u16)?;
+            }
+            ISTORE_0..=ASTORE_3 => {
+                let offset = opcode - ISTORE_0;
+                self.execute_local(frame, [ISTORE, LSTORE, FSTORE, DSTORE, ASTORE][offset as usize / 4], (offset % 4) as u16)?;
+            }
+            IALOAD..=SALOAD => {
+                pop_expect(frame, &INTEGER)?;
+                let array = pop_reference(frame)?;
+                let value = match opcode {
+                    IALOAD | BALOAD | CALOAD | SALOAD => INTEGER,
+                    LALOAD => LONG,
+                    FALOAD => FLOAT,
+                    DALOAD => DOUBLE,
+                    _ => match array {
+                        FrameType::Null => FrameType::Null,
+                        FrameType::Object(name) if name.starts_with("[[") => FrameType::Object(name[1..].to_string()),
+                        FrameType::Object(name) if name.starts_with("[L") => FrameType::Object(name[2..name.len() - 1].to_string()),
+                        value => return Err(error(&format!("aaload的操作数不是引用数组: {:?}", value))),
+                    },
+                };
+                frame.stack.push(value);
+            }
+            IASTORE..=SASTORE => {
+                match opcode {
+                    LASTORE => pop_expect(frame, &LONG)?,
+                    FASTORE => pop_expect(frame, &FLOAT)?,
+                    DASTORE => pop_expect(frame, &DOUBLE)?,
+                    AASTORE => {
+                        pop_reference(frame)?;
+                    }
+                    _ => pop_expect(frame, &INTEGER)?,
+                }
+                pop_expect(frame, &INTEGER)?;
+                pop_reference(frame)?;
+            }
+            POP => {
+                pop_category(frame, 1)?;
+            }
+            POP2 => {
+                if !pop_any(frame)?.is_wide() {
+                    pop_category(frame, 1)?;
+                }
+            }
+            DUP => {
+                let value = pop_category(frame, 1)?;
+                frame.stack.extend([value.clone(), value]);
+            }
+            DUP_X1 => {
+                let value1 = pop_category(frame, 1)?;
+                let value2 = pop_category(frame, 1)?;
+                frame.stack.extend([value1.clone(), value2, value1]);
+            }
+            DUP_X2 => {
+                let value1 = pop_category(frame, 1)?;
+                let value2 = pop_any(frame)?;
+                if value2.is_wide() {
+                    frame.stack.extend([value1.clone(), value2, value1]);
+                } else {
+                    let value3 = pop_category(frame, 1)?;
+                    frame.stack.extend([value1.clone(), value3, value2, value1]);
+                }
+            }
+            DUP2 => {
+                let value1 = pop_any(frame)?;
+                if value1.is_wide() {
+                    frame.stack.extend([value1.clone(), value1]);
+                } else {
+                    let value2 = pop_category(frame, 1)?;
+                    frame.stack.extend([value2.clone(), value1.clone(), value2, value1]);
+                }
+            }
+            DUP2_X1 => {
+                let value1 = pop_any(frame)?;
+                if value1.is_wide() {
+                    let value2 = pop_category(frame, 1)?;
+                    frame.stack.extend([value1.clone(), value2, value1]);
+                } else {
+                    let value2 = pop_category(frame, 1)?;
+                    let value3 = pop_category(frame, 1)?;
+                    frame.stack.extend([value2.clone(), value1.clone(), value3, value2, value1]);
+                }
+            }
+            DUP2_X2 => {
+                let value1 = pop_any(frame)?;
+                let mut upper = vec![value1.clone()];
+                if !value1.is_wide() {
+                    upper.insert(0, pop_category(frame, 1)?);
+                }
+                let value = pop_any(frame)?;
+                let mut lower = vec![value.clone()];
+                if !value.is_wide() {
+                    lower.insert(0, pop_category(frame, 1)?);
+                }
+                frame.stack.extend(upper.iter().cloned());
+                frame.stack.extend(lower);
+                frame.stack.extend(upper);
+            }
+            SWAP => {
+                let value1 = pop_category(frame, 1)?;
+                let value2 = pop_category(frame, 1)?;
+                frame.stack.extend([value1, value2]);
+            }
+            IADD..=DREM => {
+                let value = &TYPES[(opcode - IADD) as usize % 4];
+                pop_expect(frame, value)?;
+                pop_expect(frame, value)?;
+                frame.stack.push(value.clone());
+            }
+            INEG..=DNEG => {
+                let value = &TYPES[(opcode - INEG) as usize];
+                pop_expect(frame, value)?;
+                frame.stack.push(value.clone());
+            }
+            ISHL..=LUSHR => {
+                let value = &TYPES[(opcode - ISHL) as usize % 2];
+                pop_expect(frame, &INTEGER)?;
+                pop_expect(frame, value)?;
+                frame.stack.push(value.clone());
+            }
+            IAND..=LXOR => {
+                let value = &TYPES[(opcode - IAND) as usize % 2];
+                pop_expect(frame, value)?;
+                pop_expect(frame, value)?;
+                frame.stack.push(value.clone());
+            }
+            I2L..=I2S => {
+                let (from, to) = match opcode {
+                    I2L => (INTEGER, LONG),
+                    I2F => (INTEGER, FLOAT),
+                    I2D => (INTEGER, DOUBLE),
+                    L2I => (LONG, INTEGER),
+                    L2F => (LONG, FLOAT),
+                    L2D => (LONG, DOUBLE),
+                    F2I => (FLOAT, INTEGER),
+                    F2L => (FLOAT, LONG),
+                    F2D => (FLOAT, DOUBLE),
+                    D2I => (DOUBLE, INTEGER),
+                    D2L => (DOUBLE, LONG),
+                    D2F => (DOUBLE, FLOAT),
+                    _ => (INTEGER, INTEGER),
+                };
+                pop_expect(frame, &from)?;
+                frame.stack.push(to);
+            }
+            LCMP..=DCMPG => {
+                let value = match opcode {
+                    LCMP => LONG,
+                    FCMPL | FCMPG => FLOAT,
+                    _ => DOUBLE,
+                };
+                pop_expect(frame, &value)?;
+                pop_expect(frame, &value)?;
+                frame.stack.push(INTEGER);
+            }
+            IRETURN..=ARETURN => {
+                if opcode == ARETURN {
+                    pop_reference(frame)?;
+                } else {
+                    pop_expect(frame, &TYPES[(opcode - IRETURN) as usize])?;
+                }
+            }
+            RETURN => {}
+            ARRAYLENGTH => {
+                pop_reference(frame)?;
+                frame.stack.push(INTEGER);
+            }
+            ATHROW => self.pop_object(frame, THROWABLE_CLASS)?,
+            MONITORENTER | MONITOREXIT => {
+                pop_reference(frame)?;
+            }
+            _ => return Err(error(&format!("无效的操作码[{}]", opcode))),
+        }
+        Ok(())
+    }
+
+    /// 弹出已初始化的对象，class_name为期望的类型，非严格模式下不检查赋值关系
+    fn pop_object(&self, frame: &mut Frame, class_name: &str) -> Result<()> {
+        match pop_reference(frame)? {
+            FrameType::Null => Ok(()),
+            FrameType::Object(name) if !self.strict || is_class_assignable(class_name, &name, self.hierarchy) => Ok(()),
+            value => Err(error(&format!("操作栈顶应为已初始化的{}，实际为{:?}", class_name, value))),
+        }
+    }
+
+    fn pop_assignable(&self, frame: &mut Frame, expected: &FrameType) -> Result<()> {
+        match expected {
+            FrameType::Object(name) => self.pop_object(frame, name),
+            _ => pop_expect(frame, expected),
+        }
+    }
+
+    fn pop_arguments(&self, frame: &mut Frame, descriptor: &MethodDescriptor) -> Result<()> {
+        for parameter in descriptor.parameters.iter().rev() {
+            self.pop_assignable(frame, &FrameType::from_field_type(parameter))?;
+        }
+        Ok(())
+    }
+}
+
+/// 该指令之后的指令不会顺序执行到
+#[inline]
+pub(crate) fn ends_block(opcode: u8) -> bool {
+    matches!(opcode, GOTO | GOTO_W | RET | TABLESWITCH | LOOKUPSWITCH | IRETURN..=RETURN | ATHROW)
+}
+
+fn merge_class(a: &str, b: &str, hierarchy: &dyn ClassHierarchy) -> String {
+    match (a.strip_prefix('['), b.strip_prefix('[')) {
+        (Some(a), Some(b)) => {
+            match (element_class(a), element_class(b)) {
+                (Some(a), Some(b)) => {
+                    let merged = merge_class(&a, &b, hierarchy);
+                    if merged.starts_with('[') { format!("[{}", merged) } else { format!("[L{};", merged) }
+                }
+                _ => OBJECT_CLASS.to_string(),
+            }
+        }
+        (None, None) => hierarchy.common_super_class(a, b),
+        _ => OBJECT_CLASS.to_string(),
+    }
+}
+
+/// 按类名判断赋值关系，数组可赋给java/lang/Cloneable与java/io/Serializable
+fn is_class_assignable(target: &str, source: &str, hierarchy: &dyn ClassHierarchy) -> bool {
+    match (target.strip_prefix('['), source.strip_prefix('[')) {
+        (Some(target), Some(source)) => {
+            match (element_class(target), element_class(source)) {
+                (Some(target), Some(source)) => is_class_assignable(&target, &source, hierarchy),
+                _ => target == source,
+            }
+        }
+        (None, Some(_)) => matches!(target, OBJECT_CLASS | "java/lang/Cloneable" | "java/io/Serializable"),
+        (Some(_), None) => false,
+        (None, None) => hierarchy.is_assignable(target, source),
+    }
+}
+
+/// 数组元素的类名，name为去掉一层[后的描述符，基本类型返回None
+fn element_class(name: &str) -> Option<String> {
+    if let Some(class) = name.strip_prefix('L') {
+        Some(class.trim_end_matches(';').to_string())
+    } else if name.starts_with('[') {
+        Some(name.to_string())
+    } else {
+        None
+    }
+}
+
+fn load(frame: &mut Frame, index: u16, value: FrameType) -> Result<()> {
+    expect_local(frame, index, &value)?;
+    frame.stack.push(value);
+    Ok(())
+}
+
+fn expect_local(frame: &Frame, index: u16, value: &FrameType) -> Result<()> {
+    match frame.locals.get(index as usize) {
+        Some(local) if local == value => Ok(()),
+        local => Err(error(&format!("局部变量[{}]应为{:?}，实际为{:?}", index, value, local.unwrap_or(&FrameType::Top)))),
+    }
+}
+
+fn store(frame: &mut Frame, index: u16, expected: Option<FrameType>) -> Result<()> {
+    let value = match expected {
+        Some(expected) => {
+            pop_expect(frame, &expected)?;
+            expected
+        }
+        None => pop_reference(frame)?,
+    };
+    let index = index as usize;
+    let end = index + if value.is_wide() { 2 } else { 1 };
+    if frame.locals.len() < end {
+        frame.locals.resize(end, FrameType::Top);
+    }
+    // 覆盖long或double的后半部分时前半部分失效
+    if index > 0 && frame.locals[index - 1].is_wide() {
+        frame.locals[index - 1] = FrameType::Top;
+    }
+    if value.is_wide() {
+        frame.locals[index + 1] = FrameType::Top;
+    }
+    frame.locals[index] = value;
+    Ok(())
+}
+
+fn pop_any(frame: &mut Frame) -> Result<FrameType> {
+    frame.stack.pop().ok_or_else(|| error("操作栈下溢"))
+}
+
+fn pop_category(frame: &mut Frame, category: u8) -> Result<FrameType> {
+    let value = pop_any(frame)?;
+    if value.is_wide() != (category == 2) {
+        return Err(error(&format!("操作栈顶的值类别不符: {:?}", value)));
+    }
+    Ok(value)
+}
+
+fn pop_expect(frame: &mut Frame, expected: &FrameType) -> Result<()> {
+    let value = pop_any(frame)?;
+    if value != *expected {
+        return Err(error(&format!("操作栈顶应为{:?}，实际为{:?}", expected, value)));
+    }
+    Ok(())
+}
+
+fn pop_reference(frame: &mut Frame) -> Result<FrameType> {
+    let value = pop_any(frame)?;
+    if !value.is_reference() {
+        return Err(error(&format!("操作栈顶应为引用类型，实际为{:?}", value)));
+    }
+    Ok(value)
+}
+
+fn push_return(frame: &mut Frame, descriptor: &MethodDescriptor) {
+    if let Some(return_type) = &descriptor.return_type {
+        frame.stack.push(FrameType::from_field_type(return_type));
+    }
+}
+
+#[inline]
+fn error(msg: &str) -> MessageError {
+    MessageError::of(ErrorKind::InvalidBytecode, msg)
+}
+
+/// 旧版本中的jsr与ret合法，但帧推导不支持子程序
+fn unsupported_subroutine() -> MessageError {
+    MessageError::of(ErrorKind::UnsupportedInstruction, "不支持jsr子程序")
+}
+
+#[inline]
+fn invalid(pc: u32, msg: &str) -> MessageError {
+    error(msg).at(pc as usize)
+}
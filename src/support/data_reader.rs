@@ -1,9 +1,16 @@
-use crate::common::error::{MessageError, Result};
-use std::io::{Read, Write};
+use crate::common::error::{ErrorKind, MessageError, Result};
+use crate::common::options::ParseOptions;
+use std::io::{Cursor, Read, Write};
 use std::ops::{Deref, DerefMut};
 use crate::with_message;
 
-pub struct DataReader<T: Read> (T);
+/// position为已读取的字节数，用于在错误中记录偏移，len为已知的输入总长度
+pub struct DataReader<T: Read> {
+    inner: T,
+    position: usize,
+    len: Option<usize>,
+    options: ParseOptions,
+}
 pub struct DataWriter<T: Write> (T);
 
 pub trait ReadToType<T:Sized> {
@@ -17,16 +24,63 @@ pub trait WriteFromType<T:Sized> {
 impl<T: Read> DataReader<T> {
     #[inline]
     pub fn read_bytes(&mut self, name: &str, bytes: &mut [u8]) -> Result<()> {
-        with_message!(self.read_exact(bytes), &format!("{name}读取出错"))
+        let position = self.position;
+        if position.saturating_add(bytes.len()) > self.options.max_input_size {
+            let msg = format!("{name}读取出错: 输入超出限制{}", self.options.max_input_size);
+            return Err(MessageError::of(ErrorKind::LimitExceeded, &msg).at(position));
+        }
+        with_message!(self.inner.read_exact(bytes), &format!("{name}读取出错")).map_err(|e| e.at(position))?;
+        self.position += bytes.len();
+        Ok(())
+    }
+
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn with_options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// 设置已知的输入总长度，用于在分配前检查长度
+    pub fn with_len(mut self, len: usize) -> Self {
+        self.len = Some(len);
+        self
+    }
+
+    #[inline]
+    pub fn options(&self) -> &ParseOptions {
+        &self.options
+    }
+
+    /// 剩余的输入长度，输入总长度未知时返回None
+    #[inline]
+    pub fn remaining(&self) -> Option<usize> {
+        self.len.map(|len| len.saturating_sub(self.position))
+    }
+
+    /// 分配缓冲区前检查长度，超出max时返回LimitExceeded，超出剩余输入时返回UnexpectedEof
+    pub fn check_len(&self, name: &str, len: usize, max: usize) -> Result<()> {
+        if len > max {
+            let msg = format!("{name}长度{len}超出限制{max}");
+            return Err(MessageError::of(ErrorKind::LimitExceeded, &msg).at(self.position));
+        }
+        match self.remaining() {
+            Some(remaining) if len > remaining => {
+                let msg = format!("{name}长度{len}超出剩余输入{remaining}");
+                Err(MessageError::of(ErrorKind::UnexpectedEof, &msg).at(self.position))
+            }
+            _ => Ok(()),
+        }
     }
     #[inline]
     pub fn read_bytes_with_pre_size(&mut self, name: &str) -> Result<Vec<u8>> {
         let str_len: u16 = self.read_to(name)?;
         let str_len = str_len as usize;
-        let mut buf = Vec::with_capacity(str_len);
-        unsafe {
-            buf.set_len(str_len);
-        }
+        self.check_len(name, str_len, usize::MAX)?;
+        let mut buf = vec![0; str_len];
         self.read_bytes(name, &mut buf)?;
         Ok(buf)
     }
@@ -34,7 +88,19 @@ impl<T: Read> DataReader<T> {
 
 impl<T: Read> From<T> for DataReader<T> {
     fn from(value: T) -> Self {
-        DataReader (value)
+        DataReader {
+            inner: value,
+            position: 0,
+            len: None,
+            options: ParseOptions::default(),
+        }
+    }
+}
+
+impl<'a> DataReader<Cursor<&'a [u8]>> {
+    /// 从内存数据读取，输入总长度已知
+    pub fn from_bytes(data: &'a [u8]) -> Self {
+        DataReader::from(Cursor::new(data)).with_len(data.len())
     }
 }
 
@@ -42,13 +108,13 @@ impl<T: Read> Deref for DataReader<T> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.inner
     }
 }
 
 impl<T: Read> DerefMut for DataReader<T> {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+        &mut self.inner
     }
 }
 
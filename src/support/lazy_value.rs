@@ -0,0 +1,64 @@
+use std::sync::OnceLock;
+
+/// 首次访问时才加载的值，修改后标记为已变更
+#[derive(Debug, Clone)]
+pub struct LazyValue<T> {
+    value: OnceLock<T>,
+    modified: bool,
+}
+
+impl<T> LazyValue<T> {
+    pub fn new() -> LazyValue<T> {
+        LazyValue {
+            value: OnceLock::new(),
+            modified: false,
+        }
+    }
+
+    #[inline]
+    pub fn get(&self) -> Option<&T> {
+        self.value.get()
+    }
+
+    pub fn get_or_try_load<E>(&self, load: impl FnOnce() -> Result<T, E>) -> Result<&T, E> {
+        if let Some(value) = self.value.get() {
+            return Ok(value);
+        }
+        let value = load()?;
+        Ok(self.value.get_or_init(|| value))
+    }
+
+    /// 获取可变引用，并标记为已变更
+    pub fn get_mut_or_try_load<E>(&mut self, load: impl FnOnce() -> Result<T, E>) -> Result<&mut T, E> {
+        self.get_or_try_load(load)?;
+        self.modified = true;
+        Ok(self.value.get_mut().unwrap())
+    }
+
+    pub fn set(&mut self, value: T) {
+        self.value = OnceLock::from(value);
+        self.modified = true;
+    }
+
+    #[inline]
+    pub fn is_modified(&self) -> bool {
+        self.modified
+    }
+
+    /// 变更已同步回原始数据
+    #[inline]
+    pub fn mark_synced(&mut self) {
+        self.modified = false;
+    }
+
+    pub fn reset(&mut self) {
+        self.value = OnceLock::new();
+        self.modified = false;
+    }
+}
+
+impl<T> Default for LazyValue<T> {
+    fn default() -> Self {
+        LazyValue::new()
+    }
+}
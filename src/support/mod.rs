@@ -1 +1,2 @@
-pub mod data_reader;
\ No newline at end of file
+pub mod data_reader;
+pub mod lazy_value;
\ No newline at end of file
@@ -1,5 +1,5 @@
 use crate::classfile_constants::{JVM_CONSTANT_Class, JVM_CONSTANT_Double, JVM_CONSTANT_Dynamic, JVM_CONSTANT_Fieldref, JVM_CONSTANT_Float, JVM_CONSTANT_Integer, JVM_CONSTANT_InterfaceMethodref, JVM_CONSTANT_InvokeDynamic, JVM_CONSTANT_Long, JVM_CONSTANT_MethodHandle, JVM_CONSTANT_MethodType, JVM_CONSTANT_Methodref, JVM_CONSTANT_Module, JVM_CONSTANT_NameAndType, JVM_CONSTANT_Package, JVM_CONSTANT_String, JVM_CONSTANT_Utf8, _bindgen_ty_3};
-use crate::common::error::{MessageError, Result};
+use crate::common::error::{ErrorKind, MessageError, Result};
 
 #[repr(C, align(8))]
 #[derive(Debug)]
@@ -32,10 +32,7 @@ pub fn fast_scan_class(data: & [u8], attribute_name: &[u8], not_check_attr: bool
     let constant_size = constant_size as usize;
     let mut data_key_index = 0;
     let mut name_found = not_check_attr;
-    let mut consts = Vec::with_capacity(constant_size);
-    unsafe {
-        consts.set_len(constant_size);
-    }
+    let mut consts = vec![0; constant_size];
     consts[0] = index;
     let attribute_name_len = attribute_name.len();
     let mut find_code = true;
@@ -79,11 +76,8 @@ pub fn fast_scan_class(data: & [u8], attribute_name: &[u8], not_check_attr: bool
         let code_index_bytes = (code_index as u16).to_be_bytes();
         let size = get_u16_from_data(data, &mut index)?;
         let size = size as usize;
-        let mut method_codes = Vec::with_capacity(size);
-        unsafe {
-            method_codes.set_len(size);
-        }
-        for i in 0..size {
+        let mut method_codes = vec![(0, 0); size];
+        for method_code in method_codes.iter_mut() {
             // access_flags + name + descriptor
             index += 6;
             let attr_size = get_u16_from_data(data, &mut index)?;
@@ -94,11 +88,11 @@ pub fn fast_scan_class(data: & [u8], attribute_name: &[u8], not_check_attr: bool
                 index += 2;
                 let data_size = get_u32_from_data(data, &mut index)?;
                 index += data_size as usize;
-                if &data[start..start+2] == &code_index_bytes {
+                if data[start..start+2] == code_index_bytes {
                     code_range = (start, index);
                 }
             }
-            method_codes[i] = code_range;
+            *method_code = code_range;
         }
 
         // attribute
@@ -114,7 +108,7 @@ pub fn fast_scan_class(data: & [u8], attribute_name: &[u8], not_check_attr: bool
             index += data_size as usize;
             if name_index == data_key_index {
                 return if index > data.len() {
-                    Err(MessageError::new("读取命中的属性内容时越界"))
+                    Err(MessageError::of(ErrorKind::UnexpectedEof, "读取命中的属性内容时越界").at(start))
                 } else {
                     specify_attribute = Some(DataRange {
                         start,
@@ -164,7 +158,7 @@ pub fn handle_field_or_method(data: &[u8], index: &mut usize) -> Result<()> {
 fn get_constant_value_size(data: &[u8], index: &mut usize, attribute_name: &[u8], attribute_name_len: usize, name_found: bool, find_code: bool) -> Result<i8> {
     let type_ = match data.get(*index) {
         None => {
-            return Err(MessageError::new("读取常量类型时越界"));
+            return Err(MessageError::of(ErrorKind::UnexpectedEof, "读取常量类型时越界").at(*index));
         }
         Some(v) => *v
     };
@@ -176,7 +170,7 @@ fn get_constant_value_size(data: &[u8], index: &mut usize, attribute_name: &[u8]
             if find_code && str_size == CODE_ATTR_NAME_LEN {
                 let end = *index + CODE_ATTR_NAME_LEN;
                 if end > data.len() {
-                    return Err(MessageError::new("读取utf8越界"))
+                    return Err(MessageError::of(ErrorKind::UnexpectedEof, "读取utf8越界").at(*index))
                 }
 
                 if &data[*index..end] == CODE_ATTR_NAME {
@@ -191,7 +185,7 @@ fn get_constant_value_size(data: &[u8], index: &mut usize, attribute_name: &[u8]
                 let start = *index;
                 *index += str_size;
                 if *index > data.len() {
-                    return Err(MessageError::new("读取utf8越界"))
+                    return Err(MessageError::of(ErrorKind::UnexpectedEof, "读取utf8越界").at(start))
                 }
 
                 let eq = &data[start..*index] == attribute_name;
@@ -230,7 +224,7 @@ pub fn get_u16_from_data(data: &[u8], index: &mut usize) -> Result<u16> {
     let start = *index;
     *index += 2;
     if *index > data.len() {
-        return Err(MessageError::new("读取u16越界"))
+        return Err(MessageError::of(ErrorKind::UnexpectedEof, "读取u16越界").at(start))
     }
     unsafe {
         let ptr = data.as_ptr().add(start) as *const u16;
@@ -243,7 +237,7 @@ pub fn get_u32_from_data(data: &[u8], index: &mut usize) -> Result<u32> {
     let start = *index;
     *index += 4;
     if *index > data.len() {
-        return Err(MessageError::new("读取u32越界"))
+        return Err(MessageError::of(ErrorKind::UnexpectedEof, "读取u32越界").at(start))
     }
     unsafe {
         let ptr = data.as_ptr().add(start) as *const u32;
@@ -1,2 +1,3 @@
 mod byte_utils;
-pub mod class_scan;
\ No newline at end of file
+pub mod class_scan;
+pub mod mutf8;
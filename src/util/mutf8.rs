@@ -0,0 +1,106 @@
+use std::borrow::Cow;
+
+/// 按modified UTF-8解码（NUL为0xC0 0x80，增补字符为两个3字节的代理项），
+/// 数据不合法或无法按原样编码回去（如过长编码、孤立的代理项）时返回None
+pub fn decode(bytes: &[u8]) -> Option<String> {
+    if bytes.iter().all(|b| *b != 0 && *b < 0x80) {
+        return String::from_utf8(bytes.to_vec()).ok();
+    }
+    let mut string = String::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let b0 = bytes[i];
+        match b0 {
+            0x01..=0x7F => {
+                string.push(b0 as char);
+                i += 1;
+            }
+            0xC0..=0xDF => {
+                let b1 = continuation(bytes, i + 1)?;
+                let code = ((b0 as u32 & 0x1F) << 6) | b1;
+                if code != 0 && code < 0x80 {
+                    return None;
+                }
+                string.push(char::from_u32(code)?);
+                i += 2;
+            }
+            0xE0..=0xEF => {
+                let code = decode_3_bytes(bytes, i)?;
+                i += 3;
+                match code {
+                    0xD800..=0xDBFF => {
+                        let low = match bytes.get(i) {
+                            Some(0xE0..=0xEF) => decode_3_bytes(bytes, i)?,
+                            _ => return None,
+                        };
+                        if !(0xDC00..=0xDFFF).contains(&low) {
+                            return None;
+                        }
+                        i += 3;
+                        string.push(char::from_u32(0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00))?);
+                    }
+                    0xDC00..=0xDFFF => return None,
+                    _ => string.push(char::from_u32(code)?),
+                }
+            }
+            _ => return None,
+        }
+    }
+    Some(string)
+}
+
+#[inline]
+fn continuation(bytes: &[u8], index: usize) -> Option<u32> {
+    match bytes.get(index) {
+        Some(b) if b & 0xC0 == 0x80 => Some((b & 0x3F) as u32),
+        _ => None,
+    }
+}
+
+#[inline]
+fn decode_3_bytes(bytes: &[u8], index: usize) -> Option<u32> {
+    let b1 = continuation(bytes, index + 1)?;
+    let b2 = continuation(bytes, index + 2)?;
+    let code = ((bytes[index] as u32 & 0x0F) << 12) | (b1 << 6) | b2;
+    if code < 0x800 {
+        None
+    } else {
+        Some(code)
+    }
+}
+
+/// 编码为modified UTF-8，不含NUL与增补字符时直接借用原字符串
+pub fn encode(string: &str) -> Cow<'_, [u8]> {
+    if !string.bytes().any(|b| b == 0 || b >= 0xF0) {
+        return Cow::Borrowed(string.as_bytes());
+    }
+    let mut bytes = Vec::with_capacity(encoded_len(string));
+    for c in string.chars() {
+        match c as u32 {
+            0 => bytes.extend_from_slice(&[0xC0, 0x80]),
+            code if code >= 0x10000 => {
+                let code = code - 0x10000;
+                encode_3_bytes(&mut bytes, 0xD800 + (code >> 10));
+                encode_3_bytes(&mut bytes, 0xDC00 + (code & 0x3FF));
+            }
+            _ => bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+        }
+    }
+    Cow::Owned(bytes)
+}
+
+#[inline]
+fn encode_3_bytes(bytes: &mut Vec<u8>, code: u32) {
+    bytes.push(0xE0 | (code >> 12) as u8);
+    bytes.push(0x80 | ((code >> 6) & 0x3F) as u8);
+    bytes.push(0x80 | (code & 0x3F) as u8);
+}
+
+/// 编码为modified UTF-8后的字节数
+pub fn encoded_len(string: &str) -> usize {
+    string.len() + string.bytes().map(|b| match b {
+        0 => 1,
+        0xF0..=0xFF => 2,
+        _ => 0,
+    }).sum::<usize>()
+}
@@ -0,0 +1,329 @@
+use crate::access_flags::MethodAccessFlags;
+use crate::attribute_info::{AttributeInfo, CodeAttribute};
+use crate::common::constants::{CODE_TAG, STACK_MAP_TABLE_TAG};
+use crate::common::error::{ErrorKind, MessageError, Result};
+use crate::common::opcode::opcodes::*;
+use crate::constant_pool::ConstantPool;
+use crate::descriptor::MethodDescriptor;
+use crate::instruction::Instruction;
+use crate::jclass_info::JClassInfo;
+use crate::method_info::MethodInfo;
+use crate::stack_map::{ends_block, expand_frames, Analyzer, ClassHierarchy, Frame, FrameType, THROWABLE_CLASS};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+/// 要求StackMapTable的最低主版本号(Java 6)，更低的版本通过类型推导校验
+pub const STACK_MAP_MIN_VERSION: u16 = 50;
+/// 主版本不低于51时不允许出现jsr、jsr_w与ret
+const NO_SUBROUTINE_MIN_VERSION: u16 = 51;
+
+/// 校验错误，pc与instruction为出错的指令，与具体指令无关的错误为None
+#[derive(Debug, Clone)]
+pub struct VerifyError {
+    pub pc: Option<u32>,
+    pub instruction: Option<Instruction>,
+    pub error: MessageError,
+}
+
+impl VerifyError {
+    fn new(error: MessageError) -> VerifyError {
+        VerifyError {
+            pc: error.offset.map(|offset| offset as u32),
+            instruction: None,
+            error,
+        }
+    }
+
+    fn at(pc: u32, instruction: &Instruction, error: MessageError) -> VerifyError {
+        VerifyError {
+            pc: Some(pc),
+            instruction: Some(instruction.clone()),
+            error: error.at(pc as usize),
+        }
+    }
+
+    #[inline]
+    pub fn kind(&self) -> ErrorKind {
+        self.error.kind()
+    }
+}
+
+impl From<MessageError> for VerifyError {
+    fn from(value: MessageError) -> Self {
+        VerifyError::new(value)
+    }
+}
+
+impl From<VerifyError> for MessageError {
+    fn from(value: VerifyError) -> Self {
+        value.error
+    }
+}
+
+impl Display for VerifyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.error)?;
+        if let Some(instruction) = &self.instruction {
+            write!(f, " 指令: {:?}", instruction)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for VerifyError {
+
+}
+
+pub type VerifyResult<T> = core::result::Result<T, VerifyError>;
+
+/// 校验类中所有方法，错误路径为methods[i]
+pub fn verify_class(class: &JClassInfo, hierarchy: &dyn ClassHierarchy) -> VerifyResult<()> {
+    for (i, method) in class.methods.iter().enumerate() {
+        verify_method(class, method, hierarchy).map_err(|mut e| {
+            e.error = e.error.in_path(&format!("methods[{}]", i));
+            e
+        })?;
+    }
+    Ok(())
+}
+
+/// 按JVMS 4.10校验方法的Code属性
+///
+/// 检查指令边界与跳转目标、异常处理范围、操作栈与局部变量类型、max_stack与max_locals，
+/// 主版本不低于50时按StackMapTable逐条检查并校验其与实际帧的一致性，否则通过类型推导校验。
+/// 类型推导不支持jsr子程序，遇到时返回ErrorKind::UnsupportedInstruction而不是判定为无效字节码。
+/// 引用类型的赋值关系由hierarchy判断，hierarchy中缺少的类视为java/lang/Object的直接子类
+pub fn verify_method(class: &JClassInfo, method: &MethodInfo, hierarchy: &dyn ClassHierarchy) -> VerifyResult<()> {
+    let pool = &class.constant_pool;
+    let access = method.access();
+    let code = method.attribute(pool, CODE_TAG)
+        .map(|attribute| attribute.info(pool))
+        .transpose()?;
+    let code = match (code, access.intersects(MethodAccessFlags::ABSTRACT | MethodAccessFlags::NATIVE)) {
+        (Some(AttributeInfo::Code(code)), false) => code,
+        (None, true) => return Ok(()),
+        (Some(_), true) => return Err(verify_error("抽象方法或本地方法不能有Code属性").into()),
+        _ => return Err(verify_error("缺少Code属性").into()),
+    };
+    let name = method.get_name(pool)?;
+    let descriptor = MethodDescriptor::parse(method.get_descriptor(pool)?)?;
+    let initial = Frame::initial(class.this_class_name()?, name, &descriptor, access.contains(MethodAccessFlags::STATIC));
+    if code.codes.is_empty() {
+        return Err(verify_error("字节码为空").into());
+    }
+    if initial.locals.len() > code.max_locals as usize {
+        return Err(verify_error(&format!("参数占用的槽数{}超出max_locals[{}]", initial.locals.len(), code.max_locals)).into());
+    }
+    let verifier = Verifier {
+        analyzer: Analyzer::new(code, pool, class.this_class_name()?, hierarchy)?.strict(),
+        code,
+        pool,
+        hierarchy,
+        is_init: name == "<init>",
+        return_type: descriptor.return_type.as_ref().map(FrameType::from_field_type),
+    };
+    verifier.check_targets()?;
+    if class.major_version >= NO_SUBROUTINE_MIN_VERSION {
+        verifier.check_no_subroutines()?;
+    }
+    let handlers = verifier.check_handlers()?;
+    if class.major_version >= STACK_MAP_MIN_VERSION {
+        let table = code.attribute(pool, STACK_MAP_TABLE_TAG)
+            .map(|attribute| attribute.info(pool))
+            .transpose()?;
+        let frames = match table {
+            Some(AttributeInfo::StackMapTable(table)) => expand_frames(&initial, table, pool)
+                .map_err(|e| e.in_path(STACK_MAP_TABLE_TAG))?,
+            _ => BTreeMap::new(),
+        };
+        verifier.check_with_frames(initial, &frames, &handlers)
+    } else {
+        verifier.check_by_inference(initial)
+    }
+}
+
+struct Verifier<'a> {
+    analyzer: Analyzer<'a>,
+    code: &'a CodeAttribute,
+    pool: &'a ConstantPool,
+    hierarchy: &'a dyn ClassHierarchy,
+    is_init: bool,
+    return_type: Option<FrameType>,
+}
+
+/// 异常处理器，覆盖[start, end)范围
+struct Handler {
+    start: u32,
+    end: u32,
+    handler: u32,
+    catch_type: FrameType,
+}
+
+impl Verifier<'_> {
+    /// 跳转目标须为指令起始位置
+    fn check_targets(&self) -> VerifyResult<()> {
+        for (pc, instruction) in &self.analyzer.decoded.instructions {
+            for target in instruction.branch_targets() {
+                self.analyzer.decoded.index_of(target, *pc)
+                    .map_err(|e| VerifyError::at(*pc, instruction, e))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn check_no_subroutines(&self) -> VerifyResult<()> {
+        for (pc, instruction) in &self.analyzer.decoded.instructions {
+            if matches!(instruction, Instruction::WideLocal(RET, _)) || matches!(instruction.opcode(), JSR | JSR_W | RET) {
+                return Err(VerifyError::at(*pc, instruction, verify_error("主版本51及以上不允许jsr子程序")));
+            }
+        }
+        Ok(())
+    }
+
+    /// 异常处理范围须落在指令边界上，捕获类型须为Throwable的子类
+    fn check_handlers(&self) -> VerifyResult<Vec<Handler>> {
+        let decoded = &self.analyzer.decoded;
+        let len = self.code.codes.len() as u32;
+        let mut handlers = Vec::with_capacity(self.code.exceptions.entries.len());
+        for (i, entry) in self.code.exceptions.entries.iter().enumerate() {
+            let (start, end, handler) = (entry.start_pc as u32, entry.end_pc as u32, entry.handler_pc as u32);
+            let check = || -> Result<FrameType> {
+                if start >= end || end > len {
+                    return Err(verify_error(&format!("异常处理范围无效[{}, {})", start, end)));
+                }
+                decoded.index_of(start, start)?;
+                if end < len {
+                    decoded.index_of(end, end)?;
+                }
+                decoded.index_of(handler, handler)?;
+                if entry.catch_type == 0 {
+                    return Ok(FrameType::Object(THROWABLE_CLASS.to_string()));
+                }
+                let catch_type = FrameType::Object(self.pool.class_name(entry.catch_type)?.to_string());
+                if !FrameType::Object(THROWABLE_CLASS.to_string()).is_assignable_from(&catch_type, self.hierarchy) {
+                    return Err(verify_error(&format!("捕获类型{:?}不是Throwable的子类", catch_type)));
+                }
+                Ok(catch_type)
+            };
+            let catch_type = check().map_err(|e| VerifyError::new(e.in_path(&format!("exceptions[{}]", i))))?;
+            handlers.push(Handler { start, end, handler, catch_type });
+        }
+        Ok(handlers)
+    }
+
+    /// 按StackMapTable逐条检查，帧所在位置的实际帧须可赋给声明的帧
+    fn check_with_frames(&self, initial: Frame, frames: &BTreeMap<u32, Frame>, handlers: &[Handler]) -> VerifyResult<()> {
+        let decoded = &self.analyzer.decoded;
+        for (pc, frame) in frames {
+            decoded.index_of(*pc, *pc).map_err(|e| VerifyError::new(e.in_path(STACK_MAP_TABLE_TAG)))?;
+            self.check_limits(frame).map_err(|e| VerifyError::new(e.at(*pc as usize).in_path(STACK_MAP_TABLE_TAG)))?;
+        }
+        let mut current = Some(initial);
+        for (pc, instruction) in &decoded.instructions {
+            let at = |e: MessageError| VerifyError::at(*pc, instruction, e);
+            if let Some(declared) = frames.get(pc) {
+                if let Some(frame) = &current {
+                    if !declared.is_assignable_from(frame, self.hierarchy) {
+                        return Err(at(verify_error(&format!("帧与StackMapTable不一致: {:?}，声明为{:?}", frame, declared))));
+                    }
+                }
+                current = Some(declared.clone());
+            }
+            let frame = current.take().ok_or_else(|| at(verify_error("无条件跳转之后缺少栈映射帧")))?;
+            let mut after = frame.clone();
+            self.execute(&mut after, *pc, instruction).map_err(at)?;
+            // 指令执行前后的局部变量都须与异常处理器入口的帧兼容
+            for handler in handlers.iter().filter(|handler| (handler.start..handler.end).contains(pc)) {
+                for locals in [&frame.locals, &after.locals] {
+                    let incoming = Frame { locals: locals.clone(), stack: vec![handler.catch_type.clone()] };
+                    self.check_target(frames, handler.handler, &incoming).map_err(at)?;
+                }
+            }
+            for target in instruction.branch_targets() {
+                self.check_target(frames, target, &after).map_err(at)?;
+            }
+            if !ends_block(instruction.opcode()) {
+                current = Some(after);
+            }
+        }
+        if current.is_some() {
+            let (pc, instruction) = decoded.instructions.last().unwrap();
+            return Err(VerifyError::at(*pc, instruction, verify_error("执行越过字节码末尾")));
+        }
+        Ok(())
+    }
+
+    /// 没有StackMapTable时通过数据流分析推导各指令的帧，不可达的指令不检查
+    fn check_by_inference(&self, initial: Frame) -> VerifyResult<()> {
+        let decoded = &self.analyzer.decoded;
+        let frames = self.analyzer.run(initial).map_err(|e| match e.offset.and_then(|pc| decoded.index_of(pc as u32, 0).ok()) {
+            Some(index) => {
+                let (pc, instruction) = &decoded.instructions[index];
+                VerifyError::at(*pc, instruction, e)
+            }
+            None => VerifyError::new(e),
+        })?;
+        for ((pc, instruction), frame) in decoded.instructions.iter().zip(frames) {
+            let Some(mut frame) = frame else { continue };
+            self.execute(&mut frame, *pc, instruction).map_err(|e| VerifyError::at(*pc, instruction, e))?;
+        }
+        Ok(())
+    }
+
+    /// 模拟执行并检查返回指令与操作栈、局部变量的上限
+    fn execute(&self, frame: &mut Frame, pc: u32, instruction: &Instruction) -> Result<()> {
+        let opcode = instruction.opcode();
+        if let IRETURN..=RETURN = opcode {
+            self.check_return(frame, opcode)?;
+        }
+        self.analyzer.execute(frame, pc, instruction)?;
+        self.check_limits(frame)
+    }
+
+    fn check_return(&self, frame: &Frame, opcode: u8) -> Result<()> {
+        let expected = match &self.return_type {
+            None => RETURN,
+            Some(FrameType::Integer) => IRETURN,
+            Some(FrameType::Long) => LRETURN,
+            Some(FrameType::Float) => FRETURN,
+            Some(FrameType::Double) => DRETURN,
+            Some(_) => ARETURN,
+        };
+        if opcode != expected {
+            return Err(verify_error(&format!("返回指令[{}]与方法返回类型{:?}不符", opcode, self.return_type)));
+        }
+        if let (ARETURN, Some(return_type), Some(value)) = (opcode, &self.return_type, frame.stack.last()) {
+            if !return_type.is_assignable_from(value, self.hierarchy) {
+                return Err(verify_error(&format!("返回值{:?}不能赋给{:?}", value, return_type)));
+            }
+        }
+        if self.is_init && frame.locals.first() == Some(&FrameType::UninitializedThis) {
+            return Err(verify_error("构造方法返回前未调用父类或本类的构造方法"));
+        }
+        Ok(())
+    }
+
+    fn check_limits(&self, frame: &Frame) -> Result<()> {
+        if frame.stack_slots() > self.code.max_stack as usize {
+            return Err(verify_error(&format!("操作栈深度{}超出max_stack[{}]", frame.stack_slots(), self.code.max_stack)));
+        }
+        if frame.locals.len() > self.code.max_locals as usize {
+            return Err(verify_error(&format!("局部变量槽数{}超出max_locals[{}]", frame.locals.len(), self.code.max_locals)));
+        }
+        Ok(())
+    }
+
+    /// 跳转目标须有栈映射帧且incoming可赋给该帧
+    fn check_target(&self, frames: &BTreeMap<u32, Frame>, target: u32, incoming: &Frame) -> Result<()> {
+        match frames.get(&target) {
+            Some(declared) if declared.is_assignable_from(incoming, self.hierarchy) => Ok(()),
+            Some(declared) => Err(verify_error(&format!("跳转到[{}]的帧{:?}与StackMapTable声明的{:?}不一致", target, incoming, declared))),
+            None => Err(verify_error(&format!("跳转目标[{}]缺少栈映射帧", target))),
+        }
+    }
+}
+
+#[inline]
+fn verify_error(msg: &str) -> MessageError {
+    MessageError::of(ErrorKind::InvalidBytecode, msg)
+}
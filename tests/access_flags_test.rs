@@ -0,0 +1,97 @@
+use jclass::access_flags::{ClassAccessFlags, FieldAccessFlags, InnerClassAccessFlags, MethodAccessFlags};
+use jclass::attribute_info::AttributeInfo;
+
+mod common;
+
+use common::load_class;
+
+#[test]
+fn test_set_operations() {
+    let mut flags = MethodAccessFlags::PUBLIC | MethodAccessFlags::STATIC;
+    assert!(flags.contains(MethodAccessFlags::PUBLIC));
+    assert!(!flags.contains(MethodAccessFlags::PUBLIC | MethodAccessFlags::FINAL));
+    assert!(flags.intersects(MethodAccessFlags::PUBLIC | MethodAccessFlags::FINAL));
+    flags.insert(MethodAccessFlags::SYNCHRONIZED);
+    assert_eq!(flags.bits(), 0x0029);
+    flags.remove(MethodAccessFlags::STATIC);
+    assert_eq!(flags, MethodAccessFlags::PUBLIC | MethodAccessFlags::SYNCHRONIZED);
+    flags.set(MethodAccessFlags::FINAL, true);
+    assert_eq!(flags - MethodAccessFlags::PUBLIC, MethodAccessFlags::SYNCHRONIZED | MethodAccessFlags::FINAL);
+    assert_eq!(flags & MethodAccessFlags::FINAL, MethodAccessFlags::FINAL);
+    assert!((flags & !flags).is_empty());
+    assert_eq!(u16::from(MethodAccessFlags::from(0x8001)), 0x8001);
+
+    // 同一标志位在不同上下文中含义不同
+    assert_eq!(MethodAccessFlags::SYNCHRONIZED.bits(), ClassAccessFlags::SUPER.bits());
+    assert_eq!(MethodAccessFlags::from_bits(0x0040).flag_names(), ["ACC_BRIDGE"]);
+    assert_eq!(FieldAccessFlags::from_bits(0x0040).flag_names(), ["ACC_VOLATILE"]);
+    assert_eq!(ClassAccessFlags::from_bits(0x0021).flag_names(), ["ACC_PUBLIC", "ACC_SUPER"]);
+}
+
+#[test]
+fn test_display() {
+    assert_eq!((ClassAccessFlags::PUBLIC | ClassAccessFlags::SUPER | ClassAccessFlags::FINAL).to_string(), "public final");
+    assert_eq!((ClassAccessFlags::PUBLIC | ClassAccessFlags::INTERFACE | ClassAccessFlags::ABSTRACT).to_string(), "public");
+    assert_eq!((FieldAccessFlags::FINAL | FieldAccessFlags::STATIC | FieldAccessFlags::PRIVATE).to_string(), "private static final");
+    assert_eq!((MethodAccessFlags::SYNCHRONIZED | MethodAccessFlags::PUBLIC | MethodAccessFlags::BRIDGE).to_string(), "public synchronized");
+    assert_eq!((MethodAccessFlags::ABSTRACT | MethodAccessFlags::PROTECTED).to_string(), "protected abstract");
+    assert_eq!((InnerClassAccessFlags::STATIC | InnerClassAccessFlags::PUBLIC).to_string(), "public static");
+    assert_eq!(MethodAccessFlags::empty().to_string(), "");
+}
+
+#[test]
+fn test_validate() {
+    assert!((ClassAccessFlags::INTERFACE).validate().is_err());
+    assert!((ClassAccessFlags::INTERFACE | ClassAccessFlags::ABSTRACT).validate().is_ok());
+    assert!((ClassAccessFlags::INTERFACE | ClassAccessFlags::ABSTRACT | ClassAccessFlags::FINAL).validate().is_err());
+    assert!((ClassAccessFlags::ANNOTATION | ClassAccessFlags::ABSTRACT).validate().is_err());
+    assert!((ClassAccessFlags::FINAL | ClassAccessFlags::ABSTRACT).validate().is_err());
+    assert!(ClassAccessFlags::MODULE.validate().is_ok());
+    assert!((ClassAccessFlags::MODULE | ClassAccessFlags::PUBLIC).validate().is_err());
+
+    assert!((FieldAccessFlags::PUBLIC | FieldAccessFlags::PRIVATE).validate(false).is_err());
+    assert!((FieldAccessFlags::FINAL | FieldAccessFlags::VOLATILE).validate(false).is_err());
+    assert!((FieldAccessFlags::PUBLIC | FieldAccessFlags::STATIC).validate(true).is_err());
+    assert!((FieldAccessFlags::PUBLIC | FieldAccessFlags::STATIC | FieldAccessFlags::FINAL).validate(true).is_ok());
+
+    assert!((MethodAccessFlags::ABSTRACT | MethodAccessFlags::FINAL).validate(false).is_err());
+    assert!((MethodAccessFlags::ABSTRACT | MethodAccessFlags::PUBLIC).validate(true).is_ok());
+    assert!((MethodAccessFlags::ABSTRACT).validate(true).is_err());
+    assert!((MethodAccessFlags::PUBLIC | MethodAccessFlags::SYNCHRONIZED).validate(true).is_err());
+    assert!((MethodAccessFlags::PRIVATE | MethodAccessFlags::STATIC).validate(true).is_ok());
+
+    assert!((InnerClassAccessFlags::INTERFACE | InnerClassAccessFlags::STATIC).validate().is_err());
+    let error = ClassAccessFlags::INTERFACE.validate().unwrap_err();
+    assert!(error.msg.contains("ACC_INTERFACE"), "{}", error);
+}
+
+#[test]
+fn test_sample_flags() {
+    for name in ["Sample", "Sample$Keep", "Sample$Hidden", "Sample$Inner", "Sample$Nested", "Sample$1"] {
+        let class = load_class(name);
+        let access = class.access();
+        access.validate().unwrap();
+        let in_interface = access.contains(ClassAccessFlags::INTERFACE);
+        for field in &class.fields {
+            field.access().validate(in_interface).unwrap();
+        }
+        for method in &class.methods {
+            method.access().validate(in_interface).unwrap();
+        }
+        for attribute in &class.attributes {
+            if let AttributeInfo::InnerClasses(classes) = attribute.info(&class.constant_pool).unwrap() {
+                classes.iter().for_each(|inner| inner.access().validate().unwrap());
+            }
+        }
+    }
+
+    let class = load_class("Sample");
+    let pool = &class.constant_pool;
+    assert_eq!(class.access().to_string(), "public");
+    let field = class.fields.iter().find(|field| field.get_name(pool).unwrap() == "items").unwrap();
+    assert_eq!(field.access().to_string(), "private volatile");
+    let method = class.methods.iter().find(|method| method.get_name(pool).unwrap() == "switchTable").unwrap();
+    assert_eq!(method.access().to_string(), "public synchronized");
+    let keep = load_class("Sample$Keep");
+    assert!(keep.access().contains(ClassAccessFlags::ANNOTATION | ClassAccessFlags::INTERFACE));
+}
@@ -0,0 +1,124 @@
+use jclass::annotation::{Annotations, ParameterAnnotations, ResolvedAnnotation, ResolvedElementValue};
+
+mod common;
+
+use common::{load_class, reload};
+
+const KEEP: &str = "sample/Sample$Keep";
+
+fn element_type(name: &str) -> ResolvedElementValue {
+    ResolvedElementValue::Enum {
+        type_name: "Ljava/lang/annotation/ElementType;".to_string(),
+        const_name: name.to_string(),
+    }
+}
+
+#[test]
+fn test_read_annotations() {
+    let class = load_class("Sample");
+    let pool = &class.constant_pool;
+
+    let field = class.find_field("items", "Ljava/util/List;").unwrap().annotations(pool).unwrap();
+    let keep = field.find(KEEP).unwrap();
+    assert_eq!(keep.type_name, "Lsample/Sample$Keep;");
+    assert_eq!(keep.element("value"), Some(&ResolvedElementValue::String("field".to_string())));
+    assert_eq!(keep.element("level"), Some(&ResolvedElementValue::Int(2)));
+    // 未显式指定的元素不回退到AnnotationDefault
+    assert_eq!(keep.element("type"), None);
+
+    // @Hidden为CLASS保留策略，位于RuntimeInvisibleAnnotations
+    let init = class.find_method("<init>", "()V").unwrap().annotations(pool).unwrap();
+    assert_eq!(init.visible.len(), 1);
+    assert_eq!(init.invisible[0].class_name(), "sample/Sample$Hidden");
+    assert!(init.contains(KEEP) && init.contains("sample/Sample$Hidden"));
+
+    let switch_table = class.find_method("switchTable", "(I)I").unwrap();
+    assert!(switch_table.annotations(pool).unwrap().contains("java/lang/Deprecated"));
+    assert!(switch_table.parameter_annotations(pool).unwrap().visible.is_empty());
+
+    let mixed = class.find_method("mixed", "(JDILjava/lang/String;)J").unwrap();
+    assert!(mixed.annotations(pool).unwrap().is_empty());
+    let parameters = mixed.parameter_annotations(pool).unwrap();
+    assert_eq!(parameters.visible.len(), 4);
+    assert!(parameters.contains(2, KEEP));
+    assert!(!parameters.contains(0, KEEP));
+    assert_eq!(parameters.parameter(2).next().unwrap().element("value"), Some(&ResolvedElementValue::String("param".to_string())));
+
+    let keep_class = load_class("Sample$Keep");
+    let retention = keep_class.annotations().unwrap();
+    assert_eq!(retention.find("java/lang/annotation/Retention").unwrap().element("value"), Some(&ResolvedElementValue::Enum {
+        type_name: "Ljava/lang/annotation/RetentionPolicy;".to_string(),
+        const_name: "RUNTIME".to_string(),
+    }));
+}
+
+#[test]
+fn test_annotation_default() {
+    let class = load_class("Sample$Keep");
+    let pool = &class.constant_pool;
+    let default = |name: &str| class.methods_named(name).next().unwrap().annotation_default(pool).unwrap();
+    assert_eq!(default("value"), Some(ResolvedElementValue::String("keep".to_string())));
+    assert_eq!(default("level"), Some(ResolvedElementValue::Int(1)));
+    assert_eq!(default("type"), Some(ResolvedElementValue::Class("Ljava/lang/Object;".to_string())));
+    assert_eq!(default("targets"), Some(ResolvedElementValue::Array(vec![element_type("METHOD"), element_type("FIELD")])));
+
+    let sample = load_class("Sample");
+    assert_eq!(sample.find_method("mixed", "(JDILjava/lang/String;)J").unwrap().annotation_default(&sample.constant_pool).unwrap(), None);
+
+    let mut class = class;
+    let pool = &mut class.constant_pool;
+    let level = class.methods.iter_mut().find(|method| method.get_name(pool).unwrap() == "level").unwrap();
+    level.set_annotation_default(pool, Some(&ResolvedElementValue::Int(7))).unwrap();
+    let value = class.methods.iter_mut().find(|method| method.get_name(pool).unwrap() == "value").unwrap();
+    value.set_annotation_default(pool, None).unwrap();
+
+    let class = reload(&class);
+    let pool = &class.constant_pool;
+    assert_eq!(class.find_method("level", "()I").unwrap().annotation_default(pool).unwrap(), Some(ResolvedElementValue::Int(7)));
+    assert_eq!(class.find_method("value", "()Ljava/lang/String;").unwrap().annotation_default(pool).unwrap(), None);
+}
+
+#[test]
+fn test_write_annotations() {
+    let mut class = load_class("Sample");
+    let nested = ResolvedAnnotation {
+        type_name: "Lsample/Sample$Nested;".to_string(),
+        elements: vec![("flag".to_string(), ResolvedElementValue::Boolean(true))],
+    };
+    let mut keep = ResolvedAnnotation::new(KEEP);
+    keep.elements = vec![
+        ("value".to_string(), ResolvedElementValue::String("obfuscate".to_string())),
+        ("bytes".to_string(), ResolvedElementValue::Array(vec![ResolvedElementValue::Byte(-1), ResolvedElementValue::Short(300), ResolvedElementValue::Char('x' as u16)])),
+        ("numbers".to_string(), ResolvedElementValue::Array(vec![ResolvedElementValue::Long(1 << 40), ResolvedElementValue::Float(1.5), ResolvedElementValue::Double(-2.25)])),
+        ("type".to_string(), ResolvedElementValue::Class("[I".to_string())),
+        ("targets".to_string(), ResolvedElementValue::Array(vec![element_type("TYPE")])),
+        ("nested".to_string(), ResolvedElementValue::Annotation(nested)),
+    ];
+
+    let annotations = Annotations {
+        visible: vec![keep.clone()],
+        invisible: vec![],
+    };
+    class.set_annotations(&annotations).unwrap();
+    let pool = &mut class.constant_pool;
+    // 移除@Deprecated
+    let switch_table = class.methods.iter_mut().find(|method| method.get_name(pool).unwrap() == "switchTable").unwrap();
+    switch_table.set_annotations(pool, &Annotations::default()).unwrap();
+    let parameters = ParameterAnnotations {
+        visible: vec![vec![ResolvedAnnotation::new(KEEP)]],
+        invisible: vec![],
+    };
+    switch_table.set_parameter_annotations(pool, &parameters).unwrap();
+    let mixed = class.methods.iter_mut().find(|method| method.get_name(pool).unwrap() == "mixed").unwrap();
+    mixed.set_parameter_annotations(pool, &ParameterAnnotations::default()).unwrap();
+
+    let class = reload(&class);
+    let pool = &class.constant_pool;
+    assert_eq!(class.annotations().unwrap(), annotations);
+    let switch_table = class.find_method("switchTable", "(I)I").unwrap();
+    assert!(switch_table.annotations(pool).unwrap().is_empty());
+    assert_eq!(switch_table.parameter_annotations(pool).unwrap(), parameters);
+    let mixed = class.find_method("mixed", "(JDILjava/lang/String;)J").unwrap();
+    assert_eq!(mixed.parameter_annotations(pool).unwrap(), ParameterAnnotations::default());
+    assert!(class.find_field("items", "Ljava/util/List;").unwrap().annotations(pool).unwrap().contains(KEEP));
+}
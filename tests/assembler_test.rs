@@ -0,0 +1,325 @@
+use std::fs::read;
+use jclass::access_flags::{ClassAccessFlags, MethodAccessFlags};
+use jclass::assembler::{assemble, assemble_with_hierarchy, to_assembly};
+use jclass::attribute_info::{AttributeInfo, OriginAttribute};
+use jclass::common::error::ErrorKind;
+use jclass::common::opcode::opcodes;
+use jclass::constant_pool::ConstantValue;
+use jclass::instruction::{Instruction, Instructions};
+use jclass::jclass_info::JClassInfo;
+use jclass::verifier::verify_class;
+
+mod common;
+
+use common::{hierarchy, load_class, sample_paths};
+
+const COUNTER: &str = r#"
+; 手写的测试类
+.version 49
+.class public super fixture/Counter
+.super java/lang/Object
+.implements java/lang/Runnable
+.source "Counter.java"
+
+.field private count I
+.field public static final LIMIT J = 100
+.field public static final NAME Ljava/lang/String; = "counter\n"
+
+.method public <init>()V
+    aload_0
+    invokespecial java/lang/Object/<init>()V
+    return
+.end method
+
+.method public run()V
+    .line 10
+    aload_0
+    dup
+    getfield fixture/Counter/count I
+    iconst_1
+    iadd
+    putfield fixture/Counter/count I
+    return
+.end method
+
+.method public static classify(I)I
+    iload_0
+    tableswitch 1
+        One
+        Two
+        default : Other
+One:
+    iconst_1
+    ireturn
+Two:
+    ldc 1000000
+    ireturn
+Other:
+    iload_0
+    lookupswitch
+        -1 : One
+        default : Two
+.end method
+
+.method public static parse(Ljava/lang/String;)I
+    .throws java/io/IOException
+Start:
+    aload_0
+    invokestatic java/lang/Integer/parseInt(Ljava/lang/String;)I
+End:
+    ireturn
+Handler:
+    pop
+    iconst_m1
+    ireturn
+    .catch java/lang/NumberFormatException from Start to End using Handler
+.end method
+
+.method public abstract native unused()V
+.end method
+"#;
+
+fn code_of(class: &JClassInfo, method_index: usize) -> jclass::attribute_info::CodeAttribute {
+    let pool = &class.constant_pool;
+    for attribute in &class.methods[method_index].attributes {
+        if let AttributeInfo::Code(code) = attribute.info(pool).unwrap() {
+            return code.clone();
+        }
+    }
+    panic!("方法{}没有Code属性", method_index);
+}
+
+fn instructions(class: &JClassInfo, method_index: usize) -> Vec<Instruction> {
+    let code = code_of(class, method_index);
+    Instructions::new(&code.codes).map(|item| item.unwrap().1).collect()
+}
+
+#[test]
+fn test_assemble() {
+    let class = assemble(COUNTER).unwrap();
+    let pool = &class.constant_pool;
+    assert_eq!(class.magic, 0xCAFEBABE);
+    assert_eq!((class.major_version, class.minor_version), (49, 0));
+    assert_eq!(class.access(), ClassAccessFlags::PUBLIC | ClassAccessFlags::SUPER);
+    assert_eq!(class.this_class_name().unwrap(), "fixture/Counter");
+    assert_eq!(class.super_class_name().unwrap(), Some("java/lang/Object"));
+    assert_eq!(class.interface_names().unwrap(), vec!["java/lang/Runnable"]);
+    assert!(matches!(class.attributes[0].info(pool).unwrap(), AttributeInfo::SourceFile(index) if pool.utf8(*index).unwrap() == "Counter.java"));
+
+    // 整数字面量按描述符转换为long
+    assert_eq!(class.fields.len(), 3);
+    match class.fields[1].attributes[0].info(pool).unwrap() {
+        AttributeInfo::ConstantValue(index) => assert_eq!(pool.get(*index).unwrap(), &ConstantValue::ConstantLong(100)),
+        info => panic!("{:?}", info),
+    }
+    match class.fields[2].attributes[0].info(pool).unwrap() {
+        AttributeInfo::ConstantValue(index) => assert_eq!(pool.string(*index).unwrap(), "counter\n"),
+        info => panic!("{:?}", info),
+    }
+
+    // 未指定.limit时计算max_stack与max_locals
+    let code = code_of(&class, 1);
+    assert_eq!((code.max_stack, code.max_locals), (3, 1));
+    match code.attributes[0].info(pool).unwrap() {
+        AttributeInfo::LineNumberTable(lines) => assert_eq!((lines[0].start_pc, lines[0].line_number), (0, 10)),
+        info => panic!("{:?}", info),
+    }
+
+    let classify = instructions(&class, 2);
+    assert_eq!(classify[1], Instruction::TableSwitch { default: 29, low: 1, targets: vec![24, 26] });
+    assert!(matches!(classify[4], Instruction::Ldc(_)));
+    assert_eq!(classify[7], Instruction::LookupSwitch { default: 26, pairs: vec![(-1, 24)] });
+
+    let parse = code_of(&class, 3);
+    let entry = &parse.exceptions.entries[0];
+    assert_eq!((entry.start_pc, entry.end_pc, entry.handler_pc), (0, 4, 5));
+    assert_eq!(pool.class_name(entry.catch_type).unwrap(), "java/lang/NumberFormatException");
+    assert!(class.methods[3].attributes.iter().any(|attribute| matches!(attribute.info(pool).unwrap(), AttributeInfo::Exceptions(_))));
+
+    // 没有指令的方法不生成Code属性
+    assert!(class.methods[4].access().contains(MethodAccessFlags::NATIVE));
+    assert!(class.methods[4].attributes.is_empty());
+
+    let hierarchy = hierarchy(&class);
+    verify_class(&class, &hierarchy).unwrap();
+
+    let mut bytes = Vec::new();
+    class.write_to(&mut (&mut bytes).into()).unwrap();
+    let parsed = JClassInfo::from_bytes(&bytes).unwrap();
+    assert_eq!(to_assembly(&parsed).unwrap(), to_assembly(&class).unwrap());
+}
+
+#[test]
+fn test_operand_forms() {
+    // 仅检查操作数编码，代码无法通过类型检查，使用不需要StackMapTable的版本
+    let class = assemble(r#"
+.version 49
+.class public Forms
+.method public static forms(JI)V
+    .limit stack 4
+    .limit locals 400
+    iinc 1 1000
+    iload 300
+    lstore 0
+    ldc2_w 1.5d
+    pop2
+    ldc 0x7fffffff
+    ldc_w "text"
+    ldc class [I
+    pop
+    pop
+    pop2
+    sipush -300
+    newarray long
+    multianewarray [[I 2
+    pop2
+    invokeinterface java/util/List/get(I)Ljava/lang/Object;
+    invokestatic interface java/util/List/of()Ljava/util/List;
+    invokedynamic run()Ljava/lang/Runnable; invokestatic java/lang/invoke/LambdaMetafactory/metafactory(Ljava/lang/invoke/MethodHandles$Lookup;Ljava/lang/String;Ljava/lang/invoke/MethodType;Ljava/lang/invoke/MethodType;Ljava/lang/invoke/MethodHandle;Ljava/lang/invoke/MethodType;)Ljava/lang/invoke/CallSite; methodtype ()V handle invokestatic Forms/body()V methodtype ()V
+    return
+.end method
+"#).unwrap();
+    let pool = &class.constant_pool;
+    assert_eq!(class.super_class_name().unwrap(), Some("java/lang/Object"));
+    let code = code_of(&class, 0);
+    assert_eq!((code.max_stack, code.max_locals), (4, 400));
+    let instructions = instructions(&class, 0);
+    assert_eq!(instructions[0], Instruction::WideIinc(1, 1000));
+    assert_eq!(instructions[1], Instruction::WideLocal(opcodes::ILOAD, 300));
+    assert_eq!(instructions[2], Instruction::Local(opcodes::LSTORE, 0));
+    match &instructions[3] {
+        Instruction::Ldc2W(index) => assert_eq!(pool.get(*index).unwrap(), &ConstantValue::ConstantDouble(1.5)),
+        instruction => panic!("{:?}", instruction),
+    }
+    match &instructions[5] {
+        Instruction::Ldc(index) => assert_eq!(pool.get(*index).unwrap(), &ConstantValue::ConstantInteger(0x7fffffff)),
+        instruction => panic!("{:?}", instruction),
+    }
+    assert!(matches!(&instructions[6], Instruction::LdcW(index) if pool.string(*index).unwrap() == "text"));
+    assert!(matches!(&instructions[7], Instruction::Ldc(index) if pool.class_name(*index).unwrap() == "[I"));
+    assert_eq!(instructions[11], Instruction::Sipush(-300));
+    assert_eq!(instructions[12], Instruction::NewArray(11));
+    assert!(matches!(&instructions[13], Instruction::MultiANewArray(index, 2) if pool.class_name(*index).unwrap() == "[[I"));
+    // 省略count时按参数槽数计算
+    assert!(matches!(&instructions[15], Instruction::InvokeInterface(_, 2)));
+    assert!(matches!(&instructions[16], Instruction::Invoke(opcodes::INVOKESTATIC, index)
+        if matches!(pool.get(*index).unwrap(), ConstantValue::ConstantInterfaceMethodref(..))));
+    match &instructions[17] {
+        Instruction::InvokeDynamic(index) => assert!(matches!(pool.get(*index).unwrap(), ConstantValue::ConstantInvokeDynamic(0, _))),
+        instruction => panic!("{:?}", instruction),
+    }
+    match class.attributes[0].info(pool).unwrap() {
+        AttributeInfo::BootstrapMethods(methods) => {
+            assert_eq!(methods.len(), 1);
+            assert_eq!(methods[0].arguments.len(), 3);
+            assert_eq!(pool.method_handle(methods[0].arguments[1]).unwrap().member.name, "body");
+        }
+        info => panic!("{:?}", info),
+    }
+}
+
+#[test]
+fn test_stack_map_frames() {
+    let text = r#"
+.class public Branch
+.method public static sign(I)Ljava/lang/Object;
+    iload_0
+    ifeq Zero
+    new java/lang/StringBuilder
+    dup
+    invokespecial java/lang/StringBuilder/<init>()V
+    goto End
+Zero:
+    ldc "zero"
+End:
+    areturn
+.end method
+"#;
+    let class = assemble(text).unwrap();
+    let pool = &class.constant_pool;
+    assert_eq!(class.major_version, 52);
+    let code = code_of(&class, 0);
+    let frames = code.attributes.iter()
+        .find_map(|attribute| match attribute.info(pool).unwrap() {
+            AttributeInfo::StackMapTable(frames) => Some(frames.len()),
+            _ => None,
+        });
+    assert_eq!(frames, Some(2));
+    let hierarchy = hierarchy(&class);
+    verify_class(&class, &hierarchy).unwrap();
+    let class = assemble_with_hierarchy(text, &hierarchy).unwrap();
+    verify_class(&class, &hierarchy).unwrap();
+    assert!(assemble(&text.replace("iload_0\n    ifeq", "fconst_0\n    ifeq")).is_err());
+
+    // 处理范围内改变局部变量类型后，生成的帧仍能通过校验
+    let class = assemble(r#"
+.class public Store
+.method public static run()V
+    iconst_0
+    istore_0
+Start:
+    aconst_null
+    astore_0
+End:
+    return
+Handler:
+    pop
+    return
+    .catch all from Start to End using Handler
+.end method
+"#).unwrap();
+    verify_class(&class, &common::hierarchy(&class)).unwrap();
+}
+
+#[test]
+fn test_round_trip() {
+    for path in sample_paths() {
+        let class = JClassInfo::from_bytes(&read(&path).unwrap()).unwrap();
+        let text = to_assembly(&class).unwrap();
+        let assembled = assemble(&text).unwrap_or_else(|e| panic!("{:?}: {}", path, e));
+        assert_eq!(to_assembly(&assembled).unwrap(), text, "{:?}", path);
+        for (method, original) in assembled.methods.iter().zip(&class.methods) {
+            let name = method.get_name(&assembled.constant_pool).unwrap();
+            assert_eq!(name, original.get_name(&class.constant_pool).unwrap());
+            let codes = |class: &JClassInfo, attributes: &[OriginAttribute]| attributes.iter()
+                .find_map(|attribute| match attribute.info(&class.constant_pool).unwrap() {
+                    AttributeInfo::Code(code) => Some(code.codes.len()),
+                    _ => None,
+                });
+            assert_eq!(codes(&assembled, &method.attributes), codes(&class, &original.attributes), "{:?} {}", path, name);
+        }
+    }
+
+    // 无法解码的字符串以\xHH转义保留原始字节
+    let text = load_class("Text");
+    let assembly = to_assembly(&text).unwrap();
+    assert!(assembly.contains(r#"LONE_SURROGATE Ljava/lang/String; = "lone\xed\xa0\x80""#));
+    assert!(assembly.contains(r#"NUL_TEXT Ljava/lang/String; = "nul\u0000char""#));
+    let assembled = assemble(&assembly).unwrap();
+    assert!(assembled.constant_pool.iter().any(|(_, value)| value == &ConstantValue::ConstantUtf8Raw(b"lone\xed\xa0\x80".to_vec())));
+    // 成对的代理项转义为补充平面字符
+    let class = assemble(".class A\n.field static final S Ljava/lang/String; = \"\\ud83d\\ude00\"").unwrap();
+    assert!(class.constant_pool.iter().any(|(_, value)| value == &ConstantValue::ConstantUtf8("😀".to_string())));
+}
+
+#[test]
+fn test_errors() {
+    let error = |text: &str| assemble(text).unwrap_err();
+
+    let e = error(".class A\n.method static f()V\n    foo\n.end method");
+    assert_eq!(e.kind(), ErrorKind::Syntax);
+    assert!(e.msg.starts_with("第3行"), "{}", e);
+    assert!(error(".class A\n.method static f()V\n    goto Missing\n.end method").msg.contains("标签Missing未定义"));
+    assert!(error(".class A\n.method static f()V\nL: nop\nL: return\n.end method").msg.contains("重复定义"));
+    assert!(error(".class A\n.method static f()V\n    return").msg.contains(".end method"));
+    assert!(error(".method static f()V\n.end method").msg.contains("缺少.class"));
+    assert!(error(".class A\n.method static f()V\n    ldc 1L\n.end method").msg.contains("ldc2_w"));
+    assert!(error(".class A\n.method static f()V\n    bipush 200\n.end method").msg.contains("超出范围"));
+    assert!(error(".class A\n.method static f()V\n    return extra\n.end method").msg.contains("多余"));
+    assert!(error(".class A\n.field static S Ljava/lang/String; = 1").msg.contains("不能使用"));
+    assert!(error(".class bogus A").msg.contains("bogus"));
+    assert!(error(".class A\n.method static f()V\n    iload_0\n    tableswitch 0\n        L\n.end method").msg.contains("分支"));
+    assert!(error(".class A\n.source \"unterminated").msg.contains("结束引号"));
+    let e = error(".class A\n.method static f(\n.end method");
+    assert_eq!(e.kind(), ErrorKind::InvalidDescriptor, "{}", e);
+}
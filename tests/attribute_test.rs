@@ -0,0 +1,163 @@
+use jclass::attribute_info::{AttributeInfo, OriginAttribute};
+use jclass::constant_pool::ConstantPool;
+use jclass::field_info::FieldInfo;
+use jclass::jclass_info::JClassInfo;
+use jclass::method_info::MethodInfo;
+
+mod common;
+
+use common::{load_class, sample_classes, sample_data};
+
+fn check_round_trip(pool: &ConstantPool, attribute: &OriginAttribute, tags: &mut Vec<&'static str>) {
+    let info = attribute.to_attribute_info(pool).unwrap();
+    assert_eq!(info.byte_size(), attribute.data.len());
+    assert_eq!(OriginAttribute::new_with_info(attribute.name, &info).unwrap().data, attribute.data);
+    tags.push(info.tag());
+    if let AttributeInfo::Code(code) = info {
+        for attribute in &code.attributes {
+            check_round_trip(pool, attribute, tags);
+        }
+    }
+}
+
+#[test]
+fn test_attribute_info_round_trip() {
+    let mut tags = Vec::new();
+    for class in sample_classes() {
+        let pool = &class.constant_pool;
+        let attributes = class.attributes.iter()
+            .chain(class.fields.iter().flat_map(|field| field.attributes.iter()))
+            .chain(class.methods.iter().flat_map(|method| method.attributes.iter()));
+        for attribute in attributes {
+            check_round_trip(pool, attribute, &mut tags);
+        }
+    }
+    for tag in ["AnnotationDefault", "BootstrapMethods", "Code", "ConstantValue", "Deprecated", "EnclosingMethod",
+        "Exceptions", "InnerClasses", "LineNumberTable", "LocalVariableTable", "LocalVariableTypeTable",
+        "MethodParameters", "NestHost", "NestMembers", "RuntimeVisibleAnnotations", "RuntimeInvisibleAnnotations",
+        "RuntimeVisibleTypeAnnotations", "Signature", "SourceFile", "StackMapTable"] {
+        assert!(tags.contains(&tag), "未覆盖的属性: {tag}");
+    }
+}
+
+#[test]
+fn test_attribute_info_to_origin() {
+    let mut pool = load_class("Sample").constant_pool;
+    let origin = AttributeInfo::Exceptions(vec![3, 5]).to_origin(&mut pool).unwrap();
+    assert_eq!(origin.data, [0, 2, 0, 3, 0, 5]);
+    let info = origin.to_attribute_info(&pool).unwrap();
+    assert!(matches!(info, AttributeInfo::Exceptions(ref indexes) if indexes == &[3, 5]));
+
+    let unknown = OriginAttribute::new(0, vec![1, 2, 3]);
+    let info = unknown.to_attribute_info(&pool).unwrap();
+    assert!(matches!(info, AttributeInfo::OriginAttribute(_)));
+    assert_eq!(OriginAttribute::new_with_info(0, &info).unwrap().data, [1, 2, 3]);
+}
+
+#[test]
+fn test_lazy_attribute_info() {
+    let content = sample_data();
+    let mut class = JClassInfo::from_bytes(&content).unwrap();
+    let pool = class.constant_pool.clone();
+    let method = &mut class.methods[0];
+    let attribute = &mut method.attributes[0];
+    let first = attribute.info(&pool).unwrap() as *const AttributeInfo;
+    assert_eq!(first, attribute.info(&pool).unwrap() as *const AttributeInfo);
+    assert!(!attribute.is_modified());
+
+    let mut bytes = Vec::new();
+    class.write_to(&mut (&mut bytes).into()).unwrap();
+    assert_eq!(bytes, content);
+
+    let attribute = &mut class.methods[0].attributes[0];
+    let origin_data = attribute.data.clone();
+    if let AttributeInfo::Code(code) = attribute.info_mut(&pool).unwrap() {
+        code.max_stack += 1;
+    } else {
+        panic!("方法首个属性不是Code");
+    }
+    assert!(attribute.is_modified());
+    assert_eq!(attribute.data, origin_data);
+    let mut bytes = Vec::new();
+    class.write_to(&mut (&mut bytes).into()).unwrap();
+    assert_eq!(bytes.len(), content.len());
+    assert_eq!(bytes.len(), class.byte_size());
+    assert_ne!(bytes, content);
+
+    let attribute = &mut class.methods[0].attributes[0];
+    attribute.sync().unwrap();
+    assert!(!attribute.is_modified());
+    assert_eq!(attribute.data[..2], [origin_data[0], origin_data[1] + 1]);
+
+    // 未sync的修改优先于data，reset_info后才写出data
+    attribute.info_mut(&pool).unwrap();
+    attribute.data = origin_data.clone();
+    let mut bytes = Vec::new();
+    class.write_to(&mut (&mut bytes).into()).unwrap();
+    assert_ne!(bytes, content);
+    class.methods[0].attributes[0].reset_info();
+    let mut bytes = Vec::new();
+    class.write_to(&mut (&mut bytes).into()).unwrap();
+    assert_eq!(bytes, content);
+}
+
+#[test]
+fn test_attribute_lookup() {
+    let mut class = sample_classes().into_iter()
+        .find(|class| class.this_class_name().unwrap() == "sample/Sample")
+        .unwrap();
+    let source = class.attribute("SourceFile").unwrap();
+    assert!(matches!(source.info(&class.constant_pool).unwrap(), AttributeInfo::SourceFile(_)));
+    assert_eq!(class.attributes_named("InnerClasses").count(), 1);
+    assert!(class.attribute("Deprecated").is_none());
+
+    class.set_attribute("Custom", vec![1, 2]);
+    class.set_attribute("Custom", vec![3]);
+    assert_eq!(class.attributes_named("Custom").map(|attribute| attribute.data.clone()).collect::<Vec<_>>(), [vec![3]]);
+    let count = class.attributes.len();
+    assert_eq!(class.remove_attribute("Custom").len(), 1);
+    assert!(class.remove_attribute("Custom").is_empty());
+    assert_eq!(class.attributes.len(), count - 1);
+
+    let pool = &mut class.constant_pool;
+    let field = &mut class.fields[0];
+    assert!(field.attribute(pool, "ConstantValue").is_some());
+    field.set_attribute(pool, "Synthetic", Vec::new());
+    assert!(field.attribute(pool, "Synthetic").is_some_and(|attribute| attribute.data.is_empty()));
+
+    let method = class.methods.iter_mut()
+        .find(|method| method.get_name(pool).unwrap() == "switchTable")
+        .unwrap();
+    let code = method.code(pool).unwrap().unwrap();
+    assert!(code.attribute(pool, "LineNumberTable").is_some());
+    assert_eq!(code.attributes_named(pool, "StackMapTable").count(), 1);
+    let max_stack = code.max_stack;
+
+    let code = method.code_mut(pool).unwrap().unwrap();
+    code.max_stack += 1;
+    assert_eq!(code.remove_attribute(pool, "LocalVariableTable").len(), 1);
+    assert!(code.attribute(pool, "LocalVariableTable").is_none());
+    assert!(method.remove_attribute(pool, "Signature").is_empty());
+
+    let mut bytes = Vec::new();
+    class.write_to(&mut (&mut bytes).into()).unwrap();
+    let class = JClassInfo::from_bytes(&bytes).unwrap();
+    let pool = &class.constant_pool;
+    assert!(class.fields[0].attribute(pool, "Synthetic").is_some());
+    let method = class.methods.iter()
+        .find(|method| method.get_name(pool).unwrap() == "switchTable")
+        .unwrap();
+    let code = method.code(pool).unwrap().unwrap();
+    assert_eq!(code.max_stack, max_stack + 1);
+    assert!(code.attribute(pool, "LocalVariableTable").is_none());
+}
+
+#[test]
+fn test_send_sync() {
+    // 延迟解析的缓存不应影响解析结果在线程间共享
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<OriginAttribute>();
+    assert_send_sync::<FieldInfo>();
+    assert_send_sync::<MethodInfo>();
+    assert_send_sync::<JClassInfo>();
+}
@@ -1,12 +1,11 @@
 use std::cmp::{max, min};
-use std::collections::HashSet;
 use std::fs::{read, File};
 use std::io::{BufWriter, Cursor, Read};
 use std::time::Instant;
+use jclass::access_flags::MethodAccessFlags;
 use jclass::jclass_info::JClassInfo;
 use jclass::attribute_info::CodeAttribute;
 use jclass::common::constants::CODE_TAG;
-use jclass::constant_pool::ConstantValue;
 use jclass::util::class_scan::fast_scan_class;
 
 #[cfg(target_os = "linux")]
@@ -20,7 +19,7 @@ fn base_test() {
     let now = Instant::now();
     let info = JClassInfo::from_reader(&mut content.into());
     println!(">> {:?}", now.elapsed().as_nanos());
-    if let Ok(_) = info {
+    if info.is_ok() {
         // println!("{:?}", &info);
     }
 
@@ -36,39 +35,17 @@ fn base_test() {
         let now = Instant::now();
         let info = JClassInfo::from_reader(&mut cursor.into());
         if let Ok(info) = info {
-            let constant_count = info.constant_pool.get_constant_count();
-            let mut index_set = HashSet::with_capacity(5);
-            for i in 0..constant_count {
-                let value = info.constant_pool.get_constant_item(i);
-                match value {
-                    ConstantValue::ConstantString(utf8_index) => {
-                        if let ConstantValue::ConstantUtf8(utf8_str) = info.constant_pool.get_constant_item(*utf8_index) {
-                            if utf8_str == CODE_TAG {
-                                index_set.insert(i);
-                            }
-                        }
-                    }
-                    ConstantValue::ConstantUtf8(utf8_str) => {
-                        if utf8_str == CODE_TAG {
-                            index_set.insert(i);
-                        }
-                    }
-                    _ => {}
-                }
-            }
-            for method_info in info.methods {
+            for method_info in &info.methods {
                 let mut has_code = false;
-                for attribute_info in method_info.attributes {
-                    if index_set.contains(&attribute_info.name) {
-                        if let Ok(attr) = CodeAttribute::new_with_data(&attribute_info.data) {
-                            if attr.codes.len() <= 0 {
-                                println!("{}", attr.codes.len());
-                            }
-                            has_code = true;
+                for attribute_info in method_info.attributes_named(&info.constant_pool, CODE_TAG) {
+                    if let Ok(attr) = CodeAttribute::new_with_data(&attribute_info.data) {
+                        if attr.codes.is_empty() {
+                            println!("{}", attr.codes.len());
                         }
+                        has_code = true;
                     }
                 }
-                if !has_code && method_info.name != 161 {
+                if !has_code && !method_info.access().intersects(MethodAccessFlags::ABSTRACT | MethodAccessFlags::NATIVE) {
                     println!("not found code");
                 }
             }
@@ -151,43 +128,21 @@ fn test_to_bytes() {
     println!("{}", arr.len());
 
 
-    let constant_count = info.constant_pool.get_constant_count();
-    let mut index_set = HashSet::with_capacity(5);
-    for i in 0..constant_count {
-        let value = info.constant_pool.get_constant_item(i);
-        match value {
-            ConstantValue::ConstantString(utf8_index) => {
-                if let ConstantValue::ConstantUtf8(utf8_str) = info.constant_pool.get_constant_item(*utf8_index) {
-                    if utf8_str == CODE_TAG {
-                        index_set.insert(i);
-                    }
-                }
-            }
-            ConstantValue::ConstantUtf8(utf8_str) => {
-                if utf8_str == CODE_TAG {
-                    index_set.insert(i);
-                }
-            }
-            _ => {}
-        }
-    }
-    for method_info in info.methods {
+    for method_info in &info.methods {
         let mut has_code = false;
-        for attribute_info in method_info.attributes {
-            if index_set.contains(&attribute_info.name) {
-                if let Ok(attr) = CodeAttribute::new_with_data(&attribute_info.data) {
-                    if attr.codes.len() <= 0 {
-                        println!("{}", attr.codes.len());
-                    }
-                    has_code = true;
-                    let bytes = attr.to_bytes().unwrap();
-                    if bytes != attribute_info.data {
-                        println!("\n\ncode to byte not equals with origin data:\n{:?}\n---\n{:?}", &attribute_info.data, &bytes)
-                    }
+        for attribute_info in method_info.attributes_named(&info.constant_pool, CODE_TAG) {
+            if let Ok(attr) = CodeAttribute::new_with_data(&attribute_info.data) {
+                if attr.codes.is_empty() {
+                    println!("{}", attr.codes.len());
+                }
+                has_code = true;
+                let bytes = attr.to_bytes().unwrap();
+                if bytes != attribute_info.data {
+                    println!("\n\ncode to byte not equals with origin data:\n{:?}\n---\n{:?}", &attribute_info.data, &bytes)
                 }
             }
         }
-        if !has_code && method_info.name != 161 {
+        if !has_code && !method_info.access().intersects(MethodAccessFlags::ABSTRACT | MethodAccessFlags::NATIVE) {
             println!("not found code");
         }
     }
@@ -0,0 +1,191 @@
+use jclass::access_flags::{ClassAccessFlags, FieldAccessFlags, MethodAccessFlags};
+use jclass::attribute_info::AttributeInfo;
+use jclass::class_builder::ClassBuilder;
+use jclass::common::error::ErrorKind;
+use jclass::common::opcode::opcodes::*;
+use jclass::disassembler::disassemble;
+use jclass::jclass_info::JClassInfo;
+use jclass::verifier::verify_class;
+
+mod common;
+
+use common::hierarchy;
+
+/// 构建实现Runnable的gen/Counter：run循环累加count，main解析参数并在格式错误时回退为默认值
+fn build_counter() -> JClassInfo {
+    let mut builder = ClassBuilder::new("gen/Counter");
+    builder.interface("java/lang/Runnable")
+        .source_file("Counter.java")
+        .field(FieldAccessFlags::PRIVATE, "count", "I")
+        .constant_field(FieldAccessFlags::PUBLIC | FieldAccessFlags::STATIC | FieldAccessFlags::FINAL, "LIMIT", "I", 10).unwrap()
+        .constant_field(FieldAccessFlags::PUBLIC | FieldAccessFlags::STATIC | FieldAccessFlags::FINAL, "NAME", "Ljava/lang/String;", "counter").unwrap();
+
+    let mut init = builder.method(MethodAccessFlags::PUBLIC, "<init>", "()V");
+    init.line(1)
+        .op(ALOAD_0)
+        .invoke(INVOKESPECIAL, "java/lang/Object", "<init>", "()V").unwrap()
+        .op(RETURN);
+    init.finish().unwrap();
+
+    let mut run = builder.method(MethodAccessFlags::PUBLIC, "run", "()V");
+    let (head, end) = (run.new_label(), run.new_label());
+    run.op(ICONST_0)
+        .local(ISTORE, 1)
+        .place_label(head)
+        .local(ILOAD, 1)
+        .push_int(10)
+        .jump(IF_ICMPGE, end)
+        .op(ALOAD_0)
+        .op(DUP)
+        .field(GETFIELD, "gen/Counter", "count", "I")
+        .local(ILOAD, 1)
+        .op(IADD)
+        .field(PUTFIELD, "gen/Counter", "count", "I")
+        .iinc(1, 1)
+        .jump(GOTO, head)
+        .place_label(end)
+        .field(GETSTATIC, "java/lang/System", "out", "Ljava/io/PrintStream;")
+        .op(ALOAD_0)
+        .field(GETFIELD, "gen/Counter", "count", "I")
+        .invoke(INVOKEVIRTUAL, "java/io/PrintStream", "println", "(I)V").unwrap()
+        .op(RETURN);
+    run.finish().unwrap();
+
+    let mut parse = builder.method(MethodAccessFlags::PUBLIC | MethodAccessFlags::STATIC, "parse", "(Ljava/lang/String;)I");
+    let (start, stop, handler) = (parse.new_label(), parse.new_label(), parse.new_label());
+    parse.place_label(start)
+        .op(ALOAD_0)
+        .invoke(INVOKESTATIC, "java/lang/Integer", "parseInt", "(Ljava/lang/String;)I").unwrap()
+        .place_label(stop)
+        .op(IRETURN)
+        .place_label(handler)
+        .op(POP)
+        .push_int(-1)
+        .op(IRETURN)
+        .try_catch(start, stop, handler, Some("java/lang/NumberFormatException"))
+        .throws("java/lang/IllegalStateException");
+    parse.finish().unwrap();
+
+    let mut main = builder.method(MethodAccessFlags::PUBLIC | MethodAccessFlags::STATIC, "main", "([Ljava/lang/String;)V");
+    main.type_op(NEW, "gen/Counter")
+        .op(DUP)
+        .invoke(INVOKESPECIAL, "gen/Counter", "<init>", "()V").unwrap()
+        .invoke_interface(INVOKEINTERFACE, "java/lang/Runnable", "run", "()V").unwrap()
+        .ldc(100000)
+        .ldc(3i64)
+        .op(L2I)
+        .op(IADD)
+        .op(POP)
+        .op(RETURN);
+    main.finish().unwrap();
+
+    builder.method(MethodAccessFlags::PUBLIC | MethodAccessFlags::ABSTRACT, "unused", "()V").finish().unwrap();
+    builder.access(ClassAccessFlags::PUBLIC | ClassAccessFlags::SUPER | ClassAccessFlags::ABSTRACT);
+
+    let class = builder.build().unwrap();
+    let hierarchy = hierarchy(&class);
+    verify_class(&class, &hierarchy).unwrap();
+    class
+}
+
+fn code(class: &JClassInfo, name: &str) -> Option<jclass::attribute_info::CodeAttribute> {
+    let pool = &class.constant_pool;
+    let method = class.methods.iter().find(|method| method.get_name(pool).unwrap() == name).unwrap();
+    method.attributes.iter()
+        .filter(|attribute| pool.utf8(attribute.name).unwrap() == "Code")
+        .map(|attribute| match attribute.info(pool).unwrap() {
+            AttributeInfo::Code(code) => code.clone(),
+            _ => unreachable!(),
+        })
+        .next()
+}
+
+#[test]
+fn test_build_class() {
+    let class = build_counter();
+    assert_eq!(class.major_version, 52);
+    assert_eq!(class.this_class_name().unwrap(), "gen/Counter");
+    assert_eq!(class.super_class_name().unwrap(), Some("java/lang/Object"));
+    assert_eq!(class.interface_names().unwrap(), vec!["java/lang/Runnable"]);
+    assert_eq!(class.fields.len(), 3);
+    assert_eq!(class.methods.len(), 5);
+
+    let pool = &class.constant_pool;
+    let run = code(&class, "run").unwrap();
+    assert_eq!((run.max_stack, run.max_locals), (3, 2));
+    let tags: Vec<_> = run.attributes.iter().map(|attribute| pool.utf8(attribute.name).unwrap()).collect();
+    assert_eq!(tags, vec!["StackMapTable"]);
+    let parse = code(&class, "parse").unwrap();
+    assert_eq!(parse.exceptions.entries.len(), 1);
+    assert!(code(&class, "unused").is_none());
+
+    let text = disassemble(&class).unwrap();
+    let expected_lines = [
+        "public abstract class gen.Counter implements java.lang.Runnable {",
+        "  public static final int LIMIT;",
+        "  public static int parse(java.lang.String) throws java.lang.IllegalStateException;",
+        "       5: bipush        10",
+        "       7: invokeinterface #54,  1           // InterfaceMethod java/lang/Runnable.run:()V",
+        "      14: ldc2_w        #56                 // long 3l",
+    ];
+    for line in expected_lines {
+        assert!(text.lines().any(|l| l == line), "缺少: {}\n{}", line, text);
+    }
+
+    let mut bytes = Vec::new();
+    class.write_to(&mut (&mut bytes).into()).unwrap();
+    let parsed = JClassInfo::from_bytes(&bytes).unwrap();
+    let mut rewritten = Vec::new();
+    parsed.write_to(&mut (&mut rewritten).into()).unwrap();
+    assert_eq!(bytes, rewritten);
+}
+
+#[test]
+fn test_old_version_without_frames() {
+    let mut builder = ClassBuilder::new("gen/Old");
+    builder.version(49, 0).super_class(Some("java/lang/Thread"));
+    let mut method = builder.method(MethodAccessFlags::PUBLIC | MethodAccessFlags::STATIC, "abs", "(I)I");
+    let positive = method.new_label();
+    method.local(ILOAD, 0)
+        .jump(IFGE, positive)
+        .local(ILOAD, 0)
+        .op(INEG)
+        .op(IRETURN)
+        .place_label(positive)
+        .local(ILOAD, 0)
+        .op(IRETURN)
+        .max_stack(4);
+    method.finish().unwrap();
+    let class = builder.build().unwrap();
+    assert_eq!(class.super_class_name().unwrap(), Some("java/lang/Thread"));
+    let abs = code(&class, "abs").unwrap();
+    assert_eq!((abs.max_stack, abs.max_locals), (4, 1));
+    assert!(abs.attributes.is_empty());
+    verify_class(&class, &hierarchy(&class)).unwrap();
+}
+
+#[test]
+fn test_wide_operands() {
+    let mut builder = ClassBuilder::new("gen/Wide");
+    let mut method = builder.method(MethodAccessFlags::PUBLIC | MethodAccessFlags::STATIC, "wide", "()V");
+    method.push_int(70000)
+        .local(ISTORE, 300)
+        .iinc(300, 1000)
+        .push_int(-200)
+        .op(POP)
+        .op(RETURN);
+    method.finish().unwrap();
+    // invokeinterface的count只有一个字节
+    let mut method = builder.method(MethodAccessFlags::PUBLIC | MethodAccessFlags::STATIC, "many", "()V");
+    let descriptor = format!("({})V", "J".repeat(128));
+    let error = method.invoke_interface(INVOKEINTERFACE, "gen/Many", "call", &descriptor).err().unwrap();
+    assert_eq!(error.kind(), ErrorKind::LimitExceeded);
+    assert!(method.invoke_interface(INVOKEINTERFACE, "gen/Many", "call", &format!("({})V", "J".repeat(127))).is_ok());
+    let class = builder.build().unwrap();
+    let wide = code(&class, "wide").unwrap();
+    assert_eq!(wide.max_locals, 301);
+    let text = disassemble(&class).unwrap();
+    for line in ["       0: ldc           #7                  // int 70000", "       2: istore_w      300", "       6: iinc_w        300, 1000", "      12: sipush        -200"] {
+        assert!(text.lines().any(|l| l.starts_with(line)), "缺少: {}\n{}", line, text);
+    }
+}
@@ -0,0 +1,121 @@
+use std::fs::read;
+use jclass::access_flags::MethodAccessFlags;
+use jclass::attribute_info::AttributeInfo;
+use jclass::code_analysis::{compute_maxs, stack_effect};
+use jclass::common::error::ErrorKind;
+use jclass::common::opcode::opcodes;
+use jclass::constant_pool::ConstantPool;
+use jclass::descriptor::MethodDescriptor;
+use jclass::instruction::Instruction;
+use jclass::instruction_list::InstructionList;
+use jclass::jclass_info::JClassInfo;
+
+mod common;
+
+use common::sample_paths;
+
+#[test]
+fn test_sample_maxs() {
+    let mut count = 0;
+    for path in sample_paths() {
+        let class = JClassInfo::from_bytes(&read(&path).unwrap()).unwrap();
+        let pool = &class.constant_pool;
+        for method in &class.methods {
+            let descriptor = MethodDescriptor::parse(method.get_descriptor(pool).unwrap()).unwrap();
+            let argument_slots = descriptor.argument_slots(method.access().contains(MethodAccessFlags::STATIC));
+            for attribute in &method.attributes {
+                if let AttributeInfo::Code(code) = attribute.info(pool).unwrap() {
+                    let name = method.get_name(pool).unwrap();
+                    assert_eq!(compute_maxs(code, pool, argument_slots).unwrap(), (code.max_stack, code.max_locals), "{:?} {}", path, name);
+                    let mut copy = code.clone();
+                    (copy.max_stack, copy.max_locals) = (0, 0);
+                    copy.compute_maxs(pool, argument_slots).unwrap();
+                    assert_eq!((copy.max_stack, copy.max_locals), (code.max_stack, code.max_locals));
+                    count += 1;
+                }
+            }
+        }
+    }
+    assert!(count > 10);
+}
+
+#[test]
+fn test_type_dependent() {
+    let mut pool = ConstantPool::new(1);
+    let field = pool.add_fieldref("Foo", "value", "J");
+    let method = pool.add_methodref("Foo", "call", "(IJLjava/lang/String;)D");
+    let interface = pool.add_interface_methodref("Bar", "run", "()V");
+    let dynamic = pool.add_invoke_dynamic(0, "apply", "(I)Ljava/lang/Object;");
+    let effects = [
+        (Instruction::Field(opcodes::GETSTATIC, field), 2),
+        (Instruction::Field(opcodes::PUTSTATIC, field), -2),
+        (Instruction::Field(opcodes::GETFIELD, field), 1),
+        (Instruction::Field(opcodes::PUTFIELD, field), -3),
+        (Instruction::Invoke(opcodes::INVOKESTATIC, method), -2),
+        (Instruction::Invoke(opcodes::INVOKEVIRTUAL, method), -3),
+        (Instruction::InvokeInterface(interface, 1), -1),
+        (Instruction::InvokeDynamic(dynamic), 0),
+        (Instruction::MultiANewArray(0, 3), -2),
+        (Instruction::WideLocal(opcodes::DLOAD, 300), 2),
+        (Instruction::Simple(opcodes::DUP2_X2), 2),
+    ];
+    for (instruction, effect) in effects {
+        assert_eq!(stack_effect(&instruction, &pool).unwrap(), effect, "{:?}", instruction);
+    }
+    assert_eq!(stack_effect(&Instruction::Invoke(opcodes::INVOKESTATIC, field), &pool).unwrap_err().kind(), ErrorKind::InvalidDescriptor);
+}
+
+#[test]
+fn test_built_code() {
+    let pool = ConstantPool::new(1);
+    let mut list = InstructionList::new();
+    let (start, end, handler) = (list.new_label(), list.new_label(), list.new_label());
+    list.place_label(start)
+        .push(Instruction::Simple(opcodes::LCONST_1))
+        .push(Instruction::WideLocal(opcodes::LSTORE, 300))
+        .push(Instruction::Simple(opcodes::ICONST_0))
+        .jump(opcodes::IFEQ, end)
+        .push(Instruction::Simple(opcodes::DCONST_1))
+        .push(Instruction::Simple(opcodes::DUP2))
+        .push(Instruction::Simple(opcodes::DADD))
+        .push(Instruction::Simple(opcodes::POP2))
+        .place_label(end)
+        .push(Instruction::Simple(opcodes::RETURN))
+        .place_label(handler)
+        .push(Instruction::Simple(opcodes::ATHROW))
+        .add_exception_handler(start, end, handler, 0);
+    let mut code = list.build(0, 0).unwrap();
+    code.compute_maxs(&pool, 1).unwrap();
+    assert_eq!((code.max_stack, code.max_locals), (4, 302));
+
+    // 合并点的操作栈深度不一致
+    let mut list = InstructionList::new();
+    let end = list.new_label();
+    list.push(Instruction::Simple(opcodes::ICONST_0))
+        .push(Instruction::Simple(opcodes::ICONST_0))
+        .jump(opcodes::IFEQ, end)
+        .push(Instruction::Simple(opcodes::POP))
+        .place_label(end)
+        .push(Instruction::Simple(opcodes::RETURN));
+    let error = compute_maxs(&list.build(0, 0).unwrap(), &pool, 0).unwrap_err();
+    assert_eq!((error.kind(), error.offset), (ErrorKind::InvalidBytecode, Some(6)));
+
+    let mut list = InstructionList::new();
+    list.push(Instruction::Simple(opcodes::POP)).push(Instruction::Simple(opcodes::RETURN));
+    assert!(compute_maxs(&list.build(0, 0).unwrap(), &pool, 0).is_err());
+    let mut list = InstructionList::new();
+    list.push(Instruction::Simple(opcodes::NOP));
+    assert!(compute_maxs(&list.build(0, 0).unwrap(), &pool, 0).is_err());
+
+    // 仅异常处理器向操作栈压入异常对象
+    let mut list = InstructionList::new();
+    let (start, end, handler) = (list.new_label(), list.new_label(), list.new_label());
+    list.place_label(start)
+        .push(Instruction::Simple(opcodes::RETURN))
+        .place_label(end)
+        .place_label(handler)
+        .push(Instruction::Local(opcodes::ASTORE, 0))
+        .push(Instruction::Simple(opcodes::RETURN))
+        .add_exception_handler(start, end, handler, 0);
+    assert_eq!(compute_maxs(&list.build(0, 0).unwrap(), &pool, 0).unwrap(), (1, 1));
+}
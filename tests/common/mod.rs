@@ -0,0 +1,60 @@
+#![allow(dead_code)]
+
+use std::fs::read;
+use std::path::PathBuf;
+use jclass::jclass_info::JClassInfo;
+use jclass::stack_map::SimpleClassHierarchy;
+
+pub const SAMPLE_DIR: &str = "tests/resources/sample";
+
+/// 样例目录下所有class文件的路径，按文件名排序
+pub fn sample_paths() -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(SAMPLE_DIR).unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "class"))
+        .collect();
+    paths.sort();
+    paths
+}
+
+/// Sample.class的原始内容
+pub fn sample_data() -> Vec<u8> {
+    read(format!("{}/Sample.class", SAMPLE_DIR)).unwrap()
+}
+
+/// 解析样例目录下的name.class，name为不含包名的类文件名，如Sample$Inner
+pub fn load_class(name: &str) -> JClassInfo {
+    JClassInfo::from_bytes(&read(format!("{}/{}.class", SAMPLE_DIR, name)).unwrap()).unwrap()
+}
+
+/// 写出后重新解析，同时检查byte_size与写出的长度一致
+pub fn reload(class: &JClassInfo) -> JClassInfo {
+    let mut data = Vec::new();
+    class.write_to(&mut (&mut data).into()).unwrap();
+    assert_eq!(data.len(), class.byte_size());
+    JClassInfo::from_bytes(&data).unwrap()
+}
+
+pub fn sample_classes() -> Vec<JClassInfo> {
+    sample_paths().iter().map(|path| JClassInfo::from_bytes(&read(path).unwrap()).unwrap()).collect()
+}
+
+/// 包含class本身、测试中用到的JDK类型及a/Base、a/Left、a/Right的类层次
+pub fn hierarchy(class: &JClassInfo) -> SimpleClassHierarchy {
+    let mut hierarchy = SimpleClassHierarchy::new();
+    hierarchy.add_class(class).unwrap();
+    hierarchy.insert("java/lang/Runnable", None, true);
+    hierarchy.insert("java/util/List", None, true);
+    hierarchy.insert("java/lang/Throwable", Some("java/lang/Object"), false);
+    hierarchy.insert("java/lang/Exception", Some("java/lang/Throwable"), false);
+    hierarchy.insert("java/io/IOException", Some("java/lang/Exception"), false);
+    hierarchy.insert("java/lang/RuntimeException", Some("java/lang/Exception"), false);
+    hierarchy.insert("java/lang/NullPointerException", Some("java/lang/RuntimeException"), false);
+    hierarchy.insert("java/lang/ArithmeticException", Some("java/lang/RuntimeException"), false);
+    hierarchy.insert("java/lang/IllegalArgumentException", Some("java/lang/RuntimeException"), false);
+    hierarchy.insert("java/lang/NumberFormatException", Some("java/lang/IllegalArgumentException"), false);
+    hierarchy.insert("a/Base", Some("java/lang/Object"), false);
+    hierarchy.insert("a/Left", Some("a/Base"), false);
+    hierarchy.insert("a/Right", Some("a/Base"), false);
+    hierarchy
+}
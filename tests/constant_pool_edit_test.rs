@@ -0,0 +1,112 @@
+use jclass::attribute_info::AttributeInfo;
+use jclass::common::error::ConstantError;
+use jclass::constant_pool::ConstantValue;
+use jclass::instruction::{Instruction, Instructions};
+use jclass::jclass_info::JClassInfo;
+
+mod common;
+
+use common::{load_class, reload};
+
+/// 方法名及字节码中引用的常量，按名称展开以便比较重新编号前后的内容
+fn resolved_methods(class: &JClassInfo) -> Vec<String> {
+    let pool = &class.constant_pool;
+    let mut result = Vec::new();
+    for method in &class.methods {
+        result.push(format!("{}{}", method.get_name(pool).unwrap(), method.get_descriptor(pool).unwrap()));
+        for attribute in &method.attributes {
+            let AttributeInfo::Code(code) = attribute.info(pool).unwrap() else {
+                continue;
+            };
+            for item in Instructions::new(&code.codes) {
+                let (pc, instruction) = item.unwrap();
+                let index = match instruction {
+                    Instruction::Ldc(index) | Instruction::LdcW(index) | Instruction::Ldc2W(index) |
+                    Instruction::Field(_, index) | Instruction::Invoke(_, index) | Instruction::InvokeInterface(index, _) |
+                    Instruction::InvokeDynamic(index) | Instruction::Type(_, index) => Some(index),
+                    _ => None,
+                };
+                let operand = match index.map(|index| (index, pool.get(index).unwrap())) {
+                    Some((index, ConstantValue::ConstantClass(_))) => pool.class_name(index).unwrap().to_string(),
+                    Some((index, ConstantValue::ConstantString(_))) => pool.string(index).unwrap().to_string(),
+                    Some((_, ConstantValue::ConstantInvokeDynamic(bootstrap, name_type))) => {
+                        format!("{} {:?}", bootstrap, pool.name_and_type(*name_type).unwrap())
+                    }
+                    Some((index, ConstantValue::ConstantFieldref(..) | ConstantValue::ConstantMethodref(..) |
+                                 ConstantValue::ConstantInterfaceMethodref(..))) => format!("{:?}", pool.member_ref(index).unwrap()),
+                    Some((_, value)) => format!("{:?}", value),
+                    None => String::new(),
+                };
+                result.push(format!("{} {} {}", pc, instruction.opcode(), operand));
+            }
+        }
+    }
+    result
+}
+
+#[test]
+fn test_replace() {
+    let mut class = load_class("Sample$Inner");
+    let pool = &mut class.constant_pool;
+    let index = pool.iter()
+        .find(|(_, value)| matches!(value, ConstantValue::ConstantUtf8(name) if name == "value"))
+        .map(|(index, _)| index)
+        .unwrap();
+    let old = pool.replace(index, ConstantValue::ConstantUtf8("renamed".into())).unwrap();
+    assert!(matches!(old, ConstantValue::ConstantUtf8(name) if name == "value"));
+    assert_eq!(pool.utf8(index).unwrap(), "renamed");
+    // 替换后缓存失效，重新添加原值将得到新的索引
+    let added = pool.add_constant(ConstantValue::ConstantUtf8("value".into()));
+    assert_ne!(added, index);
+    assert_eq!(pool.add_constant(ConstantValue::ConstantUtf8("renamed".into())), index);
+
+    assert_eq!(pool.replace(index, ConstantValue::ConstantLong(1)), Err(ConstantError::SlotMismatch { index }));
+    assert_eq!(pool.replace(0, ConstantValue::ConstantInteger(1)).unwrap_err(), ConstantError::BadIndex { index: 0, count: pool.get_constant_count() });
+}
+
+#[test]
+fn test_compact_after_rename() {
+    for name in ["Sample$1", "Sample$Inner", "Sample$Keep", "Sample$Nested", "Text"] {
+        let mut class = load_class(name);
+        let mut expected = resolved_methods(&class);
+        let count = class.constant_pool.get_constant_count();
+
+        // 重命名最后一个方法，原名称常量不再被引用
+        let old_name = class.methods.last().unwrap().get_name(&class.constant_pool).unwrap().to_string();
+        let renamed = format!("{}Renamed", old_name);
+        class.methods.last_mut().unwrap().name = class.constant_pool.add_constant(ConstantValue::ConstantUtf8(renamed.clone()));
+        class.constant_pool.add_constant(ConstantValue::ConstantUtf8("unused".into()));
+        let position = expected.iter().rposition(|line| line.starts_with(&format!("{}(", old_name))).unwrap();
+        expected[position] = expected[position].replacen(&old_name, &renamed, 1);
+
+        class.compact_constant_pool().unwrap();
+        let pool = &class.constant_pool;
+        assert!(pool.get_constant_count() < count + 2, "{}", name);
+        assert!(pool.iter().all(|(_, value)| !matches!(value, ConstantValue::ConstantUtf8(text) if text == "unused")));
+
+        let class = reload(&class);
+        assert_eq!(resolved_methods(&class), expected, "{}", name);
+        assert!(class.this_class_name().unwrap().starts_with("sample/"));
+        for attribute in class.methods.iter().flat_map(|method| &method.attributes)
+            .chain(class.fields.iter().flat_map(|field| &field.attributes))
+            .chain(&class.attributes) {
+            attribute.info(&class.constant_pool).unwrap();
+        }
+    }
+}
+
+#[test]
+fn test_compact_keeps_wide_constants() {
+    let mut class = load_class("Sample$Nested");
+    let long_index = class.constant_pool.add_constant(ConstantValue::ConstantLong(7));
+    class.constant_pool.add_constant(ConstantValue::ConstantUtf8("unused".into()));
+    let mut used = vec![false; class.constant_pool.get_constant_count() as usize + 1];
+    used[long_index as usize] = true;
+    used[class.class_index as usize] = true;
+    let mapping = class.constant_pool.retain(&used);
+    let pool = &class.constant_pool;
+    // Class常量与其名称，以及Long常量和占位项
+    assert_eq!(pool.get_constant_count(), 4);
+    assert!(matches!(pool.get(mapping[long_index as usize]), Ok(ConstantValue::ConstantLong(7))));
+    assert_eq!(pool.class_name(mapping[class.class_index as usize]).unwrap(), "sample/Sample$Nested");
+}
@@ -0,0 +1,154 @@
+use std::io::Cursor;
+use jclass::attribute_info::AttributeInfo;
+use jclass::common::error::ConstantError;
+use jclass::classfile_constants::{JVM_REF_invokeInterface, JVM_REF_invokeStatic};
+use jclass::constant_pool::{ConstantPool, ConstantValue, MemberRef};
+
+mod common;
+
+use common::load_class;
+
+#[test]
+fn test_name_resolvers() {
+    let class = load_class("Sample");
+    let pool = &class.constant_pool;
+    assert_eq!(class.this_class_name().unwrap(), "sample/Sample");
+    assert_eq!(class.super_class_name().unwrap(), Some("java/lang/Object"));
+    assert_eq!(class.interface_names().unwrap(), ["java/io/Serializable", "java/lang/Comparable"]);
+
+    let method = class.methods.iter()
+        .find(|method| method.get_name(pool).unwrap() == "switchTable")
+        .unwrap();
+    assert_eq!(method.get_descriptor(pool).unwrap(), "(I)I");
+    let field = &class.fields[0];
+    assert_eq!(field.get_descriptor(pool).unwrap(), "J");
+
+    assert_eq!(pool.member_ref(1).unwrap(), MemberRef {
+        owner: "java/lang/Object",
+        name: "<init>",
+        descriptor: "()V",
+    });
+    assert!(pool.member_ref(2).is_err());
+    assert!(pool.utf8(1).is_err());
+
+    let mut strings = Vec::new();
+    for i in 1..=pool.get_constant_count() {
+        if let ConstantValue::ConstantString(_) = pool.get_constant_item(i) {
+            strings.push(pool.string(i).unwrap());
+        }
+    }
+    assert!(strings.contains(&"null map"));
+
+    let bootstrap = class.attributes.iter()
+        .find_map(|attribute| match attribute.to_attribute_info(pool).unwrap() {
+            AttributeInfo::BootstrapMethods(methods) => Some(methods),
+            _ => None,
+        })
+        .unwrap();
+    let handle = pool.method_handle(bootstrap[0].method_ref).unwrap();
+    assert_eq!(handle.member.owner, "java/lang/invoke/LambdaMetafactory");
+    assert_eq!(handle.member.name, "metafactory");
+}
+
+#[test]
+fn test_checked_access() {
+    let class = load_class("Sample");
+    let pool = &class.constant_pool;
+    let count = pool.get_constant_count();
+    assert_eq!(pool.get(0), Err(ConstantError::BadIndex { index: 0, count }));
+    assert_eq!(pool.get(count + 1), Err(ConstantError::BadIndex { index: count + 1, count }));
+    assert_eq!(pool.utf8(1), Err(ConstantError::KindMismatch { index: 1, expected: "Utf8", found: "Methodref" }));
+
+    let (long_index, _) = pool.iter()
+        .find(|(_, value)| matches!(value, ConstantValue::ConstantLong(0x1234567890)))
+        .unwrap();
+    assert_eq!(pool.get(long_index + 1), Err(ConstantError::PhantomIndex { index: long_index + 1 }));
+    assert!(pool.iter().all(|(index, _)| index != long_index + 1));
+
+    let mut expected = Vec::new();
+    let mut i = 1;
+    while i <= count {
+        let value = pool.get(i).unwrap();
+        expected.push((i, value));
+        i += match value {
+            ConstantValue::ConstantLong(_) | ConstantValue::ConstantDouble(_) => 2,
+            _ => 1,
+        };
+    }
+    assert_eq!(pool.iter().collect::<Vec<_>>(), expected);
+}
+
+#[test]
+fn test_typed_builders() {
+    let mut pool = ConstantPool::new(16);
+    let methodref = pool.add_methodref("java/lang/String", "length", "()I");
+    // Utf8×3、Class、NameAndType、Methodref
+    assert_eq!(pool.get_constant_count(), 6);
+    assert_eq!(pool.member_ref(methodref).unwrap(), MemberRef {
+        owner: "java/lang/String",
+        name: "length",
+        descriptor: "()I",
+    });
+    assert_eq!(pool.add_methodref("java/lang/String", "length", "()I"), methodref);
+    let class = pool.add_class("java/lang/String");
+    assert_eq!(pool.get_constant_count(), 6);
+    assert_eq!(pool.class_name(class).unwrap(), "java/lang/String");
+
+    let fieldref = pool.add_fieldref("java/lang/System", "out", "Ljava/io/PrintStream;");
+    let interface_methodref = pool.add_interface_methodref("java/util/List", "size", "()I");
+    assert!(matches!(pool.get(fieldref), Ok(ConstantValue::ConstantFieldref(..))));
+    assert!(matches!(pool.get(interface_methodref), Ok(ConstantValue::ConstantInterfaceMethodref(..))));
+    let string = pool.add_string("hello");
+    assert_eq!(pool.string(string).unwrap(), "hello");
+    let name_and_type = pool.add_name_and_type("run", "()V");
+    assert_eq!(pool.name_and_type(name_and_type).unwrap(), ("run", "()V"));
+
+    let long = pool.add_constant(ConstantValue::ConstantLong(1));
+    assert_eq!(pool.get(long + 1), Err(ConstantError::PhantomIndex { index: long + 1 }));
+    assert_eq!(pool.add_utf8("after"), long + 2);
+
+    let member = MemberRef {
+        owner: "java/lang/invoke/LambdaMetafactory",
+        name: "metafactory",
+        descriptor: "()V",
+    };
+    let handle = pool.add_method_handle(JVM_REF_invokeStatic as u8, &member, false);
+    assert_eq!(pool.method_handle(handle).unwrap().member, member);
+    let interface_handle = pool.add_method_handle(JVM_REF_invokeInterface as u8, &member, false);
+    let ConstantValue::ConstantMethodHandle(_, ref_index) = pool.get(interface_handle).unwrap() else {
+        panic!("不是MethodHandle常量");
+    };
+    assert!(matches!(pool.get(*ref_index), Ok(ConstantValue::ConstantInterfaceMethodref(..))));
+
+    let method_type = pool.add_method_type("()Ljava/lang/Runnable;");
+    assert!(matches!(pool.get(method_type), Ok(ConstantValue::ConstantMethodType(_))));
+    let indy = pool.add_invoke_dynamic(0, "run", "()Ljava/lang/Runnable;");
+    let ConstantValue::ConstantInvokeDynamic(0, name_type) = pool.get(indy).unwrap() else {
+        panic!("不是InvokeDynamic常量");
+    };
+    assert_eq!(pool.name_and_type(*name_type).unwrap(), ("run", "()Ljava/lang/Runnable;"));
+
+    let mut data = Vec::new();
+    pool.write_to(&mut (&mut data).into()).unwrap();
+    assert_eq!(data.len(), pool.byte_size());
+    let reloaded = ConstantPool::new_with_reader(&mut Cursor::new(data).into()).unwrap();
+    assert_eq!(reloaded.get_constant_count(), pool.get_constant_count());
+    assert_eq!(reloaded.iter().collect::<Vec<_>>(), pool.iter().collect::<Vec<_>>());
+}
+
+#[test]
+fn test_find() {
+    let class = load_class("Sample");
+    let pool = &class.constant_pool;
+    let count = pool.get_constant_count();
+    let index = pool.find(&ConstantValue::ConstantUtf8("switchTable".into())).unwrap();
+    assert_eq!(pool.utf8(index).unwrap(), "switchTable");
+    assert_eq!(pool.find(&ConstantValue::ConstantUtf8("missing".into())), None);
+    assert_eq!(pool.find(&ConstantValue::Null), None);
+    assert_eq!(pool.get_constant_count(), count);
+
+    let mut pool = pool.clone();
+    let added = pool.add_utf8("missing");
+    assert_eq!(pool.find(&ConstantValue::ConstantUtf8("missing".into())), Some(added));
+    assert_eq!(pool.find(&ConstantValue::ConstantUtf8("switchTable".into())), Some(index));
+}
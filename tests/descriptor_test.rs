@@ -0,0 +1,68 @@
+use jclass::descriptor::{binary_to_internal, internal_to_binary, FieldType, MethodDescriptor};
+
+mod common;
+
+use common::load_class;
+
+#[test]
+fn test_field_type() {
+    let field_type = FieldType::parse("[[Ljava/lang/String;").unwrap();
+    assert_eq!(field_type, FieldType::Array(Box::new(FieldType::Array(Box::new(FieldType::Object("java/lang/String".into()))))));
+    assert_eq!(field_type.dimensions(), 2);
+    assert_eq!(field_type.element_type(), &FieldType::Object("java/lang/String".into()));
+    assert_eq!(field_type.java_name(), "java.lang.String[][]");
+    assert_eq!(field_type.to_string(), "[[Ljava/lang/String;");
+    assert_eq!(field_type.slot_size(), 1);
+    assert!(field_type.is_reference());
+
+    assert_eq!("J".parse::<FieldType>().unwrap().slot_size(), 2);
+    assert_eq!("D".parse::<FieldType>().unwrap().slot_size(), 2);
+    assert!(FieldType::Int.is_primitive());
+    for invalid in ["", "V", "L;", "Ljava/lang/String", "II", "[", "Q"] {
+        assert!(FieldType::parse(invalid).is_err(), "{}", invalid);
+    }
+}
+
+#[test]
+fn test_method_descriptor() {
+    let descriptor = MethodDescriptor::parse("(IJ[DLjava/lang/Object;)V").unwrap();
+    assert_eq!(descriptor.parameters, [
+        FieldType::Int,
+        FieldType::Long,
+        FieldType::Array(Box::new(FieldType::Double)),
+        FieldType::Object("java/lang/Object".into()),
+    ]);
+    assert_eq!(descriptor.return_type, None);
+    assert_eq!(descriptor.parameter_slots(), 5);
+    assert_eq!(descriptor.argument_slots(false), 6);
+    assert_eq!(descriptor.argument_slots(true), 5);
+    assert_eq!(descriptor.return_slots(), 0);
+    assert_eq!(descriptor.to_string(), "(IJ[DLjava/lang/Object;)V");
+
+    let descriptor = MethodDescriptor::new(Vec::new(), Some(FieldType::Double));
+    assert_eq!(descriptor.to_string(), "()D");
+    assert_eq!(descriptor.return_slots(), 2);
+    for invalid in ["", "()", "(V)V", "(I", "I)V", "()VV", "()[V"] {
+        assert!(MethodDescriptor::parse(invalid).is_err(), "{}", invalid);
+    }
+}
+
+#[test]
+fn test_sample_descriptors() {
+    let class = load_class("Sample");
+    let pool = &class.constant_pool;
+    for method in &class.methods {
+        let text = method.get_descriptor(pool).unwrap();
+        assert_eq!(MethodDescriptor::parse(text).unwrap().to_string(), text);
+    }
+    for field in &class.fields {
+        let text = field.get_descriptor(pool).unwrap();
+        assert_eq!(FieldType::parse(text).unwrap().to_string(), text);
+    }
+}
+
+#[test]
+fn test_name_conversion() {
+    assert_eq!(internal_to_binary("java/util/Map$Entry"), "java.util.Map$Entry");
+    assert_eq!(binary_to_internal("java.util.Map$Entry"), "java/util/Map$Entry");
+}
@@ -0,0 +1,103 @@
+use jclass::disassembler::{disassemble, disassemble_verbose};
+
+mod common;
+
+use common::{load_class, sample_classes};
+
+#[test]
+fn test_disassemble_text() {
+    let expected = r#"Compiled from "Text.java"
+public class sample.Text {
+  public static final java.lang.String NUL_TEXT;
+
+  public static final java.lang.String EMOJI_TEXT;
+
+  public static final java.lang.String MIXED_TEXT;
+
+  public static final java.lang.String LONE_SURROGATE;
+
+  public sample.Text();
+    Code:
+       0: aload_0
+       1: invokespecial #1                  // Method java/lang/Object."<init>":()V
+       4: return
+}
+"#;
+    assert_eq!(disassemble(&load_class("Text")).unwrap(), expected);
+}
+
+#[test]
+fn test_disassemble_sample() {
+    let text = disassemble(&load_class("Sample")).unwrap();
+    let expected_lines = [
+        "public class sample.Sample<T extends java.lang.Comparable<T>> implements java.io.Serializable, java.lang.Comparable<sample.Sample<T>> {",
+        "  protected transient java.util.Map<java.lang.String, ? extends java.lang.Number> counts;",
+        "  public <R extends java.lang.Number> R generic(java.util.Map<java.lang.String, ? super R>, R) throws java.io.IOException;",
+        "       1: tableswitch   { // 0 to 3",
+        "                     3: 41",
+        "               default: 44",
+        "          }",
+        "    Exception table:",
+        "           3    25    34   Class java/lang/NullPointerException",
+        "          50    52    50   any",
+        "      11: invokeinterface #40,  1           // InterfaceMethod java/lang/Runnable.run:()V",
+        "       4: multianewarray #49,  2            // class \"[[I\"",
+        "     324: iinc_w        142, 1000",
+    ];
+    for line in expected_lines {
+        assert!(text.lines().any(|l| l == line), "缺少: {}", line);
+    }
+    // 普通模式不输出常量池及属性
+    assert!(!text.contains("Constant pool:"));
+    assert!(!text.contains("LineNumberTable:"));
+
+    let inner = disassemble(&load_class("Sample$1")).unwrap();
+    assert!(inner.contains("  sample.Sample$1();\n"));
+}
+
+#[test]
+fn test_disassemble_verbose() {
+    let text = disassemble_verbose(&load_class("Sample")).unwrap();
+    let expected_lines = [
+        "  Compiled from \"Sample.java\"",
+        "  major version: 61",
+        "  flags: (0x0021) ACC_PUBLIC, ACC_SUPER",
+        "  this_class: #11                         // sample/Sample",
+        "  interfaces: 2, fields: 5, methods: 11, attributes: 5",
+        "    #1 = Methodref          #2.#3         // java/lang/Object.\"<init>\":()V",
+        "  #264 = Utf8               BootstrapMethods",
+        "    ConstantValue: long 78187493520l",
+        "      0: #109(#110=s#111,#112=I#113)",
+        "          value=\"field\"",
+        "      stack=6, locals=11, args_size=5",
+        "            0      46     0  this   Lsample/Sample<TT;>;",
+        "      StackMapTable: number_of_entries = 5",
+        "        frame_type = 32 /* same */",
+        "      Name                           Flags",
+        "      #274 REF_invokeStatic sample/Sample.lambda$lambda$0:(Ljava/lang/String;)Ljava/lang/String;",
+        "    RuntimeVisibleParameterAnnotations:",
+        "      parameter 2:",
+        "        0: #109(#110=s#159)",
+    ];
+    for line in expected_lines {
+        assert!(text.lines().any(|l| l == line), "缺少: {}", line);
+    }
+
+    let nullable = disassemble_verbose(&load_class("Sample$Nullable")).unwrap();
+    assert!(nullable.contains("      value=[Ljava/lang/annotation/ElementType;.TYPE_USE]\n"));
+    assert!(nullable.contains("  static #21= #1 of #18;                  // Nullable=class sample/Sample$Nullable of class sample/Sample\n"));
+    assert!(nullable.contains("NestHost: class sample/Sample\n"));
+
+    let text = disassemble_verbose(&load_class("Text")).unwrap();
+    assert!(text.contains("    ConstantValue: String nul\\u0000char\n"), "{}", text);
+}
+
+#[test]
+fn test_disassemble_all() {
+    for class in sample_classes() {
+        let plain = disassemble(&class).unwrap();
+        let verbose = disassemble_verbose(&class).unwrap();
+        assert!(plain.ends_with("}\n"));
+        assert!(verbose.len() > plain.len());
+    }
+}
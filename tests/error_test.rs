@@ -0,0 +1,105 @@
+use std::io::{Cursor, Read};
+use jclass::attribute_info::{AttributeInfo, OriginAttribute};
+use jclass::common::error::{ErrorKind, MessageError, Result};
+use jclass::jclass_info::JClassInfo;
+use jclass::jclass_info_ref::JClassInfoRef;
+use jclass::with_message;
+
+mod common;
+
+use common::sample_data;
+
+fn parse(data: &[u8]) -> Result<JClassInfo> {
+    JClassInfo::from_reader(&mut Cursor::new(data).into())
+}
+
+#[test]
+fn test_truncated() {
+    let data = sample_data();
+    for len in [0, 3, 9, 100, data.len() / 2, data.len() - 1] {
+        let error = parse(&data[..len]).unwrap_err();
+        assert!(error.is_eof(), "{}: {}", len, error);
+        assert!(error.offset.unwrap() <= len, "{}: {}", len, error);
+        let error = JClassInfoRef::from_bytes(&data[..len]).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::UnexpectedEof, "{}: {}", len, error);
+    }
+    let class = parse(&data).unwrap();
+    let methods_start = 8 + class.constant_pool.byte_size() + 8 + 2 * class.interfaces.len()
+        + 2 + class.fields.iter().map(|field| field.byte_size()).sum::<usize>();
+    let error = parse(&data[..methods_start + 10]).unwrap_err();
+    assert!(error.path.starts_with("methods[0]"), "{}", error);
+    let error = JClassInfoRef::from_bytes(&data[..methods_start + 10]).unwrap_err();
+    assert!(error.path.starts_with("methods[0]"), "{}", error);
+}
+
+#[test]
+fn test_malformed() {
+    let mut data = sample_data();
+    data[0] = 0;
+    let error = parse(&data).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::BadMagic);
+    assert_eq!(error.offset, Some(0));
+    assert_eq!(JClassInfoRef::from_bytes(&data).unwrap_err().kind(), ErrorKind::BadMagic);
+
+    let mut data = sample_data();
+    // 魔术头、版本与常量数量之后为第一个常量的类型
+    data[10] = 99;
+    let error = parse(&data).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidConstantTag);
+    assert_eq!(error.offset, Some(10));
+    assert_eq!(error.path, "constant_pool[1]");
+    assert_eq!(error.to_string(), "constant_pool[1]: 无效的常量类型[99] (偏移10)");
+    let error = JClassInfoRef::from_bytes(&data).unwrap_err();
+    assert_eq!((error.kind(), error.offset, error.path.as_str()), (ErrorKind::InvalidConstantTag, Some(10), "constant_pool[1]"));
+}
+
+#[test]
+fn test_attribute_path() {
+    let class = parse(&sample_data()).unwrap();
+    let pool = &class.constant_pool;
+    let code = class.methods.iter()
+        .flat_map(|method| &method.attributes)
+        .find(|attribute| matches!(attribute.info(pool), Ok(AttributeInfo::Code(_))))
+        .unwrap();
+    let mut data = code.data.clone();
+    data.truncate(data.len() - 1);
+    let error = OriginAttribute::new(code.name, data).to_attribute_info(pool).unwrap_err();
+    assert!(error.is_eof());
+    assert!(error.path.starts_with("Code.attributes["), "{}", error);
+
+    let mut data = code.data.clone();
+    data.push(0);
+    let error = AttributeInfo::new_with_data("Code", &data).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidAttribute);
+    assert_eq!(error.offset, Some(code.data.len()));
+    assert_eq!(error.path, "Code");
+
+    // 无法识别的属性可能包含常量索引，不能重新编号
+    let mut class = parse(&sample_data()).unwrap();
+    let name = class.constant_pool.add_utf8("Custom");
+    class.methods[1].attributes.push(OriginAttribute::new(name, vec![0, 1]));
+    let count = class.constant_pool.get_constant_count();
+    let error = class.compact_constant_pool().unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::UnsupportedAttribute);
+    assert!(error.path.starts_with("methods[1]"), "{}", error);
+    // 失败时不修改常量池与任何属性
+    assert_eq!(class.constant_pool.get_constant_count(), count);
+    assert!(class.methods.iter().flat_map(|method| &method.attributes).all(|attribute| !attribute.is_modified()));
+}
+
+#[test]
+fn test_with_message() {
+    let mut buf = [0; 4];
+    let result: Result<()> = with_message!(Cursor::new([1u8, 2]).read_exact(&mut buf), "读取测试数据");
+    let error = result.unwrap_err();
+    assert!(error.is_eof());
+    assert!(error.msg.starts_with("读取测试数据: "));
+
+    let inner = MessageError::of(ErrorKind::BadIndex, "索引错误").at(3).in_path("[2]").in_path("interfaces");
+    assert_eq!(inner.path, "interfaces[2]");
+    let result: Result<()> = with_message!(Err::<(), _>(inner), "外层");
+    let error = result.unwrap_err();
+    assert_eq!((error.kind(), error.offset), (ErrorKind::BadIndex, Some(3)));
+    assert_eq!(error.to_string(), "interfaces[2]: 外层: 索引错误 (偏移3)");
+    assert_eq!(MessageError::new("其他").kind(), ErrorKind::Other);
+}
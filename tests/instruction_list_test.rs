@@ -0,0 +1,102 @@
+use jclass::attribute_info::CodeAttribute;
+use jclass::common::error::ErrorKind;
+use jclass::common::opcode::opcodes;
+use jclass::instruction::Instruction;
+use jclass::instruction_list::{inverse_condition, InstructionList};
+
+fn decode(code: &CodeAttribute) -> Vec<(u32, Instruction)> {
+    code.instructions().map(|item| item.unwrap()).collect()
+}
+
+#[test]
+fn test_labels() {
+    let mut list = InstructionList::new();
+    let (loop_start, end, default, handler) = (list.new_label(), list.new_label(), list.new_label(), list.new_label());
+    list.place_label(loop_start)
+        .push(Instruction::Simple(opcodes::ILOAD_1))
+        .jump(opcodes::IFEQ, end)
+        .push(Instruction::Simple(opcodes::ILOAD_1))
+        .table_switch(0, default, vec![loop_start, end])
+        .place_label(default)
+        .jump(opcodes::GOTO, loop_start)
+        .place_label(end)
+        .push(Instruction::Simple(opcodes::RETURN))
+        .place_label(handler)
+        .push(Instruction::Simple(opcodes::ATHROW))
+        .add_exception_handler(loop_start, end, handler, 0);
+    let code = list.build(1, 2).unwrap();
+    assert_eq!((code.max_stack, code.max_locals), (1, 2));
+    assert_eq!(decode(&code), vec![
+        (0, Instruction::Simple(opcodes::ILOAD_1)),
+        (1, Instruction::Branch(opcodes::IFEQ, 31)),
+        (4, Instruction::Simple(opcodes::ILOAD_1)),
+        (5, Instruction::TableSwitch { default: 28, low: 0, targets: vec![0, 31] }),
+        (28, Instruction::Branch(opcodes::GOTO, 0)),
+        (31, Instruction::Simple(opcodes::RETURN)),
+        (32, Instruction::Simple(opcodes::ATHROW)),
+    ]);
+    let entry = &code.exceptions.entries[0];
+    assert_eq!((entry.start_pc, entry.end_pc, entry.handler_pc, entry.catch_type), (0, 31, 32, 0));
+    assert_eq!(list.assemble().unwrap().label_pc(default), Some(28));
+}
+
+/// 在跳转与目标之间填充超过32767字节的指令
+fn far_jump(opcode: u8) -> Vec<(u32, Instruction)> {
+    let mut list = InstructionList::new();
+    let (target, back) = (list.new_label(), list.new_label());
+    list.place_label(back)
+        .push(Instruction::Simple(opcodes::ICONST_0))
+        .jump(opcode, target)
+        .lookup_switch(back, vec![(1, target)]);
+    for _ in 0..11000 {
+        list.push(Instruction::Sipush(1));
+    }
+    list.place_label(target).push(Instruction::Simple(opcodes::RETURN));
+    decode(&list.build(1, 1).unwrap())
+}
+
+#[test]
+fn test_widen() {
+    let instructions = far_jump(opcodes::GOTO);
+    let target = instructions.last().unwrap().0;
+    assert_eq!(instructions[1], (1, Instruction::BranchW(opcodes::GOTO_W, target)));
+    // lookupswitch在goto_w变长后重新填充
+    assert_eq!(instructions[2].0, 6);
+
+    let instructions = far_jump(opcodes::JSR);
+    assert_eq!(instructions[1].1, Instruction::BranchW(opcodes::JSR_W, target));
+
+    let instructions = far_jump(opcodes::IF_ICMPLT);
+    let target = instructions.last().unwrap().0;
+    assert_eq!(instructions[1], (1, Instruction::Branch(opcodes::IF_ICMPGE, 9)));
+    assert_eq!(instructions[2], (4, Instruction::BranchW(opcodes::GOTO_W, target)));
+    assert_eq!(instructions[3].0, 9);
+
+    assert_eq!(inverse_condition(opcodes::IFNULL), opcodes::IFNONNULL);
+    assert_eq!(inverse_condition(opcodes::IF_ACMPNE), opcodes::IF_ACMPEQ);
+}
+
+#[test]
+fn test_invalid() {
+    let mut list = InstructionList::new();
+    let label = list.new_label();
+    list.jump(opcodes::GOTO, label);
+    assert_eq!(list.assemble().unwrap_err().kind(), ErrorKind::InvalidBytecode);
+    list.place_label(label).place_label(label);
+    assert_eq!(list.assemble().unwrap_err().kind(), ErrorKind::InvalidBytecode);
+
+    let mut list = InstructionList::new();
+    list.push(Instruction::Branch(opcodes::GOTO, 0));
+    assert!(list.assemble().is_err());
+
+    let mut list = InstructionList::new();
+    let label = list.new_label();
+    list.place_label(label).jump(opcodes::IADD, label);
+    assert!(list.assemble().is_err());
+
+    let mut list = InstructionList::new();
+    let (start, end) = (list.new_label(), list.new_label());
+    list.place_label(end).place_label(start).push(Instruction::Simple(opcodes::RETURN))
+        .add_exception_handler(start, end, start, 0);
+    assert!(list.assemble().is_err());
+}
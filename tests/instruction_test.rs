@@ -0,0 +1,108 @@
+use jclass::attribute_info::{AttributeInfo, CodeAttribute};
+use jclass::common::error::ErrorKind;
+use jclass::common::opcode::opcodes;
+use jclass::instruction::{encode_instructions, Instruction, Instructions};
+use jclass::jclass_info::JClassInfo;
+
+mod common;
+
+use common::load_class;
+
+fn method_codes(class: &JClassInfo) -> Vec<(String, CodeAttribute)> {
+    let pool = &class.constant_pool;
+    let mut codes = Vec::new();
+    for method in &class.methods {
+        for attribute in &method.attributes {
+            if let AttributeInfo::Code(code) = attribute.info(pool).unwrap() {
+                codes.push((method.get_name(pool).unwrap().to_string(), code.clone()));
+            }
+        }
+    }
+    codes
+}
+
+fn decode_method(name: &str) -> Vec<(u32, Instruction)> {
+    let class = load_class("Sample");
+    let (_, code) = method_codes(&class).into_iter().find(|(method, _)| method == name).unwrap();
+    code.instructions().collect::<jclass::common::error::Result<_>>().unwrap()
+}
+
+#[test]
+fn test_round_trip() {
+    let class = load_class("Sample");
+    for (name, code) in method_codes(&class) {
+        let decoded: Vec<_> = code.instructions().map(|item| item.unwrap()).collect();
+        let mut pc = 0;
+        for (at, instruction) in &decoded {
+            assert_eq!(*at, pc, "{}", name);
+            pc += instruction.byte_size(pc) as u32;
+        }
+        assert_eq!(pc as usize, code.codes.len(), "{}", name);
+        let instructions: Vec<_> = decoded.into_iter().map(|(_, instruction)| instruction).collect();
+        assert_eq!(encode_instructions(&instructions).unwrap(), code.codes, "{}", name);
+    }
+}
+
+#[test]
+fn test_operands() {
+    let instructions = decode_method("switchTable");
+    assert_eq!(instructions[1], (1, Instruction::TableSwitch { default: 44, low: 0, targets: vec![32, 35, 38, 41] }));
+    assert_eq!(instructions[2], (32, Instruction::Bipush(10)));
+
+    let instructions = decode_method("switchLookup");
+    match &instructions[1].1 {
+        Instruction::LookupSwitch { pairs, .. } => assert_eq!(pairs.len(), 3),
+        instruction => panic!("{:?}", instruction),
+    }
+
+    let instructions = decode_method("wideLocals");
+    assert!(instructions.iter().any(|(_, instruction)| *instruction == Instruction::WideIinc(142, 1000)));
+    let codes = [opcodes::WIDE, opcodes::LSTORE, 0x01, 0x2C];
+    assert_eq!(Instruction::decode(&codes, 0).unwrap(), Instruction::WideLocal(opcodes::LSTORE, 300));
+    assert_eq!(encode_instructions(&[Instruction::WideLocal(opcodes::LSTORE, 300)]).unwrap(), codes);
+
+    let class = load_class("Sample");
+    let all: Vec<_> = method_codes(&class).iter()
+        .flat_map(|(_, code)| code.instructions().map(|item| item.unwrap().1).collect::<Vec<_>>())
+        .collect();
+    assert!(all.iter().any(|instruction| matches!(instruction, Instruction::InvokeInterface(_, 1))));
+    assert!(all.iter().any(|instruction| matches!(instruction, Instruction::MultiANewArray(_, 2))));
+}
+
+#[test]
+fn test_switch_padding() {
+    let switch = Instruction::LookupSwitch { default: 20, pairs: vec![(-1, 30)] };
+    for pc in 0..4 {
+        let mut codes = vec![opcodes::NOP; pc as usize];
+        switch.encode_to(pc, &mut codes).unwrap();
+        assert_eq!(codes.len() - pc as usize, switch.byte_size(pc));
+        assert_eq!(codes.len() % 4, 0);
+        assert_eq!(Instruction::decode(&codes, pc).unwrap(), switch);
+    }
+}
+
+#[test]
+fn test_invalid() {
+    let errors = [
+        (vec![opcodes::BIPUSH], ErrorKind::UnexpectedEof),
+        (vec![opcodes::WIDE, opcodes::IADD, 0, 0], ErrorKind::InvalidBytecode),
+        (vec![0xFE], ErrorKind::InvalidBytecode),
+        (vec![opcodes::GOTO, 0xFF, 0xF0], ErrorKind::InvalidBytecode),
+        (vec![opcodes::TABLESWITCH, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0], ErrorKind::InvalidBytecode),
+        (vec![opcodes::LOOKUPSWITCH, 0, 0, 0, 0, 0, 0, 0, 0x7F, 0xFF, 0xFF, 0xFF], ErrorKind::UnexpectedEof),
+    ];
+    for (codes, kind) in errors {
+        let error = Instruction::decode(&codes, 0).unwrap_err();
+        assert_eq!((error.kind(), error.offset), (kind, Some(0)), "{:?}", codes);
+    }
+
+    let mut iter = Instructions::new(&[opcodes::NOP, 0xFE, opcodes::NOP]);
+    assert_eq!(iter.next().unwrap().unwrap(), (0, Instruction::Simple(opcodes::NOP)));
+    assert_eq!(iter.next().unwrap().unwrap_err().offset, Some(1));
+    assert!(iter.next().is_none());
+
+    assert!(encode_instructions(&[Instruction::Simple(opcodes::BIPUSH)]).is_err());
+    assert!(encode_instructions(&[Instruction::Ldc(256)]).is_err());
+    assert!(encode_instructions(&[Instruction::Branch(opcodes::GOTO, 40000)]).is_err());
+    assert_eq!(encode_instructions(&[Instruction::BranchW(opcodes::GOTO_W, 40000)]).unwrap().len(), 5);
+}
@@ -0,0 +1,73 @@
+use std::borrow::Cow;
+use std::fs::read;
+use jclass::attribute_info::AttributeInfo;
+use jclass::common::error::ErrorKind;
+use jclass::constant_pool::ConstantValue;
+use jclass::jclass_info::JClassInfo;
+use jclass::jclass_info_ref::JClassInfoRef;
+
+const SAMPLE_CLASS_PATH: &str = "tests/resources/sample/Sample.class";
+const TEXT_CLASS_PATH: &str = "tests/resources/sample/Text.class";
+
+#[test]
+fn test_ref_matches_full_parse() {
+    let content = read(SAMPLE_CLASS_PATH).unwrap();
+    let info_ref = JClassInfoRef::from_bytes(&content).unwrap();
+    let info = info_ref.to_class_info().unwrap();
+
+    assert_eq!(info_ref.constant_pool.get_constant_count(), info.constant_pool.get_constant_count());
+    for i in 0..=info.constant_pool.get_constant_count() {
+        assert_eq!(&info_ref.constant_pool.get_constant_item(i).unwrap(), info.constant_pool.get_constant_item(i));
+    }
+    assert_eq!(info_ref.constant_pool.class_name(info_ref.class_index).unwrap(), "sample/Sample");
+    assert_eq!(info_ref.interfaces, info.interfaces);
+    assert_eq!(info_ref.methods.len(), info.methods.len());
+    for (method_ref, method) in info_ref.methods.iter().zip(&info.methods) {
+        assert_eq!(method_ref.name, method.name);
+        assert_eq!(method_ref.attributes.len(), method.attributes.len());
+        for (attribute_ref, attribute) in method_ref.attributes.iter().zip(&method.attributes) {
+            assert_eq!(attribute_ref.data, attribute.data.as_slice());
+        }
+        let code = method_ref.find_attribute(&info_ref.constant_pool, "Code").unwrap();
+        assert!(matches!(code.to_attribute_info(&info_ref.constant_pool).unwrap(), AttributeInfo::Code(_)));
+    }
+    assert!(info_ref.find_attribute("SourceFile").is_some());
+    assert!(info_ref.find_attribute("Code").is_none());
+}
+
+#[test]
+fn test_ref_borrows_utf8() {
+    let content = read(TEXT_CLASS_PATH).unwrap();
+    let info_ref = JClassInfoRef::from_bytes(&content).unwrap();
+    let pool = &info_ref.constant_pool;
+    let mut borrowed = 0;
+    for i in 1..=pool.get_constant_count() {
+        if let ConstantValue::ConstantUtf8(string) = pool.get_constant_item(i).unwrap() {
+            match pool.utf8(i).unwrap() {
+                Cow::Borrowed(value) => {
+                    assert_eq!(value, string);
+                    borrowed += 1;
+                }
+                Cow::Owned(value) => {
+                    assert_eq!(value, string);
+                    assert!(string.contains('\0') || string.chars().any(|c| c as u32 >= 0x10000));
+                }
+            }
+        }
+    }
+    assert!(borrowed > 0);
+    assert!(JClassInfoRef::from_bytes(&content[..content.len() - 1]).is_err());
+
+    // 标准UTF-8的增补字符与孤立代理项在两种解析方式中都不是字符串
+    let mut class = JClassInfo::from_bytes(&content).unwrap();
+    let indices = ["emoji😀".as_bytes(), b"lone\xED\xA0\x80"].map(|bytes| class.constant_pool.add_constant(ConstantValue::ConstantUtf8Raw(bytes.to_vec())));
+    let mut content = Vec::new();
+    class.write_to(&mut (&mut content).into()).unwrap();
+    let info_ref = JClassInfoRef::from_bytes(&content).unwrap();
+    let info = info_ref.to_class_info().unwrap();
+    for index in indices {
+        assert!(matches!(info.constant_pool.get_constant_item(index), ConstantValue::ConstantUtf8Raw(_)));
+        assert_eq!(info_ref.constant_pool.get_constant_item(index).unwrap(), info.constant_pool.get_constant_item(index).clone());
+        assert_eq!(info_ref.constant_pool.utf8(index).unwrap_err().kind(), ErrorKind::InvalidUtf8);
+    }
+}
@@ -0,0 +1,108 @@
+use jclass::access_flags::MethodAccessFlags;
+use jclass::constant_pool::{ConstantValue, MemberRef};
+use jclass::jclass_info::JClassInfo;
+
+mod common;
+
+use common::{load_class, reload};
+
+/// 常量池中所属类为本类的成员引用
+fn self_references(class: &JClassInfo) -> Vec<String> {
+    let pool = &class.constant_pool;
+    let this_class = class.this_class_name().unwrap();
+    pool.iter()
+        .filter_map(|(index, _)| pool.member_ref(index).ok())
+        .filter(|member| member.owner == this_class)
+        .map(|member| format!("{}:{}", member.name, member.descriptor))
+        .collect()
+}
+
+#[test]
+fn test_find_members() {
+    let mut class = load_class("Sample");
+    let pool = &class.constant_pool;
+    let method = class.find_method("switchTable", "(I)I").unwrap();
+    assert_eq!(method.get_name(pool).unwrap(), "switchTable");
+    assert!(method.access().contains(MethodAccessFlags::SYNCHRONIZED));
+    assert!(class.find_method("switchTable", "(J)I").is_none());
+    assert!(class.find_method("missing", "()V").is_none());
+
+    let field = class.find_field("ANSWER", "I").unwrap();
+    assert_eq!(field.get_descriptor(pool).unwrap(), "I");
+    assert!(class.find_field("ANSWER", "J").is_none());
+
+    let descriptors: Vec<_> = class.methods_named("compareTo")
+        .map(|method| method.get_descriptor(&class.constant_pool).unwrap())
+        .collect();
+    assert_eq!(descriptors, ["(Lsample/Sample;)I", "(Ljava/lang/Object;)I"]);
+    assert_eq!(class.methods_named("missing").count(), 0);
+
+    class.find_method_mut("switchTable", "(I)I").unwrap().set_access(MethodAccessFlags::PUBLIC);
+    class.find_field_mut("ANSWER", "I").unwrap().attributes.clear();
+    let class = reload(&class);
+    assert_eq!(class.find_method("switchTable", "(I)I").unwrap().access(), MethodAccessFlags::PUBLIC);
+    assert!(class.find_field("ANSWER", "I").unwrap().attributes.is_empty());
+}
+
+#[test]
+fn test_add_and_remove_method() {
+    let mut class = load_class("Sample$Inner");
+    let count = class.methods.len();
+    let method = class.add_method(MethodAccessFlags::PUBLIC | MethodAccessFlags::ABSTRACT, "added", "(I)V").unwrap();
+    assert!(method.attributes.is_empty());
+    assert!(class.add_method(MethodAccessFlags::PUBLIC, "added", "(I)V").is_err());
+    assert!(class.add_method(MethodAccessFlags::PUBLIC, "broken", "(I").is_err());
+    assert_eq!(class.methods.len(), count + 1);
+
+    let class = reload(&class);
+    assert!(class.find_method("added", "(I)V").unwrap().access().contains(MethodAccessFlags::ABSTRACT));
+
+    let mut class = class;
+    let removed = class.remove_method("value", "()I").unwrap();
+    assert_eq!(removed.get_name(&class.constant_pool).unwrap(), "value");
+    assert!(class.remove_method("value", "()I").is_none());
+    assert_eq!(class.methods.len(), count);
+    class.compact_constant_pool().unwrap();
+    let class = reload(&class);
+    assert!(class.find_method("value", "()I").is_none());
+    assert!(class.find_method("added", "(I)V").is_some());
+}
+
+#[test]
+fn test_rename_member() {
+    let mut class = load_class("Sample");
+    // 桥接方法compareTo(Object)调用compareTo(Sample)，二者共享名称常量
+    class.rename_member("compareTo", "(Lsample/Sample;)I", "compareSample").unwrap();
+    // 私有lambda方法由BootstrapMethods中的方法句柄引用
+    class.rename_member("lambda$lambda$0", "(Ljava/lang/String;)Ljava/lang/String;", "transform").unwrap();
+    class.rename_member("items", "Ljava/util/List;", "elements").unwrap();
+
+    assert!(class.rename_member("missing", "()V", "other").is_err());
+    assert!(class.rename_member("switchTable", "(I)I", "switchLookup").is_err());
+    class.rename_member("switchTable", "(I)I", "switchTable").unwrap();
+    assert!(class.rename_member("switchTable", "(I)I", "arrays").is_ok());
+
+    let class = reload(&class);
+    assert!(class.find_method("compareSample", "(Lsample/Sample;)I").is_some());
+    assert!(class.find_method("compareTo", "(Ljava/lang/Object;)I").is_some());
+    assert!(class.find_method("compareTo", "(Lsample/Sample;)I").is_none());
+    assert!(class.find_method("transform", "(Ljava/lang/String;)Ljava/lang/String;").is_some());
+    assert!(class.find_field("elements", "Ljava/util/List;").is_some());
+    assert!(class.find_method("arrays", "(I)I").is_some());
+
+    let references = self_references(&class);
+    assert!(references.contains(&"compareSample:(Lsample/Sample;)I".to_string()), "{:?}", references);
+    assert!(references.contains(&"elements:Ljava/util/List;".to_string()), "{:?}", references);
+    assert!(!references.iter().any(|reference| reference.starts_with("compareTo:(Lsample") || reference.starts_with("items:")));
+
+    let pool = &class.constant_pool;
+    let handles: Vec<_> = pool.iter()
+        .filter(|(_, value)| matches!(value, ConstantValue::ConstantMethodHandle(..)))
+        .map(|(index, _)| pool.method_handle(index).unwrap().member)
+        .collect();
+    assert!(handles.contains(&MemberRef {
+        owner: "sample/Sample",
+        name: "transform",
+        descriptor: "(Ljava/lang/String;)Ljava/lang/String;",
+    }));
+}
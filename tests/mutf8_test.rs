@@ -0,0 +1,54 @@
+use std::fs::read;
+use std::io::Cursor;
+use jclass::common::error::ConstantError;
+use jclass::constant_pool::ConstantValue;
+use jclass::jclass_info::JClassInfo;
+use jclass::util::mutf8;
+
+const TEXT_CLASS_PATH: &str = "tests/resources/sample/Text.class";
+
+#[test]
+fn test_mutf8_codec() {
+    for text in ["plain", "nul\0char", "emoji😀", "café 中文", ""] {
+        let bytes = mutf8::encode(text);
+        assert_eq!(bytes.len(), mutf8::encoded_len(text));
+        assert_eq!(mutf8::decode(&bytes).as_deref(), Some(text));
+    }
+    assert_eq!(&*mutf8::encode("a\0b"), &[b'a', 0xC0, 0x80, b'b']);
+    assert_eq!(&*mutf8::encode("😀"), &[0xED, 0xA0, 0xBD, 0xED, 0xB8, 0x80]);
+    // 标准UTF-8的增补字符、裸NUL、过长编码与孤立代理项均不是合法的modified UTF-8
+    assert_eq!(mutf8::decode("😀".as_bytes()), None);
+    assert_eq!(mutf8::decode(&[b'a', 0]), None);
+    assert_eq!(mutf8::decode(&[0xC1, 0x81]), None);
+    assert_eq!(mutf8::decode(&[0xED, 0xA0, 0x80]), None);
+}
+
+#[test]
+fn test_mutf8_constants_round_trip() {
+    let content = read(TEXT_CLASS_PATH).unwrap();
+    let info = JClassInfo::from_reader(&mut Cursor::new(&content).into()).unwrap();
+    let pool = &info.constant_pool;
+    let mut strings = Vec::new();
+    let mut raws = Vec::new();
+    for i in 1..=pool.get_constant_count() {
+        match pool.get_constant_item(i) {
+            ConstantValue::ConstantUtf8(string) => strings.push(string.clone()),
+            value @ ConstantValue::ConstantUtf8Raw(bytes) => {
+                assert_eq!((value.tag(), value.value()), (1, 1));
+                assert_eq!(pool.utf8(i), Err(ConstantError::InvalidUtf8 { index: i }));
+                assert_ne!(value, &ConstantValue::ConstantUtf8(String::from_utf8_lossy(bytes).into_owned()));
+                raws.push(bytes.clone());
+            }
+            _ => {}
+        }
+    }
+    assert!(strings.iter().any(|s| s == "nul\0char"));
+    assert!(strings.iter().any(|s| s == "emoji😀"));
+    assert!(strings.iter().any(|s| s == "café 中文"));
+    assert_eq!(raws, [b"lone\xED\xA0\x80".to_vec()]);
+
+    let mut bytes = Vec::with_capacity(info.byte_size());
+    info.write_to(&mut (&mut bytes).into()).unwrap();
+    assert_eq!(bytes.len(), info.byte_size());
+    assert_eq!(bytes, content);
+}
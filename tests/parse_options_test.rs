@@ -0,0 +1,117 @@
+use jclass::attribute_info::AttributeInfo;
+use jclass::common::constants::CODE_TAG;
+use jclass::common::error::ErrorKind;
+use jclass::common::options::ParseOptions;
+use jclass::jclass_info::JClassInfo;
+
+mod common;
+
+use common::sample_data;
+
+/// 第一个方法的第一个属性长度字段的偏移
+fn first_method_attribute_len_offset(class: &JClassInfo) -> usize {
+    let methods_start = 8 + class.constant_pool.byte_size() + 8 + 2 * class.interfaces.len()
+        + 2 + class.fields.iter().map(|field| field.byte_size()).sum::<usize>();
+    // 方法数量、访问标识、名称、描述符、属性数量、属性名
+    methods_start + 2 + 6 + 2 + 2
+}
+
+#[test]
+fn test_default_options() {
+    let data = sample_data();
+    let class = JClassInfo::from_bytes(&data).unwrap();
+    assert!(!class.methods.is_empty());
+    let class = JClassInfo::from_bytes_with_options(&data, ParseOptions::unlimited()).unwrap();
+    assert!(!class.methods.is_empty());
+}
+
+#[test]
+fn test_hostile_attribute_len() {
+    let data = sample_data();
+    let class = JClassInfo::from_bytes(&data).unwrap();
+    let offset = first_method_attribute_len_offset(&class);
+
+    // 超出剩余数据，不会按声明的长度分配
+    let mut hostile = data.clone();
+    hostile[offset..offset + 4].copy_from_slice(&0x00FF_FFFFu32.to_be_bytes());
+    let error = JClassInfo::from_bytes(&hostile).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::UnexpectedEof, "{}", error);
+    assert_eq!(error.offset, Some(offset + 4));
+    assert!(error.path.starts_with("methods[0].attributes[0]"), "{}", error);
+
+    // 原先按i32读取时为负数
+    hostile[offset..offset + 4].copy_from_slice(&0xFFFF_FFF0u32.to_be_bytes());
+    let error = JClassInfo::from_bytes(&hostile).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::LimitExceeded, "{}", error);
+    let error = JClassInfo::from_bytes_with_options(&hostile, ParseOptions::unlimited()).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::UnexpectedEof, "{}", error);
+
+    let options = ParseOptions {
+        max_attribute_size: 4,
+        ..ParseOptions::default()
+    };
+    let error = JClassInfo::from_bytes_with_options(&data, options).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::LimitExceeded, "{}", error);
+}
+
+#[test]
+fn test_code_size() {
+    let class = JClassInfo::from_bytes(&sample_data()).unwrap();
+    let pool = &class.constant_pool;
+    let code = class.methods.iter()
+        .flat_map(|method| &method.attributes)
+        .find(|attribute| pool.utf8(attribute.name).unwrap() == CODE_TAG)
+        .unwrap();
+    let data = &code.data;
+    let code_len = u32::from_be_bytes(data[4..8].try_into().unwrap()) as usize;
+    assert!(AttributeInfo::new_with_data(CODE_TAG, data).unwrap().is_some());
+
+    let options = ParseOptions {
+        max_code_size: code_len - 1,
+        ..ParseOptions::default()
+    };
+    let error = AttributeInfo::new_with_data_and_options(CODE_TAG, data, options).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::LimitExceeded, "{}", error);
+    assert_eq!((error.offset, error.path.as_str()), (Some(8), CODE_TAG));
+
+    let mut hostile = data.to_vec();
+    hostile[4..8].copy_from_slice(&0x0000_FFFFu32.to_be_bytes());
+    let error = AttributeInfo::new_with_data(CODE_TAG, &hostile).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::UnexpectedEof, "{}", error);
+    hostile[4..8].copy_from_slice(&0x7FFF_FFFFu32.to_be_bytes());
+    let error = AttributeInfo::new_with_data(CODE_TAG, &hostile).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::LimitExceeded, "{}", error);
+}
+
+#[test]
+fn test_constant_count() {
+    let data = sample_data();
+    let count = u16::from_be_bytes([data[8], data[9]]) as usize;
+    let options = ParseOptions {
+        max_constant_count: count - 1,
+        ..ParseOptions::default()
+    };
+    let error = JClassInfo::from_bytes_with_options(&data, options).unwrap_err();
+    assert_eq!((error.kind(), error.offset), (ErrorKind::LimitExceeded, Some(8)));
+    let options = ParseOptions {
+        max_constant_count: count,
+        ..ParseOptions::default()
+    };
+    assert!(JClassInfo::from_bytes_with_options(&data, options).is_ok());
+}
+
+#[test]
+fn test_input_size() {
+    let data = sample_data();
+    let options = ParseOptions {
+        max_input_size: data.len() - 1,
+        ..ParseOptions::default()
+    };
+    let error = JClassInfo::from_bytes_with_options(&data, options).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::LimitExceeded);
+    let options = ParseOptions {
+        max_input_size: data.len(),
+        ..ParseOptions::default()
+    };
+    assert!(JClassInfo::from_bytes_with_options(&data, options).is_ok());
+}
@@ -0,0 +1,143 @@
+package sample;
+
+import java.io.IOException;
+import java.io.Serializable;
+import java.lang.annotation.ElementType;
+import java.lang.annotation.Retention;
+import java.lang.annotation.RetentionPolicy;
+import java.lang.annotation.Target;
+import java.util.ArrayList;
+import java.util.List;
+import java.util.Map;
+import java.util.function.Supplier;
+
+public class Sample<T extends Comparable<T>> implements Serializable, Comparable<Sample<T>> {
+
+    @Retention(RetentionPolicy.RUNTIME)
+    public @interface Keep {
+        String value() default "keep";
+        int level() default 1;
+        Class<?> type() default Object.class;
+        ElementType[] targets() default {ElementType.METHOD, ElementType.FIELD};
+    }
+
+    @Retention(RetentionPolicy.CLASS)
+    @interface Hidden {
+    }
+
+    @Target(ElementType.TYPE_USE)
+    @Retention(RetentionPolicy.RUNTIME)
+    @interface Nullable {
+    }
+
+    public static final long BIG = 0x1234567890L;
+    public static final double PI = 3.14159;
+    public static final int ANSWER = 42;
+
+    @Keep(value = "field", level = 2)
+    private volatile List<@Nullable T> items = new ArrayList<>();
+    protected transient Map<String, ? extends Number> counts;
+
+    class Inner {
+        int value() {
+            return items.size();
+        }
+    }
+
+    static class Nested<K> {
+        K key;
+    }
+
+    @Keep
+    @Hidden
+    public Sample() {
+    }
+
+    @Deprecated
+    public synchronized int switchTable(int x) {
+        switch (x) {
+            case 0:
+                return 10;
+            case 1:
+                return 11;
+            case 2:
+                return 12;
+            case 3:
+                return 13;
+            default:
+                return -1;
+        }
+    }
+
+    public int switchLookup(int x) {
+        switch (x) {
+            case -100:
+                return 1;
+            case 0:
+                return 2;
+            case 100000:
+                return 3;
+            default:
+                return 0;
+        }
+    }
+
+    public <R extends Number> R generic(Map<String, ? super R> map, R value) throws IOException {
+        if (map == null) {
+            throw new IOException("null map");
+        }
+        return value;
+    }
+
+    public long mixed(long a, double b, @Keep("param") int c, final String d) {
+        long result = a;
+        try {
+            result += (long) b * c;
+            result += d.length();
+        } catch (NullPointerException | ArithmeticException e) {
+            result = -1;
+        } finally {
+            result ^= 1;
+        }
+        return result;
+    }
+
+    public Supplier<String> lambda(String prefix) {
+        Runnable local = new Runnable() {
+            @Override
+            public void run() {
+                System.out.println(prefix);
+            }
+        };
+        local.run();
+        return () -> prefix + ANSWER + BIG;
+    }
+
+    public int[][] arrays(int n) {
+        int[][] grid = new int[n][n + 1];
+        String[] names = new String[n];
+        for (int i = 0; i < n; i++) {
+            names[i] = String.valueOf(i);
+            grid[i][0] = names[i].length();
+        }
+        return grid;
+    }
+
+    public int wideLocals(int p0) {
+        long l0 = 0, l1 = 1, l2 = 2, l3 = 3, l4 = 4, l5 = 5, l6 = 6, l7 = 7, l8 = 8, l9 = 9;
+        long m0 = 0, m1 = 1, m2 = 2, m3 = 3, m4 = 4, m5 = 5, m6 = 6, m7 = 7, m8 = 8, m9 = 9;
+        long n0 = 0, n1 = 1, n2 = 2, n3 = 3, n4 = 4, n5 = 5, n6 = 6, n7 = 7, n8 = 8, n9 = 9;
+        long o0 = 0, o1 = 1, o2 = 2, o3 = 3, o4 = 4, o5 = 5, o6 = 6, o7 = 7, o8 = 8, o9 = 9;
+        long q0 = 0, q1 = 1, q2 = 2, q3 = 3, q4 = 4, q5 = 5, q6 = 6, q7 = 7, q8 = 8, q9 = 9;
+        long r0 = 0, r1 = 1, r2 = 2, r3 = 3, r4 = 4, r5 = 5, r6 = 6, r7 = 7, r8 = 8, r9 = 9;
+        long s0 = 0, s1 = 1, s2 = 2, s3 = 3, s4 = 4, s5 = 5, s6 = 6, s7 = 7, s8 = 8, s9 = 9;
+        int w = p0;
+        w += 1000;
+        return w + (int) (l9 + m9 + n9 + o9 + q9 + r9 + s9);
+    }
+
+    @Override
+    public int compareTo(Sample<T> o) {
+        return Integer.compare(items.size(), o.items.size());
+    }
+}
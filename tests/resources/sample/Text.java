@@ -0,0 +1,8 @@
+package sample;
+
+public class Text {
+    public static final String NUL_TEXT = "nul\u0000char";
+    public static final String EMOJI_TEXT = "emoji😀";
+    public static final String MIXED_TEXT = "café 中文";
+    public static final String LONE_SURROGATE = "lone\uD800";
+}
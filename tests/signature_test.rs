@@ -0,0 +1,100 @@
+use jclass::descriptor::FieldType;
+use jclass::signature::{find_signature, ClassSignature, ClassTypeSignature, MethodSignature, TypeArgument, TypeSignature};
+
+mod common;
+
+use common::load_class;
+
+#[test]
+fn test_round_trip() {
+    for signature in [
+        "<T::Ljava/lang/Comparable<TT;>;>Ljava/lang/Object;Ljava/io/Serializable;Ljava/lang/Comparable<Lsample/Sample<TT;>;>;",
+        "<K:Ljava/lang/Object;V:Ljava/lang/Number;:Ljava/lang/Runnable;>Ljava/util/AbstractMap<TK;TV;>;",
+        "Ljava/lang/Object;",
+    ] {
+        assert_eq!(ClassSignature::parse(signature).unwrap().to_string(), signature);
+    }
+    for signature in [
+        "<R:Ljava/lang/Number;>(Ljava/util/Map<Ljava/lang/String;-TR;>;TR;)TR;^Ljava/io/IOException;",
+        "(I[[J[TT;)V",
+        "<E:Ljava/lang/Exception;>()V^TE;^Ljava/lang/Error;",
+        "()Lsample/Outer<TT;>.Inner<*>.Deep;",
+    ] {
+        assert_eq!(MethodSignature::parse(signature).unwrap().to_string(), signature);
+    }
+    for signature in ["Ljava/util/List<+Ljava/lang/Number;>;", "TT;", "[Ljava/util/Map$Entry<**>;", "I"] {
+        assert_eq!(TypeSignature::parse(signature).unwrap().to_string(), signature);
+    }
+    for invalid in ["", "Ljava/lang/Object", "<T>Ljava/lang/Object;", "TT", "L<TT;>;", "Ljava/util/List<>;"] {
+        assert!(TypeSignature::parse(invalid).is_err() || ClassSignature::parse(invalid).is_err(), "{}", invalid);
+    }
+    for invalid in ["()", "(V)V", "()V^I", "()VV"] {
+        assert!(MethodSignature::parse(invalid).is_err(), "{}", invalid);
+    }
+}
+
+#[test]
+fn test_structure() {
+    let signature = MethodSignature::parse("<R:Ljava/lang/Number;>(Ljava/util/Map<Ljava/lang/String;-TR;>;TR;)TR;^Ljava/io/IOException;").unwrap();
+    assert_eq!(signature.type_parameters[0].name, "R");
+    assert_eq!(signature.type_parameters[0].class_bound, Some(TypeSignature::Class(ClassTypeSignature::new("java/lang/Number"))));
+    let TypeSignature::Class(map) = &signature.parameters[0] else {
+        panic!("参数不是类类型");
+    };
+    assert_eq!(map.type_arguments, [
+        TypeArgument::Exact(TypeSignature::Class(ClassTypeSignature::new("java/lang/String"))),
+        TypeArgument::Super(TypeSignature::TypeVariable("R".into())),
+    ]);
+    assert_eq!(signature.return_type, Some(TypeSignature::TypeVariable("R".into())));
+
+    let signature = TypeSignature::parse("Lsample/Outer<TT;>.Inner<[I>;").unwrap();
+    let TypeSignature::Class(class) = &signature else {
+        panic!("不是类类型");
+    };
+    assert_eq!(class.erased_name(), "sample/Outer$Inner");
+    assert_eq!(class.inner[0].type_arguments, [
+        TypeArgument::Exact(TypeSignature::Array(Box::new(TypeSignature::Base(FieldType::Int)))),
+    ]);
+
+    let signature = ClassSignature::parse("<T::Ljava/lang/Runnable;>Ljava/lang/Object;").unwrap();
+    assert_eq!(signature.type_parameters[0].class_bound, None);
+    assert_eq!(signature.type_parameters[0].interface_bounds.len(), 1);
+}
+
+#[test]
+fn test_rename() {
+    let mut rename = |name: &str| match name {
+        "sample/Outer" => Some("a/A".to_string()),
+        "sample/Outer$Inner" => Some("a/A$B".to_string()),
+        "java/lang/String" => Some("a/S".to_string()),
+        _ => None,
+    };
+    let mut signature = MethodSignature::parse("<T:Ljava/lang/String;>(Lsample/Outer<Ljava/lang/String;>.Inner.Deep;)[Ljava/lang/String;^TT;").unwrap();
+    signature.rename_classes(&mut rename);
+    assert_eq!(signature.to_string(), "<T:La/S;>(La/A<La/S;>.B.Deep;)[La/S;^TT;");
+}
+
+#[test]
+fn test_sample_signatures() {
+    let class = load_class("Sample");
+    let pool = &class.constant_pool;
+    let signature = find_signature(&class.attributes, pool).unwrap().unwrap();
+    let parsed = ClassSignature::parse(signature).unwrap();
+    assert_eq!(parsed.to_string(), signature);
+    assert_eq!(parsed.interfaces.len(), 2);
+
+    let mut count = 0;
+    for method in &class.methods {
+        if let Some(signature) = find_signature(&method.attributes, pool).unwrap() {
+            assert_eq!(MethodSignature::parse(signature).unwrap().to_string(), signature);
+            count += 1;
+        }
+    }
+    for field in &class.fields {
+        if let Some(signature) = find_signature(&field.attributes, pool).unwrap() {
+            assert_eq!(TypeSignature::parse(signature).unwrap().to_string(), signature);
+            count += 1;
+        }
+    }
+    assert!(count >= 4);
+}
@@ -0,0 +1,278 @@
+use std::collections::BTreeMap;
+use jclass::access_flags::MethodAccessFlags;
+use jclass::attribute_info::{AttributeInfo, CodeAttribute, OriginAttribute, StackMapFrame, VerificationTypeInfo};
+use jclass::common::error::ErrorKind;
+use jclass::common::opcode::opcodes;
+use jclass::constant_pool::ConstantPool;
+use jclass::descriptor::MethodDescriptor;
+use jclass::instruction::Instruction;
+use jclass::instruction_list::InstructionList;
+use jclass::jclass_info::JClassInfo;
+use jclass::method_info::MethodInfo;
+use jclass::stack_map::{compute_frames, encode_frames, expand_frames, regenerate_stack_map_table, ClassHierarchy, Frame, FrameType, SimpleClassHierarchy};
+
+mod common;
+
+use common::{hierarchy, sample_classes};
+
+fn code_of(class: &JClassInfo, method: &MethodInfo) -> Option<CodeAttribute> {
+    method.attributes.iter().find_map(|attribute| match attribute.info(&class.constant_pool).unwrap() {
+        AttributeInfo::Code(code) => Some(code.clone()),
+        _ => None,
+    })
+}
+
+fn initial_frame(class: &JClassInfo, method: &MethodInfo) -> Frame {
+    let pool = &class.constant_pool;
+    let descriptor = MethodDescriptor::parse(method.get_descriptor(pool).unwrap()).unwrap();
+    let is_static = method.access().contains(MethodAccessFlags::STATIC);
+    Frame::initial(class.this_class_name().unwrap(), method.get_name(pool).unwrap(), &descriptor, is_static)
+}
+
+fn stack_map_table(class: &JClassInfo, code: &CodeAttribute) -> Vec<StackMapFrame> {
+    code.attributes.iter().find_map(|attribute| match attribute.info(&class.constant_pool).unwrap() {
+        AttributeInfo::StackMapTable(frames) => Some(frames.clone()),
+        _ => None,
+    }).unwrap_or_default()
+}
+
+/// 将javac生成的StackMapTable展开为按pc索引的帧
+fn javac_frames(class: &JClassInfo, method: &MethodInfo, code: &CodeAttribute) -> BTreeMap<u32, Frame> {
+    expand_frames(&initial_frame(class, method), &stack_map_table(class, code), &class.constant_pool).unwrap()
+}
+
+#[test]
+fn test_sample_frames() {
+    let mut count = 0;
+    for class in sample_classes() {
+        let hierarchy = hierarchy(&class);
+        for method in &class.methods {
+            let Some(code) = code_of(&class, method) else { continue };
+            let name = method.get_name(&class.constant_pool).unwrap();
+            let computed = compute_frames(&class, method, &code, &hierarchy).unwrap();
+            let expected = javac_frames(&class, method, &code);
+            assert_eq!(computed.keys().collect::<Vec<_>>(), expected.keys().collect::<Vec<_>>(), "{}", name);
+            for (pc, frame) in &computed {
+                let javac = &expected[pc];
+                assert_eq!(frame.stack, javac.stack, "{} {}", name, pc);
+                // javac会移除离开作用域的局部变量
+                assert!(frame.locals.starts_with(&javac.locals), "{} {}: {:?} {:?}", name, pc, frame.locals, javac.locals);
+            }
+            count += computed.len();
+        }
+    }
+    assert!(count > 10);
+}
+
+#[test]
+fn test_encode_frames() {
+    for class in sample_classes() {
+        for method in &class.methods {
+            let Some(code) = code_of(&class, method) else { continue };
+            let frames = javac_frames(&class, method, &code);
+            let mut pool = class.constant_pool.clone();
+            let encoded = encode_frames(&initial_frame(&class, method), &frames, &mut pool);
+            // 展开后重新压缩得到与javac相同的帧
+            assert_eq!(encoded, stack_map_table(&class, &code));
+        }
+    }
+}
+
+#[test]
+fn test_absolute_pcs() {
+    let mut count = 0;
+    for class in sample_classes() {
+        for method in &class.methods {
+            let Some(code) = code_of(&class, method) else { continue };
+            let attribute = code.attributes.iter()
+                .find(|attribute| matches!(attribute.info(&class.constant_pool), Ok(AttributeInfo::StackMapTable(_))));
+            let Some(attribute) = attribute else { continue };
+            let table = stack_map_table(&class, &code);
+            let pcs = StackMapFrame::absolute_pcs(&table);
+            assert_eq!(pcs, javac_frames(&class, method, &code).into_keys().collect::<Vec<_>>());
+
+            // 按绝对pc重新设置后编码得到相同的字节
+            let mut patched = table.clone();
+            StackMapFrame::set_absolute_pcs(&mut patched, &pcs).unwrap();
+            assert_eq!(patched, table);
+            let encoded = OriginAttribute::new_with_info(attribute.name, &AttributeInfo::StackMapTable(patched)).unwrap();
+            assert_eq!(encoded.data, attribute.data);
+            count += 1;
+        }
+    }
+    assert!(count > 0);
+}
+
+#[test]
+fn test_patch_frames() {
+    let stack = VerificationTypeInfo::Integer;
+    let mut frames = vec![
+        StackMapFrame::SameFrame { offset_delta: 4 },
+        StackMapFrame::SameLocals1StackItemFrame { offset_delta: 2, stack: stack.clone() },
+        StackMapFrame::ChopFrame { k: 1, offset_delta: 3 },
+    ];
+    assert_eq!(StackMapFrame::absolute_pcs(&frames), vec![4, 7, 11]);
+
+    // 在pc 5处插入100字节，之后的帧偏移增大
+    StackMapFrame::set_absolute_pcs(&mut frames, &[4, 107, 111]).unwrap();
+    assert_eq!(frames, vec![
+        StackMapFrame::SameFrame { offset_delta: 4 },
+        StackMapFrame::SameLocals1StackItemFrameExtended { offset_delta: 102, stack: stack.clone() },
+        StackMapFrame::ChopFrame { k: 1, offset_delta: 3 },
+    ]);
+    StackMapFrame::set_absolute_pcs(&mut frames, &[100, 107, 111]).unwrap();
+    assert_eq!(frames[0], StackMapFrame::SameFrameExtended { offset_delta: 100 });
+    // 扩展形式不会自动缩短
+    StackMapFrame::set_absolute_pcs(&mut frames, &[4, 7, 11]).unwrap();
+    assert_eq!(frames[1], StackMapFrame::SameLocals1StackItemFrameExtended { offset_delta: 2, stack });
+    assert_eq!(StackMapFrame::absolute_pcs(&frames), vec![4, 7, 11]);
+
+    for pcs in [&[4, 7][..], &[4, 4, 11], &[4, 11, 7], &[4, 7, 70000]] {
+        let error = StackMapFrame::set_absolute_pcs(&mut frames, pcs).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidAttribute, "{:?}", pcs);
+    }
+}
+
+#[test]
+fn test_expand_frames() {
+    let pool = ConstantPool::new(1);
+    let initial = Frame::initial("a/Test", "test", &MethodDescriptor::parse("(JI)V").unwrap(), true);
+    let frames = expand_frames(&initial, &[
+        StackMapFrame::AppendFrame { offset_delta: 2, locals: vec![VerificationTypeInfo::Double] },
+        StackMapFrame::ChopFrame { k: 2, offset_delta: 0 },
+        StackMapFrame::SameLocals1StackItemFrame { offset_delta: 1, stack: VerificationTypeInfo::Null },
+        StackMapFrame::FullFrame { offset_delta: 0, locals: vec![VerificationTypeInfo::Top, VerificationTypeInfo::Float], stack: vec![] },
+    ], &pool).unwrap();
+    assert_eq!(frames.keys().copied().collect::<Vec<_>>(), vec![2, 3, 5, 6]);
+    assert_eq!(frames[&2].locals, vec![FrameType::Long, FrameType::Top, FrameType::Integer, FrameType::Double, FrameType::Top]);
+    assert_eq!(frames[&3].locals, vec![FrameType::Long, FrameType::Top]);
+    assert_eq!((frames[&5].locals.len(), &frames[&5].stack[..]), (2, &[FrameType::Null][..]));
+    assert_eq!(frames[&6].locals, vec![FrameType::Top, FrameType::Float]);
+
+    let error = expand_frames(&initial, &[StackMapFrame::ChopFrame { k: 3, offset_delta: 0 }], &pool).unwrap_err();
+    assert_eq!((error.kind(), error.path.as_str()), (ErrorKind::InvalidAttribute, "[0]"));
+}
+
+#[test]
+fn test_regenerate() {
+    for mut class in sample_classes() {
+        let hierarchy = hierarchy(&class);
+        for i in 0..class.methods.len() {
+            let Some(before) = code_of(&class, &class.methods[i]) else { continue };
+            let expected = compute_frames(&class, &class.methods[i], &before, &hierarchy).unwrap();
+            regenerate_stack_map_table(&mut class, i, &hierarchy).unwrap();
+            let after = code_of(&class, &class.methods[i]).unwrap();
+            assert_eq!(after.codes, before.codes);
+            assert_eq!(javac_frames(&class, &class.methods[i], &after), expected);
+        }
+        let mut data = Vec::new();
+        class.write_to(&mut (&mut data).into()).unwrap();
+        let reloaded = JClassInfo::from_bytes(&data).unwrap();
+        assert_eq!(reloaded.methods.len(), class.methods.len());
+    }
+}
+
+#[test]
+fn test_merge() {
+    let mut hierarchy = SimpleClassHierarchy::new();
+    hierarchy.insert("a/Base", Some("java/lang/Object"), false);
+    hierarchy.insert("a/Left", Some("a/Base"), false);
+    hierarchy.insert("a/Right", Some("a/Base"), false);
+    hierarchy.insert("a/Api", None, true);
+    assert_eq!(hierarchy.common_super_class("a/Left", "a/Right"), "a/Base");
+    assert_eq!(hierarchy.common_super_class("a/Left", "a/Api"), "java/lang/Object");
+    assert!(hierarchy.is_assignable("a/Base", "a/Left"));
+    assert!(!hierarchy.is_assignable("a/Left", "a/Base"));
+
+    let left = FrameType::Object("a/Left".to_string());
+    let right = FrameType::Object("a/Right".to_string());
+    assert_eq!(left.merge(&right, &hierarchy), FrameType::Object("a/Base".to_string()));
+    assert_eq!(left.merge(&FrameType::Null, &hierarchy), left);
+    assert_eq!(left.merge(&FrameType::Integer, &hierarchy), FrameType::Top);
+    let arrays = (FrameType::Object("[La/Left;".to_string()), FrameType::Object("[La/Right;".to_string()));
+    assert_eq!(arrays.0.merge(&arrays.1, &hierarchy), FrameType::Object("[La/Base;".to_string()));
+    let arrays = (FrameType::Object("[I".to_string()), FrameType::Object("[J".to_string()));
+    assert_eq!(arrays.0.merge(&arrays.1, &hierarchy), FrameType::Object("java/lang/Object".to_string()));
+}
+
+/// 构造静态方法test(Z)La/Base;，两个分支分别创建a/Left与a/Right
+fn branch_class(pool: &mut ConstantPool) -> CodeAttribute {
+    let left = pool.add_class("a/Left");
+    let right = pool.add_class("a/Right");
+    let left_init = pool.add_methodref("a/Left", "<init>", "()V");
+    let right_init = pool.add_methodref("a/Right", "<init>", "()V");
+    let mut list = InstructionList::new();
+    let (else_label, end) = (list.new_label(), list.new_label());
+    list.push(Instruction::Simple(opcodes::ILOAD_0))
+        .jump(opcodes::IFEQ, else_label)
+        .push(Instruction::Type(opcodes::NEW, left))
+        .push(Instruction::Simple(opcodes::DUP))
+        .push(Instruction::Invoke(opcodes::INVOKESPECIAL, left_init))
+        .jump(opcodes::GOTO, end)
+        .place_label(else_label)
+        .push(Instruction::Type(opcodes::NEW, right))
+        .push(Instruction::Simple(opcodes::DUP))
+        .push(Instruction::Invoke(opcodes::INVOKESPECIAL, right_init))
+        .place_label(end)
+        .push(Instruction::Simple(opcodes::ARETURN));
+    list.build(2, 1).unwrap()
+}
+
+#[test]
+fn test_built_method() {
+    let mut class = sample_classes().into_iter().next().unwrap();
+    let code = branch_class(&mut class.constant_pool);
+    let mut method = class.methods[0].clone();
+    method.name = class.constant_pool.add_utf8("test");
+    method.descriptor = class.constant_pool.add_utf8("(Z)La/Base;");
+    method.set_access(MethodAccessFlags::PUBLIC | MethodAccessFlags::STATIC);
+
+    let hierarchy = hierarchy(&class);
+    let frames = compute_frames(&class, &method, &code, &hierarchy).unwrap();
+    let pcs: Vec<_> = frames.keys().copied().collect();
+    assert_eq!(pcs, vec![14, 21]);
+    assert_eq!(frames[&21].stack, vec![FrameType::Object("a/Base".to_string())]);
+    assert_eq!(frames[&21].locals, vec![FrameType::Integer]);
+
+    let encoded = encode_frames(&initial_frame(&class, &method), &frames, &mut class.constant_pool);
+    assert!(matches!(encoded[0], StackMapFrame::SameFrame { offset_delta: 14 }));
+    assert!(matches!(encoded[1], StackMapFrame::SameLocals1StackItemFrame { offset_delta: 6, .. }));
+
+    // 分支上的操作栈类型不兼容
+    let mut list = InstructionList::new();
+    let end = list.new_label();
+    list.push(Instruction::Simple(opcodes::ICONST_0))
+        .push(Instruction::Simple(opcodes::ILOAD_0))
+        .jump(opcodes::IFEQ, end)
+        .push(Instruction::Simple(opcodes::POP))
+        .push(Instruction::Simple(opcodes::FCONST_0))
+        .place_label(end)
+        .push(Instruction::Simple(opcodes::ARETURN));
+    let error = compute_frames(&class, &method, &list.build(2, 1).unwrap(), &hierarchy).unwrap_err();
+    assert_eq!((error.kind(), error.offset), (ErrorKind::InvalidBytecode, Some(7)));
+
+    // 不可达的代码
+    let mut list = InstructionList::new();
+    list.push(Instruction::Simple(opcodes::ACONST_NULL))
+        .push(Instruction::Simple(opcodes::ARETURN))
+        .push(Instruction::Simple(opcodes::NOP));
+    let error = compute_frames(&class, &method, &list.build(1, 1).unwrap(), &hierarchy).unwrap_err();
+    assert_eq!(error.offset, Some(2));
+
+    // 处理范围内的存储指令改变了局部变量类型，处理器入口需合并执行后的局部变量
+    let mut list = InstructionList::new();
+    let (start, end, handler) = (list.new_label(), list.new_label(), list.new_label());
+    list.push(Instruction::Simple(opcodes::ICONST_0))
+        .push(Instruction::Simple(opcodes::ISTORE_0))
+        .place_label(start)
+        .push(Instruction::Simple(opcodes::ACONST_NULL))
+        .push(Instruction::Simple(opcodes::ASTORE_0))
+        .place_label(end)
+        .push(Instruction::Simple(opcodes::RETURN))
+        .place_label(handler)
+        .push(Instruction::Simple(opcodes::POP))
+        .push(Instruction::Simple(opcodes::RETURN))
+        .add_exception_handler(start, end, handler, 0);
+    let frames = compute_frames(&class, &method, &list.build(1, 1).unwrap(), &hierarchy).unwrap();
+    assert_eq!(frames[&5].locals, vec![FrameType::Top]);
+}
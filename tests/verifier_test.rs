@@ -0,0 +1,282 @@
+use jclass::access_flags::MethodAccessFlags;
+use jclass::attribute_info::{AttributeInfo, CodeAttribute, ExceptionTable, ExceptionTableEntry, OriginAttribute, StackMapFrame, VerificationTypeInfo};
+use jclass::common::error::ErrorKind;
+use jclass::common::opcode::opcodes;
+use jclass::instruction::Instruction;
+use jclass::instruction_list::InstructionList;
+use jclass::jclass_info::JClassInfo;
+use jclass::method_info::MethodInfo;
+use jclass::stack_map::regenerate_stack_map_table;
+use jclass::verifier::{verify_class, verify_method, VerifyError};
+
+mod common;
+
+use common::{hierarchy, sample_classes};
+
+/// 向类中加入方法，frames为true时生成StackMapTable，返回方法下标
+fn add_method(class: &mut JClassInfo, name: &str, descriptor: &str, code: CodeAttribute, frames: bool) -> usize {
+    let pool = &mut class.constant_pool;
+    let method = MethodInfo {
+        access_flags: (MethodAccessFlags::PUBLIC | MethodAccessFlags::STATIC).bits(),
+        name: pool.add_utf8(name),
+        descriptor: pool.add_utf8(descriptor),
+        attributes: vec![OriginAttribute::new_with_info(pool.add_utf8("Code"), &AttributeInfo::Code(code)).unwrap()],
+    };
+    class.methods.push(method);
+    let index = class.methods.len() - 1;
+    if frames {
+        regenerate_stack_map_table(class, index, &hierarchy(class)).unwrap();
+    }
+    index
+}
+
+fn verify(class: &JClassInfo, index: usize) -> Result<(), VerifyError> {
+    verify_method(class, &class.methods[index], &hierarchy(class))
+}
+
+fn sample() -> JClassInfo {
+    sample_classes().into_iter().find(|class| class.this_class_name().unwrap() == "sample/Sample").unwrap()
+}
+
+/// 两个分支分别创建a/Left与a/Right并返回
+fn branch_code(class: &mut JClassInfo) -> CodeAttribute {
+    let pool = &mut class.constant_pool;
+    let (left, right) = (pool.add_class("a/Left"), pool.add_class("a/Right"));
+    let left_init = pool.add_methodref("a/Left", "<init>", "()V");
+    let right_init = pool.add_methodref("a/Right", "<init>", "()V");
+    let mut list = InstructionList::new();
+    let (else_label, end) = (list.new_label(), list.new_label());
+    list.push(Instruction::Simple(opcodes::ILOAD_0))
+        .jump(opcodes::IFEQ, else_label)
+        .push(Instruction::Type(opcodes::NEW, left))
+        .push(Instruction::Simple(opcodes::DUP))
+        .push(Instruction::Invoke(opcodes::INVOKESPECIAL, left_init))
+        .jump(opcodes::GOTO, end)
+        .place_label(else_label)
+        .push(Instruction::Type(opcodes::NEW, right))
+        .push(Instruction::Simple(opcodes::DUP))
+        .push(Instruction::Invoke(opcodes::INVOKESPECIAL, right_init))
+        .place_label(end)
+        .push(Instruction::Simple(opcodes::ARETURN));
+    list.build(2, 1).unwrap()
+}
+
+fn simple_code(instructions: &[Instruction], max_stack: u16, max_locals: u16) -> CodeAttribute {
+    let mut list = InstructionList::new();
+    for instruction in instructions {
+        list.push(instruction.clone());
+    }
+    list.build(max_stack, max_locals).unwrap()
+}
+
+#[test]
+fn test_sample_classes() {
+    for mut class in sample_classes() {
+        let hierarchy = hierarchy(&class);
+        verify_class(&class, &hierarchy).unwrap();
+        for i in 0..class.methods.len() {
+            regenerate_stack_map_table(&mut class, i, &hierarchy).unwrap();
+        }
+        verify_class(&class, &hierarchy).unwrap();
+        // 低版本不使用StackMapTable
+        class.major_version = 49;
+        verify_class(&class, &hierarchy).unwrap();
+    }
+}
+
+#[test]
+fn test_type_errors() {
+    let mut class = sample();
+    let code = branch_code(&mut class);
+    let index = add_method(&mut class, "test", "(Z)La/Base;", code.clone(), true);
+    verify(&class, index).unwrap();
+    let index = add_method(&mut class, "test", "(Z)La/Left;", code, true);
+    let error = verify(&class, index).unwrap_err();
+    assert_eq!((error.pc, error.instruction.clone()), (Some(21), Some(Instruction::Simple(opcodes::ARETURN))));
+    assert_eq!(error.kind(), ErrorKind::InvalidBytecode);
+
+    let code = simple_code(&[Instruction::Simple(opcodes::ICONST_0), Instruction::Simple(opcodes::IRETURN)], 1, 0);
+    let index = add_method(&mut class, "test", "()V", code, true);
+    assert_eq!(verify(&class, index).unwrap_err().pc, Some(1));
+
+    let code = simple_code(&[
+        Instruction::Simple(opcodes::ICONST_0),
+        Instruction::Simple(opcodes::FCONST_0),
+        Instruction::Simple(opcodes::FADD),
+        Instruction::Simple(opcodes::RETURN),
+    ], 2, 0);
+    let index = add_method(&mut class, "test", "()V", code, false);
+    let error = verify(&class, index).unwrap_err();
+    assert_eq!((error.pc, error.instruction.clone()), (Some(2), Some(Instruction::Simple(opcodes::FADD))));
+
+    let right = class.constant_pool.add_class("a/Right");
+    let accept = class.constant_pool.add_methodref("sample/Sample", "accept", "(La/Left;)V");
+    let code = simple_code(&[
+        Instruction::Type(opcodes::NEW, right),
+        Instruction::Simple(opcodes::DUP),
+        Instruction::Invoke(opcodes::INVOKESPECIAL, class.constant_pool.add_methodref("a/Right", "<init>", "()V")),
+        Instruction::Invoke(opcodes::INVOKESTATIC, accept),
+        Instruction::Simple(opcodes::RETURN),
+    ], 2, 0);
+    let index = add_method(&mut class, "test", "()V", code, false);
+    let error = verify(&class, index).unwrap_err();
+    assert_eq!((error.pc, error.instruction.clone()), (Some(7), Some(Instruction::Invoke(opcodes::INVOKESTATIC, accept))));
+    assert!(error.to_string().contains("a/Left"), "{}", error);
+}
+
+#[test]
+fn test_limits() {
+    let mut class = sample();
+    let code = simple_code(&[
+        Instruction::Simple(opcodes::ICONST_0),
+        Instruction::Simple(opcodes::ICONST_1),
+        Instruction::Simple(opcodes::IADD),
+        Instruction::Simple(opcodes::IRETURN),
+    ], 1, 0);
+    let index = add_method(&mut class, "test", "()I", code, false);
+    assert_eq!(verify(&class, index).unwrap_err().pc, Some(1));
+
+    let code = simple_code(&[
+        Instruction::Simple(opcodes::LCONST_0),
+        Instruction::Simple(opcodes::LSTORE_1),
+        Instruction::Simple(opcodes::RETURN),
+    ], 2, 2);
+    let index = add_method(&mut class, "test", "()V", code, false);
+    let error = verify(&class, index).unwrap_err();
+    assert_eq!(error.pc, Some(1));
+    assert!(error.error.msg.contains("max_locals"), "{}", error);
+
+    // 参数超出max_locals
+    let code = simple_code(&[Instruction::Simple(opcodes::RETURN)], 0, 1);
+    let index = add_method(&mut class, "test", "(J)V", code, false);
+    assert_eq!(verify(&class, index).unwrap_err().pc, None);
+}
+
+#[test]
+fn test_branch_targets() {
+    let mut class = sample();
+    // goto跳到自身的操作数上
+    let code = CodeAttribute {
+        max_stack: 0,
+        max_locals: 0,
+        codes: vec![opcodes::GOTO, 0, 2, opcodes::RETURN],
+        exceptions: ExceptionTable { entries: Vec::new() },
+        attributes: Vec::new(),
+    };
+    let index = add_method(&mut class, "test", "()V", code, false);
+    let error = verify(&class, index).unwrap_err();
+    assert_eq!((error.pc, error.instruction.clone()), (Some(0), Some(Instruction::Branch(opcodes::GOTO, 2))));
+
+    // 执行越过字节码末尾
+    let code = simple_code(&[Instruction::Simple(opcodes::NOP)], 0, 0);
+    let index = add_method(&mut class, "test", "()V", code, false);
+    assert_eq!(verify(&class, index).unwrap_err().pc, Some(0));
+
+    // javac 1.4及以前编译的finally子程序合法但不支持推导，51及以上版本不允许出现
+    let mut list = InstructionList::new();
+    let subroutine = list.new_label();
+    list.jump(opcodes::JSR, subroutine)
+        .push(Instruction::Simple(opcodes::RETURN))
+        .place_label(subroutine)
+        .push(Instruction::Local(opcodes::ASTORE, 0))
+        .push(Instruction::Local(opcodes::RET, 0));
+    let index = add_method(&mut class, "test", "()V", list.build(1, 1).unwrap(), false);
+    class.major_version = 49;
+    assert_eq!(verify(&class, index).unwrap_err().kind(), ErrorKind::UnsupportedInstruction);
+    class.major_version = 51;
+    let error = verify(&class, index).unwrap_err();
+    assert_eq!((error.kind(), error.pc), (ErrorKind::InvalidBytecode, Some(0)));
+}
+
+#[test]
+fn test_stack_map_table() {
+    let mut class = sample();
+    let code = branch_code(&mut class);
+    let index = add_method(&mut class, "test", "(Z)La/Base;", code.clone(), false);
+    let error = verify(&class, index).unwrap_err();
+    assert_eq!((error.pc, error.instruction.clone()), (Some(1), Some(Instruction::Branch(opcodes::IFEQ, 14))));
+    assert!(error.error.msg.contains("缺少栈映射帧"), "{}", error);
+
+    // 修改合并处的帧
+    let mut patched = code.clone();
+    let integer_table = vec![
+        StackMapFrame::SameFrame { offset_delta: 14 },
+        StackMapFrame::SameLocals1StackItemFrame { offset_delta: 6, stack: VerificationTypeInfo::Integer },
+    ];
+    let name = class.constant_pool.add_utf8("StackMapTable");
+    patched.attributes = vec![OriginAttribute::new_with_info(name, &AttributeInfo::StackMapTable(integer_table)).unwrap()];
+    let index = add_method(&mut class, "test", "(Z)La/Base;", patched.clone(), false);
+    let error = verify(&class, index).unwrap_err();
+    assert_eq!((error.pc, error.instruction.clone()), (Some(11), Some(Instruction::Branch(opcodes::GOTO, 21))));
+
+    // 帧不在指令起始位置
+    let misplaced = vec![StackMapFrame::SameFrame { offset_delta: 15 }];
+    patched.attributes = vec![OriginAttribute::new_with_info(name, &AttributeInfo::StackMapTable(misplaced)).unwrap()];
+    let index = add_method(&mut class, "test", "(Z)La/Base;", patched.clone(), false);
+    let error = verify(&class, index).unwrap_err();
+    assert_eq!((error.pc, error.instruction.clone(), error.error.path.as_str()), (Some(15), None, "StackMapTable"));
+
+    let underflow = vec![StackMapFrame::ChopFrame { k: 2, offset_delta: 14 }];
+    patched.attributes = vec![OriginAttribute::new_with_info(name, &AttributeInfo::StackMapTable(underflow)).unwrap()];
+    let index = add_method(&mut class, "test", "(Z)La/Base;", patched, false);
+    assert_eq!(verify(&class, index).unwrap_err().kind(), ErrorKind::InvalidAttribute);
+}
+
+#[test]
+fn test_handlers() {
+    let mut class = sample();
+    let base = class.constant_pool.add_class("a/Base");
+    let mut list = InstructionList::new();
+    let (start, end, handler) = (list.new_label(), list.new_label(), list.new_label());
+    list.place_label(start)
+        .push(Instruction::Simple(opcodes::NOP))
+        .place_label(end)
+        .push(Instruction::Simple(opcodes::RETURN))
+        .place_label(handler)
+        .push(Instruction::Simple(opcodes::ATHROW))
+        .add_exception_handler(start, end, handler, 0);
+    let code = list.build(1, 0).unwrap();
+    let index = add_method(&mut class, "test", "()V", code.clone(), true);
+    verify(&class, index).unwrap();
+
+    let mut invalid = code.clone();
+    invalid.exceptions.entries[0].catch_type = base;
+    let index = add_method(&mut class, "test", "()V", invalid, true);
+    let error = verify(&class, index).unwrap_err();
+    assert_eq!(error.error.path, "exceptions[0]");
+    assert!(error.error.msg.contains("Throwable"), "{}", error);
+
+    for (start_pc, end_pc) in [(1, 1), (0, 4)] {
+        let mut invalid = code.clone();
+        invalid.exceptions.entries = vec![ExceptionTableEntry { start_pc, end_pc, handler_pc: 2, catch_type: 0 }];
+        let index = add_method(&mut class, "test", "()V", invalid, false);
+        assert_eq!(verify(&class, index).unwrap_err().error.path, "exceptions[0]");
+    }
+
+    // 处理器入口的帧声明了多余的局部变量
+    let mut mismatched = code.clone();
+    let table = vec![StackMapFrame::FullFrame {
+        offset_delta: 2,
+        locals: vec![VerificationTypeInfo::Integer],
+        stack: vec![VerificationTypeInfo::Object(class.constant_pool.add_class("java/lang/Throwable"))],
+    }];
+    let name = class.constant_pool.add_utf8("StackMapTable");
+    mismatched.max_locals = 1;
+    mismatched.attributes = vec![OriginAttribute::new_with_info(name, &AttributeInfo::StackMapTable(table)).unwrap()];
+    let index = add_method(&mut class, "test", "()V", mismatched, false);
+    let error = verify(&class, index).unwrap_err();
+    assert_eq!((error.pc, error.instruction.clone()), (Some(0), Some(Instruction::Simple(opcodes::NOP))));
+}
+
+#[test]
+fn test_constructor() {
+    let mut class = sample();
+    let index = class.methods.iter().position(|method| method.get_name(&class.constant_pool).unwrap() == "<init>").unwrap();
+    let mut method = class.methods[index].clone();
+    let code = simple_code(&[Instruction::Simple(opcodes::RETURN)], 0, 1);
+    method.attributes = vec![OriginAttribute::new_with_info(class.constant_pool.add_utf8("Code"), &AttributeInfo::Code(code)).unwrap()];
+    class.methods.push(method);
+    let error = verify_class(&class, &hierarchy(&class)).unwrap_err();
+    assert_eq!(error.error.path, format!("methods[{}]", class.methods.len() - 1));
+    assert_eq!((error.pc, error.instruction.clone()), (Some(0), Some(Instruction::Simple(opcodes::RETURN))));
+}